@@ -0,0 +1,395 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use oxc_resolver::{Alias, AliasValue};
+use swc_common::{sync::Lrc, FileName, SourceMap};
+use swc_ecma_ast::{
+    Callee, EsVersion, Expr, KeyValueProp, Lit, MemberProp, MetaPropKind, Prop, PropName,
+    PropOrSpread,
+};
+use swc_ecma_parser::{EsSyntax, Syntax, TsSyntax};
+use swc_ecma_visit::{Visit, VisitWith};
+use tracing::debug;
+use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
+
+/// The bundler configs that aliases are read from, without their extensions
+const BUNDLER_CONFIGS: &[&str] = &["webpack.config", "rspack.config", "vite.config"];
+const BUNDLER_CONFIG_EXTENSIONS: &[&str] = &["js", "mjs", "cjs", "ts", "mts", "cts"];
+
+/// The aliases in webpack, rspack and vite configs, i.e. `resolve.alias`.
+///
+/// Configs are JavaScript, so only aliases that can be read without running
+/// the config are found, like string literals and
+/// `path.resolve(__dirname, "src")`.
+#[derive(Debug, Default)]
+pub struct BundlerAliases {
+    /// The aliases of the config in each directory that's been checked, or
+    /// `None` if there's no config in the directory
+    by_dir: Mutex<HashMap<AbsoluteSystemPathBuf, Option<Alias>>>,
+}
+
+impl BundlerAliases {
+    /// Gets the aliases from the closest bundler config above `file`. Configs
+    /// outside of `root` aren't used.
+    pub fn for_file(&self, root: &AbsoluteSystemPath, file: &AbsoluteSystemPath) -> Alias {
+        let mut by_dir = self.by_dir.lock().expect("lock poisoned");
+        for dir in file
+            .ancestors()
+            .skip(1)
+            .take_while(|dir| dir.as_path().starts_with(root.as_path()))
+        {
+            let aliases = by_dir
+                .entry(dir.to_owned())
+                .or_insert_with(|| find_config(dir).map(|config| read_aliases(dir, &config)));
+            if let Some(aliases) = aliases {
+                return aliases.clone();
+            }
+        }
+
+        Vec::new()
+    }
+}
+
+fn find_config(dir: &AbsoluteSystemPath) -> Option<AbsoluteSystemPathBuf> {
+    BUNDLER_CONFIGS
+        .iter()
+        .flat_map(|name| {
+            BUNDLER_CONFIG_EXTENSIONS
+                .iter()
+                .map(move |extension| dir.join_component(&format!("{name}.{extension}")))
+        })
+        .find(|config| config.exists())
+}
+
+fn read_aliases(dir: &AbsoluteSystemPath, config: &AbsoluteSystemPath) -> Alias {
+    let Ok(contents) = config.read_to_string() else {
+        return Vec::new();
+    };
+
+    let source_map: Lrc<SourceMap> = Default::default();
+    let file = source_map.new_source_file(FileName::Custom(config.to_string()).into(), contents);
+    let syntax = if matches!(config.extension(), Some("ts" | "mts" | "cts")) {
+        Syntax::Typescript(TsSyntax::default())
+    } else {
+        Syntax::Es(EsSyntax::default())
+    };
+    let module = match swc_ecma_parser::parse_file_as_module(
+        &file,
+        syntax,
+        EsVersion::EsNext,
+        None,
+        &mut Vec::new(),
+    ) {
+        Ok(module) => module,
+        Err(err) => {
+            debug!("failed to parse {}: {:?}", config, err);
+            return Vec::new();
+        }
+    };
+
+    let mut finder = AliasFinder {
+        dir,
+        aliases: Vec::new(),
+    };
+    module.visit_with(&mut finder);
+    debug!("found aliases in {}: {:?}", config, finder.aliases);
+    finder.aliases
+}
+
+struct AliasFinder<'a> {
+    dir: &'a AbsoluteSystemPath,
+    aliases: Alias,
+}
+
+impl AliasFinder<'_> {
+    fn add(&mut self, key: String, value: &Expr) {
+        let value = match value {
+            Expr::Lit(Lit::Bool(bool)) if !bool.value => Some(AliasValue::Ignore),
+            value => self.eval_path(value).map(AliasValue::Path),
+        };
+        match value {
+            Some(value) => self.aliases.push((key, vec![value])),
+            None => debug!("skipping alias `{}` that can't be read statically", key),
+        }
+    }
+
+    /// Evaluates an expression for a path, like `"./src"`, `__dirname` or
+    /// `path.resolve(__dirname, "src")`. Relative paths are relative to the
+    /// config, and anything that isn't a path, like `"preact/compat"`, is
+    /// returned as is.
+    fn eval_path(&self, expr: &Expr) -> Option<String> {
+        match expr {
+            Expr::Lit(Lit::Str(str)) => Some(self.relative_to_config(&str.value)),
+            Expr::Tpl(tpl) if tpl.exprs.is_empty() => tpl
+                .quasis
+                .first()
+                .and_then(|quasi| quasi.cooked.as_ref())
+                .map(|cooked| self.relative_to_config(cooked)),
+            Expr::Paren(paren) => self.eval_path(&paren.expr),
+            Expr::Ident(ident) if ident.sym == *"__dirname" => Some(self.dir.to_string()),
+            // `import.meta.dirname`
+            Expr::Member(member)
+                if matches!(&*member.obj, Expr::MetaProp(meta) if meta.kind == MetaPropKind::ImportMeta)
+                    && matches!(&member.prop, MemberProp::Ident(prop) if prop.sym == *"dirname") =>
+            {
+                Some(self.dir.to_string())
+            }
+            // `new URL("./src", import.meta.url)`
+            Expr::New(new) if matches!(&*new.callee, Expr::Ident(ident) if ident.sym == *"URL") => {
+                let arg = new.args.as_ref()?.first()?;
+                self.eval_path(&arg.expr)
+            }
+            Expr::Call(call) => {
+                let Callee::Expr(callee) = &call.callee else {
+                    return None;
+                };
+                let name = match &**callee {
+                    Expr::Ident(ident) => ident.sym.as_str(),
+                    Expr::Member(member) => match &member.prop {
+                        MemberProp::Ident(prop) => prop.sym.as_str(),
+                        _ => return None,
+                    },
+                    _ => return None,
+                };
+                match name {
+                    // `process.cwd()` is usually the directory of the config
+                    "cwd" => Some(self.dir.to_string()),
+                    "fileURLToPath" => self.eval_path(&call.args.first()?.expr),
+                    // `path.resolve` and `path.join`. Each absolute segment
+                    // replaces the path so far, like `path.resolve` does.
+                    "resolve" | "join" => {
+                        let mut path = self.dir.to_owned();
+                        for arg in &call.args {
+                            path = AbsoluteSystemPathBuf::from_unknown(
+                                &path,
+                                self.eval_path(&arg.expr)?,
+                            );
+                        }
+                        Some(path.clean().ok()?.to_string())
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn relative_to_config(&self, path: &str) -> String {
+        if path == "." || path.starts_with("./") || path.starts_with("../") {
+            AbsoluteSystemPathBuf::from_unknown(self.dir, path)
+                .clean()
+                .map_or_else(|_| path.to_string(), |path| path.to_string())
+        } else {
+            path.to_string()
+        }
+    }
+}
+
+fn prop_name(name: &PropName) -> Option<String> {
+    match name {
+        PropName::Ident(ident) => Some(ident.sym.to_string()),
+        PropName::Str(str) => Some(str.value.to_string()),
+        _ => None,
+    }
+}
+
+impl Visit for AliasFinder<'_> {
+    fn visit_key_value_prop(&mut self, prop: &KeyValueProp) {
+        if prop_name(&prop.key).as_deref() != Some("alias") {
+            prop.visit_children_with(self);
+            return;
+        }
+
+        match &*prop.value {
+            // `alias: { "@": "./src" }`
+            Expr::Object(object) => {
+                for prop in &object.props {
+                    if let PropOrSpread::Prop(prop) = prop {
+                        if let Prop::KeyValue(alias) = &**prop {
+                            if let Some(key) = prop_name(&alias.key) {
+                                self.add(key, &alias.value);
+                            }
+                        }
+                    }
+                }
+            }
+            // Vite's `alias: [{ find: "@", replacement: "./src" }]`
+            Expr::Array(array) => {
+                for element in array.elems.iter().flatten() {
+                    let Expr::Object(object) = &*element.expr else {
+                        continue;
+                    };
+                    let field = |name: &str| {
+                        object.props.iter().find_map(|prop| match prop {
+                            PropOrSpread::Prop(prop) => match &**prop {
+                                Prop::KeyValue(field)
+                                    if prop_name(&field.key).as_deref() == Some(name) =>
+                                {
+                                    Some(&*field.value)
+                                }
+                                _ => None,
+                            },
+                            _ => None,
+                        })
+                    };
+                    // `find` can also be a regex, which can't be used
+                    if let (Some(Expr::Lit(Lit::Str(find))), Some(replacement)) =
+                        (field("find"), field("replacement"))
+                    {
+                        self.add(find.value.to_string(), replacement);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use oxc_resolver::{Alias, AliasValue};
+    use tempfile::tempdir;
+    use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
+
+    use super::BundlerAliases;
+
+    /// The aliases as `(alias, path)`, where the path is `None` for aliases
+    /// that are ignored
+    fn describe(aliases: Alias) -> Vec<(String, Option<String>)> {
+        aliases
+            .into_iter()
+            .flat_map(|(key, values)| {
+                values.into_iter().map(move |value| {
+                    let path = match value {
+                        AliasValue::Path(path) => Some(path),
+                        AliasValue::Ignore => None,
+                    };
+                    (key.clone(), path)
+                })
+            })
+            .collect()
+    }
+
+    fn aliases_with_config(name: &str, config: &str) -> (AbsoluteSystemPathBuf, Alias) {
+        let tmp = tempdir().unwrap();
+        let root = AbsoluteSystemPathBuf::try_from(tmp.path()).unwrap();
+        root.join_component(name)
+            .create_with_contents(config)
+            .unwrap();
+        let aliases =
+            BundlerAliases::default().for_file(&root, &root.join_components(&["src", "index.ts"]));
+        (root, aliases)
+    }
+
+    fn path(root: &AbsoluteSystemPath, components: &[&str]) -> Option<String> {
+        Some(root.join_components(components).to_string())
+    }
+
+    #[test]
+    fn test_webpack_config() {
+        let (root, aliases) = aliases_with_config(
+            "webpack.config.js",
+            r#"
+            const path = require("path");
+            module.exports = {
+              resolve: {
+                alias: {
+                  "@": path.resolve(__dirname, "src"),
+                  utils: "./src/utils",
+                  react: "preact/compat",
+                  "ignored-module": false,
+                  dynamic: getAlias(),
+                },
+              },
+            };
+            "#,
+        );
+
+        assert_eq!(
+            describe(aliases),
+            vec![
+                ("@".to_string(), path(&root, &["src"])),
+                ("utils".to_string(), path(&root, &["src", "utils"])),
+                ("react".to_string(), Some("preact/compat".to_string())),
+                ("ignored-module".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_vite_config() {
+        let (root, aliases) = aliases_with_config(
+            "vite.config.ts",
+            r#"
+            import { fileURLToPath } from "node:url";
+            import { defineConfig } from "vite";
+
+            export default defineConfig({
+              resolve: {
+                alias: [
+                  { find: "@", replacement: fileURLToPath(new URL("./src", import.meta.url)) },
+                  { find: /^~/, replacement: "" },
+                ],
+              },
+            });
+            "#,
+        );
+
+        assert_eq!(
+            describe(aliases),
+            vec![("@".to_string(), path(&root, &["src"]))]
+        );
+    }
+
+    #[test]
+    fn test_rspack_config() {
+        let (root, aliases) = aliases_with_config(
+            "rspack.config.mjs",
+            r#"
+            import path from "node:path";
+
+            export default {
+              resolve: {
+                alias: {
+                  "~": path.join(import.meta.dirname, "lib"),
+                  config: `${import.meta.dirname}/config`,
+                },
+              },
+            };
+            "#,
+        );
+
+        assert_eq!(
+            describe(aliases),
+            vec![("~".to_string(), path(&root, &["lib"]))]
+        );
+    }
+
+    #[test]
+    fn test_closest_config_is_used() {
+        let tmp = tempdir().unwrap();
+        let root = AbsoluteSystemPathBuf::try_from(tmp.path()).unwrap();
+        root.join_component("vite.config.js")
+            .create_with_contents(r#"export default { resolve: { alias: { "@": "./root" } } };"#)
+            .unwrap();
+        let app = root.join_components(&["apps", "web"]);
+        app.create_dir_all().unwrap();
+        app.join_component("webpack.config.cjs")
+            .create_with_contents(r#"module.exports = { resolve: { alias: { "@": "./app" } } };"#)
+            .unwrap();
+
+        let aliases = BundlerAliases::default();
+        assert_eq!(
+            describe(aliases.for_file(&root, &app.join_components(&["src", "index.ts"]))),
+            vec![("@".to_string(), path(&app, &["app"]))]
+        );
+        assert_eq!(
+            describe(aliases.for_file(&root, &root.join_component("index.ts"))),
+            vec![("@".to_string(), path(&root, &["root"]))]
+        );
+        // Configs above the root aren't used
+        let package = root.join_components(&["packages", "ui"]);
+        assert!(aliases
+            .for_file(&package, &package.join_components(&["src", "index.ts"]))
+            .is_empty());
+    }
+}
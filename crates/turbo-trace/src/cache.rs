@@ -0,0 +1,516 @@
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hasher,
+    io,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf, RelativeUnixPath};
+
+use crate::{
+    import_finder::{ImportKind, ImportType},
+    tracer::{ImportEdge, SeenFile, UnresolvedDynamicImport},
+};
+
+/// Bumped whenever the format of the cache changes, so old caches are
+/// ignored instead of being misread
+#[allow(dead_code)]
+const CACHE_VERSION: u32 = 5;
+
+/// The resolved imports of traced files, saved to disk so later traces only
+/// parse and resolve the files that changed.
+///
+/// Entries are keyed by the content hash of the file and a hash of what its
+/// imports were resolved with, i.e. the resolver options, the contents of the
+/// tsconfig and the ones it extends, the import type and the workspace
+/// packages. Each entry also records the `package.json`s its imports were
+/// resolved through and the entries of the directories they were resolved
+/// in, which are checked when it's read. Files with errors aren't cached, so
+/// their errors are reported every time.
+#[derive(Debug, Default)]
+pub struct TraceCache {
+    /// Where the cache is saved, or `None` if nothing is cached
+    path: Option<AbsoluteSystemPathBuf>,
+    files: Mutex<HashMap<AbsoluteSystemPathBuf, CachedImports>>,
+    /// The hashes of the inputs that have been read, so each one is only
+    /// read once however many entries depend on it
+    input_hashes: Mutex<HashMap<AbsoluteSystemPathBuf, Option<u64>>>,
+    changed: AtomicBool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheFile {
+    version: u32,
+    files: HashMap<String, CachedImports>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct CachedImports {
+    content_hash: u64,
+    resolution_hash: u64,
+    edges: Vec<CachedEdge>,
+    unresolved_dynamic_imports: Vec<UnresolvedDynamicImport>,
+    inputs: Vec<CachedInput>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct CachedEdge {
+    to: String,
+    specifier: String,
+    kind: ImportKind,
+    import_type: ImportType,
+    start: usize,
+    end: usize,
+}
+
+/// A file or directory the imports were resolved with, and its hash, or
+/// `None` if it couldn't be read
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct CachedInput {
+    path: String,
+    kind: InputKind,
+    hash: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum InputKind {
+    /// A file, hashed by its contents
+    File,
+    /// A directory, hashed by the names of its entries. Adding a file can
+    /// change what an import resolves to, e.g. `./utils` resolves to
+    /// `utils.ts` over `utils/index.ts`.
+    Directory,
+}
+
+pub fn content_hash(contents: &[u8]) -> u64 {
+    let mut hasher = twox_hash::XxHash64::with_seed(0);
+    hasher.write(contents);
+    hasher.finish()
+}
+
+/// Hashes the names of the entries in `dir`, or returns `None` if it can't
+/// be read
+fn listing_hash(dir: &AbsoluteSystemPath) -> Option<u64> {
+    let mut names = std::fs::read_dir(dir.as_std_path())
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name())
+        .collect::<Vec<_>>();
+    names.sort();
+    let mut hasher = twox_hash::XxHash64::with_seed(0);
+    for name in names {
+        hasher.write(name.as_encoded_bytes());
+        hasher.write_u8(0);
+    }
+    Some(hasher.finish())
+}
+
+impl TraceCache {
+    /// Loads the cache saved at `path`. A cache that's missing, unreadable or
+    /// from another version of the tracer is treated as empty.
+    #[allow(dead_code)]
+    pub fn load(path: AbsoluteSystemPathBuf) -> Self {
+        let files = path
+            .read_to_string()
+            .ok()
+            .and_then(|contents| serde_json::from_str::<CacheFile>(&contents).ok())
+            .filter(|cache| cache.version == CACHE_VERSION)
+            .map(|cache| {
+                cache
+                    .files
+                    .into_iter()
+                    .filter_map(|(path, imports)| {
+                        Some((AbsoluteSystemPathBuf::new(path).ok()?, imports))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            path: Some(path),
+            files: Mutex::new(files),
+            input_hashes: Mutex::default(),
+            changed: AtomicBool::new(false),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.path.is_some()
+    }
+
+    /// Gets the imports of `file` if they were cached with the same contents
+    /// and resolution inputs. Imports that no longer exist, or files added
+    /// where imports are resolved, mean the file could resolve differently,
+    /// so they're a miss too.
+    pub fn get(
+        &self,
+        file: &AbsoluteSystemPath,
+        content_hash: u64,
+        resolution_hash: u64,
+    ) -> Option<(Vec<AbsoluteSystemPathBuf>, SeenFile)> {
+        let cached = self
+            .files
+            .lock()
+            .expect("lock poisoned")
+            .get(file)
+            .filter(|cached| {
+                cached.content_hash == content_hash && cached.resolution_hash == resolution_hash
+            })?
+            .clone();
+
+        // Checked without holding the lock, since it reads the filesystem
+        let edges = cached
+            .edges
+            .into_iter()
+            .map(|edge| {
+                Some(ImportEdge {
+                    to: AbsoluteSystemPathBuf::new(edge.to).ok()?,
+                    specifier: edge.specifier,
+                    kind: edge.kind,
+                    import_type: edge.import_type,
+                    start: edge.start,
+                    end: edge.end,
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+        if !edges.iter().all(|edge| edge.to.exists()) {
+            return None;
+        }
+        let inputs_match = cached.inputs.iter().all(|input| {
+            AbsoluteSystemPathBuf::new(input.path.as_str())
+                .is_ok_and(|path| self.hash_input(&path, input.kind) == input.hash)
+        });
+        if !inputs_match {
+            return None;
+        }
+        debug!("using cached imports of {}", file);
+
+        let seen_file = SeenFile::new(None, edges, cached.unresolved_dynamic_imports);
+        Some((seen_file.imports.clone(), seen_file))
+    }
+
+    /// Hashes the contents of `path`, or returns `None` if it can't be read.
+    /// The hash is kept for the life of the cache, so files that change while
+    /// a trace is running are only noticed by the next one.
+    pub(crate) fn input_hash(&self, path: &AbsoluteSystemPath) -> Option<u64> {
+        self.hash_input(path, InputKind::File)
+    }
+
+    fn hash_input(&self, path: &AbsoluteSystemPath, kind: InputKind) -> Option<u64> {
+        if let Some(hash) = self.input_hashes.lock().expect("lock poisoned").get(path) {
+            return *hash;
+        }
+        let hash = match kind {
+            InputKind::File => path.read().ok().map(|contents| content_hash(&contents)),
+            InputKind::Directory => listing_hash(path),
+        };
+        self.input_hashes
+            .lock()
+            .expect("lock poisoned")
+            .insert(path.to_owned(), hash);
+        hash
+    }
+
+    pub fn insert(
+        &self,
+        file: &AbsoluteSystemPath,
+        content_hash: u64,
+        resolution_hash: u64,
+        seen_file: &SeenFile,
+    ) {
+        if !self.is_enabled() {
+            return;
+        }
+        let cached = CachedImports {
+            content_hash,
+            resolution_hash,
+            edges: seen_file
+                .edges
+                .iter()
+                .map(|edge| CachedEdge {
+                    to: edge.to.to_string(),
+                    specifier: edge.specifier.clone(),
+                    kind: edge.kind,
+                    import_type: edge.import_type,
+                    start: edge.start,
+                    end: edge.end,
+                })
+                .collect(),
+            unresolved_dynamic_imports: seen_file.unresolved_dynamic_imports.clone(),
+            inputs: resolution_inputs(file, &seen_file.edges)
+                .into_iter()
+                .map(|(path, kind)| CachedInput {
+                    hash: self.hash_input(&path, kind),
+                    path: path.to_string(),
+                    kind,
+                })
+                .collect(),
+        };
+
+        let mut files = self.files.lock().expect("lock poisoned");
+        // Files that were traced again without changing don't need saving
+        if files.get(file) != Some(&cached) {
+            files.insert(file.to_owned(), cached);
+            self.changed.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Saves the cache if anything was added since it was loaded or last
+    /// saved. Entries for files that were deleted are dropped.
+    #[allow(dead_code)]
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if !self.changed.swap(false, Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        // Copied so the filesystem isn't read while holding the lock
+        let files = self.files.lock().expect("lock poisoned").clone();
+        let cache = CacheFile {
+            version: CACHE_VERSION,
+            files: files
+                .into_iter()
+                .filter(|(file, _)| file.exists())
+                .map(|(file, imports)| (file.to_string(), imports))
+                .collect(),
+        };
+
+        path.ensure_dir()?;
+        // Written to a temporary file first so a trace that's running at the
+        // same time never reads half of the cache
+        let temp_path =
+            AbsoluteSystemPathBuf::new(format!("{path}.tmp")).expect("path is still absolute");
+        temp_path.create_with_contents(serde_json::to_string(&cache)?)?;
+        temp_path.rename(path)
+    }
+}
+
+/// The files and directories that decide what the imports of `file` resolve
+/// to.
+///
+/// These are the `package.json`s of `file`, for `imports` and `type`, and of
+/// the packages it imports, for `exports` and `main`. Reinstalling a package
+/// changes its `package.json` too, since it has the version. They're also the
+/// directories that relative imports are looked up in and that the imports
+/// resolved to, since a file added there could be picked over the current
+/// one.
+fn resolution_inputs(
+    file: &AbsoluteSystemPath,
+    edges: &[ImportEdge],
+) -> Vec<(AbsoluteSystemPathBuf, InputKind)> {
+    let file_dir = file.parent();
+    let dirs = std::iter::once(file)
+        .chain(edges.iter().map(|edge| &*edge.to))
+        .filter_map(|path| path.parent())
+        .collect::<HashSet<_>>();
+    let package_jsons = dirs.iter().filter_map(|dir| {
+        dir.ancestors()
+            .map(|dir| dir.join_component("package.json"))
+            .find(|package_json| package_json.exists())
+    });
+
+    // `./utils` is looked up in the importing file's directory, as `utils.ts`,
+    // `utils/index.ts` and so on
+    let lookup_dirs = edges
+        .iter()
+        .filter(|edge| edge.specifier.starts_with('.'))
+        .filter_map(|edge| {
+            let specifier = RelativeUnixPath::new(&edge.specifier).ok()?;
+            file_dir?
+                .join_unix_path(specifier)
+                .parent()
+                .map(|dir| dir.to_owned())
+        });
+    let import_dirs = edges
+        .iter()
+        .filter_map(|edge| edge.to.parent())
+        .map(|dir| dir.to_owned());
+
+    let mut inputs = package_jsons
+        .map(|path| (path, InputKind::File))
+        .chain(
+            lookup_dirs
+                .chain(import_dirs)
+                .map(|dir| (dir, InputKind::Directory)),
+        )
+        .collect::<Vec<_>>();
+    inputs.sort_by(|(a, _), (b, _)| a.cmp(b));
+    inputs.dedup_by(|(a, _), (b, _)| a == b);
+    inputs
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+    use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
+
+    use super::TraceCache;
+    use crate::{
+        import_finder::{ImportKind, ImportType},
+        tracer::{ImportEdge, SeenFile},
+    };
+
+    /// A repo where `src/index.ts` imports the `pkg` package
+    fn setup(root: &AbsoluteSystemPath) -> (AbsoluteSystemPathBuf, SeenFile) {
+        let file = root.join_components(&["src", "index.ts"]);
+        let imported = root.join_components(&["node_modules", "pkg", "index.js"]);
+        for (path, contents) in [
+            (root.join_component("package.json"), r#"{"name": "root"}"#),
+            (file.clone(), r#"import "pkg";"#),
+            (
+                root.join_components(&["node_modules", "pkg", "package.json"]),
+                r#"{"name": "pkg", "main": "index.js"}"#,
+            ),
+            (imported.clone(), ""),
+        ] {
+            path.ensure_dir().unwrap();
+            path.create_with_contents(contents).unwrap();
+        }
+
+        let edges = vec![ImportEdge {
+            to: imported,
+            specifier: "pkg".to_string(),
+            kind: ImportKind::Static,
+            import_type: ImportType::Value,
+            start: 0,
+            end: 13,
+        }];
+        (file, SeenFile::new(None, edges, Vec::new()))
+    }
+
+    fn cache_path(root: &AbsoluteSystemPath) -> AbsoluteSystemPathBuf {
+        root.join_components(&[".turbo", "trace", "imports.json"])
+    }
+
+    /// Caches the imports of `file` and loads the saved cache, like the next
+    /// trace would
+    fn save_and_reload(
+        root: &AbsoluteSystemPath,
+        file: &AbsoluteSystemPath,
+        seen_file: &SeenFile,
+    ) -> TraceCache {
+        let cache = TraceCache::load(cache_path(root));
+        cache.insert(file, 1, 2, seen_file);
+        cache.save().unwrap();
+        TraceCache::load(cache_path(root))
+    }
+
+    #[test]
+    fn test_get_cached_imports() {
+        let tmp = tempdir().unwrap();
+        let root = AbsoluteSystemPathBuf::try_from(tmp.path()).unwrap();
+        let (file, seen_file) = setup(&root);
+
+        let cache = save_and_reload(&root, &file, &seen_file);
+        let (imports, cached) = cache.get(&file, 1, 2).unwrap();
+        assert_eq!(imports, seen_file.imports);
+        assert_eq!(cached.edges, seen_file.edges);
+
+        assert!(cache.get(&file, 3, 2).is_none());
+        assert!(cache.get(&file, 1, 3).is_none());
+    }
+
+    #[test]
+    fn test_deleted_import_is_a_miss() {
+        let tmp = tempdir().unwrap();
+        let root = AbsoluteSystemPathBuf::try_from(tmp.path()).unwrap();
+        let (file, seen_file) = setup(&root);
+
+        let cache = save_and_reload(&root, &file, &seen_file);
+        seen_file.edges[0].to.remove_file().unwrap();
+        assert!(cache.get(&file, 1, 2).is_none());
+    }
+
+    #[test]
+    fn test_changed_package_json_is_a_miss() {
+        let tmp = tempdir().unwrap();
+        let root = AbsoluteSystemPathBuf::try_from(tmp.path()).unwrap();
+        let (file, seen_file) = setup(&root);
+
+        save_and_reload(&root, &file, &seen_file);
+        root.join_components(&["node_modules", "pkg", "package.json"])
+            .create_with_contents(r#"{"name": "pkg", "exports": "./other.js"}"#)
+            .unwrap();
+        let cache = TraceCache::load(cache_path(&root));
+        assert!(cache.get(&file, 1, 2).is_none());
+
+        // The importing file's own package.json is an input too, since it
+        // has `imports` and `type`
+        let cache = save_and_reload(&root, &file, &seen_file);
+        assert!(cache.get(&file, 1, 2).is_some());
+        root.join_component("package.json")
+            .create_with_contents(r#"{"name": "root", "type": "module"}"#)
+            .unwrap();
+        let cache = TraceCache::load(cache_path(&root));
+        assert!(cache.get(&file, 1, 2).is_none());
+    }
+
+    #[test]
+    fn test_added_file_that_would_resolve_is_a_miss() {
+        let tmp = tempdir().unwrap();
+        let root = AbsoluteSystemPathBuf::try_from(tmp.path()).unwrap();
+        let file = root.join_components(&["src", "index.ts"]);
+        let imported = root.join_components(&["src", "utils", "index.ts"]);
+        for (path, contents) in [
+            (root.join_component("package.json"), r#"{"name": "root"}"#),
+            (file.clone(), r#"import "./utils";"#),
+            (imported.clone(), ""),
+        ] {
+            path.ensure_dir().unwrap();
+            path.create_with_contents(contents).unwrap();
+        }
+        let seen_file = SeenFile::new(
+            None,
+            vec![ImportEdge {
+                to: imported,
+                specifier: "./utils".to_string(),
+                kind: ImportKind::Static,
+                import_type: ImportType::Value,
+                start: 0,
+                end: 17,
+            }],
+            Vec::new(),
+        );
+
+        let cache = save_and_reload(&root, &file, &seen_file);
+        assert!(cache.get(&file, 1, 2).is_some());
+
+        // `./utils` now resolves to `utils.ts` instead
+        root.join_components(&["src", "utils.ts"])
+            .create_with_contents("")
+            .unwrap();
+        let cache = TraceCache::load(cache_path(&root));
+        assert!(cache.get(&file, 1, 2).is_none());
+    }
+
+    #[test]
+    fn test_save_only_when_changed() {
+        let tmp = tempdir().unwrap();
+        let root = AbsoluteSystemPathBuf::try_from(tmp.path()).unwrap();
+        let (file, seen_file) = setup(&root);
+
+        let cache = TraceCache::load(cache_path(&root));
+        cache.save().unwrap();
+        assert!(!cache_path(&root).exists());
+
+        let cache = save_and_reload(&root, &file, &seen_file);
+        assert!(cache_path(&root).exists());
+
+        // Tracing the file again with the same result isn't a change
+        cache_path(&root).remove_file().unwrap();
+        cache.insert(&file, 1, 2, &seen_file);
+        cache.save().unwrap();
+        assert!(!cache_path(&root).exists());
+
+        cache.insert(&file, 3, 2, &seen_file);
+        cache.save().unwrap();
+        assert!(cache_path(&root).exists());
+    }
+}
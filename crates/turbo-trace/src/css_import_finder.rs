@@ -0,0 +1,249 @@
+/// The file extensions of stylesheets we trace imports in
+pub const STYLESHEET_EXTENSIONS: &[&str] = &["css", "scss", "sass", "less"];
+
+/// An `@import`, `@use` or `@forward` in a stylesheet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CssImport {
+    pub specifier: String,
+    /// Byte offset of the start of the at-rule
+    pub start: usize,
+    /// Byte offset of the end of the imported specifier
+    pub end: usize,
+}
+
+/// Finds the imports in a CSS, SCSS or LESS stylesheet.
+///
+/// This isn't a full parser, it only understands enough of the syntax to find
+/// at-rules outside of strings and comments. Imports of remote URLs are
+/// skipped since they don't correspond to a file.
+pub fn find_css_imports(source: &str, extension: Option<&str>) -> Vec<CssImport> {
+    let line_comments = matches!(extension, Some("scss" | "sass" | "less"));
+    let bytes = source.as_bytes();
+    let mut imports = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = source[i + 2..]
+                    .find("*/")
+                    .map_or(bytes.len(), |end| i + end + 4);
+            }
+            b'/' if line_comments && bytes.get(i + 1) == Some(&b'/') => {
+                i = source[i..].find('\n').map_or(bytes.len(), |end| i + end);
+            }
+            quote @ (b'"' | b'\'') => {
+                i = string_end(bytes, i + 1, quote).unwrap_or(bytes.len());
+            }
+            b'@' => {
+                let rule_start = i;
+                let name_end = bytes[i + 1..]
+                    .iter()
+                    .position(|b| !b.is_ascii_alphabetic())
+                    .map_or(bytes.len(), |end| i + 1 + end);
+                let name = &source[i + 1..name_end];
+                i = name_end;
+                match name {
+                    "import" => {
+                        // `@import a, b;` imports multiple stylesheets
+                        loop {
+                            i = skip_whitespace(bytes, i);
+                            // LESS allows options before the specifier, e.g.
+                            // `@import (reference) "foo";`
+                            if bytes.get(i) == Some(&b'(') {
+                                i = source[i..].find(')').map_or(bytes.len(), |end| i + end + 1);
+                                i = skip_whitespace(bytes, i);
+                            }
+                            let Some((specifier, end)) = read_specifier(source, i) else {
+                                break;
+                            };
+                            add_import(&mut imports, specifier, rule_start, end);
+                            i = skip_whitespace(bytes, end);
+                            if bytes.get(i) != Some(&b',') {
+                                break;
+                            }
+                            i += 1;
+                        }
+                    }
+                    "use" | "forward" => {
+                        let start = skip_whitespace(bytes, i);
+                        if let Some((specifier, end)) = read_specifier(source, start) {
+                            // `sass:math` and friends are built into Sass
+                            if !specifier.starts_with("sass:") {
+                                add_import(&mut imports, specifier, rule_start, end);
+                            }
+                            i = end;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            _ => i += 1,
+        }
+    }
+
+    imports
+}
+
+fn add_import(imports: &mut Vec<CssImport>, specifier: &str, start: usize, end: usize) {
+    let is_remote = ["http://", "https://", "//", "data:"]
+        .iter()
+        .any(|prefix| specifier.starts_with(prefix));
+    if !is_remote && !specifier.is_empty() {
+        imports.push(CssImport {
+            specifier: specifier.to_string(),
+            start,
+            end,
+        });
+    }
+}
+
+/// Returns the index after the closing quote of a string whose contents start
+/// at `i`, or `None` if the string is never closed
+fn string_end(bytes: &[u8], mut i: usize, quote: u8) -> Option<usize> {
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b if b == quote => return Some(i + 1),
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+fn skip_whitespace(bytes: &[u8], mut i: usize) -> usize {
+    while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+        i += 1;
+    }
+    i
+}
+
+/// Reads a quoted string or `url(...)` starting at `i`, returning its contents
+/// and the index after it
+fn read_specifier(source: &str, i: usize) -> Option<(&str, usize)> {
+    let bytes = source.as_bytes();
+    match bytes.get(i)? {
+        quote @ (b'"' | b'\'') => {
+            let end = string_end(bytes, i + 1, *quote)?;
+            Some((&source[i + 1..end - 1], end))
+        }
+        _ if source[i..].starts_with("url(") => {
+            let close = source[i..].find(')').map(|end| i + end)?;
+            let inner = source[i + 4..close].trim();
+            let inner = inner
+                .strip_prefix(['"', '\''])
+                .and_then(|inner| inner.strip_suffix(['"', '\'']))
+                .unwrap_or(inner);
+            Some((inner, close + 1))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn specifiers(source: &str, extension: &str) -> Vec<String> {
+        find_css_imports(source, Some(extension))
+            .into_iter()
+            .map(|import| import.specifier)
+            .collect()
+    }
+
+    #[test]
+    fn test_import_strings() {
+        assert_eq!(
+            specifiers("@import \"./reset.css\";\n@import './theme.css';", "css"),
+            vec!["./reset.css", "./theme.css"]
+        );
+    }
+
+    #[test]
+    fn test_import_url() {
+        assert_eq!(
+            specifiers(
+                "@import url(./reset.css);\n@import url( \"./theme.css\" );\n@import \
+                 url('./print.css') print;",
+                "css"
+            ),
+            vec!["./reset.css", "./theme.css", "./print.css"]
+        );
+    }
+
+    #[test]
+    fn test_import_offsets() {
+        let source = "a {}\n@import url(\"./reset.css\") screen;";
+        let imports = find_css_imports(source, Some("css"));
+        assert_eq!(
+            imports,
+            vec![CssImport {
+                specifier: "./reset.css".to_string(),
+                start: 5,
+                end: 31,
+            }]
+        );
+        assert_eq!(&source[5..31], "@import url(\"./reset.css\")");
+    }
+
+    #[test]
+    fn test_import_several() {
+        assert_eq!(
+            specifiers("@import \"a.css\", url(b.css), 'c.css';", "css"),
+            vec!["a.css", "b.css", "c.css"]
+        );
+    }
+
+    #[test]
+    fn test_remote_imports_are_skipped() {
+        assert_eq!(
+            specifiers(
+                "@import url(https://example.com/a.css);\n@import \
+                 \"//cdn.example.com/b.css\";\n@import url(data:text/css,a{});\n@import \
+                 \"./local.css\";",
+                "css"
+            ),
+            vec!["./local.css"]
+        );
+    }
+
+    #[test]
+    fn test_imports_in_comments_and_strings_are_skipped() {
+        let source = "/* @import \"a.css\"; */\na::before { content: \"@import 'b.css'\"; \
+                      }\n@import \"c.css\";";
+        assert_eq!(specifiers(source, "css"), vec!["c.css"]);
+    }
+
+    #[test]
+    fn test_line_comments() {
+        let source = "// @import \"a.scss\";\n@import \"b.scss\";";
+        assert_eq!(specifiers(source, "scss"), vec!["b.scss"]);
+        // `//` doesn't start a comment in plain CSS
+        assert_eq!(
+            specifiers(
+                "a { background: url(//example.com/a.png); }\n@import \"b.css\";",
+                "css"
+            ),
+            vec!["b.css"]
+        );
+    }
+
+    #[test]
+    fn test_sass_use_and_forward() {
+        assert_eq!(
+            specifiers(
+                "@use \"sass:math\";\n@use \"./variables\" as vars;\n@forward 'src/list';",
+                "scss"
+            ),
+            vec!["./variables", "src/list"]
+        );
+    }
+
+    #[test]
+    fn test_less_import_options() {
+        assert_eq!(
+            specifiers("@import (reference) \"./mixins.less\";", "less"),
+            vec!["./mixins.less"]
+        );
+    }
+}
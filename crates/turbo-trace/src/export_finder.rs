@@ -0,0 +1,206 @@
+use swc_common::Span;
+use swc_ecma_ast::{
+    Decl, ExportSpecifier, ModuleDecl, ModuleExportName, ObjectPatProp, Pat, TsModuleName,
+};
+use swc_ecma_visit::Visit;
+
+use crate::import_finder::ImportedNames;
+
+/// What kind of declaration an export refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportKind {
+    Function,
+    Class,
+    Variable,
+    Interface,
+    TypeAlias,
+    Enum,
+    Module,
+    /// `export default ...` or `export = ...`
+    Default,
+    /// `export { foo }`, where `foo` is declared elsewhere in the file
+    Named,
+    /// `export { foo } from "./foo"`
+    Reexport,
+    /// `export * as foo from "./foo"`
+    Namespace,
+    /// `export * from "./foo"`
+    All,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Export {
+    /// The name the export is visible as from other files. This is `default`
+    /// for default exports and `*` for `export * from`.
+    pub name: String,
+    pub kind: ExportKind,
+    pub span: Span,
+    /// Where the export comes from, if it's re-exported from another module
+    pub reexport: Option<Reexport>,
+}
+
+/// The module an export is re-exported from, along with the exports of that
+/// module it uses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reexport {
+    pub specifier: String,
+    pub names: ImportedNames,
+}
+
+/// Finds the top level exports of a module.
+#[derive(Default)]
+pub struct ExportFinder {
+    exports: Vec<Export>,
+}
+
+impl ExportFinder {
+    pub fn exports(&self) -> &[Export] {
+        &self.exports
+    }
+
+    fn add(&mut self, name: impl Into<String>, kind: ExportKind, span: Span) {
+        self.add_reexport(name, kind, span, None);
+    }
+
+    fn add_reexport(
+        &mut self,
+        name: impl Into<String>,
+        kind: ExportKind,
+        span: Span,
+        reexport: Option<Reexport>,
+    ) {
+        self.exports.push(Export {
+            name: name.into(),
+            kind,
+            span,
+            reexport,
+        });
+    }
+
+    fn add_pattern(&mut self, pat: &Pat, span: Span) {
+        match pat {
+            Pat::Ident(ident) => self.add(ident.id.sym.as_str(), ExportKind::Variable, span),
+            Pat::Array(array) => {
+                for elem in array.elems.iter().flatten() {
+                    self.add_pattern(elem, span);
+                }
+            }
+            Pat::Object(object) => {
+                for prop in &object.props {
+                    match prop {
+                        ObjectPatProp::KeyValue(key_value) => {
+                            self.add_pattern(&key_value.value, span)
+                        }
+                        ObjectPatProp::Assign(assign) => {
+                            self.add(assign.key.sym.as_str(), ExportKind::Variable, span)
+                        }
+                        ObjectPatProp::Rest(rest) => self.add_pattern(&rest.arg, span),
+                    }
+                }
+            }
+            Pat::Rest(rest) => self.add_pattern(&rest.arg, span),
+            Pat::Assign(assign) => self.add_pattern(&assign.left, span),
+            Pat::Invalid(_) | Pat::Expr(_) => {}
+        }
+    }
+}
+
+fn export_name(name: &ModuleExportName) -> String {
+    match name {
+        ModuleExportName::Ident(ident) => ident.sym.to_string(),
+        ModuleExportName::Str(s) => s.value.to_string(),
+    }
+}
+
+impl Visit for ExportFinder {
+    // Exports can only be at the top level of a module, so we don't visit
+    // any further down
+    fn visit_module_decl(&mut self, decl: &ModuleDecl) {
+        match decl {
+            ModuleDecl::ExportDecl(export) => match &export.decl {
+                Decl::Fn(f) => self.add(f.ident.sym.as_str(), ExportKind::Function, export.span),
+                Decl::Class(c) => self.add(c.ident.sym.as_str(), ExportKind::Class, export.span),
+                Decl::Var(var) => {
+                    for declarator in &var.decls {
+                        self.add_pattern(&declarator.name, export.span);
+                    }
+                }
+                Decl::TsInterface(i) => {
+                    self.add(i.id.sym.as_str(), ExportKind::Interface, export.span)
+                }
+                Decl::TsTypeAlias(t) => {
+                    self.add(t.id.sym.as_str(), ExportKind::TypeAlias, export.span)
+                }
+                Decl::TsEnum(e) => self.add(e.id.sym.as_str(), ExportKind::Enum, export.span),
+                Decl::TsModule(m) => {
+                    let name = match &m.id {
+                        TsModuleName::Ident(ident) => ident.sym.to_string(),
+                        TsModuleName::Str(s) => s.value.to_string(),
+                    };
+                    self.add(name, ExportKind::Module, export.span)
+                }
+                Decl::Using(_) => {}
+            },
+            ModuleDecl::ExportNamed(named) => {
+                let reexport = |names| {
+                    named.src.as_ref().map(|src| Reexport {
+                        specifier: src.value.to_string(),
+                        names,
+                    })
+                };
+                for specifier in &named.specifiers {
+                    match specifier {
+                        ExportSpecifier::Named(specifier) => {
+                            let name =
+                                export_name(specifier.exported.as_ref().unwrap_or(&specifier.orig));
+                            let kind = if named.src.is_some() {
+                                ExportKind::Reexport
+                            } else {
+                                ExportKind::Named
+                            };
+                            let names = ImportedNames::Named(vec![export_name(&specifier.orig)]);
+                            self.add_reexport(name, kind, named.span, reexport(names));
+                        }
+                        ExportSpecifier::Namespace(specifier) => {
+                            self.add_reexport(
+                                export_name(&specifier.name),
+                                ExportKind::Namespace,
+                                named.span,
+                                reexport(ImportedNames::All),
+                            );
+                        }
+                        ExportSpecifier::Default(specifier) => {
+                            self.add_reexport(
+                                specifier.exported.sym.as_str(),
+                                ExportKind::Reexport,
+                                named.span,
+                                reexport(ImportedNames::Named(vec!["default".to_string()])),
+                            );
+                        }
+                    }
+                }
+            }
+            ModuleDecl::ExportDefaultDecl(export) => {
+                self.add("default", ExportKind::Default, export.span)
+            }
+            ModuleDecl::ExportDefaultExpr(export) => {
+                self.add("default", ExportKind::Default, export.span)
+            }
+            ModuleDecl::TsExportAssignment(export) => {
+                self.add("default", ExportKind::Default, export.span)
+            }
+            ModuleDecl::ExportAll(export) => self.add_reexport(
+                "*",
+                ExportKind::All,
+                export.span,
+                Some(Reexport {
+                    specifier: export.src.value.to_string(),
+                    names: ImportedNames::All,
+                }),
+            ),
+            ModuleDecl::Import(_)
+            | ModuleDecl::TsImportEquals(_)
+            | ModuleDecl::TsNamespaceExport(_) => {}
+        }
+    }
+}
@@ -1,5 +1,9 @@
+use serde::{Deserialize, Serialize};
 use swc_common::{Span, Spanned};
-use swc_ecma_ast::{Decl, ModuleDecl, Stmt};
+use swc_ecma_ast::{
+    CallExpr, Callee, Decl, ExportSpecifier, Expr, ImportSpecifier, Lit, ModuleDecl,
+    ModuleExportName, Stmt,
+};
 use swc_ecma_visit::{Visit, VisitWith};
 
 use crate::tracer::ImportTraceType;
@@ -9,15 +13,58 @@ use crate::tracer::ImportTraceType;
 /// Either an import with a `type` keyword (indicating that it is importing only
 /// types) or an import without the `type` keyword (indicating that it is
 /// importing values and possibly types).
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ImportType {
     Type,
     Value,
 }
 
+/// The syntax used to import a module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ImportKind {
+    /// `import foo from "./foo"`
+    Static,
+    /// `import("./foo")`
+    Dynamic,
+    /// `require("./foo")`
+    Require,
+    /// `export { foo } from "./foo"` or `export * from "./foo"`
+    Reexport,
+}
+
+/// The exports of a module that an import uses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportedNames {
+    /// Every export of the module might be used, e.g. for namespace imports,
+    /// dynamic imports and requires.
+    All,
+    /// Only these exports are used. `default` is used for default imports.
+    /// This is empty for side effect imports like `import "./foo"`.
+    Named(Vec<String>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Import {
+    /// The module specifier, i.e. the string being imported
+    pub specifier: String,
+    pub span: Span,
+    pub import_type: ImportType,
+    pub kind: ImportKind,
+    pub names: ImportedNames,
+}
+
+/// A dynamic import or require whose argument isn't a string literal, e.g.
+/// `import(`./locales/${locale}`)`, so the imported file can't be known
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DynamicImport {
+    pub kind: ImportKind,
+    pub span: Span,
+}
+
 pub struct ImportFinder {
     import_type: ImportTraceType,
-    imports: Vec<(String, Span, ImportType)>,
+    imports: Vec<Import>,
+    dynamic_imports: Vec<DynamicImport>,
 }
 
 impl Default for ImportFinder {
@@ -31,38 +78,173 @@ impl ImportFinder {
         Self {
             import_type,
             imports: Vec::new(),
+            dynamic_imports: Vec::new(),
         }
     }
 
-    pub fn imports(&self) -> &[(String, Span, ImportType)] {
+    pub fn imports(&self) -> &[Import] {
         &self.imports
     }
+
+    pub fn dynamic_imports(&self) -> &[DynamicImport] {
+        &self.dynamic_imports
+    }
+
+    fn add_dynamic(&mut self, kind: ImportKind, span: Span) {
+        // Dynamic imports and requires always import values
+        if self.import_type != ImportTraceType::Types {
+            self.dynamic_imports.push(DynamicImport { kind, span });
+        }
+    }
+
+    fn add(
+        &mut self,
+        specifier: String,
+        span: Span,
+        import_type: ImportType,
+        kind: ImportKind,
+        names: ImportedNames,
+    ) {
+        let included = match self.import_type {
+            ImportTraceType::All => true,
+            ImportTraceType::Types => import_type == ImportType::Type,
+            ImportTraceType::Values => import_type == ImportType::Value,
+        };
+        if included {
+            self.imports.push(Import {
+                specifier,
+                span,
+                import_type,
+                kind,
+                names,
+            });
+        }
+    }
+}
+
+fn is_require(expr: &Expr) -> bool {
+    matches!(expr, Expr::Ident(ident) if ident.sym == *"require")
+}
+
+/// The type of an import with a `type` keyword
+fn import_type(type_only: bool) -> ImportType {
+    if type_only {
+        ImportType::Type
+    } else {
+        ImportType::Value
+    }
 }
 
 impl Visit for ImportFinder {
     fn visit_module_decl(&mut self, decl: &ModuleDecl) {
-        if let ModuleDecl::Import(import) = decl {
-            let import_type = if import.type_only {
-                ImportType::Type
-            } else {
-                ImportType::Value
-            };
-            match self.import_type {
-                ImportTraceType::All => {
-                    self.imports
-                        .push((import.src.value.to_string(), import.span, import_type));
+        match decl {
+            // `export { foo } from "./foo"` and `export * as foo from "./foo"`
+            ModuleDecl::ExportNamed(named) => {
+                let Some(src) = &named.src else {
+                    return;
+                };
+                let mut names = Vec::new();
+                for specifier in &named.specifiers {
+                    match specifier {
+                        ExportSpecifier::Named(specifier) => names.push(match &specifier.orig {
+                            ModuleExportName::Ident(ident) => ident.sym.to_string(),
+                            ModuleExportName::Str(s) => s.value.to_string(),
+                        }),
+                        ExportSpecifier::Default(_) => names.push("default".to_string()),
+                        ExportSpecifier::Namespace(_) => {
+                            self.add(
+                                src.value.to_string(),
+                                named.span,
+                                import_type(named.type_only),
+                                ImportKind::Reexport,
+                                ImportedNames::All,
+                            );
+                            return;
+                        }
+                    }
                 }
-                ImportTraceType::Types if import.type_only => {
-                    self.imports
-                        .push((import.src.value.to_string(), import.span, import_type));
+                self.add(
+                    src.value.to_string(),
+                    named.span,
+                    import_type(named.type_only),
+                    ImportKind::Reexport,
+                    ImportedNames::Named(names),
+                );
+                return;
+            }
+            ModuleDecl::ExportAll(export) => {
+                self.add(
+                    export.src.value.to_string(),
+                    export.span,
+                    import_type(export.type_only),
+                    ImportKind::Reexport,
+                    ImportedNames::All,
+                );
+                return;
+            }
+            _ => {}
+        }
+
+        if let ModuleDecl::Import(import) = decl {
+            let import_type = import_type(import.type_only);
+            let mut names = Vec::new();
+            for specifier in &import.specifiers {
+                match specifier {
+                    ImportSpecifier::Named(named) => names.push(match &named.imported {
+                        Some(ModuleExportName::Ident(ident)) => ident.sym.to_string(),
+                        Some(ModuleExportName::Str(s)) => s.value.to_string(),
+                        None => named.local.sym.to_string(),
+                    }),
+                    ImportSpecifier::Default(_) => names.push("default".to_string()),
+                    ImportSpecifier::Namespace(_) => {
+                        self.add(
+                            import.src.value.to_string(),
+                            import.span,
+                            import_type,
+                            ImportKind::Static,
+                            ImportedNames::All,
+                        );
+                        return;
+                    }
                 }
-                ImportTraceType::Values if !import.type_only => {
-                    self.imports
-                        .push((import.src.value.to_string(), import.span, import_type));
+            }
+            self.add(
+                import.src.value.to_string(),
+                import.span,
+                import_type,
+                ImportKind::Static,
+                ImportedNames::Named(names),
+            );
+        } else {
+            // Exported declarations, like `export const load = () =>
+            // import("./foo")`, can contain dynamic imports and requires
+            decl.visit_children_with(self);
+        }
+    }
+
+    fn visit_call_expr(&mut self, call_expr: &CallExpr) {
+        let arg = call_expr.args.first().map(|arg| &*arg.expr);
+        match &call_expr.callee {
+            Callee::Import(_) => match arg {
+                Some(Expr::Lit(Lit::Str(lit_str))) => self.add(
+                    lit_str.value.to_string(),
+                    call_expr.span,
+                    ImportType::Value,
+                    ImportKind::Dynamic,
+                    ImportedNames::All,
+                ),
+                Some(_) => self.add_dynamic(ImportKind::Dynamic, call_expr.span),
+                None => {}
+            },
+            // Requires with a string literal are found in `visit_stmt`
+            Callee::Expr(expr) if is_require(expr) => {
+                if arg.is_some_and(|arg| !matches!(arg, Expr::Lit(Lit::Str(_)))) {
+                    self.add_dynamic(ImportKind::Require, call_expr.span);
                 }
-                _ => {}
             }
+            _ => {}
         }
+        call_expr.visit_children_with(self);
     }
 
     fn visit_stmt(&mut self, stmt: &Stmt) {
@@ -78,11 +260,13 @@ impl Visit for ImportFinder {
                                             lit_str,
                                         )) = &*arg.expr
                                         {
-                                            self.imports.push((
+                                            self.add(
                                                 lit_str.value.to_string(),
                                                 expr.span(),
                                                 ImportType::Value,
-                                            ));
+                                                ImportKind::Require,
+                                                ImportedNames::All,
+                                            );
                                         }
                                     }
                                 }
@@ -0,0 +1,262 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt,
+    sync::Arc,
+};
+
+use swc_common::SourceMap;
+use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
+
+use crate::tracer::{SeenFile, TraceError, TraceResult};
+
+/// The imports of every source file in a repository, indexed by the file
+/// that's imported.
+///
+/// Building the graph parses every file, so it's meant to be built once with
+/// `Tracer::import_graph` and then asked about as many files as needed. It's
+/// a snapshot of the files when it was built, so it should be rebuilt when
+/// they change.
+pub struct ImportGraph {
+    source_map: Arc<SourceMap>,
+    files: HashMap<AbsoluteSystemPathBuf, SeenFile>,
+    /// The files that import each file, sorted by path
+    importers: HashMap<AbsoluteSystemPathBuf, Vec<AbsoluteSystemPathBuf>>,
+    errors: Vec<TraceError>,
+}
+
+impl fmt::Debug for ImportGraph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ImportGraph")
+            .field("files", &self.files)
+            .field("importers", &self.importers)
+            .field("errors", &self.errors)
+            .finish()
+    }
+}
+
+impl ImportGraph {
+    pub(crate) fn new(
+        source_map: Arc<SourceMap>,
+        files: HashMap<AbsoluteSystemPathBuf, SeenFile>,
+        errors: Vec<TraceError>,
+    ) -> Self {
+        let mut importers: HashMap<_, Vec<_>> = HashMap::new();
+        for (file, seen_file) in &files {
+            for import in &seen_file.imports {
+                importers
+                    .entry(import.clone())
+                    .or_default()
+                    .push(file.clone());
+            }
+        }
+        for importers in importers.values_mut() {
+            importers.sort();
+            importers.dedup();
+        }
+
+        Self {
+            source_map,
+            files,
+            importers,
+            errors,
+        }
+    }
+
+    /// The source map the files were parsed with, which the spans in
+    /// `errors` point into
+    #[allow(dead_code)]
+    pub fn source_map(&self) -> &Arc<SourceMap> {
+        &self.source_map
+    }
+
+    /// The files that were parsed to build the graph
+    #[allow(dead_code)]
+    pub fn files(&self) -> impl Iterator<Item = &AbsoluteSystemPath> {
+        self.files.keys().map(|file| file.as_ref())
+    }
+
+    /// The imports of `file`, or `None` if it isn't in the graph, e.g.
+    /// because it couldn't be parsed
+    #[allow(dead_code)]
+    pub fn file(&self, file: &AbsoluteSystemPath) -> Option<&SeenFile> {
+        self.files.get(file)
+    }
+
+    /// The errors from parsing and resolving the files in the graph
+    #[allow(dead_code)]
+    pub fn errors(&self) -> &[TraceError] {
+        &self.errors
+    }
+
+    /// The files that directly import `file`
+    #[allow(dead_code)]
+    pub fn importers_of(&self, file: &AbsoluteSystemPath) -> &[AbsoluteSystemPathBuf] {
+        self.importers.get(file).map_or(&[], |importers| importers)
+    }
+
+    /// Finds the files that import `files`, either directly or through other
+    /// files, up to `max_depth` levels away. A depth of 1 only includes files
+    /// that directly import one of `files`.
+    ///
+    /// `files` are included in the result, so cycles through them can be
+    /// found. Every error in the graph is included too, and the importers
+    /// that are further than `max_depth` away are in `truncated_files`.
+    pub fn dependents_of(
+        &self,
+        files: &[AbsoluteSystemPathBuf],
+        max_depth: Option<usize>,
+    ) -> TraceResult {
+        let mut usages = HashMap::new();
+        let mut truncated_files = HashSet::new();
+        for file in files {
+            if let Some(seen_file) = self.files.get(file) {
+                usages.insert(file.clone(), seen_file.clone());
+            }
+        }
+
+        let mut queue = files
+            .iter()
+            .map(|file| (file.as_ref(), 0))
+            .collect::<VecDeque<(&AbsoluteSystemPath, _)>>();
+        while let Some((file, depth)) = queue.pop_front() {
+            if max_depth.map_or(false, |max_depth| depth >= max_depth) {
+                truncated_files.extend(
+                    self.importers_of(file)
+                        .iter()
+                        .filter(|importer| !usages.contains_key(*importer))
+                        .cloned(),
+                );
+                continue;
+            }
+            for importer in self.importers_of(file) {
+                if usages.contains_key(importer) {
+                    continue;
+                }
+                let seen_file = self.files.get(importer).cloned().unwrap_or_default();
+                usages.insert(importer.clone(), seen_file);
+                queue.push_back((importer, depth + 1));
+            }
+        }
+
+        let mut truncated_files = truncated_files.into_iter().collect::<Vec<_>>();
+        truncated_files.sort();
+        TraceResult {
+            source_map: self.source_map.clone(),
+            files: usages,
+            errors: self.errors.clone(),
+            truncated_files,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+    use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
+
+    use super::ImportGraph;
+    use crate::tracer::{TraceError, Tracer};
+
+    /// Builds the graph of a repo where `a` imports `b`, `b` imports `c` and
+    /// `c` imports `b` back
+    async fn build_graph(root: &AbsoluteSystemPath) -> ImportGraph {
+        for (name, contents) in [
+            ("a.ts", r#"import { b } from "./b";"#),
+            ("b.ts", r#"import { c } from "./c"; export const b = 1;"#),
+            (
+                "c.ts",
+                r#"import type { b } from "./b"; import "./missing"; export const c = 1;"#,
+            ),
+            ("d.ts", "export const d = 1;"),
+        ] {
+            root.join_component(name)
+                .create_with_contents(contents)
+                .unwrap();
+        }
+        Tracer::new(root.to_owned(), Vec::new(), None)
+            .import_graph()
+            .await
+    }
+
+    fn sorted(
+        files: impl IntoIterator<Item = AbsoluteSystemPathBuf>,
+    ) -> Vec<AbsoluteSystemPathBuf> {
+        let mut files = files.into_iter().collect::<Vec<_>>();
+        files.sort();
+        files
+    }
+
+    #[tokio::test]
+    async fn test_importers_of() {
+        let tmp = tempdir().unwrap();
+        let root = AbsoluteSystemPathBuf::try_from(tmp.path()).unwrap();
+        let graph = build_graph(&root).await;
+        let file = |name| root.join_component(name);
+
+        assert_eq!(
+            sorted(graph.files().map(|file| file.to_owned())),
+            vec![file("a.ts"), file("b.ts"), file("c.ts"), file("d.ts")]
+        );
+        assert_eq!(
+            graph.importers_of(&file("b.ts")),
+            [file("a.ts"), file("c.ts")]
+        );
+        assert_eq!(graph.importers_of(&file("c.ts")), [file("b.ts")]);
+        assert!(graph.importers_of(&file("a.ts")).is_empty());
+        assert!(graph.importers_of(&file("d.ts")).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_file_imports() {
+        let tmp = tempdir().unwrap();
+        let root = AbsoluteSystemPathBuf::try_from(tmp.path()).unwrap();
+        let graph = build_graph(&root).await;
+
+        let c = graph.file(&root.join_component("c.ts")).unwrap();
+        assert_eq!(
+            c.edges
+                .iter()
+                .map(|edge| edge.specifier.as_str())
+                .collect::<Vec<_>>(),
+            ["./b"]
+        );
+        assert_eq!(
+            c.unresolved_imports
+                .iter()
+                .map(|import| import.specifier.as_str())
+                .collect::<Vec<_>>(),
+            ["./missing"]
+        );
+        assert!(graph.file(&root.join_component("missing.ts")).is_none());
+
+        assert!(matches!(
+            graph.errors(),
+            [TraceError::Resolve { import, .. }] if import == "./missing"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_dependents_of() {
+        let tmp = tempdir().unwrap();
+        let root = AbsoluteSystemPathBuf::try_from(tmp.path()).unwrap();
+        let graph = build_graph(&root).await;
+        let file = |name| root.join_component(name);
+
+        let result = graph.dependents_of(&[file("c.ts")], None);
+        assert_eq!(
+            sorted(result.files.into_keys()),
+            vec![file("a.ts"), file("b.ts"), file("c.ts")]
+        );
+        assert!(result.truncated_files.is_empty());
+
+        let result = graph.dependents_of(&[file("c.ts")], Some(1));
+        assert_eq!(
+            sorted(result.files.into_keys()),
+            vec![file("b.ts"), file("c.ts")]
+        );
+        assert_eq!(result.truncated_files, vec![file("a.ts")]);
+
+        let result = graph.dependents_of(&[file("d.ts")], None);
+        assert_eq!(sorted(result.files.into_keys()), vec![file("d.ts")]);
+    }
+}
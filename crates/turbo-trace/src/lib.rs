@@ -1,6 +1,23 @@
 #![deny(clippy::all)]
+mod bundler_config;
+mod cache;
+mod css_import_finder;
+mod export_finder;
 mod import_finder;
+mod import_graph;
+mod single_file_component;
 mod tracer;
 
-pub use import_finder::{ImportFinder, ImportType};
-pub use tracer::{ImportTraceType, TraceError, TraceResult, Tracer};
+pub use bundler_config::BundlerAliases;
+pub use cache::{content_hash, TraceCache};
+pub use export_finder::{Export, ExportFinder, ExportKind, Reexport};
+pub use import_finder::{
+    DynamicImport, Import, ImportFinder, ImportKind, ImportType, ImportedNames,
+};
+pub use import_graph::ImportGraph;
+pub use single_file_component::{extract_script, is_single_file_component, SfcScript};
+pub use tracer::{
+    span_offsets, FileKind, ImportEdge, ImportTraceType, ResolveFailure, RuntimeResolvers,
+    SeenFile, TraceError, TraceResult, TracedLevel, Tracer, UnresolvedDynamicImport,
+    UnresolvedImport, WorkspacePackages,
+};
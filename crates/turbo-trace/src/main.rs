@@ -1,4 +1,9 @@
+mod bundler_config;
+mod cache;
+mod css_import_finder;
 mod import_finder;
+mod import_graph;
+mod single_file_component;
 mod tracer;
 
 use camino::Utf8PathBuf;
@@ -41,7 +46,7 @@ async fn main() -> Result<(), PathError> {
     let tracer = Tracer::new(abs_cwd, files, args.ts_config);
 
     let result = if args.reverse {
-        tracer.reverse_trace().await
+        tracer.reverse_trace(args.depth).await
     } else {
         tracer.trace(args.depth).await
     };
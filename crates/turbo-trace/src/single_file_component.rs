@@ -0,0 +1,215 @@
+use swc_ecma_parser::{EsSyntax, Syntax, TsSyntax};
+use turbopath::AbsoluteSystemPath;
+
+/// The file extensions of single-file components we trace imports in
+pub const SFC_EXTENSIONS: &[&str] = &["vue", "svelte"];
+
+pub fn is_single_file_component(path: &AbsoluteSystemPath) -> bool {
+    path.extension()
+        .is_some_and(|extension| SFC_EXTENSIONS.contains(&extension))
+}
+
+/// The `<script>` blocks of a Vue or Svelte component
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SfcScript {
+    /// The component with everything outside of its `<script>` blocks
+    /// replaced by whitespace, so offsets in the script are the same as in the
+    /// component
+    pub source: String,
+    /// The `lang` attribute of the first `<script>` block that has one
+    pub lang: Option<String>,
+}
+
+impl SfcScript {
+    pub fn syntax(&self) -> Syntax {
+        match self.lang.as_deref() {
+            Some(lang @ ("ts" | "tsx")) => Syntax::Typescript(TsSyntax {
+                tsx: lang == "tsx",
+                decorators: true,
+                ..Default::default()
+            }),
+            _ => Syntax::Es(EsSyntax {
+                jsx: true,
+                ..Default::default()
+            }),
+        }
+    }
+}
+
+/// Extracts the `<script>` blocks from a Vue or Svelte component.
+///
+/// Components can have more than one block, like Vue's `<script>` and
+/// `<script setup>` or Svelte's `<script context="module">`, so all of them
+/// are kept. Blocks that are commented out or that load a `src` aren't
+/// scripts we can parse, and are skipped.
+pub fn extract_script(source: &str) -> SfcScript {
+    // Lowercasing ASCII keeps byte offsets the same
+    let lowercase = source.to_ascii_lowercase();
+    let mut script: Vec<u8> = source
+        .bytes()
+        .map(|byte| match byte {
+            b'\n' | b'\r' => byte,
+            _ => b' ',
+        })
+        .collect();
+    let mut lang = None;
+
+    let mut i = 0;
+    while let Some(offset) = lowercase[i..].find('<') {
+        let tag_start = i + offset;
+        if lowercase[tag_start..].starts_with("<!--") {
+            i = lowercase[tag_start..]
+                .find("-->")
+                .map_or(source.len(), |end| tag_start + end + 3);
+            continue;
+        }
+
+        let name_end = tag_start + "<script".len();
+        let is_script = lowercase[tag_start..].starts_with("<script")
+            && lowercase[name_end..]
+                .bytes()
+                .next()
+                .is_some_and(|byte| byte == b'>' || byte == b'/' || byte.is_ascii_whitespace());
+        if !is_script {
+            i = tag_start + 1;
+            continue;
+        }
+
+        let Some(tag_end) = lowercase[name_end..].find('>').map(|end| name_end + end) else {
+            break;
+        };
+        let attributes = &source[name_end..tag_end];
+        let content_start = tag_end + 1;
+        let content_end = lowercase[content_start..]
+            .find("</script")
+            .map_or(source.len(), |end| content_start + end);
+        i = content_end;
+
+        if attributes.ends_with('/') || attribute(attributes, "src").is_some() {
+            continue;
+        }
+        if lang.is_none() {
+            lang = attribute(attributes, "lang");
+        }
+        script[content_start..content_end]
+            .copy_from_slice(&source.as_bytes()[content_start..content_end]);
+    }
+
+    SfcScript {
+        // Only whole blocks are copied and they start and end at ASCII
+        // characters, so the script is still valid UTF-8
+        source: String::from_utf8(script).expect("script is valid UTF-8"),
+        lang,
+    }
+}
+
+/// Finds the value of an attribute like `lang="ts"` in the attributes of a
+/// tag
+fn attribute(attributes: &str, name: &str) -> Option<String> {
+    let mut rest = attributes;
+    while let Some(start) = rest.find(name) {
+        let preceded_by_space = rest[..start]
+            .chars()
+            .next_back()
+            .map_or(true, char::is_whitespace);
+        rest = &rest[start + name.len()..];
+        if !preceded_by_space {
+            continue;
+        }
+        let Some(value) = rest.trim_start().strip_prefix('=') else {
+            continue;
+        };
+        let value = value.trim_start();
+        return match value.chars().next()? {
+            quote @ ('"' | '\'') => {
+                let value = &value[1..];
+                Some(value[..value.find(quote)?].to_string())
+            }
+            _ => Some(
+                value
+                    .split(|c: char| c.is_whitespace() || c == '/')
+                    .next()?
+                    .to_string(),
+            ),
+        };
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The non-whitespace parts of the extracted script
+    fn script_lines(script: &SfcScript) -> Vec<&str> {
+        script
+            .source
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect()
+    }
+
+    #[test]
+    fn test_script() {
+        let source = "<template>\n  <Button />\n</template>\n\n<script>\nimport Button from \
+                      \"./button.vue\";\nexport default { components: { Button } };\n</script>\n";
+        let script = extract_script(source);
+
+        assert_eq!(script.source.len(), source.len());
+        assert_eq!(
+            script_lines(&script),
+            vec![
+                "import Button from \"./button.vue\";",
+                "export default { components: { Button } };"
+            ]
+        );
+        assert_eq!(script.lang, None);
+        // Offsets in the script are the same as in the component
+        let offset = source.find("import").unwrap();
+        assert_eq!(&script.source[offset..offset + 6], "import");
+    }
+
+    #[test]
+    fn test_script_setup() {
+        let source = "<script lang=\"ts\">\nexport const name = \"card\";\n</script>\n<script \
+                      setup lang=\"ts\">\nimport { ref } from \"vue\";\nconst count = \
+                      ref(0);\n</script>\n<template><p>{{ count }}</p></template>\n";
+        let script = extract_script(source);
+
+        assert_eq!(
+            script_lines(&script),
+            vec![
+                "export const name = \"card\";",
+                "import { ref } from \"vue\";",
+                "const count = ref(0);"
+            ]
+        );
+        assert_eq!(script.lang.as_deref(), Some("ts"));
+        assert!(matches!(script.syntax(), Syntax::Typescript(_)));
+    }
+
+    #[test]
+    fn test_lang_from_later_block() {
+        let script = extract_script(
+            "<script>\nconst a = 1;\n</script>\n<script setup lang='tsx'>\nconst b = <div \
+             />;\n</script>",
+        );
+        assert_eq!(script.lang.as_deref(), Some("tsx"));
+        assert!(matches!(
+            script.syntax(),
+            Syntax::Typescript(TsSyntax { tsx: true, .. })
+        ));
+    }
+
+    #[test]
+    fn test_skipped_blocks() {
+        let source = "<!-- <script>import \"./commented\";</script> -->\n<script \
+                      src=\"./external.js\"></script>\n<SCRIPT context=\"module\">\nimport \
+                      \"./module\";\n</SCRIPT>\n<scripts>import \"./not-a-script\";</scripts>";
+        let script = extract_script(source);
+
+        assert_eq!(script_lines(&script), vec!["import \"./module\";"]);
+    }
+}
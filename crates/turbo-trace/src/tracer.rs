@@ -1,28 +1,73 @@
-use std::{collections::HashMap, fmt, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt,
+    hash::Hasher,
+    path::Path,
+    sync::{Arc, Mutex},
+};
 
 use camino::{Utf8Path, Utf8PathBuf};
 use globwalk::WalkType;
+use jsonc_parser::JsonValue;
 use miette::{Diagnostic, Report, SourceSpan};
 use oxc_resolver::{
-    EnforceExtension, ResolveError, ResolveOptions, Resolver, TsconfigOptions, TsconfigReferences,
+    Alias, AliasValue, EnforceExtension, ResolveError, ResolveOptions, Resolver, TsconfigOptions,
+    TsconfigReferences,
 };
+use serde::{Deserialize, Serialize};
 use swc_common::{
     comments::SingleThreadedComments,
     errors::{ColorConfig, Handler},
     input::StringInput,
-    FileName, SourceMap,
+    BytePos, FileName, SourceMap, Span,
 };
 use swc_ecma_ast::EsVersion;
 use swc_ecma_parser::{lexer::Lexer, Capturing, EsSyntax, Parser, Syntax, TsSyntax};
 use swc_ecma_visit::VisitWith;
 use thiserror::Error;
-use tokio::task::JoinSet;
+use tokio::{sync::mpsc, task::JoinSet};
 use tracing::{debug, error};
 use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf, PathError};
 
-use crate::import_finder::ImportFinder;
+use crate::{
+    bundler_config::BundlerAliases,
+    cache::{content_hash, TraceCache},
+    css_import_finder::{find_css_imports, CssImport, STYLESHEET_EXTENSIONS},
+    import_finder::{Import, ImportFinder, ImportKind, ImportType},
+    import_graph::ImportGraph,
+    single_file_component::{extract_script, is_single_file_component, SFC_EXTENSIONS},
+};
 
-#[derive(Debug, Default)]
+/// The extensions of the scripts we parse for imports
+const SCRIPT_EXTENSIONS: &[&str] = &["js", "jsx", "mjs", "cjs", "ts", "tsx", "mts", "cts"];
+
+/// Whether a traced file can import other files
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FileKind {
+    /// A script, stylesheet or component, whose imports are traced
+    #[default]
+    Source,
+    /// A file that's imported but can't import anything itself, like JSON,
+    /// an image or a WebAssembly module
+    Asset,
+}
+
+impl FileKind {
+    pub fn of(path: &AbsoluteSystemPath) -> Self {
+        match path.extension() {
+            Some(extension)
+                if !SCRIPT_EXTENSIONS.contains(&extension)
+                    && !STYLESHEET_EXTENSIONS.contains(&extension)
+                    && !SFC_EXTENSIONS.contains(&extension) =>
+            {
+                FileKind::Asset
+            }
+            _ => FileKind::Source,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct SeenFile {
     // We have to add these because of a Rust bug where dead code analysis
     // doesn't work properly in multi-target crates
@@ -30,6 +75,101 @@ pub struct SeenFile {
     // https://github.com/rust-lang/rust/issues/95513
     #[allow(dead_code)]
     pub ast: Option<swc_ecma_ast::Module>,
+    /// The files this file imports, in the order they're imported
+    #[allow(dead_code)]
+    pub imports: Vec<AbsoluteSystemPathBuf>,
+    /// The imported files that are only imported with `import type`, so they
+    /// aren't needed at runtime
+    #[allow(dead_code)]
+    pub type_only_imports: HashSet<AbsoluteSystemPathBuf>,
+    /// The dynamic imports and requires that couldn't be traced because their
+    /// argument isn't a string literal
+    #[allow(dead_code)]
+    pub unresolved_dynamic_imports: Vec<UnresolvedDynamicImport>,
+    /// Every import that was resolved to a file, in the order they're
+    /// imported. A file can be imported more than once.
+    #[allow(dead_code)]
+    pub edges: Vec<ImportEdge>,
+    /// The imports that couldn't be resolved, which are reported as errors
+    /// too. Files with errors aren't cached, so this is always complete.
+    #[allow(dead_code)]
+    pub unresolved_imports: Vec<UnresolvedImport>,
+}
+
+impl SeenFile {
+    pub(crate) fn new(
+        ast: Option<swc_ecma_ast::Module>,
+        edges: Vec<ImportEdge>,
+        unresolved_dynamic_imports: Vec<UnresolvedDynamicImport>,
+    ) -> Self {
+        let value_imports = edges
+            .iter()
+            .filter(|edge| edge.import_type == ImportType::Value)
+            .map(|edge| &edge.to)
+            .collect::<HashSet<_>>();
+        let type_only_imports = edges
+            .iter()
+            .filter(|edge| !value_imports.contains(&edge.to))
+            .map(|edge| edge.to.clone())
+            .collect();
+
+        Self {
+            ast,
+            imports: edges.iter().map(|edge| edge.to.clone()).collect(),
+            type_only_imports,
+            unresolved_dynamic_imports,
+            edges,
+            unresolved_imports: Vec::new(),
+        }
+    }
+}
+
+/// An import of a file, with byte offsets of the import into the importing
+/// file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportEdge {
+    /// The imported file
+    #[allow(dead_code)]
+    pub to: AbsoluteSystemPathBuf,
+    /// What the file was imported as, e.g. `./foo` or `react`
+    #[allow(dead_code)]
+    pub specifier: String,
+    #[allow(dead_code)]
+    pub kind: ImportKind,
+    #[allow(dead_code)]
+    pub import_type: ImportType,
+    #[allow(dead_code)]
+    pub start: usize,
+    #[allow(dead_code)]
+    pub end: usize,
+}
+
+/// An import that couldn't be resolved to a file, with byte offsets of the
+/// import into the importing file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnresolvedImport {
+    #[allow(dead_code)]
+    pub specifier: String,
+    #[allow(dead_code)]
+    pub kind: ImportKind,
+    #[allow(dead_code)]
+    pub import_type: ImportType,
+    #[allow(dead_code)]
+    pub start: usize,
+    #[allow(dead_code)]
+    pub end: usize,
+}
+
+/// A dynamic import or require that can't be traced, with byte offsets into
+/// the file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnresolvedDynamicImport {
+    #[allow(dead_code)]
+    pub kind: ImportKind,
+    #[allow(dead_code)]
+    pub start: usize,
+    #[allow(dead_code)]
+    pub end: usize,
 }
 
 pub struct Tracer {
@@ -39,6 +179,173 @@ pub struct Tracer {
     cwd: AbsoluteSystemPathBuf,
     errors: Vec<TraceError>,
     import_type: ImportTraceType,
+    workspace_packages: WorkspacePackages,
+    aliases: Alias,
+    bundler_aliases: BundlerAliases,
+    cache: Arc<TraceCache>,
+    /// The resolver for the files in each directory, or `None` if they use
+    /// the tracer's resolver. Each file is resolved with its closest tsconfig,
+    /// so files in different packages can use different `paths`.
+    resolvers: Mutex<HashMap<AbsoluteSystemPathBuf, Option<Arc<Resolver>>>>,
+    /// The `resolution_hash` of the resolver for the files in each directory,
+    /// which is only needed when there's a cache
+    resolution_hashes: Mutex<HashMap<AbsoluteSystemPathBuf, u64>>,
+    case_checker: CaseChecker,
+    /// How many levels of files in `node_modules` have their imports traced.
+    /// `None` traces every external file that's reachable.
+    external_depth: Option<usize>,
+    /// Where the files of each level are sent once it's been traced
+    progress: Option<mpsc::UnboundedSender<TracedLevel>>,
+}
+
+/// The files found in a level of a trace, with what was found tracing them.
+/// Files that weren't traced, like files in `node_modules` past
+/// `set_follow_external`, have an empty `SeenFile`.
+pub type TracedLevel = Vec<(AbsoluteSystemPathBuf, SeenFile)>;
+
+/// The packages in the workspace, by name. Imports of these packages are
+/// resolved to their source directly, so they're followed even when the
+/// package isn't linked into `node_modules`.
+#[derive(Debug, Default)]
+pub struct WorkspacePackages(HashMap<String, AbsoluteSystemPathBuf>);
+
+impl WorkspacePackages {
+    #[allow(dead_code)]
+    pub fn new(packages: HashMap<String, AbsoluteSystemPathBuf>) -> Self {
+        Self(packages)
+    }
+
+    /// Resolves an import of a workspace package, e.g. `@repo/ui/button`,
+    /// using the package's `exports`, falling back to `main` and paths relative
+    /// to the package. Returns `None` if the import isn't of a workspace
+    /// package, or if it can't be resolved this way.
+    fn resolve(&self, resolver: &Resolver, import: &str) -> Option<AbsoluteSystemPathBuf> {
+        let mut segments = import.splitn(if import.starts_with('@') { 3 } else { 2 }, '/');
+        let name_len = if import.starts_with('@') {
+            segments.next()?.len() + 1 + segments.next()?.len()
+        } else {
+            segments.next()?.len()
+        };
+        let (name, subpath) = import.split_at(name_len);
+        // Packages of types are imported without their `@types/` scope
+        let (name, package_dir) = self
+            .0
+            .get_key_value(name)
+            .or_else(|| self.0.get_key_value(format!("@types/{name}").as_str()))?;
+
+        let package_resolver = Tracer::infer_resolver_with_ts_config(
+            &package_dir.join_component("package.json"),
+            resolver,
+        );
+        let resolver = package_resolver.as_ref().unwrap_or(resolver);
+        // Packages with `exports` can import themselves by name, which applies
+        // the `exports` map
+        resolver
+            .resolve(package_dir, &format!("{name}{subpath}"))
+            .or_else(|_| resolver.resolve(package_dir, &format!(".{subpath}")))
+            .ok()
+            .and_then(|resolved| resolved.into_path_buf().try_into().ok())
+    }
+}
+
+/// Resolvers that only match the `exports` and `imports` conditions that Node
+/// uses at runtime.
+///
+/// A value import resolves to the file that's actually loaded, e.g. the
+/// CommonJS build of a package for a `require`. Type imports use every
+/// condition, so they find a package's `types`.
+pub struct RuntimeResolvers {
+    import: Resolver,
+    require: Resolver,
+    /// Whether the importing file is CommonJS because of its extension, so
+    /// its static imports are compiled to `require`s
+    commonjs: bool,
+}
+
+impl RuntimeResolvers {
+    pub fn new(resolver: &Resolver) -> Self {
+        let with_conditions = |conditions: &[&str]| {
+            let options = resolver.options().clone();
+            resolver.clone_with_options(ResolveOptions {
+                condition_names: conditions.iter().map(|c| c.to_string()).collect(),
+                ..options
+            })
+        };
+        Self {
+            import: with_conditions(&["node", "import", "default"]),
+            require: with_conditions(&["node", "require", "default"]),
+            commonjs: false,
+        }
+    }
+
+    /// Creates the resolvers for the imports in `file_path`. Static imports
+    /// and re-exports in CommonJS files are resolved like `require`s, since
+    /// that's what TypeScript compiles them to. Dynamic imports stay imports.
+    ///
+    /// `.cts` and `.cjs` files are always CommonJS. Other scripts are when
+    /// the closest `package.json` has `"type": "commonjs"`. Without a `type`,
+    /// Node treats a file that uses `import` as a module, so it isn't.
+    pub fn for_file(resolver: &Resolver, file_path: &AbsoluteSystemPath) -> Self {
+        let commonjs = match file_path.extension() {
+            Some("cts" | "cjs") => true,
+            Some("js" | "jsx" | "ts" | "tsx") => {
+                Self::package_type(resolver, file_path).as_deref() == Some("commonjs")
+            }
+            _ => false,
+        };
+        Self {
+            commonjs,
+            ..Self::new(resolver)
+        }
+    }
+
+    /// The `type` in the closest `package.json` to `file_path`. It's found by
+    /// resolving the file itself, so the resolver's cache of `package.json`s
+    /// is used.
+    fn package_type(resolver: &Resolver, file_path: &AbsoluteSystemPath) -> Option<String> {
+        let resolution = resolver
+            .resolve(
+                file_path.parent()?,
+                &format!("./{}", file_path.file_name()?),
+            )
+            .ok()?;
+        let package_type = resolution.package_json()?.r#type.as_ref()?;
+        package_type
+            .as_str()
+            .map(|package_type| package_type.to_string())
+    }
+
+    /// Resolves `import` with the conditions for how it's imported. If that
+    /// fails, e.g. because a value is imported from a package that only
+    /// exports `types`, every condition is tried with `resolver`.
+    pub fn resolve(
+        &self,
+        resolver: &Resolver,
+        workspace_packages: &WorkspacePackages,
+        file_dir: &AbsoluteSystemPath,
+        import: &Import,
+    ) -> Result<Option<AbsoluteSystemPathBuf>, ResolveFailure> {
+        let resolve_with =
+            |resolver: &Resolver| match workspace_packages.resolve(resolver, &import.specifier) {
+                Some(path) => Ok(Some(path)),
+                None => Tracer::resolve_import(resolver, file_dir, &import.specifier),
+            };
+
+        let runtime_resolver = match (import.import_type, import.kind) {
+            (ImportType::Type, _) => None,
+            (ImportType::Value, ImportKind::Require) => Some(&self.require),
+            (ImportType::Value, ImportKind::Static | ImportKind::Reexport) if self.commonjs => {
+                Some(&self.require)
+            }
+            (ImportType::Value, _) => Some(&self.import),
+        };
+        if let Some(runtime_resolver) = runtime_resolver {
+            if let Ok(resolved) = resolve_with(runtime_resolver) {
+                return Ok(resolved);
+            }
+        }
+        resolve_with(resolver)
+    }
 }
 
 #[derive(Clone, Debug, Error, Diagnostic)]
@@ -62,6 +369,21 @@ pub enum TraceError {
         #[help]
         reason: String,
     },
+    #[error("import of `{import}` in `{file_path}` doesn't match the case of `{actual_path}`")]
+    #[diagnostic(help(
+        "this only resolves on case-insensitive filesystems, like the defaults on macOS and \
+         Windows"
+    ))]
+    CaseMismatch {
+        import: String,
+        file_path: String,
+        /// The imported file, cased the way it is on disk
+        actual_path: String,
+        #[label("import here")]
+        span: SourceSpan,
+        #[source_code]
+        text: String,
+    },
     #[error("failed to walk files")]
     GlobError(Arc<globwalk::WalkError>),
 }
@@ -86,13 +408,100 @@ impl TraceResult {
             }
         }
     }
+
+    /// The imports between the files in the result, as pairs of the importing
+    /// file and the import, sorted by the importing file. Each file's imports
+    /// are in the order they're imported, and imports of files that aren't in
+    /// the result, like the ones cut off by a maximum depth, are left out.
+    #[allow(dead_code)]
+    pub fn edges(&self) -> Vec<(&AbsoluteSystemPath, &ImportEdge)> {
+        let mut files = self.files.iter().collect::<Vec<_>>();
+        files.sort_by(|(a, _), (b, _)| a.cmp(b));
+        files
+            .into_iter()
+            .flat_map(|(from, file)| {
+                file.edges
+                    .iter()
+                    .filter(|edge| self.files.contains_key(&edge.to))
+                    .map(move |edge| (from.as_ref(), edge))
+            })
+            .collect()
+    }
+
+    /// Finds the import cycles between the files in the result. Each cycle is
+    /// the shortest one through at least one of its files, and starts at the
+    /// file that sorts first. A file that imports itself is a cycle of length
+    /// one.
+    #[allow(dead_code)]
+    pub fn cycles(&self) -> Vec<Vec<AbsoluteSystemPathBuf>> {
+        let mut cycles = Vec::new();
+        let mut starts = self.files.keys().collect::<Vec<_>>();
+        starts.sort();
+
+        for start in starts {
+            let Some(cycle) = self.shortest_cycle(start) else {
+                continue;
+            };
+            let first = cycle
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, file)| *file)
+                .map_or(0, |(i, _)| i);
+            let mut cycle = cycle;
+            cycle.rotate_left(first);
+            if !cycles.contains(&cycle) {
+                cycles.push(cycle);
+            }
+        }
+
+        cycles
+    }
+
+    /// Breadth first search from `start` back to itself
+    #[allow(dead_code)]
+    fn shortest_cycle(&self, start: &AbsoluteSystemPathBuf) -> Option<Vec<AbsoluteSystemPathBuf>> {
+        let imports = |file: &AbsoluteSystemPathBuf| {
+            self.files
+                .get(file)
+                .into_iter()
+                .flat_map(|seen| seen.imports.iter())
+                .filter(|import| self.files.contains_key(*import))
+        };
+
+        let mut parents: HashMap<&AbsoluteSystemPathBuf, &AbsoluteSystemPathBuf> = HashMap::new();
+        let mut queue = VecDeque::from([start]);
+        while let Some(file) = queue.pop_front() {
+            for import in imports(file) {
+                if import == start {
+                    let mut cycle = vec![file.clone()];
+                    let mut current = file;
+                    while let Some(parent) = parents.get(current) {
+                        cycle.push((*parent).clone());
+                        current = parent;
+                    }
+                    cycle.reverse();
+                    return Some(cycle);
+                }
+                if !parents.contains_key(import) {
+                    parents.insert(import, file);
+                    queue.push_back(import);
+                }
+            }
+        }
+
+        None
+    }
 }
 
 pub struct TraceResult {
     #[allow(dead_code)]
-    source_map: Arc<SourceMap>,
+    pub(crate) source_map: Arc<SourceMap>,
     pub errors: Vec<TraceError>,
     pub files: HashMap<AbsoluteSystemPathBuf, SeenFile>,
+    /// The files that were left out because they're further away than the
+    /// maximum depth, sorted by path. When this isn't empty, the result only
+    /// covers part of the graph.
+    pub truncated_files: Vec<AbsoluteSystemPathBuf>,
 }
 
 impl fmt::Debug for TraceResult {
@@ -100,10 +509,18 @@ impl fmt::Debug for TraceResult {
         f.debug_struct("TraceResult")
             .field("files", &self.files)
             .field("errors", &self.errors)
+            .field("truncated_files", &self.truncated_files)
             .finish()
     }
 }
 
+/// Why an import couldn't be resolved to a file.
+#[derive(Debug)]
+pub enum ResolveFailure {
+    PathEncoding(Arc<PathError>),
+    Unresolved(ResolveError),
+}
+
 /// The type of imports to trace.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[allow(dead_code)]
@@ -116,6 +533,238 @@ pub enum ImportTraceType {
     Values,
 }
 
+/// Hashes everything that affects what a file's imports resolve to, other
+/// than which files exist and the `package.json`s they're resolved through,
+/// which `TraceCache` checks itself.
+fn resolution_hash(
+    resolver: &Resolver,
+    workspace_packages: &WorkspacePackages,
+    import_type: ImportTraceType,
+    cache: &TraceCache,
+) -> u64 {
+    let options = resolver.options();
+    let mut hasher = twox_hash::XxHash64::with_seed(0);
+    hasher.write(format!("{options:?}").as_bytes());
+    if let Some(tsconfig) = &options.tsconfig {
+        for config_file in tsconfig_chain(resolver, &tsconfig.config_file) {
+            hasher.write(config_file.as_str().as_bytes());
+            hasher.write_u64(cache.input_hash(&config_file).unwrap_or_default());
+        }
+    }
+    let mut packages = workspace_packages.0.iter().collect::<Vec<_>>();
+    packages.sort();
+    hasher.write(format!("{packages:?} {import_type:?}").as_bytes());
+    hasher.finish()
+}
+
+/// The tsconfig at `config_file` and every tsconfig it extends, found the
+/// way the resolver finds them
+fn tsconfig_chain(resolver: &Resolver, config_file: &Path) -> Vec<AbsoluteSystemPathBuf> {
+    // Like the resolver, packages are searched for a `tsconfig.json` without
+    // reading their `package.json`
+    let extends_resolver = resolver.clone_with_options(ResolveOptions {
+        description_files: vec![],
+        extensions: vec![".json".into()],
+        main_files: vec!["tsconfig.json".into()],
+        ..ResolveOptions::default()
+    });
+
+    let mut chain = Vec::new();
+    let mut queue = AbsoluteSystemPathBuf::try_from(config_file)
+        .into_iter()
+        .collect::<VecDeque<_>>();
+    while let Some(config_file) = queue.pop_front() {
+        if chain.contains(&config_file) {
+            continue;
+        }
+        if let Some(dir) = config_file.parent() {
+            for extends in tsconfig_extends(&config_file) {
+                let extended = if extends.starts_with('.') || Utf8Path::new(&extends).is_absolute()
+                {
+                    AbsoluteSystemPathBuf::from_unknown(dir, extends.as_str())
+                        .clean()
+                        .ok()
+                } else {
+                    extends_resolver
+                        .resolve(dir, &extends)
+                        .ok()
+                        .and_then(|resolution| {
+                            AbsoluteSystemPathBuf::try_from(resolution.path()).ok()
+                        })
+                };
+                queue.extend(extended);
+            }
+        }
+        chain.push(config_file);
+    }
+    chain
+}
+
+/// The `extends` of the tsconfig at `config_file`, which can be one config or
+/// several
+fn tsconfig_extends(config_file: &AbsoluteSystemPath) -> Vec<String> {
+    let Ok(contents) = config_file.read_to_string() else {
+        return Vec::new();
+    };
+    let Ok(Some(JsonValue::Object(config))) =
+        jsonc_parser::parse_to_value(&contents, &Default::default())
+    else {
+        return Vec::new();
+    };
+    match config.get("extends") {
+        Some(JsonValue::String(extends)) => vec![extends.to_string()],
+        Some(JsonValue::Array(extends)) => extends
+            .iter()
+            .filter_map(|extends| match extends {
+                JsonValue::String(extends) => Some(extends.to_string()),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The files that can import other files, i.e. the ones that are parsed to
+/// find what imports a file
+pub(crate) fn source_files(
+    root: &AbsoluteSystemPath,
+) -> Result<HashSet<AbsoluteSystemPathBuf>, globwalk::WalkError> {
+    // Built from the same extensions as `FileKind::of`, so every file that's
+    // traced as a source is also parsed when looking for importers
+    let include = SCRIPT_EXTENSIONS
+        .iter()
+        .chain(SFC_EXTENSIONS)
+        .chain(STYLESHEET_EXTENSIONS)
+        .map(|extension| format!("**/*.{extension}").parse().expect("valid glob"))
+        .collect::<Vec<_>>();
+    globwalk::globwalk(
+        root,
+        &include,
+        &[
+            "**/node_modules/**".parse().expect("valid glob"),
+            "**/.next/**".parse().expect("valid glob"),
+        ],
+        WalkType::Files,
+    )
+}
+
+/// Whether `path` is in an installed package, rather than in the workspace.
+/// Only the part of the path under `root` is checked, so a repository that's
+/// checked out inside a `node_modules` isn't external. Files outside of `root`
+/// are external, since they can only come from the package manager.
+fn is_external(root: &AbsoluteSystemPath, path: &AbsoluteSystemPath) -> bool {
+    root.anchor(path).map_or(true, |path| {
+        path.components()
+            .any(|component| component.as_str() == "node_modules")
+    })
+}
+
+/// Checks that imports are cased the way the files are on disk. The entries
+/// of each directory are only read once per trace, since most imports are of
+/// files in the same few directories.
+#[derive(Debug)]
+pub(crate) struct CaseChecker {
+    root: AbsoluteSystemPathBuf,
+    dir_entries: Mutex<HashMap<Utf8PathBuf, Option<Arc<Vec<String>>>>>,
+}
+
+impl CaseChecker {
+    pub(crate) fn new(root: AbsoluteSystemPathBuf) -> Self {
+        Self {
+            root,
+            dir_entries: Mutex::default(),
+        }
+    }
+
+    /// Finds how `resolved` is cased on disk, if it's cased differently. This
+    /// happens when an import only resolves because the filesystem is
+    /// case-insensitive.
+    ///
+    /// Only the part of the path that isn't shared with `file_dir` is checked,
+    /// since that's the part the import chose. Installed packages are skipped,
+    /// since their paths come from the package manager.
+    pub(crate) fn on_disk_path(
+        &self,
+        file_dir: &AbsoluteSystemPath,
+        resolved: &AbsoluteSystemPath,
+    ) -> Option<AbsoluteSystemPathBuf> {
+        if is_external(&self.root, resolved) {
+            return None;
+        }
+
+        let shared = file_dir
+            .as_path()
+            .components()
+            .zip(resolved.as_path().components())
+            .take_while(|(dir, resolved)| dir == resolved)
+            .count();
+        let mut actual = resolved
+            .as_path()
+            .components()
+            .take(shared)
+            .collect::<Utf8PathBuf>();
+        let mut is_mismatched = false;
+        for component in resolved.as_path().components().skip(shared) {
+            let name = component.as_str();
+            let entries = self.dir_entries(&actual)?;
+            // A directory on a case-sensitive filesystem can have entries that
+            // only differ by case, so an exact match is preferred
+            if entries.iter().any(|entry| entry == name) {
+                actual.push(name);
+            } else {
+                actual.push(
+                    entries
+                        .iter()
+                        .find(|entry| entry.eq_ignore_ascii_case(name))?,
+                );
+                is_mismatched = true;
+            }
+        }
+
+        is_mismatched
+            .then(|| AbsoluteSystemPathBuf::new(actual).ok())
+            .flatten()
+    }
+
+    /// The names of the entries in `dir`, or `None` if it can't be read
+    fn dir_entries(&self, dir: &Utf8Path) -> Option<Arc<Vec<String>>> {
+        if let Some(entries) = self.dir_entries.lock().expect("lock poisoned").get(dir) {
+            return entries.clone();
+        }
+
+        // Read without holding the lock, so other files can be checked in the
+        // meantime
+        let entries = std::fs::read_dir(dir).ok().map(|entries| {
+            Arc::new(
+                entries
+                    .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+                    .collect::<Vec<_>>(),
+            )
+        });
+        self.dir_entries
+            .lock()
+            .expect("lock poisoned")
+            .entry(dir.to_owned())
+            .or_insert(entries)
+            .clone()
+    }
+}
+
+fn is_stylesheet(path: &AbsoluteSystemPath) -> bool {
+    path.extension()
+        .map_or(false, |ext| STYLESHEET_EXTENSIONS.contains(&ext))
+}
+
+/// Converts a span into start and end byte offsets into the file, where
+/// `file_start` is the position of the file in the `SourceMap` it was parsed
+/// with
+pub fn span_offsets(span: Span, file_start: BytePos) -> (usize, usize) {
+    (
+        span.lo.0.saturating_sub(file_start.0) as usize,
+        span.hi.0.saturating_sub(file_start.0) as usize,
+    )
+}
+
 impl Tracer {
     pub fn new(
         cwd: AbsoluteSystemPathBuf,
@@ -130,10 +779,19 @@ impl Tracer {
         Self {
             files,
             ts_config,
+            case_checker: CaseChecker::new(cwd.clone()),
             cwd,
             import_type: ImportTraceType::All,
             errors: Vec::new(),
             source_map: Arc::new(SourceMap::default()),
+            workspace_packages: WorkspacePackages::default(),
+            aliases: Vec::new(),
+            bundler_aliases: BundlerAliases::default(),
+            cache: Arc::default(),
+            resolvers: Mutex::default(),
+            resolution_hashes: Mutex::default(),
+            external_depth: Some(0),
+            progress: None,
         }
     }
 
@@ -142,11 +800,64 @@ impl Tracer {
         self.import_type = import_type;
     }
 
-    #[tracing::instrument(skip(resolver, source_map))]
-    pub async fn get_imports_from_file(
+    /// Resolves imports of these packages to their source, instead of relying
+    /// on them being installed in `node_modules`
+    #[allow(dead_code)]
+    pub fn set_workspace_packages(&mut self, workspace_packages: WorkspacePackages) {
+        self.workspace_packages = workspace_packages;
+    }
+
+    /// Resolves imports that start with an alias, e.g. `@` in
+    /// `@/components/button`, to the alias's path. These take precedence over
+    /// aliases found in bundler configs.
+    #[allow(dead_code)]
+    pub fn set_aliases(&mut self, aliases: Vec<(String, String)>) {
+        self.aliases = aliases
+            .into_iter()
+            .map(|(alias, path)| (alias, vec![AliasValue::Path(path)]))
+            .collect();
+    }
+
+    /// Reuses the imports of files that were traced before, and saves the
+    /// imports of the files that are traced now. The cache isn't saved to
+    /// disk until `TraceCache::save` is called.
+    #[allow(dead_code)]
+    pub fn set_cache(&mut self, cache: Arc<TraceCache>) {
+        self.cache = cache;
+    }
+
+    /// Traces the imports of files in `node_modules` too, up to `max_depth`
+    /// levels into external packages. By default, files in `node_modules` are
+    /// included in the result, but their imports aren't traced.
+    #[allow(dead_code)]
+    pub fn set_follow_external(&mut self, max_depth: Option<usize>) {
+        self.external_depth = max_depth;
+    }
+
+    /// Sends the files of each level to `progress` as soon as the level has
+    /// been traced, so they can be used before the whole trace has finished
+    #[allow(dead_code)]
+    pub fn set_progress(&mut self, progress: mpsc::UnboundedSender<TracedLevel>) {
+        self.progress = Some(progress);
+    }
+
+    fn resolver(&self) -> Resolver {
+        let resolver = Self::create_resolver(self.ts_config.as_deref());
+        if self.aliases.is_empty() {
+            return resolver;
+        }
+        let mut options = resolver.options().clone();
+        options.alias.extend(self.aliases.iter().cloned());
+        resolver.clone_with_options(options)
+    }
+
+    #[tracing::instrument(skip(case_checker, resolver, source_map, workspace_packages))]
+    pub(crate) async fn get_imports_from_file(
+        case_checker: &CaseChecker,
         source_map: &SourceMap,
         errors: &mut Vec<TraceError>,
         resolver: &Resolver,
+        workspace_packages: &WorkspacePackages,
         file_path: &AbsoluteSystemPath,
         import_type: ImportTraceType,
     ) -> Option<(Vec<AbsoluteSystemPathBuf>, SeenFile)> {
@@ -158,12 +869,20 @@ impl Tracer {
 
         let comments = SingleThreadedComments::default();
 
+        // Only the scripts of Vue and Svelte components have imports we can
+        // parse, and they're kept at the same offsets so spans still point
+        // into the component
+        let script = is_single_file_component(file_path).then(|| extract_script(&file_content));
         let source_file = source_map.new_source_file(
             FileName::Custom(file_path.to_string()).into(),
-            file_content.clone(),
+            script
+                .as_ref()
+                .map_or_else(|| file_content.clone(), |script| script.source.clone()),
         );
 
-        let syntax = if matches!(file_path.extension(), Some("ts") | Some("tsx")) {
+        let syntax = if let Some(script) = &script {
+            script.syntax()
+        } else if matches!(file_path.extension(), Some("ts" | "tsx" | "mts" | "cts")) {
             Syntax::Typescript(TsSyntax {
                 tsx: file_path.extension() == Some("tsx"),
                 decorators: true,
@@ -199,62 +918,64 @@ impl Tracer {
         module.visit_with(&mut finder);
         // Convert found imports/requires to absolute paths and add them to files to
         // visit
-        let mut files = Vec::new();
-        for (import, span, _) in finder.imports() {
+        let file_start = source_file.start_pos;
+        let mut edges = Vec::new();
+        let mut unresolved_imports = Vec::new();
+        let runtime_resolvers = RuntimeResolvers::for_file(resolver, file_path);
+        for found_import in finder.imports() {
+            let Import {
+                specifier: import,
+                span,
+                import_type,
+                kind,
+                ..
+            } = found_import;
             debug!("processing {} in {}", import, file_path);
             let Some(file_dir) = file_path.parent() else {
                 errors.push(TraceError::RootFile(file_path.to_owned()));
                 continue;
             };
-            match resolver.resolve(file_dir, import) {
-                Ok(resolved) => {
-                    debug!("resolved {:?}", resolved);
-                    match resolved.into_path_buf().try_into().map_err(Arc::new) {
-                        Ok(path) => files.push(path),
-                        Err(err) => {
-                            errors.push(TraceError::PathEncoding(err));
-                            continue;
-                        }
+            let resolved =
+                runtime_resolvers.resolve(resolver, workspace_packages, file_dir, found_import);
+            match resolved {
+                Ok(Some(path)) => {
+                    if let Some(actual_path) = case_checker.on_disk_path(file_dir, &path) {
+                        let (start, end) = source_map.span_to_char_offset(&source_file, *span);
+                        let start = start as usize;
+                        let end = end as usize;
+
+                        errors.push(TraceError::CaseMismatch {
+                            import: import.to_string(),
+                            file_path: file_path.to_string(),
+                            actual_path: actual_path.to_string(),
+                            span: SourceSpan::new(start.into(), (end - start).into()),
+                            text: file_content.clone(),
+                        });
                     }
+                    let (start, end) = span_offsets(*span, file_start);
+                    edges.push(ImportEdge {
+                        to: path,
+                        specifier: import.clone(),
+                        kind: *kind,
+                        import_type: *import_type,
+                        start,
+                        end,
+                    })
                 }
-                Err(err @ ResolveError::Builtin { .. }) => {
-                    debug!("built in: {:?}", err);
+                Ok(None) => {}
+                Err(ResolveFailure::PathEncoding(err)) => {
+                    errors.push(TraceError::PathEncoding(err));
                 }
-                Err(err) => {
-                    if !import.starts_with(".") {
-                        // Try to resolve the import as a type import via `@/types/<import>`
-                        let type_package = format!("@types/{}", import);
-                        debug!("trying to resolve type import: {}", type_package);
-                        let resolved_type_import = resolver
-                            .resolve(file_dir, type_package.as_str())
-                            .ok()
-                            .and_then(|resolved| resolved.into_path_buf().try_into().ok());
-
-                        if let Some(resolved_type_import) = resolved_type_import {
-                            debug!("resolved type import succeeded");
-                            files.push(resolved_type_import);
-                            continue;
-                        }
-                    }
-
-                    // Also try without the extension just in case the wrong extension is used
-                    let without_extension = Utf8Path::new(import).with_extension("");
-                    debug!(
-                        "trying to resolve extensionless import: {}",
-                        without_extension
-                    );
-                    let resolved_extensionless_import = resolver
-                        .resolve(file_dir, without_extension.as_str())
-                        .ok()
-                        .and_then(|resolved| resolved.into_path_buf().try_into().ok());
-
-                    if let Some(resolved_extensionless_import) = resolved_extensionless_import {
-                        debug!("resolved extensionless import succeeded");
-                        files.push(resolved_extensionless_import);
-                        continue;
-                    }
+                Err(ResolveFailure::Unresolved(err)) => {
+                    let (start, end) = span_offsets(*span, file_start);
+                    unresolved_imports.push(UnresolvedImport {
+                        specifier: import.clone(),
+                        kind: *kind,
+                        import_type: *import_type,
+                        start,
+                        end,
+                    });
 
-                    debug!("failed to resolve: {:?}", err);
                     let (start, end) = source_map.span_to_char_offset(&source_file, *span);
                     let start = start as usize;
                     let end = end as usize;
@@ -270,50 +991,396 @@ impl Tracer {
             }
         }
 
-        Some((files, SeenFile { ast: Some(module) }))
+        let unresolved_dynamic_imports = finder
+            .dynamic_imports()
+            .iter()
+            .map(|import| {
+                let (start, end) = span_offsets(import.span, file_start);
+                UnresolvedDynamicImport {
+                    kind: import.kind,
+                    start,
+                    end,
+                }
+            })
+            .collect();
+
+        let seen_file = SeenFile {
+            unresolved_imports,
+            ..SeenFile::new(Some(module), edges, unresolved_dynamic_imports)
+        };
+        Some((seen_file.imports.clone(), seen_file))
+    }
+
+    /// Finds the files imported by `file_path`, which can either be a script
+    /// or a stylesheet. The imports are read from `cache` when the file and
+    /// how it's resolved haven't changed. `cache` comes with the resolution
+    /// hash of `resolver`, and is `None` if nothing is cached.
+    #[allow(clippy::too_many_arguments)]
+    async fn get_imports(
+        case_checker: &CaseChecker,
+        source_map: &SourceMap,
+        errors: &mut Vec<TraceError>,
+        resolver: &Resolver,
+        workspace_packages: &WorkspacePackages,
+        file_path: &AbsoluteSystemPath,
+        import_type: ImportTraceType,
+        cache: Option<(&TraceCache, u64)>,
+    ) -> Option<(Vec<AbsoluteSystemPathBuf>, SeenFile)> {
+        let Some((cache, resolution_hash)) = cache else {
+            return Self::get_uncached_imports(
+                case_checker,
+                source_map,
+                errors,
+                resolver,
+                workspace_packages,
+                file_path,
+                import_type,
+            )
+            .await;
+        };
+
+        let content_hash = tokio::fs::read(file_path)
+            .await
+            .ok()
+            .map(|contents| content_hash(&contents));
+        if let Some(imports) =
+            content_hash.and_then(|hash| cache.get(file_path, hash, resolution_hash))
+        {
+            return Some(imports);
+        }
+
+        let error_count = errors.len();
+        let imports = Self::get_uncached_imports(
+            case_checker,
+            source_map,
+            errors,
+            resolver,
+            workspace_packages,
+            file_path,
+            import_type,
+        )
+        .await;
+        if let (Some(content_hash), Some((_, seen_file))) = (content_hash, &imports) {
+            if errors.len() == error_count {
+                cache.insert(file_path, content_hash, resolution_hash, seen_file);
+            }
+        }
+        imports
+    }
+
+    async fn get_uncached_imports(
+        case_checker: &CaseChecker,
+        source_map: &SourceMap,
+        errors: &mut Vec<TraceError>,
+        resolver: &Resolver,
+        workspace_packages: &WorkspacePackages,
+        file_path: &AbsoluteSystemPath,
+        import_type: ImportTraceType,
+    ) -> Option<(Vec<AbsoluteSystemPathBuf>, SeenFile)> {
+        if is_stylesheet(file_path) {
+            // Stylesheets can't import types
+            if import_type == ImportTraceType::Types {
+                return Some((Vec::new(), SeenFile::default()));
+            }
+            Self::get_imports_from_stylesheet(errors, resolver, file_path).await
+        } else {
+            Self::get_imports_from_file(
+                case_checker,
+                source_map,
+                errors,
+                resolver,
+                workspace_packages,
+                file_path,
+                import_type,
+            )
+            .await
+        }
+    }
+
+    /// Finds the files imported by `@import`, `@use` and `@forward` in a
+    /// CSS, SCSS or LESS file.
+    pub async fn get_imports_from_stylesheet(
+        errors: &mut Vec<TraceError>,
+        resolver: &Resolver,
+        file_path: &AbsoluteSystemPath,
+    ) -> Option<(Vec<AbsoluteSystemPathBuf>, SeenFile)> {
+        let Ok(file_content) = tokio::fs::read_to_string(&file_path).await else {
+            errors.push(TraceError::FileNotFound(file_path.to_owned()));
+            return None;
+        };
+        let Some(file_dir) = file_path.parent() else {
+            errors.push(TraceError::RootFile(file_path.to_owned()));
+            return None;
+        };
+
+        let mut edges = Vec::new();
+        let mut unresolved_imports = Vec::new();
+        for CssImport {
+            specifier,
+            start,
+            end,
+        } in find_css_imports(&file_content, file_path.extension())
+        {
+            match Self::resolve_css_import(resolver, file_dir, &specifier, file_path.extension()) {
+                Ok(Some(path)) => edges.push(ImportEdge {
+                    to: path,
+                    specifier,
+                    kind: ImportKind::Static,
+                    import_type: ImportType::Value,
+                    start,
+                    end,
+                }),
+                Ok(None) => {}
+                Err(ResolveFailure::PathEncoding(err)) => {
+                    errors.push(TraceError::PathEncoding(err));
+                }
+                Err(ResolveFailure::Unresolved(err)) => {
+                    unresolved_imports.push(UnresolvedImport {
+                        specifier: specifier.clone(),
+                        kind: ImportKind::Static,
+                        import_type: ImportType::Value,
+                        start,
+                        end,
+                    });
+                    errors.push(TraceError::Resolve {
+                        import: specifier,
+                        file_path: file_path.to_string(),
+                        span: SourceSpan::new(start.into(), (end - start).into()),
+                        text: file_content.clone(),
+                        reason: err.to_string(),
+                    });
+                }
+            }
+        }
+
+        let seen_file = SeenFile {
+            unresolved_imports,
+            ..SeenFile::new(None, edges, Vec::new())
+        };
+        Some((seen_file.imports.clone(), seen_file))
+    }
+
+    /// Resolves a stylesheet import. Imports are relative to the stylesheet
+    /// unless they start with `~`, which is the convention for importing
+    /// from `node_modules`. Sass partials (`_foo.scss`) and index files are
+    /// tried for Sass imports. Anything that isn't found next to the
+    /// stylesheet is resolved like a JavaScript import.
+    fn resolve_css_import(
+        resolver: &Resolver,
+        file_dir: &AbsoluteSystemPath,
+        specifier: &str,
+        extension: Option<&str>,
+    ) -> Result<Option<AbsoluteSystemPathBuf>, ResolveFailure> {
+        // Root relative imports are resolved by the server, not the file system
+        if specifier.starts_with('/') {
+            return Ok(None);
+        }
+        if let Some(package) = specifier.strip_prefix('~') {
+            return Self::resolve_import(resolver, file_dir, package);
+        }
+
+        let base = Utf8Path::new(specifier);
+        let mut candidates = vec![base.to_owned()];
+        match extension {
+            Some("scss" | "sass") => {
+                let file_name = base.file_name().unwrap_or_default();
+                for ext in ["scss", "sass", "css"] {
+                    candidates.push(Utf8PathBuf::from(format!("{specifier}.{ext}")));
+                    candidates.push(base.with_file_name(format!("_{file_name}.{ext}")));
+                    candidates.push(base.join(format!("_index.{ext}")));
+                    candidates.push(base.join(format!("index.{ext}")));
+                }
+            }
+            Some("less") => {
+                candidates.push(Utf8PathBuf::from(format!("{specifier}.less")));
+                candidates.push(Utf8PathBuf::from(format!("{specifier}.css")));
+            }
+            _ => {}
+        }
+
+        if let Some(path) = candidates
+            .into_iter()
+            .map(|candidate| AbsoluteSystemPathBuf::from_unknown(file_dir, candidate))
+            .find(|candidate| candidate.as_std_path().is_file())
+        {
+            return Ok(Some(path));
+        }
+
+        Self::resolve_import(resolver, file_dir, specifier)
     }
 
-    pub async fn trace_file(
-        &mut self,
+    /// Resolves `import` relative to `file_dir`. Returns `None` for builtin
+    /// modules, which don't correspond to a file.
+    pub fn resolve_import(
         resolver: &Resolver,
-        file_path: AbsoluteSystemPathBuf,
-        depth: usize,
-        seen: &mut HashMap<AbsoluteSystemPathBuf, SeenFile>,
-    ) {
-        let file_resolver = Self::infer_resolver_with_ts_config(&file_path, resolver);
-        let resolver = file_resolver.as_ref().unwrap_or(resolver);
+        file_dir: &AbsoluteSystemPath,
+        import: &str,
+    ) -> Result<Option<AbsoluteSystemPathBuf>, ResolveFailure> {
+        let err = match resolver.resolve(file_dir, import) {
+            Ok(resolved) => {
+                debug!("resolved {:?}", resolved);
+                return resolved
+                    .into_path_buf()
+                    .try_into()
+                    .map(Some)
+                    .map_err(|err| ResolveFailure::PathEncoding(Arc::new(err)));
+            }
+            Err(err @ ResolveError::Builtin { .. }) => {
+                debug!("built in: {:?}", err);
+                return Ok(None);
+            }
+            Err(err) => err,
+        };
+
+        if !import.starts_with(".") {
+            // Try to resolve the import as a type import via `@/types/<import>`
+            let type_package = format!("@types/{}", import);
+            debug!("trying to resolve type import: {}", type_package);
+            let resolved_type_import = resolver
+                .resolve(file_dir, type_package.as_str())
+                .ok()
+                .and_then(|resolved| resolved.into_path_buf().try_into().ok());
 
-        if seen.contains_key(&file_path) {
-            return;
+            if let Some(resolved_type_import) = resolved_type_import {
+                debug!("resolved type import succeeded");
+                return Ok(Some(resolved_type_import));
+            }
         }
 
-        let entry = seen.entry(file_path.clone()).or_default();
+        // Also try without the extension just in case the wrong extension is used
+        let without_extension = Utf8Path::new(import).with_extension("");
+        debug!(
+            "trying to resolve extensionless import: {}",
+            without_extension
+        );
+        let resolved_extensionless_import = resolver
+            .resolve(file_dir, without_extension.as_str())
+            .ok()
+            .and_then(|resolved| resolved.into_path_buf().try_into().ok());
+
+        if let Some(resolved_extensionless_import) = resolved_extensionless_import {
+            debug!("resolved extensionless import succeeded");
+            return Ok(Some(resolved_extensionless_import));
+        }
 
-        if matches!(file_path.extension(), Some("css") | Some("json")) {
-            return;
+        debug!("failed to resolve: {:?}", err);
+        Err(ResolveFailure::Unresolved(err))
+    }
+
+    /// Finds the imports of `file_path`, using the closest tsconfig and
+    /// bundler config to resolve them
+    async fn trace_file(
+        &self,
+        resolver: &Resolver,
+        file_path: &AbsoluteSystemPath,
+        errors: &mut Vec<TraceError>,
+    ) -> Option<(Vec<AbsoluteSystemPathBuf>, SeenFile)> {
+        // Assets are included as they are, since they don't import anything
+        if FileKind::of(file_path) == FileKind::Asset {
+            return Some((Vec::new(), SeenFile::default()));
         }
 
-        let Some((imports, seen_file)) = Self::get_imports_from_file(
+        let file_resolver = self.file_resolver(resolver, file_path);
+        let resolver = file_resolver.as_deref().unwrap_or(resolver);
+        let cache = self
+            .cache
+            .is_enabled()
+            .then(|| (&*self.cache, self.resolution_hash(resolver, file_path)));
+
+        Self::get_imports(
+            &self.case_checker,
             &self.source_map,
-            &mut self.errors,
+            errors,
             resolver,
-            &file_path,
+            &self.workspace_packages,
+            file_path,
             self.import_type,
+            cache,
         )
         .await
-        else {
-            return;
+    }
+
+    /// Gets the `resolution_hash` of `resolver`, the resolver for `file_path`.
+    /// Like the resolver, it's only computed once per directory.
+    fn resolution_hash(&self, resolver: &Resolver, file_path: &AbsoluteSystemPath) -> u64 {
+        let hash = || {
+            resolution_hash(
+                resolver,
+                &self.workspace_packages,
+                self.import_type,
+                &self.cache,
+            )
+        };
+        let Some(dir) = file_path.parent() else {
+            return hash();
         };
+        if let Some(hash) = self
+            .resolution_hashes
+            .lock()
+            .expect("lock poisoned")
+            .get(dir)
+        {
+            return *hash;
+        }
+
+        let hash = hash();
+        self.resolution_hashes
+            .lock()
+            .expect("lock poisoned")
+            .insert(dir.to_owned(), hash);
+        hash
+    }
 
-        *entry = seen_file;
+    /// Gets the resolver for `file_path` from `infer_resolver`. Every file in
+    /// a directory has the same closest tsconfig, `node_modules` and bundler
+    /// config, so the resolver is only created once per directory.
+    fn file_resolver(
+        &self,
+        resolver: &Resolver,
+        file_path: &AbsoluteSystemPath,
+    ) -> Option<Arc<Resolver>> {
+        let dir = file_path.parent()?;
+        if let Some(file_resolver) = self.resolvers.lock().expect("lock poisoned").get(dir) {
+            return file_resolver.clone();
+        }
 
-        self.files
-            .extend(imports.into_iter().map(|import| (import, depth + 1)));
+        // Created without holding the lock, so other files can be traced in
+        // the meantime
+        let file_resolver =
+            Self::infer_resolver(&self.cwd, file_path, resolver, &self.bundler_aliases)
+                .map(Arc::new);
+        self.resolvers
+            .lock()
+            .expect("lock poisoned")
+            .entry(dir.to_owned())
+            .or_insert(file_resolver)
+            .clone()
+    }
+
+    /// Creates a resolver for `file_path` like `infer_resolver_with_ts_config`,
+    /// which also uses the aliases from the closest bundler config under
+    /// `root`, e.g. `resolve.alias` in `vite.config.ts`.
+    pub fn infer_resolver(
+        root: &AbsoluteSystemPath,
+        file_path: &AbsoluteSystemPath,
+        existing_resolver: &Resolver,
+        bundler_aliases: &BundlerAliases,
+    ) -> Option<Resolver> {
+        let file_resolver = Self::infer_resolver_with_ts_config(file_path, existing_resolver);
+        let aliases = bundler_aliases.for_file(root, file_path);
+        if aliases.is_empty() {
+            return file_resolver;
+        }
+
+        let resolver = file_resolver.as_ref().unwrap_or(existing_resolver);
+        let mut options = resolver.options().clone();
+        options.alias.extend(aliases);
+        Some(resolver.clone_with_options(options))
     }
 
     /// Attempts to find the closest tsconfig and creates a resolver with it,
     /// so alias resolution, e.g. `@/foo/bar`, works.
-    fn infer_resolver_with_ts_config(
+    pub fn infer_resolver_with_ts_config(
         root: &AbsoluteSystemPath,
         existing_resolver: &Resolver,
     ) -> Option<Resolver> {
@@ -363,6 +1430,10 @@ impl Tracer {
             .with_extension(".d.ts")
             .with_extension(".mjs")
             .with_extension(".cjs")
+            .with_extension(".mts")
+            .with_extension(".cts")
+            .with_extension(".d.mts")
+            .with_extension(".d.cts")
             // Some packages export a `module` field instead of `main`. This is non-standard,
             // but was a proposal at some point.
             .with_main_field("module")
@@ -371,6 +1442,24 @@ impl Tracer {
             // We add a bunch so oxc_resolver can resolve all kinds of imports.
             .with_condition_names(&["import", "require", "node", "types", "default"]);
 
+        // TypeScript sources are imported with the extension they compile to,
+        // e.g. `./foo.mjs` for `foo.mts`. The file itself is preferred, since
+        // that's what Node loads.
+        options.extension_alias = [
+            (".js", &[".js", ".ts", ".tsx", ".d.ts"][..]),
+            (".jsx", &[".jsx", ".tsx"]),
+            (".mjs", &[".mjs", ".mts", ".d.mts"]),
+            (".cjs", &[".cjs", ".cts", ".d.cts"]),
+        ]
+        .into_iter()
+        .map(|(extension, aliases)| {
+            (
+                extension.to_string(),
+                aliases.iter().map(|alias| alias.to_string()).collect(),
+            )
+        })
+        .collect();
+
         if let Some(ts_config) = ts_config {
             options.tsconfig = Some(TsconfigOptions {
                 config_file: ts_config.as_std_path().into(),
@@ -381,55 +1470,140 @@ impl Tracer {
         Resolver::new(options)
     }
 
+    /// Traces the imports of the files, up to `max_depth` imports away.
+    ///
+    /// The files are traced a level at a time, with the files in each level
+    /// traced in parallel. This means every file is traced at the shallowest
+    /// depth it's imported at.
+    ///
+    /// Files in `node_modules` are only traced as far as
+    /// `set_follow_external` allows. The files they import are still
+    /// included, without their own imports.
+    ///
+    /// Files that are imported from the last level but weren't already
+    /// traced are reported in `truncated_files`. Imports back into the traced
+    /// files, like the cycles that reach the last level, don't truncate the
+    /// result.
     pub async fn trace(mut self, max_depth: Option<usize>) -> TraceResult {
         let mut seen: HashMap<AbsoluteSystemPathBuf, SeenFile> = HashMap::new();
-        let resolver = Self::create_resolver(self.ts_config.as_deref());
+        let mut truncated_files = HashSet::new();
+        let mut errors = std::mem::take(&mut self.errors);
+        // Each file is paired with its depth and how many levels into
+        // external packages it is, if it's external. The files the trace
+        // starts from are always traced.
+        let mut level = std::mem::take(&mut self.files)
+            .into_iter()
+            .map(|(file, depth)| (file, depth, None))
+            .collect::<Vec<_>>();
+        let resolver = Arc::new(self.resolver());
+        let shared_self = Arc::new(self);
 
-        while let Some((file_path, file_depth)) = self.files.pop() {
-            if let Some(max_depth) = max_depth {
-                if file_depth > max_depth {
+        while !level.is_empty() {
+            let mut futures = JoinSet::new();
+            let mut level_files = Vec::new();
+            for (index, (file, depth, external_depth)) in level.into_iter().enumerate() {
+                if seen.contains_key(&file) {
                     continue;
                 }
+                if max_depth.is_some_and(|max_depth| depth > max_depth) {
+                    truncated_files.insert(file);
+                    continue;
+                }
+                // Files that can't be read or parsed are still included
+                seen.insert(file.clone(), SeenFile::default());
+                level_files.push(file.clone());
+                let is_traced = external_depth.map_or(true, |external_depth| {
+                    shared_self
+                        .external_depth
+                        .map_or(true, |max_depth| external_depth <= max_depth)
+                });
+                if !is_traced {
+                    debug!("not tracing external file {}", file);
+                    continue;
+                }
+
+                let shared_self = shared_self.clone();
+                let resolver = resolver.clone();
+                futures.spawn(async move {
+                    let mut errors = Vec::new();
+                    let imports = shared_self.trace_file(&resolver, &file, &mut errors).await;
+                    (index, file, depth, external_depth, errors, imports)
+                });
+            }
+
+            let mut results = Vec::with_capacity(futures.len());
+            while let Some(result) = futures.join_next().await {
+                results.push(result.expect("trace task panicked"));
+            }
+            // Handled in the order the files were found in, so errors and
+            // the next level are in the same order every time
+            results.sort_by_key(|(index, ..)| *index);
+
+            let mut next_level = Vec::new();
+            for (_, file, depth, external_depth, file_errors, imports) in results {
+                errors.extend(file_errors);
+                if let Some((imports, seen_file)) = imports {
+                    next_level.extend(imports.into_iter().map(|import| {
+                        let import_external_depth = is_external(&shared_self.cwd, &import)
+                            .then(|| external_depth.map_or(1, |external_depth| external_depth + 1));
+                        (import, depth + 1, import_external_depth)
+                    }));
+                    seen.insert(file, seen_file);
+                }
             }
-            self.trace_file(&resolver, file_path, file_depth, &mut seen)
-                .await;
+            if let Some(progress) = &shared_self.progress {
+                let traced_level = level_files
+                    .into_iter()
+                    .map(|file| {
+                        let seen_file = seen.get(&file).cloned().unwrap_or_default();
+                        (file, seen_file)
+                    })
+                    .collect();
+                // The receiver may have stopped listening, which doesn't stop the trace
+                let _ = progress.send(traced_level);
+            }
+            level = next_level;
         }
 
+        let mut truncated_files = truncated_files.into_iter().collect::<Vec<_>>();
+        truncated_files.sort();
         TraceResult {
-            source_map: self.source_map.clone(),
+            source_map: shared_self.source_map.clone(),
             files: seen,
-            errors: self.errors,
-        }
-    }
-
-    pub async fn reverse_trace(self) -> TraceResult {
-        let files = match globwalk::globwalk(
-            &self.cwd,
-            &[
-                "**/*.js".parse().expect("valid glob"),
-                "**/*.jsx".parse().expect("valid glob"),
-                "**/*.ts".parse().expect("valid glob"),
-                "**/*.tsx".parse().expect("valid glob"),
-            ],
-            &[
-                "**/node_modules/**".parse().expect("valid glob"),
-                "**/.next/**".parse().expect("valid glob"),
-            ],
-            WalkType::Files,
-        ) {
+            errors,
+            truncated_files,
+        }
+    }
+
+    /// Finds the files that import the traced files, either directly or
+    /// through other files, up to `max_depth` levels away. A depth of 1 only
+    /// includes files that directly import a traced file.
+    pub async fn reverse_trace(self, max_depth: Option<usize>) -> TraceResult {
+        let files = self
+            .files
+            .iter()
+            .map(|(file, _)| file.clone())
+            .collect::<Vec<_>>();
+        self.import_graph().await.dependents_of(&files, max_depth)
+    }
+
+    /// Parses every source file under the working directory to find which
+    /// files import each file. The files passed to `Tracer::new` aren't used.
+    pub async fn import_graph(self) -> ImportGraph {
+        let files = match source_files(&self.cwd) {
             Ok(files) => files,
             Err(e) => {
-                return TraceResult {
-                    source_map: self.source_map.clone(),
-                    files: HashMap::new(),
-                    errors: vec![TraceError::GlobError(Arc::new(e))],
-                }
+                return ImportGraph::new(
+                    self.source_map.clone(),
+                    HashMap::new(),
+                    vec![TraceError::GlobError(Arc::new(e))],
+                )
             }
         };
 
         let mut futures = JoinSet::new();
 
-        let resolver = Arc::new(Self::create_resolver(self.ts_config.as_deref()));
+        let resolver = Arc::new(self.resolver());
         let source_map = self.source_map.clone();
         let shared_self = Arc::new(self);
 
@@ -437,52 +1611,246 @@ impl Tracer {
             let shared_self = shared_self.clone();
             let resolver = resolver.clone();
             futures.spawn(async move {
-                let file_resolver = Self::infer_resolver_with_ts_config(&file, &resolver);
-                let resolver = file_resolver.as_ref().unwrap_or(&resolver);
                 let mut errors = Vec::new();
+                let imports = shared_self.trace_file(&resolver, &file, &mut errors).await;
 
-                let Some((imported_files, seen_file)) = Self::get_imports_from_file(
-                    &shared_self.source_map,
-                    &mut errors,
-                    resolver,
-                    &file,
-                    shared_self.import_type,
-                )
-                .await
-                else {
-                    return (errors, None);
-                };
-
-                for import in imported_files {
-                    if shared_self
-                        .files
-                        .iter()
-                        .any(|(source, _)| import.as_path() == source.as_path())
-                    {
-                        return (errors, Some((file, seen_file)));
-                    }
-                }
-
-                (errors, None)
+                (errors, file, imports)
             });
         }
 
-        let mut usages = HashMap::new();
+        let mut parsed_files = HashMap::new();
         let mut errors = Vec::new();
 
         while let Some(result) = futures.join_next().await {
-            let (errs, file) = result.unwrap();
+            let (errs, file, imports) = result.unwrap();
             errors.extend(errs);
 
-            if let Some((path, seen_file)) = file {
-                usages.insert(path, seen_file);
+            if let Some((_, seen_file)) = imports {
+                parsed_files.insert(file, seen_file);
             }
         }
 
-        TraceResult {
-            source_map,
-            files: usages,
-            errors,
+        ImportGraph::new(source_map, parsed_files, errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+    use tokio::sync::mpsc;
+    use turbopath::AbsoluteSystemPathBuf;
+
+    use super::{
+        is_external, resolution_hash, tsconfig_chain, CaseChecker, ImportTraceType, Tracer,
+        WorkspacePackages,
+    };
+    use crate::cache::TraceCache;
+
+    #[test]
+    fn test_extended_tsconfigs_are_hashed() {
+        let tmp = tempdir().unwrap();
+        let root = AbsoluteSystemPathBuf::try_from(tmp.path()).unwrap();
+        let tsconfig = root.join_components(&["packages", "ui", "tsconfig.json"]);
+        let base = root.join_components(&["packages", "ui", "base.json"]);
+        let shared = root.join_components(&["node_modules", "@repo", "tsconfig", "react.json"]);
+        for (path, contents) in [
+            (
+                &tsconfig,
+                r#"{
+                  // Both forms of `extends`
+                  "extends": ["./base.json", "@repo/tsconfig/react.json"]
+                }"#,
+            ),
+            (&base, r#"{ "extends": "./tsconfig.json" }"#),
+            (&shared, r#"{ "compilerOptions": { "paths": {} } }"#),
+        ] {
+            path.ensure_dir().unwrap();
+            path.create_with_contents(contents).unwrap();
+        }
+
+        let resolver = Tracer::create_resolver(Some(&tsconfig));
+        assert_eq!(
+            tsconfig_chain(&resolver, tsconfig.as_std_path()),
+            vec![tsconfig.clone(), base, shared.clone()]
+        );
+
+        let hash = || {
+            resolution_hash(
+                &resolver,
+                &WorkspacePackages::default(),
+                ImportTraceType::All,
+                &TraceCache::default(),
+            )
+        };
+        let before = hash();
+        shared
+            .create_with_contents(r#"{ "compilerOptions": { "paths": { "@/*": ["./src/*"] } } }"#)
+            .unwrap();
+        assert_ne!(hash(), before);
+    }
+
+    #[test]
+    fn test_is_external() {
+        let root = AbsoluteSystemPathBuf::new(if cfg!(windows) {
+            r"C:\node_modules\repo"
+        } else {
+            "/node_modules/repo"
+        })
+        .unwrap();
+
+        assert!(!is_external(
+            &root,
+            &root.join_components(&["packages", "ui", "index.ts"])
+        ));
+        assert!(is_external(
+            &root,
+            &root.join_components(&["node_modules", "react", "index.js"])
+        ));
+        assert!(is_external(
+            &root,
+            &root.join_components(&["packages", "ui", "node_modules", "react", "index.js"])
+        ));
+        // Outside of the repository
+        assert!(is_external(
+            &root,
+            &root
+                .parent()
+                .unwrap()
+                .join_components(&["react", "index.js"])
+        ));
+    }
+
+    #[test]
+    fn test_case_mismatch() {
+        let tmp = tempdir().unwrap();
+        let root = AbsoluteSystemPathBuf::try_from(tmp.path()).unwrap();
+        let file_dir = root.join_component("src");
+        let button = root.join_components(&["src", "components", "Button.tsx"]);
+        button.ensure_dir().unwrap();
+        button.create_with_contents("").unwrap();
+
+        let checker = CaseChecker::new(root.clone());
+        assert_eq!(checker.on_disk_path(&file_dir, &button), None);
+        assert_eq!(
+            checker.on_disk_path(
+                &file_dir,
+                &root.join_components(&["src", "Components", "button.tsx"])
+            ),
+            Some(button.clone())
+        );
+        // Installed packages aren't checked
+        let react = root.join_components(&["node_modules", "React", "index.js"]);
+        react.ensure_dir().unwrap();
+        react.create_with_contents("").unwrap();
+        assert_eq!(
+            checker.on_disk_path(
+                &file_dir,
+                &root.join_components(&["node_modules", "react", "index.js"])
+            ),
+            None
+        );
+
+        // Directories are only read once, so a file that's renamed during a
+        // trace keeps the case it had
+        button
+            .rename(&root.join_components(&["src", "components", "button.tsx"]))
+            .unwrap();
+        assert_eq!(checker.on_disk_path(&file_dir, &button), None);
+        assert_eq!(
+            CaseChecker::new(root.clone()).on_disk_path(&file_dir, &button),
+            Some(root.join_components(&["src", "components", "button.tsx"]))
+        );
+    }
+
+    /// Traces `index.ts` in a package with `package_json`, which imports a
+    /// package with separate builds for `import` and `require`
+    async fn trace_conditional_import(package_json: &str) -> Vec<String> {
+        let tmp = tempdir().unwrap();
+        let root = AbsoluteSystemPathBuf::try_from(tmp.path()).unwrap();
+        let index = root.join_component("index.ts");
+        for (path, contents) in [
+            (root.join_component("package.json"), package_json),
+            (index.clone(), r#"import "pkg";"#),
+            (
+                root.join_components(&["node_modules", "pkg", "package.json"]),
+                r#"{ "exports": { "import": "./esm.js", "require": "./cjs.js" } }"#,
+            ),
+            (root.join_components(&["node_modules", "pkg", "esm.js"]), ""),
+            (root.join_components(&["node_modules", "pkg", "cjs.js"]), ""),
+        ] {
+            path.ensure_dir().unwrap();
+            path.create_with_contents(contents).unwrap();
         }
+
+        let result = Tracer::new(root.clone(), vec![index.clone()], None)
+            .trace(None)
+            .await;
+        let mut files = result
+            .files
+            .into_keys()
+            .filter(|file| file != &index)
+            .map(|file| root.anchor(&file).unwrap().to_string())
+            .collect::<Vec<_>>();
+        files.sort();
+        files
+    }
+
+    fn root_relative(components: &[&str]) -> String {
+        components.join(std::path::MAIN_SEPARATOR_STR)
+    }
+
+    #[tokio::test]
+    async fn test_commonjs_package_type() {
+        let esm = root_relative(&["node_modules", "pkg", "esm.js"]);
+        let cjs = root_relative(&["node_modules", "pkg", "cjs.js"]);
+        assert_eq!(trace_conditional_import("{}").await, vec![esm.clone()]);
+        assert_eq!(
+            trace_conditional_import(r#"{ "type": "module" }"#).await,
+            vec![esm]
+        );
+        assert_eq!(
+            trace_conditional_import(r#"{ "type": "commonjs" }"#).await,
+            vec![cjs]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_progress_sends_each_level() {
+        let tmp = tempdir().unwrap();
+        let root = AbsoluteSystemPathBuf::try_from(tmp.path()).unwrap();
+        let main = root.join_component("main.ts");
+        let foo = root.join_component("foo.ts");
+        let bar = root.join_component("bar.ts");
+        for (path, contents) in [
+            (&main, r#"import "./foo";"#),
+            (&foo, r#"import "./bar";"#),
+            (&bar, ""),
+        ] {
+            path.create_with_contents(contents).unwrap();
+        }
+
+        let (progress, mut levels) = mpsc::unbounded_channel();
+        let mut tracer = Tracer::new(root.clone(), vec![main.clone()], None);
+        tracer.set_progress(progress);
+        let result = tracer.trace(None).await;
+        assert!(result.errors.is_empty());
+
+        let mut sent = Vec::new();
+        while let Some(level) = levels.recv().await {
+            sent.push(
+                level
+                    .into_iter()
+                    .map(|(file, seen_file)| (file, seen_file.imports))
+                    .collect::<Vec<_>>(),
+            );
+        }
+        assert_eq!(
+            sent,
+            vec![
+                vec![(main, vec![foo.clone()])],
+                vec![(foo, vec![bar.clone()])],
+                vec![(bar, vec![])],
+            ]
+        );
     }
 }
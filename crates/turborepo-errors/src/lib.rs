@@ -1,4 +1,5 @@
 use std::{
+    collections::BTreeMap,
     fmt::Display,
     ops::{Deref, Range},
     sync::Arc,
@@ -247,6 +248,20 @@ impl<T: WithMetadata> WithMetadata for Vec<T> {
     }
 }
 
+impl<K, V: WithMetadata> WithMetadata for BTreeMap<K, V> {
+    fn add_text(&mut self, text: Arc<str>) {
+        for value in self.values_mut() {
+            value.add_text(text.clone());
+        }
+    }
+
+    fn add_path(&mut self, path: Arc<str>) {
+        for value in self.values_mut() {
+            value.add_path(path.clone());
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
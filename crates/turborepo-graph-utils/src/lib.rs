@@ -1,6 +1,10 @@
 mod walker;
 
-use std::{collections::HashSet, fmt::Display, hash::Hash};
+use std::{
+    collections::{BTreeSet, HashSet},
+    fmt::Display,
+    hash::Hash,
+};
 
 use itertools::Itertools;
 use petgraph::{
@@ -57,6 +61,10 @@ pub fn validate_graph<G: Display>(graph: &Graph<G, ()>) -> Result<(), Error> {
         return Err(Error::CyclicDependencies(cycles_lines));
     }
 
+    validate_no_self_dependencies(graph)
+}
+
+pub fn validate_no_self_dependencies<G: Display>(graph: &Graph<G, ()>) -> Result<(), Error> {
     for edge in graph.edge_references() {
         if edge.source() == edge.target() {
             let node = graph
@@ -69,6 +77,97 @@ pub fn validate_graph<G: Display>(graph: &Graph<G, ()>) -> Result<(), Error> {
     Ok(())
 }
 
+/// The most cycles that `cycles` lists for a single strongly connected
+/// component. The number of cycles can grow exponentially with the number of
+/// edges in a component, so past this we only report the component.
+pub const MAX_COMPONENT_CYCLES: usize = 100;
+
+/// The most paths `cycles` extends while searching a single strongly connected
+/// component, so a dense component without many cycles can't take forever.
+const MAX_COMPONENT_SEARCH_STEPS: usize = 100_000;
+
+/// The cycles in a graph that span more than one node
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Cycles {
+    /// Each elementary cycle as the ordered path of nodes along the cycle,
+    /// starting from the node with the lowest index. The edge from the last
+    /// node back to the first is implied.
+    pub cycles: Vec<Vec<NodeIndex>>,
+    /// Strongly connected components that have too many cycles to list. Their
+    /// cycles aren't included in `cycles`.
+    pub components: Vec<Vec<NodeIndex>>,
+}
+
+/// Returns every elementary cycle in the graph that spans more than one node.
+///
+/// Components with more than `MAX_COMPONENT_CYCLES` cycles are returned as a
+/// whole instead of listing their cycles.
+pub fn cycles<N, E>(graph: &Graph<N, E>) -> Cycles {
+    let mut result = Cycles::default();
+    for component in petgraph::algo::tarjan_scc(graph) {
+        if component.len() < 2 {
+            continue;
+        }
+        let members: BTreeSet<_> = component.into_iter().collect();
+        let mut search = CycleSearch {
+            graph,
+            members: &members,
+            cycles: Vec::new(),
+            steps: 0,
+        };
+        // Only searching through nodes with a higher index than the start node
+        // guarantees that each cycle is found exactly once.
+        let complete = members
+            .iter()
+            .all(|&start| search.find_cycles(&mut vec![start]));
+        if complete {
+            result.cycles.extend(search.cycles);
+        } else {
+            result.components.push(members.into_iter().collect());
+        }
+    }
+    result
+}
+
+struct CycleSearch<'a, N, E> {
+    graph: &'a Graph<N, E>,
+    members: &'a BTreeSet<NodeIndex>,
+    cycles: Vec<Vec<NodeIndex>>,
+    steps: usize,
+}
+
+impl<N, E> CycleSearch<'_, N, E> {
+    /// Returns false if the search gave up before finding every cycle
+    fn find_cycles(&mut self, path: &mut Vec<NodeIndex>) -> bool {
+        let start = path[0];
+        let current = *path.last().expect("path contains start node");
+        // Collect into a set to skip duplicate edges and get a stable ordering
+        let neighbors: BTreeSet<_> = self.graph.neighbors(current).collect();
+        for next in neighbors {
+            if next == start {
+                if path.len() > 1 {
+                    if self.cycles.len() == MAX_COMPONENT_CYCLES {
+                        return false;
+                    }
+                    self.cycles.push(path.clone());
+                }
+            } else if next > start && self.members.contains(&next) && !path.contains(&next) {
+                self.steps += 1;
+                if self.steps > MAX_COMPONENT_SEARCH_STEPS {
+                    return false;
+                }
+                path.push(next);
+                let complete = self.find_cycles(path);
+                path.pop();
+                if !complete {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
+
 pub use walker::{WalkMessage, Walker};
 
 #[cfg(test)]
@@ -107,4 +206,66 @@ mod test {
         	d, c, b, a
         "###);
     }
+
+    #[test]
+    fn test_cycles() {
+        /*
+         a -> b --> c -> d
+         |    |\____/    |
+         |     \_______/ |
+          \_____________/
+        */
+        let mut g = Graph::new();
+        let a = g.add_node("a");
+        let b = g.add_node("b");
+        let c = g.add_node("c");
+        let d = g.add_node("d");
+        let e = g.add_node("e");
+
+        g.add_edge(a, b, ());
+        g.add_edge(b, c, ());
+        g.add_edge(c, b, ());
+        g.add_edge(c, d, ());
+        g.add_edge(d, b, ());
+        g.add_edge(d, a, ());
+        // self dependencies and edges leaving the cycle aren't cycles
+        g.add_edge(e, e, ());
+        g.add_edge(d, e, ());
+
+        assert_eq!(
+            cycles(&g),
+            Cycles {
+                cycles: vec![vec![a, b, c, d], vec![b, c], vec![b, c, d]],
+                components: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn test_too_many_cycles() {
+        // Every pair of nodes in a complete graph of 6 nodes depend on each
+        // other, which makes for 409 cycles
+        let mut g = Graph::new();
+        let nodes = (0..6).map(|i| g.add_node(i)).collect::<Vec<_>>();
+        for &from in &nodes {
+            for &to in &nodes {
+                if from != to {
+                    g.add_edge(from, to, ());
+                }
+            }
+        }
+        // A separate cycle is still listed
+        let x = g.add_node(6);
+        let y = g.add_node(7);
+        g.add_edge(x, y, ());
+        g.add_edge(y, x, ());
+
+        assert_eq!(
+            cycles(&g),
+            Cycles {
+                cycles: vec![vec![x, y]],
+                components: vec![nodes],
+            }
+        );
+    }
 }
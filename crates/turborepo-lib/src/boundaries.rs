@@ -9,16 +9,11 @@ use miette::{Diagnostic, NamedSource, Report, SourceSpan};
 use oxc_resolver::{ResolveError, Resolver};
 use regex::Regex;
 use swc_common::{
-    comments::SingleThreadedComments,
     errors::{ColorConfig, Handler},
-    input::StringInput,
-    FileName, SourceMap,
+    SourceMap,
 };
-use swc_ecma_ast::EsVersion;
-use swc_ecma_parser::{lexer::Lexer, Capturing, EsSyntax, Parser, Syntax, TsSyntax};
-use swc_ecma_visit::VisitWith;
 use thiserror::Error;
-use turbo_trace::{ImportFinder, ImportType, Tracer};
+use turbo_trace::{ImportGraph, ImportType, TraceError, Tracer};
 use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf, PathRelation, RelativeUnixPath};
 use turborepo_repository::{
     package_graph::{PackageName, PackageNode},
@@ -111,13 +106,14 @@ impl BoundariesResult {
 }
 
 impl Run {
-    pub async fn check_boundaries(&self) -> Result<BoundariesResult, Error> {
+    /// Checks the imports of the files in the filtered packages, using the
+    /// imports in `graph`
+    pub async fn check_boundaries(&self, graph: &ImportGraph) -> Result<BoundariesResult, Error> {
         let packages = self.pkg_dep_graph().packages();
         let repo = Repository::discover(self.repo_root()).ok().map(Mutex::new);
         let mut diagnostics = vec![];
-        let source_map = SourceMap::default();
         for (package_name, package_info) in packages {
-            if !self.filtered_pkgs().contains(package_name)
+            if !self.filtered_pkgs().contains_key(package_name)
                 || matches!(package_name, PackageName::Root)
             {
                 continue;
@@ -135,11 +131,11 @@ impl Run {
             let package_diagnostics = self
                 .check_package(
                     &repo,
+                    graph,
                     &package_root,
                     &package_info.package_json,
                     internal_dependencies,
                     unresolved_external_dependencies,
-                    &source_map,
                 )
                 .await?;
 
@@ -147,7 +143,7 @@ impl Run {
         }
 
         Ok(BoundariesResult {
-            source_map: Arc::new(source_map),
+            source_map: graph.source_map().clone(),
             diagnostics,
         })
     }
@@ -160,101 +156,81 @@ impl Run {
     async fn check_package(
         &self,
         repo: &Option<Mutex<Repository>>,
+        graph: &ImportGraph,
         package_root: &AbsoluteSystemPath,
         package_json: &PackageJson,
         internal_dependencies: HashSet<&PackageNode>,
         unresolved_external_dependencies: Option<&BTreeMap<String, String>>,
-        source_map: &SourceMap,
     ) -> Result<Vec<BoundariesDiagnostic>, Error> {
-        let files = globwalk::globwalk(
-            package_root,
-            &[
-                "**/*.js".parse().unwrap(),
-                "**/*.jsx".parse().unwrap(),
-                "**/*.ts".parse().unwrap(),
-                "**/*.tsx".parse().unwrap(),
-                "**/*.vue".parse().unwrap(),
-                "**/*.svelte".parse().unwrap(),
-            ],
-            &["**/node_modules/**".parse().unwrap()],
-            globwalk::WalkType::Files,
-        )?;
-
-        let mut diagnostics: Vec<BoundariesDiagnostic> = Vec::new();
+        let is_package_file = |file: &AbsoluteSystemPath| {
+            matches!(
+                file.extension(),
+                Some("js" | "jsx" | "ts" | "tsx" | "vue" | "svelte")
+            ) && matches!(package_root.relation_to_path(file), PathRelation::Parent)
+                && !repo.as_ref().is_some_and(|repo| {
+                    let repo = repo.lock().expect("lock poisoned");
+                    matches!(repo.status_should_ignore(file.as_std_path()), Ok(true))
+                })
+        };
+
+        let mut diagnostics: Vec<BoundariesDiagnostic> = graph
+            .errors()
+            .iter()
+            .filter_map(|error| match error {
+                TraceError::ParseError(file_path, err) if is_package_file(file_path) => Some(
+                    BoundariesDiagnostic::ParseError(file_path.clone(), err.clone()),
+                ),
+                _ => None,
+            })
+            .collect();
         // We assume the tsconfig.json is at the root of the package
         let tsconfig_path = package_root.join_component("tsconfig.json");
 
         let resolver =
             Tracer::create_resolver(tsconfig_path.exists().then(|| tsconfig_path.as_ref()));
 
+        let mut files = graph
+            .files()
+            .filter(|file| is_package_file(file))
+            .collect::<Vec<_>>();
+        files.sort_by_key(|file| file.as_str());
         for file_path in files {
-            if let Some(repo) = repo {
-                let repo = repo.lock().expect("lock poisoned");
-                if matches!(repo.status_should_ignore(file_path.as_std_path()), Ok(true)) {
-                    continue;
-                }
+            let Some(seen_file) = graph.file(file_path) else {
+                continue;
             };
             // Read the file content
             let Ok(file_content) = tokio::fs::read_to_string(&file_path).await else {
                 return Err(Error::FileNotFound(file_path.to_owned()));
             };
 
-            let comments = SingleThreadedComments::default();
-
-            let source_file = source_map.new_source_file(
-                FileName::Custom(file_path.to_string()).into(),
-                file_content.clone(),
-            );
-
-            let syntax = if matches!(file_path.extension(), Some("ts") | Some("tsx")) {
-                Syntax::Typescript(TsSyntax {
-                    tsx: file_path.extension() == Some("tsx"),
-                    decorators: true,
-                    ..Default::default()
-                })
-            } else {
-                Syntax::Es(EsSyntax {
-                    jsx: true,
-                    ..Default::default()
-                })
-            };
-
-            let lexer = Lexer::new(
-                syntax,
-                EsVersion::EsNext,
-                StringInput::from(&*source_file),
-                Some(&comments),
-            );
-
-            let mut parser = Parser::new_from(Capturing::new(lexer));
-
-            // Parse the file as a module
-            let module = match parser.parse_module() {
-                Ok(module) => module,
-                Err(err) => {
-                    diagnostics.push(BoundariesDiagnostic::ParseError(file_path.to_owned(), err));
-                    continue;
-                }
-            };
-
-            // Visit the AST and find imports
-            let mut finder = ImportFinder::default();
-            module.visit_with(&mut finder);
-            for (import, span, import_type) in finder.imports() {
-                let (start, end) = source_map.span_to_char_offset(&source_file, *span);
-                let start = start as usize;
-                let end = end as usize;
+            // Imports are checked by what they import, not what they resolve
+            // to, so the ones that didn't resolve are checked too
+            let mut imports = seen_file
+                .edges
+                .iter()
+                .map(|edge| (&edge.specifier, edge.import_type, edge.start, edge.end))
+                .chain(seen_file.unresolved_imports.iter().map(|import| {
+                    (
+                        &import.specifier,
+                        import.import_type,
+                        import.start,
+                        import.end,
+                    )
+                }))
+                .collect::<Vec<_>>();
+            imports.sort_by_key(|(_, _, start, _)| *start);
+            for (import, import_type, start, end) in imports {
                 let span = SourceSpan::new(start.into(), (end - start).into());
 
                 // We have a file import
                 let check_result = if import.starts_with(".") {
-                    self.check_file_import(&file_path, package_root, import, span, &file_content)?
+                    self.check_file_import(file_path, package_root, import, span, &file_content)?
                 } else if Self::is_potential_package_name(import) {
                     self.check_package_import(
                         import,
-                        *import_type,
+                        import_type,
                         span,
-                        &file_path,
+                        file_path,
                         &file_content,
                         package_json,
                         &internal_dependencies,
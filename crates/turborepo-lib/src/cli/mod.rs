@@ -4,6 +4,7 @@ use std::{
     ffi::OsString,
     fmt::{self, Display},
     io, mem, process,
+    time::Duration,
 };
 
 use biome_deserialize_macros::Deserializable;
@@ -312,6 +313,34 @@ pub enum OutputFormat {
     Json,
 }
 
+#[derive(Copy, Clone, Debug, Default, ValueEnum, Serialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum QueryOutputFormat {
+    /// The GraphQL response as JSON
+    #[default]
+    Json,
+    /// Each item of a list result as JSON on its own line
+    Jsonl,
+    /// Each item of a list result as a row of CSV
+    Csv,
+    /// The items of a list result as a GitHub Actions matrix, i.e.
+    /// `{"include":[...]}`. When `$GITHUB_OUTPUT` is set, the matrix is also
+    /// appended to it as the `matrix` output.
+    #[serde(rename = "github-matrix")]
+    GithubMatrix,
+}
+
+impl Display for QueryOutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            QueryOutputFormat::Json => "json",
+            QueryOutputFormat::Jsonl => "jsonl",
+            QueryOutputFormat::Csv => "csv",
+            QueryOutputFormat::GithubMatrix => "github-matrix",
+        })
+    }
+}
+
 impl fmt::Display for OutputFormat {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str(match self {
@@ -699,9 +728,40 @@ pub enum Command {
     /// Query your monorepo using GraphQL. If no query is provided, spins up a
     /// GraphQL server with GraphiQL.
     Query {
-        /// Pass variables to the query via a JSON file
+        /// Pass variables to the query, either as a JSON string or a path to
+        /// a JSON file
         #[clap(short = 'V', long, requires = "query")]
-        variables: Option<Utf8PathBuf>,
+        variables: Option<String>,
+        /// The operation to run when the query contains multiple operations
+        #[clap(long, requires = "query")]
+        operation_name: Option<String>,
+        /// The format to output the result in. `jsonl`, `csv` and
+        /// `github-matrix` only work for queries that return a single list,
+        /// such as `packages` or `affectedPackages`
+        #[clap(long, value_enum, default_value_t = QueryOutputFormat::Json, requires = "query")]
+        output: QueryOutputFormat,
+        /// Print the schema for the query API in GraphQL SDL and exit
+        #[clap(long, conflicts_with_all = ["query", "variables", "operation_name"])]
+        schema: bool,
+        /// Start a GraphQL server with a GraphiQL playground instead of running
+        /// a query. The repository is only loaded once, so queries to the
+        /// server are faster than running `turbo query` for each one.
+        #[clap(long, conflicts_with_all = ["query", "schema"])]
+        serve: bool,
+        /// The port for the GraphQL server to listen on
+        #[clap(long, default_value_t = crate::query::DEFAULT_PORT, conflicts_with_all = ["query", "schema"])]
+        port: u16,
+        /// The maximum nesting depth of a query
+        #[clap(long, conflicts_with = "schema")]
+        max_depth: Option<usize>,
+        /// The maximum complexity of a query. Each field counts as 1, except
+        /// for fields that trace, parse, hash or search for files, like
+        /// `dependencies`, `ast` and `files`, which count as 100.
+        #[clap(long, conflicts_with = "schema")]
+        max_complexity: Option<usize>,
+        /// The number of seconds a query can run for before it's cancelled
+        #[clap(long, conflicts_with = "schema", value_parser = clap::value_parser!(u64).range(1..))]
+        timeout: Option<u64>,
         /// The query to run, either a file path or a query string
         query: Option<String>,
     },
@@ -855,6 +915,10 @@ pub struct ExecutionArgs {
     /// turbo decide based on its own heuristics. (default auto)
     #[clap(long, value_enum)]
     pub log_order: Option<LogOrder>,
+    /// Save the given --output-logs and --log-order as the defaults for
+    /// future runs
+    #[clap(long)]
+    pub save_output_preferences: bool,
     /// Only executes the tasks specified, does not execute parent tasks.
     #[clap(long)]
     pub only: bool,
@@ -885,6 +949,7 @@ impl ExecutionArgs {
         track_usage!(telemetry, self.continue_execution, |val| val);
         track_usage!(telemetry, self.single_package, |val| val);
         track_usage!(telemetry, self.only, |val| val);
+        track_usage!(telemetry, self.save_output_preferences, |val| val);
         track_usage!(telemetry, &self.cache_dir, Option::is_some);
         track_usage!(telemetry, &self.pkg_inference_root, Option::is_some);
 
@@ -1490,17 +1555,50 @@ pub async fn run(
             })?;
             Ok(exit_code)
         }
-        Command::Query { query, variables } => {
+        Command::Query {
+            query,
+            variables,
+            operation_name,
+            output,
+            schema,
+            serve,
+            port,
+            max_depth,
+            max_complexity,
+            timeout,
+        } => {
             warn!("query command is experimental and may change in the future");
             let query = query.clone();
             let variables = variables.clone();
+            let operation_name = operation_name.clone();
+            let output = *output;
+            let schema = *schema;
+            let serve = *serve;
+            let port = *port;
+            let limits = crate::query::QueryLimits {
+                max_depth: *max_depth,
+                max_complexity: *max_complexity,
+                timeout: timeout.map(Duration::from_secs),
+            };
             let event = CommandEventBuilder::new("query").with_parent(&root_telemetry);
             event.track_call();
 
             let base = CommandBase::new(cli_args, repo_root, version, color_config)?;
             event.track_ui_mode(base.opts.run_opts.ui_mode);
 
-            let query = query::run(base, event, query, variables.as_deref()).await?;
+            let query = query::run(
+                base,
+                event,
+                query,
+                variables.as_deref(),
+                operation_name,
+                output,
+                schema,
+                serve,
+                port,
+                limits,
+            )
+            .await?;
 
             Ok(query)
         }
@@ -1,3 +1,4 @@
+use turbo_trace::Tracer;
 use turborepo_telemetry::events::command::CommandEventBuilder;
 
 use crate::{
@@ -16,7 +17,10 @@ pub async fn run(base: CommandBase, telemetry: CommandEventBuilder) -> Result<i3
         .build(&handler, telemetry)
         .await?;
 
-    let result = run.check_boundaries().await?;
+    let graph = Tracer::new(run.repo_root().to_owned(), Vec::new(), None)
+        .import_graph()
+        .await;
+    let result = run.check_boundaries(&graph).await?;
 
     result.emit();
 
@@ -640,7 +640,7 @@ mod test {
             .build()?;
 
         let mut base = CommandBase::from_opts(
-            Opts::new(&repo_root, &Args::default(), config)?,
+            Opts::new(&repo_root, &Args::default(), config, None)?,
             repo_root.clone(),
             "1.0.0",
             ColorConfig::new(false),
@@ -702,7 +702,7 @@ mod test {
             .build()?;
 
         let mut base = CommandBase::from_opts(
-            Opts::new(&repo_root, &Args::default(), config)?,
+            Opts::new(&repo_root, &Args::default(), config, None)?,
             repo_root.clone(),
             "",
             ColorConfig::new(false),
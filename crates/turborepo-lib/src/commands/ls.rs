@@ -157,7 +157,7 @@ impl<'a> RepositoryDetails<'a> {
         let mut packages: Vec<_> = package_graph
             .packages()
             .filter_map(|(package_name, package_info)| {
-                if !filtered_pkgs.contains(package_name) {
+                if !filtered_pkgs.contains_key(package_name) {
                     return None;
                 }
                 if matches!(package_name, PackageName::Root) {
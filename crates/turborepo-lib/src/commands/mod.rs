@@ -4,7 +4,7 @@ use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
 use turborepo_api_client::{APIAuth, APIClient};
 use turborepo_auth::{TURBO_TOKEN_DIR, TURBO_TOKEN_FILE};
 use turborepo_dirs::config_dir;
-use turborepo_ui::ColorConfig;
+use turborepo_ui::{tui::user_preferences_path, ColorConfig};
 
 use crate::{
     cli,
@@ -46,7 +46,7 @@ impl CommandBase {
         color_config: ColorConfig,
     ) -> Result<Self, cli::Error> {
         let config = Self::load_config(&repo_root, &args)?;
-        let opts = Opts::new(&repo_root, &args, config)?;
+        let opts = Opts::new(&repo_root, &args, config, user_preferences_path())?;
 
         Ok(Self {
             repo_root,
@@ -278,6 +278,7 @@ impl<'a> Prune<'a> {
 
         let package_graph = PackageGraph::builder(&base.repo_root, root_package_json)
             .with_allow_no_package_manager(allow_missing_package_manager)
+            .with_peer_dependencies(base.opts().repo_opts.include_peer_dependencies)
             .build()
             .await?;
 
@@ -1,16 +1,23 @@
-use std::{fs, sync::Arc};
+use std::{
+    env,
+    ffi::OsStr,
+    fs,
+    io::{self, Write},
+    sync::Arc,
+};
 
 use async_graphql::{EmptyMutation, EmptySubscription, Request, Schema, ServerError, Variables};
-use camino::Utf8Path;
 use miette::{Diagnostic, Report, SourceSpan};
+use serde_json::json;
 use thiserror::Error;
 use turbopath::AbsoluteSystemPathBuf;
 use turborepo_telemetry::events::command::CommandEventBuilder;
 
 use crate::{
+    cli::QueryOutputFormat,
     commands::{run::get_signal, CommandBase},
     query,
-    query::{Error, RepositoryQuery},
+    query::{Error, QueryLimits, RepositoryQuery},
     run::builder::RunBuilder,
     signal::SignalHandler,
 };
@@ -54,21 +61,37 @@ impl QueryError {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn run(
     base: CommandBase,
     telemetry: CommandEventBuilder,
     query: Option<String>,
-    variables_path: Option<&Utf8Path>,
+    variables: Option<&str>,
+    operation_name: Option<String>,
+    output: QueryOutputFormat,
+    print_schema: bool,
+    serve: bool,
+    port: u16,
+    limits: QueryLimits,
 ) -> Result<i32, Error> {
     let signal = get_signal()?;
     let handler = SignalHandler::new(signal);
 
-    let run_builder = RunBuilder::new(base)?
+    let mut run_builder = RunBuilder::new(base)?
         .add_all_tasks()
         .do_not_validate_engine();
+    if serve {
+        // The server uses the daemon's package discovery, and its subscriptions
+        // use the same daemon connection
+        run_builder = run_builder.long_lived();
+    }
     let run = run_builder.build(&handler, telemetry).await?;
 
-    if let Some(query) = query {
+    if print_schema {
+        // Print the same schema the server uses, including its subscriptions
+        let schema = query::build_schema(None, Arc::new(run), limits);
+        println!("{}", schema.sdl());
+    } else if let Some(query) = query {
         let trimmed_query = query.trim();
         // If the arg starts with "query" or "mutation", and ends in a bracket, it's
         // likely a direct query If it doesn't, it's a file path, so we need to
@@ -83,25 +106,67 @@ pub async fn run(
             fs::read_to_string(AbsoluteSystemPathBuf::from_unknown(run.repo_root(), query))?
         };
 
-        let schema = Schema::new(
-            RepositoryQuery::new(Arc::new(run)),
-            EmptyMutation,
-            EmptySubscription,
-        );
+        let schema = limits
+            .apply(Schema::build(
+                RepositoryQuery::new(Arc::new(run)),
+                EmptyMutation,
+                EmptySubscription,
+            ))
+            .extension(query::PerQueryCache)
+            .finish();
 
-        let variables: Variables = variables_path
-            .map(AbsoluteSystemPathBuf::from_cwd)
-            .transpose()?
-            .map(|path| path.read_to_string())
+        // Like the query, variables can either be passed directly or as a path
+        // to a file
+        let variables: Variables = variables
+            .map(|variables| {
+                if variables.trim_start().starts_with('{') {
+                    Ok::<_, Error>(variables.to_string())
+                } else {
+                    Ok(AbsoluteSystemPathBuf::from_cwd(variables)?.read_to_string()?)
+                }
+            })
             .transpose()?
             .map(|content| serde_json::from_str(&content))
             .transpose()?
             .unwrap_or_default();
 
-        let request = Request::new(&query).variables(variables);
+        let mut request = Request::new(&query).variables(variables);
+        if let Some(operation_name) = operation_name {
+            request = request.operation_name(operation_name);
+        }
 
         let result = schema.execute(request).await;
-        println!("{}", serde_json::to_string_pretty(&result)?);
+        match output {
+            QueryOutputFormat::Json => println!("{}", serde_json::to_string_pretty(&result)?),
+            QueryOutputFormat::Jsonl | QueryOutputFormat::Csv | QueryOutputFormat::GithubMatrix => {
+                let data = serde_json::to_value(&result.data)?;
+                // If the query failed there's usually no list to print, so we
+                // only complain about the shape of the result if there are no
+                // errors to explain it
+                match find_list(&data) {
+                    Some(items) if output == QueryOutputFormat::Jsonl => {
+                        for item in items {
+                            println!("{}", serde_json::to_string(item)?);
+                        }
+                    }
+                    Some(items) if output == QueryOutputFormat::Csv => {
+                        print!("{}", to_csv(items))
+                    }
+                    Some(items) => {
+                        let matrix = serde_json::to_string(&json!({ "include": items }))?;
+                        println!("{matrix}");
+                        if let Some(github_output) = env::var_os("GITHUB_OUTPUT") {
+                            write_github_output(&github_output, &matrix)
+                                .map_err(Error::GithubOutput)?;
+                        }
+                    }
+                    None if result.errors.is_empty() => {
+                        return Err(Error::NotAList(output.to_string()))
+                    }
+                    None => {}
+                }
+            }
+        }
         if !result.errors.is_empty() {
             for error in result.errors {
                 let error = QueryError::new(error, query.clone());
@@ -109,8 +174,137 @@ pub async fn run(
             }
         }
     } else {
-        query::run_query_server(run, handler).await?;
+        // Without `--serve`, the playground is opened for the user
+        query::run_query_server(run, handler, port, !serve, limits).await?;
     }
 
     Ok(0)
 }
+
+/// Appends the matrix as the `matrix` output of the current GitHub Actions
+/// step, so later jobs can use it with `fromJSON(needs.<job>.outputs.matrix)`
+fn write_github_output(path: &OsStr, matrix: &str) -> io::Result<()> {
+    let mut file = fs::OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(path)?;
+    writeln!(file, "matrix={matrix}")
+}
+
+/// Finds the list in a result like `{ "packages": { "items": [...] } }`.
+/// Every object above the list must have a single field, or an `items` field.
+fn find_list(mut value: &serde_json::Value) -> Option<&Vec<serde_json::Value>> {
+    loop {
+        match value {
+            serde_json::Value::Array(items) => return Some(items),
+            serde_json::Value::Object(fields) if fields.len() == 1 => {
+                value = fields.values().next()?;
+            }
+            serde_json::Value::Object(fields) => value = fields.get("items")?,
+            _ => return None,
+        }
+    }
+}
+
+/// Converts the items of a list to CSV. Nested objects are flattened into
+/// columns like `owner.name`, and lists are written as JSON.
+fn to_csv(items: &[serde_json::Value]) -> String {
+    fn flatten(prefix: String, value: &serde_json::Value, row: &mut Vec<(String, String)>) {
+        match value {
+            serde_json::Value::Object(fields) => {
+                for (key, value) in fields {
+                    let key = if prefix.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{prefix}.{key}")
+                    };
+                    flatten(key, value, row);
+                }
+            }
+            serde_json::Value::Null => row.push((prefix, String::new())),
+            serde_json::Value::String(value) => row.push((prefix, value.clone())),
+            value => row.push((prefix, value.to_string())),
+        }
+    }
+
+    fn escape(field: &str) -> String {
+        if field.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            field.to_string()
+        }
+    }
+
+    let rows = items
+        .iter()
+        .map(|item| {
+            let mut row = Vec::new();
+            flatten(String::new(), item, &mut row);
+            row
+        })
+        .collect::<Vec<_>>();
+
+    // Items can be missing fields, so the columns are every field seen in
+    // any item, in the order they're first seen
+    let mut columns: Vec<&str> = Vec::new();
+    for (column, _) in rows.iter().flatten() {
+        if !columns.contains(&column.as_str()) {
+            columns.push(column);
+        }
+    }
+
+    let mut csv = String::new();
+    let header = columns
+        .iter()
+        .map(|column| escape(if column.is_empty() { "value" } else { column }))
+        .collect::<Vec<_>>();
+    csv.push_str(&header.join(","));
+    csv.push('\n');
+    for row in &rows {
+        let fields = columns
+            .iter()
+            .map(|column| {
+                row.iter()
+                    .find(|(key, _)| key == column)
+                    .map_or_else(String::new, |(_, value)| escape(value))
+            })
+            .collect::<Vec<_>>();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::{find_list, to_csv};
+
+    #[test]
+    fn test_find_list() {
+        let data =
+            json!({ "packages": { "length": 2, "items": [{ "name": "a" }, { "name": "b" }] } });
+        assert_eq!(
+            find_list(&data),
+            Some(&vec![json!({ "name": "a" }), json!({ "name": "b" })])
+        );
+        assert_eq!(
+            find_list(&json!({ "version": "2.0.0", "packages": [] })),
+            None
+        );
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let items = vec![
+            json!({ "name": "a,b", "owner": { "name": "say \"hi\"" } }),
+            json!({ "name": "c", "tags": ["x", "y"], "owner": null }),
+        ];
+        assert_eq!(
+            to_csv(&items),
+            "name,owner.name,tags,owner\n\"a,b\",\"say \
+             \"\"hi\"\"\",,\nc,,\"[\"\"x\"\",\"\"y\"\"]\",\n"
+        );
+    }
+}
@@ -55,9 +55,12 @@ pub async fn run(base: CommandBase, telemetry: CommandEventBuilder) -> Result<i3
             analytics_handle.close_with_timeout().await;
         }
 
-        // We only stop if it's the TUI, for the web UI we don't need to stop
-        if let Some(UISender::Tui(sender)) = sender {
-            sender.stop().await;
+        // We only stop if it's the TUI or the accessible UI, for the web UI we
+        // don't need to stop
+        match sender {
+            Some(UISender::Tui(sender)) => sender.stop().await,
+            Some(UISender::Accessible(sender)) => sender.stop(),
+            _ => {}
         }
 
         if let Some(handle) = handle {
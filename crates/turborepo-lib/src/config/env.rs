@@ -28,6 +28,10 @@ const TURBO_MAPPING: &[(&str, &str)] = [
         "turbo_dangerously_disable_package_manager_check",
         "allow_no_package_manager",
     ),
+    (
+        "turbo_include_peer_dependencies",
+        "include_peer_dependencies",
+    ),
     ("turbo_daemon", "daemon"),
     ("turbo_env_mode", "env_mode"),
     ("turbo_cache_dir", "cache_dir"),
@@ -149,12 +153,17 @@ impl ResolvedConfigurationOptions for EnvVars {
             .map_err(Error::InvalidUploadTimeout)?;
 
         // Process experimentalUI
-        let ui =
-            self.truthy_value("ui")
-                .flatten()
-                .map(|ui| if ui { UIMode::Tui } else { UIMode::Stream });
+        let ui = match self.output_map.get("ui").map(String::as_str) {
+            Some("accessible") => Some(UIMode::Accessible),
+            _ => {
+                self.truthy_value("ui")
+                    .flatten()
+                    .map(|ui| if ui { UIMode::Tui } else { UIMode::Stream })
+            }
+        };
 
         let allow_no_package_manager = self.truthy_value("allow_no_package_manager").flatten();
+        let include_peer_dependencies = self.truthy_value("include_peer_dependencies").flatten();
 
         // Process daemon
         let daemon = self.truthy_value("daemon").flatten();
@@ -213,6 +222,7 @@ impl ResolvedConfigurationOptions for EnvVars {
             enabled: None,
             ui,
             allow_no_package_manager,
+            include_peer_dependencies,
             daemon,
             force,
             remote_only,
@@ -270,6 +280,7 @@ fn map_environment<'a>(
 #[cfg(test)]
 mod test {
     use camino::Utf8PathBuf;
+    use test_case::test_case;
 
     use super::*;
     use crate::{
@@ -308,6 +319,7 @@ mod test {
             "turbo_dangerously_disable_package_manager_check".into(),
             "true".into(),
         );
+        env.insert("turbo_include_peer_dependencies".into(), "1".into());
         env.insert("turbo_daemon".into(), "true".into());
         env.insert("turbo_preflight".into(), "true".into());
         env.insert("turbo_env_mode".into(), "strict".into());
@@ -341,6 +353,7 @@ mod test {
         assert_eq!(turbo_remote_cache_timeout, config.timeout.unwrap());
         assert_eq!(Some(UIMode::Tui), config.ui);
         assert_eq!(Some(true), config.allow_no_package_manager);
+        assert_eq!(Some(true), config.include_peer_dependencies);
         assert_eq!(Some(true), config.daemon);
         assert_eq!(Some(EnvMode::Strict), config.env_mode);
         assert_eq!(cache_dir, config.cache_dir.unwrap());
@@ -395,4 +408,18 @@ mod test {
         assert!(!config.run_summary());
         assert!(!config.allow_no_turbo_json());
     }
+
+    #[test_case("accessible", Some(UIMode::Accessible) ; "accessible")]
+    #[test_case("true", Some(UIMode::Tui) ; "truthy")]
+    #[test_case("0", Some(UIMode::Stream) ; "falsy")]
+    fn test_ui_env_setting(value: &str, expected: Option<UIMode>) {
+        let mut env: HashMap<OsString, OsString> = HashMap::new();
+        env.insert("turbo_ui".into(), value.into());
+
+        let config = EnvVars::new(&env)
+            .unwrap()
+            .get_configuration_options(&ConfigurationOptions::default())
+            .unwrap();
+        assert_eq!(config.ui, expected);
+    }
 }
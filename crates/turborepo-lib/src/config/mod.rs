@@ -192,6 +192,23 @@ pub enum Error {
         #[source_code]
         text: NamedSource,
     },
+    #[error("Allowed package cycle `{cycle}` no longer exists in the package graph.")]
+    #[diagnostic(help("Remove it from `allowedPackageCycles`."))]
+    StaleAllowedPackageCycle {
+        cycle: String,
+        #[label("cycle listed here")]
+        span: Option<SourceSpan>,
+        #[source_code]
+        text: NamedSource,
+    },
+    #[error("Package `{package}` in `sharedFiles` does not exist.")]
+    UnknownSharedFileOwner {
+        package: String,
+        #[label("package listed here")]
+        span: Option<SourceSpan>,
+        #[source_code]
+        text: NamedSource,
+    },
     #[error("Cannot load turbo.json for {0} in single package mode.")]
     InvalidTurboJsonLoad(PackageName),
 }
@@ -242,6 +259,8 @@ pub struct ConfigurationOptions {
     pub(crate) ui: Option<UIMode>,
     #[serde(rename = "dangerouslyDisablePackageManagerCheck")]
     pub(crate) allow_no_package_manager: Option<bool>,
+    #[serde(rename = "includePeerDependencies")]
+    pub(crate) include_peer_dependencies: Option<bool>,
     pub(crate) daemon: Option<bool>,
     #[serde(rename = "envMode")]
     pub(crate) env_mode: Option<EnvMode>,
@@ -321,8 +340,9 @@ impl ConfigurationOptions {
     }
 
     pub fn ui(&self) -> UIMode {
-        // If we aren't hooked up to a TTY, then do not use TUI
-        if !atty::is(atty::Stream::Stdout) {
+        // If we aren't hooked up to a TTY, then do not use TUI. The accessible
+        // UI only prints lines, so it works without one.
+        if self.ui != Some(UIMode::Accessible) && !atty::is(atty::Stream::Stdout) {
             return UIMode::Stream;
         }
 
@@ -345,6 +365,10 @@ impl ConfigurationOptions {
         self.allow_no_package_manager.unwrap_or_default()
     }
 
+    pub fn include_peer_dependencies(&self) -> bool {
+        self.include_peer_dependencies.unwrap_or_default()
+    }
+
     pub fn daemon(&self) -> Option<bool> {
         // hardcode to off in CI
         if turborepo_ci::is_ci() {
@@ -536,6 +560,7 @@ mod test {
         assert_eq!(defaults.timeout(), DEFAULT_TIMEOUT);
         assert_eq!(defaults.spaces_id(), None);
         assert!(!defaults.allow_no_package_manager());
+        assert!(!defaults.include_peer_dependencies());
         let repo_root = AbsoluteSystemPath::new(if cfg!(windows) {
             "C:\\fake\\repo"
         } else {
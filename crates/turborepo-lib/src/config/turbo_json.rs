@@ -44,6 +44,7 @@ impl<'a> TurboJsonReader<'a> {
             .map(|spaces_id| spaces_id.into());
         opts.ui = turbo_json.ui;
         opts.allow_no_package_manager = turbo_json.allow_no_package_manager;
+        opts.include_peer_dependencies = turbo_json.include_peer_dependencies;
         opts.daemon = turbo_json.daemon.map(|daemon| *daemon.as_inner());
         opts.env_mode = turbo_json.env_mode;
         opts.cache_dir = cache_dir;
@@ -166,16 +166,44 @@ impl Engine<Built> {
             .iter()
             .flat_map(|pkg| self.package_tasks.get(pkg))
             .flatten()
+            .copied()
             .collect();
+        self.create_engine_for_entrypoints(&entrypoint_indices, true)
+    }
+
+    /// Creates an instance of `Engine` that only contains `task`, and the
+    /// tasks that depend on it if `include_dependents` is set. This is used
+    /// to re-run a task in watch mode without any of its files changing.
+    /// Returns `None` if the task isn't in this engine or can't be
+    /// interrupted to run it again.
+    pub fn create_engine_for_task(
+        &self,
+        task: &TaskId<'static>,
+        include_dependents: bool,
+    ) -> Option<Engine<Built>> {
+        let index = *self.task_lookup.get(task)?;
+        let def = self.task_definitions.get(task)?;
+        if def.persistent && !def.interruptible {
+            return None;
+        }
+        Some(self.create_engine_for_entrypoints(&[index], include_dependents))
+    }
 
-        // We reverse the graph because we want the *dependents* of entrypoint tasks
-        let mut reversed_graph = self.task_graph.clone();
-        reversed_graph.reverse();
+    fn create_engine_for_entrypoints(
+        &self,
+        entrypoint_indices: &[petgraph::graph::NodeIndex],
+        include_dependents: bool,
+    ) -> Engine<Built> {
+        let node_distances = include_dependents.then(|| {
+            // We reverse the graph because we want the *dependents* of entrypoint tasks
+            let mut reversed_graph = self.task_graph.clone();
+            reversed_graph.reverse();
 
-        // This is `O(V^3)`, so in theory a bottleneck. Running dijkstra's
-        // algorithm for each entrypoint task could potentially be faster.
-        let node_distances = petgraph::algo::floyd_warshall::floyd_warshall(&reversed_graph, |_| 1)
-            .expect("no negative cycles");
+            // This is `O(V^3)`, so in theory a bottleneck. Running dijkstra's
+            // algorithm for each entrypoint task could potentially be faster.
+            petgraph::algo::floyd_warshall::floyd_warshall(&reversed_graph, |_| 1)
+                .expect("no negative cycles")
+        });
 
         let new_graph = self.task_graph.filter_map(
             |node_idx, node| {
@@ -193,10 +221,11 @@ impl Engine<Built> {
                 // If the node is reachable from any of the entrypoint tasks, we include it
                 entrypoint_indices
                     .iter()
-                    .any(|idx| {
-                        node_distances
-                            .get(&(**idx, node_idx))
-                            .map_or(false, |dist| *dist != i32::MAX)
+                    .any(|idx| match &node_distances {
+                        Some(node_distances) => node_distances
+                            .get(&(*idx, node_idx))
+                            .map_or(false, |dist| *dist != i32::MAX),
+                        None => *idx == node_idx,
                     })
                     .then_some(node.clone())
             },
@@ -816,4 +845,57 @@ mod test {
         assert!(tasks.contains(&&TaskNode::Task(a_dev_task_id)));
         assert!(tasks.contains(&&TaskNode::Task(b_build_task_id)));
     }
+
+    #[tokio::test]
+    async fn test_get_subgraph_for_task() {
+        let mut engine = Engine::new();
+
+        // `b#build` depends on `a#build`, which depends on `a#codegen`
+        let a_codegen_task_id = TaskId::new("a", "codegen");
+        let a_build_task_id = TaskId::new("a", "build");
+        let b_build_task_id = TaskId::new("b", "build");
+        let a_dev_task_id = TaskId::new("a", "dev");
+
+        let a_codegen_idx = engine.get_index(&a_codegen_task_id);
+        let a_build_idx = engine.get_index(&a_build_task_id);
+        let b_build_idx = engine.get_index(&b_build_task_id);
+        engine.get_index(&a_dev_task_id);
+        for task_id in [&a_codegen_task_id, &a_build_task_id, &b_build_task_id] {
+            engine.add_definition(task_id.clone(), TaskDefinition::default());
+        }
+        engine.add_definition(
+            a_dev_task_id.clone(),
+            TaskDefinition {
+                persistent: true,
+                ..Default::default()
+            },
+        );
+        engine.task_graph.add_edge(a_build_idx, a_codegen_idx, ());
+        engine.task_graph.add_edge(b_build_idx, a_build_idx, ());
+
+        let engine = engine.seal();
+        let alone = engine
+            .create_engine_for_task(&a_build_task_id, false)
+            .expect("task is in the engine");
+        let tasks: Vec<_> = alone.tasks().collect();
+        assert_eq!(tasks, [&TaskNode::Task(a_build_task_id.clone())]);
+
+        let with_dependents = engine
+            .create_engine_for_task(&a_build_task_id, true)
+            .expect("task is in the engine");
+        let tasks: Vec<_> = with_dependents.tasks().collect();
+        assert_eq!(tasks.len(), 2);
+        assert!(tasks.contains(&&TaskNode::Task(a_build_task_id)));
+        assert!(tasks.contains(&&TaskNode::Task(b_build_task_id)));
+
+        assert!(
+            engine
+                .create_engine_for_task(&a_dev_task_id, false)
+                .is_none(),
+            "persistent tasks keep running"
+        );
+        assert!(engine
+            .create_engine_for_task(&TaskId::new("c", "build"), false)
+            .is_none());
+    }
 }
@@ -1,11 +1,14 @@
 use std::backtrace;
 
 use camino::Utf8PathBuf;
+use clap::ValueEnum;
 use serde::Serialize;
 use thiserror::Error;
+use tracing::warn;
 use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf, AnchoredSystemPathBuf};
 use turborepo_api_client::APIAuth;
 use turborepo_cache::{CacheOpts, RemoteCacheOpts};
+use turborepo_ui::tui::PreferenceLoader;
 
 use crate::{
     cli::{
@@ -61,6 +64,7 @@ pub struct RepoOpts {
     pub root_turbo_json_path: AbsoluteSystemPathBuf,
     pub allow_no_package_manager: bool,
     pub allow_no_turbo_json: bool,
+    pub include_peer_dependencies: bool,
 }
 
 /// The fully resolved options for Turborepo. This is the combination of config,
@@ -117,10 +121,14 @@ impl Opts {
 }
 
 impl Opts {
+    /// `user_preferences_path` is where the user's TUI preferences are, which
+    /// `turbo run` reads the defaults for `--output-logs` and `--log-order`
+    /// from
     pub fn new(
         repo_root: &AbsoluteSystemPath,
         args: &Args,
         config: ConfigurationOptions,
+        user_preferences_path: Option<AbsoluteSystemPathBuf>,
     ) -> Result<Self, Error> {
         let team_id = config.team_id();
         let team_slug = config.team_slug();
@@ -148,6 +156,16 @@ impl Opts {
 
                 (&Box::new(execution_args), &Box::default())
             }
+            Some(Command::Query { .. }) => {
+                // Task hashes reported by queries should match the ones `turbo run` uses,
+                // so we use the same defaults as the `run` arguments.
+                let execution_args = ExecutionArgs {
+                    framework_inference: true,
+                    ..Default::default()
+                };
+
+                (&Box::new(execution_args), &Box::default())
+            }
             Some(Command::Boundaries { filter }) => {
                 let execution_args = ExecutionArgs {
                     filter: filter.clone(),
@@ -159,12 +177,19 @@ impl Opts {
             _ => (&Box::default(), &Box::default()),
         };
 
+        let output_preferences = match &args.command {
+            Some(Command::Run { .. }) => {
+                OutputPreferences::load(repo_root, user_preferences_path, execution_args)
+            }
+            _ => OutputPreferences::default(),
+        };
         let inputs = OptsInputs {
             repo_root,
             run_args: run_args.as_ref(),
             execution_args: execution_args.as_ref(),
             config: &config,
             api_auth: &api_auth,
+            output_preferences: &output_preferences,
         };
         let run_opts = RunOpts::try_from(inputs)?;
         let cache_opts = CacheOpts::try_from(inputs)?;
@@ -191,6 +216,65 @@ struct OptsInputs<'a> {
     execution_args: &'a ExecutionArgs,
     config: &'a ConfigurationOptions,
     api_auth: &'a Option<APIAuth>,
+    output_preferences: &'a OutputPreferences,
+}
+
+/// The defaults for `--output-logs` and `--log-order` kept with the TUI's
+/// preferences, which apply when they aren't passed or configured
+#[derive(Debug, Clone, Copy, Default)]
+struct OutputPreferences {
+    output_logs: Option<OutputLogsMode>,
+    log_order: Option<LogOrder>,
+}
+
+impl OutputPreferences {
+    /// Reads the defaults, first saving the flags passed with
+    /// `--save-output-preferences` as the new ones. Preferences that can't be
+    /// read or written are only warned about, so they can't stop a run.
+    fn load(
+        repo_root: &AbsoluteSystemPath,
+        user_preferences_path: Option<AbsoluteSystemPathBuf>,
+        execution_args: &ExecutionArgs,
+    ) -> Self {
+        let mut loader = match PreferenceLoader::with_user_preferences(
+            &repo_root.to_owned(),
+            user_preferences_path,
+        ) {
+            Ok(loader) => loader,
+            Err(err) => {
+                warn!("unable to read output preferences: {err}");
+                return Self::default();
+            }
+        };
+        if execution_args.save_output_preferences {
+            if let Some(output_logs) = execution_args.output_logs {
+                loader.set_output_logs(output_logs.to_string());
+            }
+            if let Some(log_order) = execution_args.log_order {
+                loader.set_log_order(log_order.to_string());
+            }
+            if let Err(err) = loader.flush_to_disk() {
+                warn!("unable to save output preferences: {err}");
+            }
+        }
+        Self {
+            output_logs: parse_preference("output_logs", loader.output_logs()),
+            log_order: parse_preference("log_order", loader.log_order()),
+        }
+    }
+}
+
+/// Parses a preference the way the flag it's the default for is parsed,
+/// warning about values that aren't one of the flag's
+fn parse_preference<T: ValueEnum>(name: &str, value: Option<&str>) -> Option<T> {
+    let value = value?;
+    match T::from_str(value, false) {
+        Ok(value) => Some(value),
+        Err(_) => {
+            warn!("ignoring unknown {name} preference '{value}'");
+            None
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, Default, Serialize)]
@@ -201,7 +285,10 @@ pub struct RunCacheOpts {
 impl<'a> From<OptsInputs<'a>> for RunCacheOpts {
     fn from(inputs: OptsInputs<'a>) -> Self {
         RunCacheOpts {
-            task_output_logs_override: inputs.execution_args.output_logs,
+            task_output_logs_override: inputs
+                .execution_args
+                .output_logs
+                .or(inputs.output_preferences.output_logs),
         }
     }
 }
@@ -286,11 +373,13 @@ impl<'a> From<OptsInputs<'a>> for RepoOpts {
         let root_turbo_json_path = inputs.config.root_turbo_json_path(inputs.repo_root);
         let allow_no_package_manager = inputs.config.allow_no_package_manager();
         let allow_no_turbo_json = inputs.config.allow_no_turbo_json();
+        let include_peer_dependencies = inputs.config.include_peer_dependencies();
 
         RepoOpts {
             root_turbo_json_path,
             allow_no_package_manager,
             allow_no_turbo_json,
+            include_peer_dependencies,
         }
     }
 }
@@ -314,7 +403,14 @@ impl<'a> TryFrom<OptsInputs<'a>> for RunOpts {
             f => GraphOpts::File(f.to_string()),
         });
 
-        let (is_github_actions, log_order, log_prefix) = match inputs.config.log_order() {
+        // `--log-order` lands in the config, so the preference only applies
+        // when it isn't passed or configured anywhere
+        let log_order = inputs
+            .config
+            .log_order
+            .or(inputs.output_preferences.log_order)
+            .unwrap_or_default();
+        let (is_github_actions, log_order, log_prefix) = match log_order {
             LogOrder::Auto if turborepo_ci::Vendor::get_constant() == Some("GITHUB_ACTIONS") => (
                 true,
                 ResolvedLogOrder::Grouped,
@@ -546,11 +642,11 @@ mod test {
     use test_case::test_case;
     use turbopath::AbsoluteSystemPathBuf;
     use turborepo_cache::{CacheActions, CacheConfig, CacheOpts};
-    use turborepo_ui::ColorConfig;
+    use turborepo_ui::{tui::Preferences, ColorConfig};
 
-    use super::{APIClientOpts, RepoOpts, RunOpts};
+    use super::{APIClientOpts, RepoOpts, ResolvedLogOrder, RunOpts};
     use crate::{
-        cli::{Command, DryRunMode, RunArgs},
+        cli::{Command, DryRunMode, OutputLogsMode, RunArgs},
         commands::CommandBase,
         config::ConfigurationOptions,
         opts::{Opts, RunCacheOpts, ScopeOpts},
@@ -693,6 +789,7 @@ mod test {
                 root_turbo_json_path,
                 allow_no_package_manager: false,
                 allow_no_turbo_json: false,
+                include_peer_dependencies: false,
             },
             api_client_opts: APIClientOpts {
                 api_url: "".to_string(),
@@ -788,6 +885,53 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_output_preferences_are_defaults() -> Result<(), anyhow::Error> {
+        let tmpdir = TempDir::new()?;
+        let repo_root = AbsoluteSystemPathBuf::try_from(tmpdir.path())?;
+        let user_tmpdir = TempDir::new()?;
+        let user_preferences_path =
+            AbsoluteSystemPathBuf::try_from(user_tmpdir.path())?.join_component("tui.json");
+        user_preferences_path.create_with_contents(r#"{"output_logs": "errors-only"}"#)?;
+        Preferences::import(&repo_root, r#"{"log_order": "grouped"}"#)?;
+
+        let opts = |args: &[&str]| -> Result<Opts, anyhow::Error> {
+            let args = Args::try_parse_from(args)?;
+            let config = CommandBase::load_config(&repo_root, &args)?;
+            Ok(Opts::new(
+                &repo_root,
+                &args,
+                config,
+                Some(user_preferences_path.clone()),
+            )?)
+        };
+        let defaults = opts(&["turbo", "run", "build"])?;
+        assert_eq!(
+            defaults.runcache_opts.task_output_logs_override,
+            Some(OutputLogsMode::ErrorsOnly)
+        );
+        assert_eq!(defaults.run_opts.log_order, ResolvedLogOrder::Grouped);
+
+        // Only `turbo run` reads them
+        let ls = opts(&["turbo", "ls"])?;
+        assert_eq!(ls.runcache_opts.task_output_logs_override, None);
+
+        let flags = opts(&[
+            "turbo",
+            "run",
+            "build",
+            "--output-logs=full",
+            "--log-order=stream",
+        ])?;
+        assert_eq!(
+            flags.runcache_opts.task_output_logs_override,
+            Some(OutputLogsMode::Full)
+        );
+        assert_eq!(flags.run_opts.log_order, ResolvedLogOrder::Stream);
+
+        Ok(())
+    }
+
     #[test]
     fn test_cache_config_force_remote_enable() -> Result<(), anyhow::Error> {
         let tmpdir = TempDir::new()?;
@@ -854,6 +998,7 @@ mod test {
             &AbsoluteSystemPathBuf::default(),
             &args,
             ConfigurationOptions::default(),
+            None,
         )?;
 
         insta::assert_json_snapshot!(
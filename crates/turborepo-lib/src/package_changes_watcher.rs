@@ -15,13 +15,16 @@ use turborepo_filewatch::{
     NotifyError, OptionalWatch,
 };
 use turborepo_repository::{
-    change_mapper::{ChangeMapper, GlobalDepsPackageChangeMapper, PackageChanges},
+    change_mapper::{
+        ChangeMapper, DefaultPackageChangeMapper, GlobalDepsPackageChangeMapper, PackageChanges,
+        PackageInclusionReason,
+    },
     package_graph::{PackageGraph, PackageGraphBuilder, PackageName, WorkspacePackage},
     package_json::PackageJson,
 };
 use turborepo_scm::package_deps::GitHashes;
 
-use crate::turbo_json::{TurboJson, TurboJsonLoader, CONFIG_FILE};
+use crate::turbo_json::{RawTurboJson, TurboJson, TurboJsonLoader, CONFIG_FILE};
 
 #[derive(Clone)]
 pub enum PackageChangeEvent {
@@ -115,6 +118,9 @@ fn is_in_git_folder(path: &AnchoredSystemPath) -> bool {
 struct RepoState {
     root_turbo_json: Option<TurboJson>,
     pkg_dep_graph: PackageGraph,
+    // The lockfile the package graph was built from, so that when it changes
+    // we can tell which packages' dependencies changed
+    lockfile_contents: Option<Vec<u8>>,
 }
 
 impl RepoState {
@@ -125,7 +131,14 @@ impl RepoState {
                 .iter()
                 .flat_map(|turbo| turbo.global_deps.iter())
                 .map(|s| s.as_str()),
-        ) else {
+        )
+        .and_then(|mapper| {
+            mapper.with_shared_files(
+                self.root_turbo_json
+                    .iter()
+                    .flat_map(|turbo| turbo.shared_files()),
+            )
+        }) else {
             tracing::debug!("package change mapper not available, package watcher not available");
             return None;
         };
@@ -161,7 +174,15 @@ impl Subscriber {
             tracing::debug!("no package.json found, package watcher not available");
             return None;
         };
+        // The package graph has to be built before the root turbo.json can be fully
+        // loaded, so only read the options that affect graph construction here.
+        let include_peer_dependencies =
+            RawTurboJson::read(&self.repo_root, &self.repo_root.join_component(CONFIG_FILE))
+                .ok()
+                .and_then(|turbo_json| turbo_json.include_peer_dependencies)
+                .unwrap_or_default();
         let Ok(pkg_dep_graph) = PackageGraphBuilder::new(&self.repo_root, root_package_json)
+            .with_peer_dependencies(include_peer_dependencies)
             .build()
             .await
         else {
@@ -178,6 +199,12 @@ impl Subscriber {
         .ok()
         .cloned();
 
+        let lockfile_contents = pkg_dep_graph
+            .package_manager()
+            .lockfile_path(&self.repo_root)
+            .read()
+            .ok();
+
         let gitignore_path = self.repo_root.join_component(".gitignore");
         let (root_gitignore, _) = Gitignore::new(&gitignore_path);
 
@@ -185,6 +212,7 @@ impl Subscriber {
             RepoState {
                 root_turbo_json,
                 pkg_dep_graph,
+                lockfile_contents,
             },
             root_gitignore,
         ))
@@ -342,7 +370,41 @@ impl Subscriber {
                     continue;
                 }
 
-                let changed_packages = change_mapper.changed_packages(changed_files.clone(), None);
+                let lockfile_path = repo_state
+                    .pkg_dep_graph
+                    .package_manager()
+                    .lockfile_path(&self.repo_root);
+                let lockfile_changed = ChangeMapper::<DefaultPackageChangeMapper>::lockfile_changed(
+                    &self.repo_root,
+                    &changed_files,
+                    &lockfile_path,
+                );
+                let lockfile_change = if lockfile_changed {
+                    // The package graph was built from the old lockfile, so we rebuild it
+                    // from the new one and compare the two to find the packages whose
+                    // dependencies changed
+                    let previous_lockfile = repo_state.lockfile_contents.take();
+                    match self.initialize_repo_state().await {
+                        Some((new_repo_state, new_gitignore)) => {
+                            repo_state = new_repo_state;
+                            root_gitignore = new_gitignore;
+                            change_mapper = match repo_state.get_change_mapper() {
+                                Some(change_mapper) => change_mapper,
+                                None => {
+                                    break;
+                                }
+                            };
+                        }
+                        None => {
+                            break;
+                        }
+                    }
+                    Some(previous_lockfile)
+                } else {
+                    None
+                };
+                let changed_packages =
+                    change_mapper.changed_packages(changed_files.clone(), lockfile_change);
 
                 tracing::warn!("changed_files: {:?}", changed_files);
                 tracing::warn!("changed_packages: {:?}", changed_packages);
@@ -354,6 +416,10 @@ impl Subscriber {
                         let _ = self
                             .package_change_events_tx
                             .send(PackageChangeEvent::Rediscover);
+                        // The repo state was already rebuilt from the new lockfile
+                        if lockfile_changed {
+                            continue;
+                        }
                         match self.initialize_repo_state().await {
                             Some((new_repo_state, new_gitignore)) => {
                                 repo_state = new_repo_state;
@@ -370,13 +436,11 @@ impl Subscriber {
                             }
                         }
                     }
-                    Ok(PackageChanges::Some(filtered_pkgs)) => {
-                        let mut filtered_pkgs: HashSet<WorkspacePackage> =
-                            filtered_pkgs.into_keys().collect();
+                    Ok(PackageChanges::Some(mut filtered_pkgs)) => {
                         // If the root package has changed, we only send it if we have root
                         // tasks. Otherwise it's not worth sending as it will only
                         // pollute up the output logs
-                        if filtered_pkgs.contains(&root_pkg) {
+                        if filtered_pkgs.contains_key(&root_pkg) {
                             let has_root_tasks = repo_state
                                 .root_turbo_json
                                 .as_ref()
@@ -386,8 +450,14 @@ impl Subscriber {
                             }
                         }
 
-                        for pkg in filtered_pkgs {
-                            if !self.is_same_hash(&pkg, &mut package_file_hashes).await {
+                        for (pkg, reason) in filtered_pkgs {
+                            // A package whose dependencies changed in the lockfile has the
+                            // same files as before, but still needs to be rerun
+                            let lockfile_changed =
+                                matches!(reason, PackageInclusionReason::LockfileChanged { .. });
+                            if lockfile_changed
+                                || !self.is_same_hash(&pkg, &mut package_file_hashes).await
+                            {
                                 let _ = self.package_change_events_tx.send(
                                     PackageChangeEvent::Package {
                                         name: pkg.name.clone(),
@@ -437,3 +507,112 @@ impl Subscriber {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::{sync::Arc, time::Duration};
+
+    use serde_json::json;
+    use tokio::sync::broadcast;
+    use turbopath::AbsoluteSystemPathBuf;
+    use turborepo_filewatch::{
+        cookies::CookieWriter, hash_watcher::HashWatcher, package_watcher::PackageWatcher,
+        FileSystemWatcher,
+    };
+    use turborepo_repository::package_graph::PackageName;
+    use turborepo_scm::SCM;
+
+    use super::{PackageChangeEvent, PackageChangesWatcher};
+
+    fn npm_lockfile(lodash_version: &str) -> String {
+        json!({
+            "name": "root",
+            "lockfileVersion": 3,
+            "requires": true,
+            "packages": {
+                "": { "name": "root", "workspaces": ["packages/*"] },
+                "node_modules/a": { "resolved": "packages/a", "link": true },
+                "node_modules/b": { "resolved": "packages/b", "link": true },
+                "node_modules/lodash": { "version": lodash_version },
+                "packages/a": {
+                    "version": "1.0.0",
+                    "dependencies": { "lodash": "^4.17.0" }
+                },
+                "packages/b": { "version": "1.0.0" }
+            }
+        })
+        .to_string()
+    }
+
+    async fn next_event(rx: &mut broadcast::Receiver<PackageChangeEvent>) -> PackageChangeEvent {
+        tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .expect("timed out waiting for a package change")
+            .expect("package changes channel closed")
+    }
+
+    #[tokio::test]
+    async fn test_lockfile_change_reruns_dependents() -> Result<(), anyhow::Error> {
+        let tmp = tempfile::tempdir()?;
+        let repo_root = AbsoluteSystemPathBuf::try_from(tmp.path())?.to_realpath()?;
+        repo_root
+            .join_component("package.json")
+            .create_with_contents(
+                json!({
+                    "name": "root",
+                    "packageManager": "npm@10.5.0",
+                    "workspaces": ["packages/*"]
+                })
+                .to_string(),
+            )?;
+        repo_root
+            .join_component("turbo.json")
+            .create_with_contents(json!({ "tasks": { "build": {} } }).to_string())?;
+        repo_root
+            .join_component(".gitignore")
+            .create_with_contents(".turbo\n")?;
+        for (name, dependencies) in [("a", json!({ "lodash": "^4.17.0" })), ("b", json!({}))] {
+            let package_json = repo_root.join_components(&["packages", name, "package.json"]);
+            package_json.ensure_dir()?;
+            package_json.create_with_contents(
+                json!({ "name": name, "dependencies": dependencies }).to_string(),
+            )?;
+        }
+        let lockfile = repo_root.join_component("package-lock.json");
+        lockfile.create_with_contents(npm_lockfile("4.17.20"))?;
+
+        let watcher = FileSystemWatcher::new_with_default_cookie_dir(&repo_root)?;
+        let recv = watcher.watch();
+        let cookie_writer = CookieWriter::new(
+            watcher.cookie_dir(),
+            Duration::from_millis(100),
+            recv.clone(),
+        );
+        let package_watcher = PackageWatcher::new(repo_root.clone(), recv.clone(), cookie_writer)?;
+        let hash_watcher = Arc::new(HashWatcher::new(
+            repo_root.clone(),
+            package_watcher.watch_discovery(),
+            recv.clone(),
+            SCM::new(&repo_root),
+        ));
+        let package_changes_watcher =
+            PackageChangesWatcher::new(repo_root.clone(), recv, hash_watcher);
+        let mut package_changes = package_changes_watcher.package_changes().await;
+
+        assert!(matches!(
+            next_event(&mut package_changes).await,
+            PackageChangeEvent::Rediscover
+        ));
+
+        lockfile.create_with_contents(npm_lockfile("4.17.21"))?;
+        match next_event(&mut package_changes).await {
+            PackageChangeEvent::Package { name } => assert_eq!(name, PackageName::from("a")),
+            PackageChangeEvent::Rediscover => panic!("only a's dependencies changed"),
+        }
+        // b's dependencies are the same, so it isn't rerun
+        let rest = tokio::time::timeout(Duration::from_millis(500), package_changes.recv()).await;
+        assert!(rest.is_err(), "unexpected package change");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,190 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+use async_graphql::{
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextExecute},
+    Response,
+};
+use tracing::warn;
+use turbo_trace::{ImportGraph, TraceCache};
+use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
+
+use crate::{
+    query::{
+        codeowners::CodeOwners,
+        file::{ImportType, ParseOutcome, TaskInputs, TraceResult},
+        Error,
+    },
+    run::task_id::TaskId,
+};
+
+/// Work that's shared between the resolvers of a single query, so that a file
+/// that's parsed or a trace that's run by one resolver isn't redone by
+/// another. Traces parse files themselves, so their ASTs are only reused by
+/// the files in the trace's result.
+///
+/// Every query gets a new cache from `PerQueryCache`, so a server doesn't
+/// keep results from before a file changed.
+#[derive(Default)]
+pub struct QueryCache {
+    parses: Mutex<HashMap<AbsoluteSystemPathBuf, (u64, Arc<ParseOutcome>)>>,
+    traces: Mutex<HashMap<TraceKey, TraceResult>>,
+    import_graphs: Mutex<HashMap<ImportGraphKey, Arc<ImportGraph>>>,
+    /// The compiled `inputs` of each task
+    task_inputs: Mutex<HashMap<TaskId<'static>, Arc<TaskInputs>>>,
+    /// The repository's CODEOWNERS file, once it's been looked for
+    codeowners: Mutex<Option<Option<Arc<CodeOwners>>>>,
+    /// The imports of traced files, which unlike the rest of the cache is
+    /// saved to `.turbo` and reused by later invocations
+    trace_cache: OnceLock<Arc<TraceCache>>,
+}
+
+/// The arguments to `File.dependencies`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TraceKey {
+    pub path: AbsoluteSystemPathBuf,
+    pub depth: Option<usize>,
+    pub ts_config: Option<String>,
+    pub import_type: Option<ImportType>,
+    pub emit_errors: bool,
+    pub follow_workspace_dependencies: bool,
+    pub follow_external: bool,
+    pub external_depth: Option<usize>,
+}
+
+/// The arguments to `File.dependents` that change the import graph
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ImportGraphKey {
+    pub ts_config: Option<String>,
+    pub import_type: Option<ImportType>,
+}
+
+tokio::task_local! {
+    static CURRENT: Arc<QueryCache>;
+}
+
+/// Runs every query with its own `QueryCache`
+pub struct PerQueryCache;
+
+impl ExtensionFactory for PerQueryCache {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(PerQueryCache)
+    }
+}
+
+#[async_trait::async_trait]
+impl Extension for PerQueryCache {
+    async fn execute(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        operation_name: Option<&str>,
+        next: NextExecute<'_>,
+    ) -> Response {
+        CURRENT
+            .scope(Arc::default(), next.run(ctx, operation_name))
+            .await
+    }
+}
+
+impl QueryCache {
+    /// The cache of the query that's running. Outside of a query, e.g. in a
+    /// subscription, this is a new cache every time, so nothing is reused.
+    pub fn current() -> Arc<Self> {
+        CURRENT.try_with(Arc::clone).unwrap_or_default()
+    }
+
+    pub fn trace_cache(&self, repo_root: &AbsoluteSystemPath) -> Arc<TraceCache> {
+        self.trace_cache
+            .get_or_init(|| {
+                Arc::new(TraceCache::load(repo_root.join_components(&[
+                    ".turbo",
+                    "trace",
+                    "imports.json",
+                ])))
+            })
+            .clone()
+    }
+
+    /// Saves the imports of the files that have been traced since the last
+    /// save. Failing to save only makes the next trace slower, so it's not an
+    /// error.
+    pub fn save_trace_cache(&self) {
+        if let Some(cache) = self.trace_cache.get() {
+            if let Err(err) = cache.save() {
+                warn!("failed to save trace cache: {}", err);
+            }
+        }
+    }
+
+    /// Loads and parses the CODEOWNERS file the first time it's needed
+    pub fn codeowners(
+        &self,
+        repo_root: &AbsoluteSystemPath,
+    ) -> Result<Option<Arc<CodeOwners>>, Error> {
+        let mut codeowners = self.codeowners.lock().expect("lock poisoned");
+        if let Some(codeowners) = &*codeowners {
+            return Ok(codeowners.clone());
+        }
+        let loaded = CodeOwners::load(repo_root)?.map(Arc::new);
+        *codeowners = Some(loaded.clone());
+        Ok(loaded)
+    }
+
+    /// Compiles the globs in a task's `inputs` the first time they're needed
+    pub fn task_inputs(
+        &self,
+        task_id: &TaskId<'static>,
+        inputs: &[String],
+    ) -> Result<Arc<TaskInputs>, Error> {
+        let mut task_inputs = self.task_inputs.lock().expect("lock poisoned");
+        if let Some(compiled) = task_inputs.get(task_id) {
+            return Ok(compiled.clone());
+        }
+        let compiled = Arc::new(TaskInputs::new(inputs)?);
+        task_inputs.insert(task_id.clone(), compiled.clone());
+        Ok(compiled)
+    }
+
+    pub fn get_parse(&self, path: &AbsoluteSystemPath, hash: u64) -> Option<Arc<ParseOutcome>> {
+        let parses = self.parses.lock().expect("lock poisoned");
+        parses
+            .get(path)
+            .filter(|(cached_hash, _)| *cached_hash == hash)
+            .map(|(_, parse)| parse.clone())
+    }
+
+    pub fn insert_parse(&self, path: &AbsoluteSystemPath, hash: u64, parse: Arc<ParseOutcome>) {
+        self.parses
+            .lock()
+            .expect("lock poisoned")
+            .insert(path.to_owned(), (hash, parse));
+    }
+
+    /// Gets the import graph built with `key`. The cache only lasts for one
+    /// query, so the graph is never from before a file changed.
+    pub fn get_import_graph(&self, key: &ImportGraphKey) -> Option<Arc<ImportGraph>> {
+        let graphs = self.import_graphs.lock().expect("lock poisoned");
+        graphs.get(key).cloned()
+    }
+
+    pub fn insert_import_graph(&self, key: ImportGraphKey, graph: Arc<ImportGraph>) {
+        self.import_graphs
+            .lock()
+            .expect("lock poisoned")
+            .insert(key, graph);
+    }
+
+    pub fn get_trace(&self, key: &TraceKey) -> Option<TraceResult> {
+        let traces = self.traces.lock().expect("lock poisoned");
+        traces.get(key).cloned()
+    }
+
+    pub fn insert_trace(&self, key: TraceKey, result: TraceResult) {
+        self.traces
+            .lock()
+            .expect("lock poisoned")
+            .insert(key, result);
+    }
+}
@@ -0,0 +1,112 @@
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
+
+use crate::query::Error;
+
+/// Where GitHub looks for a CODEOWNERS file, in the order it looks
+const CODEOWNERS_PATHS: &[&[&str]] = &[
+    &[".github", "CODEOWNERS"],
+    &["CODEOWNERS"],
+    &["docs", "CODEOWNERS"],
+];
+
+/// The rules in a repository's CODEOWNERS file
+pub struct CodeOwners {
+    repo_root: AbsoluteSystemPathBuf,
+    rules: Vec<(Gitignore, Vec<String>)>,
+}
+
+impl CodeOwners {
+    /// Loads the CODEOWNERS file for the repository, if there is one
+    pub fn load(repo_root: &AbsoluteSystemPath) -> Result<Option<Self>, Error> {
+        for path in CODEOWNERS_PATHS {
+            let path = repo_root.join_components(path);
+            if path.exists() {
+                return Ok(Some(Self::parse(repo_root, &path.read_to_string()?)?));
+            }
+        }
+        Ok(None)
+    }
+
+    fn parse(repo_root: &AbsoluteSystemPath, contents: &str) -> Result<Self, Error> {
+        let mut rules = Vec::new();
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut parts = line.split_whitespace();
+            let Some(pattern) = parts.next() else {
+                continue;
+            };
+            let owners = parts
+                .take_while(|owner| !owner.starts_with('#'))
+                .map(|owner| owner.to_string())
+                .collect();
+
+            // CODEOWNERS patterns follow the same rules as `.gitignore`
+            let mut builder = GitignoreBuilder::new(repo_root);
+            builder.add_line(None, pattern).map_err(Error::CodeOwners)?;
+            rules.push((builder.build().map_err(Error::CodeOwners)?, owners));
+        }
+        Ok(Self {
+            repo_root: repo_root.to_owned(),
+            rules,
+        })
+    }
+
+    /// The owners of a file. The last rule that matches the file wins, and
+    /// it's possible for a rule to have no owners. Files outside of the
+    /// repository have no owners.
+    pub fn owners(&self, path: &AbsoluteSystemPath) -> &[String] {
+        // The matchers panic if they're given a path outside of the root
+        let Ok(path) = self.repo_root.anchor(path) else {
+            return &[];
+        };
+        self.rules
+            .iter()
+            .rev()
+            .find(|(matcher, _)| {
+                matcher
+                    .matched_path_or_any_parents(path.as_path(), false)
+                    .is_ignore()
+            })
+            .map_or(&[], |(_, owners)| owners.as_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+    use turbopath::AbsoluteSystemPathBuf;
+
+    use super::CodeOwners;
+
+    #[test]
+    fn test_owners() {
+        let tmp = tempdir().unwrap();
+        let repo_root = AbsoluteSystemPathBuf::try_from(tmp.path()).unwrap();
+        let codeowners = CodeOwners::parse(
+            &repo_root,
+            "* @everyone\n/packages/ @packages-team # comment\n*.md\n",
+        )
+        .unwrap();
+
+        let owners = |path: &[&str]| codeowners.owners(&repo_root.join_components(path));
+        assert_eq!(owners(&["package.json"]), ["@everyone"]);
+        assert_eq!(owners(&["packages", "ui", "index.ts"]), ["@packages-team"]);
+        assert!(owners(&["packages", "ui", "README.md"]).is_empty());
+    }
+
+    #[test]
+    fn test_owners_outside_repo() {
+        let tmp = tempdir().unwrap();
+        let repo_root = AbsoluteSystemPathBuf::try_from(tmp.path()).unwrap();
+        let codeowners =
+            CodeOwners::parse(&repo_root.join_component("repo"), "* @everyone\n").unwrap();
+
+        assert!(codeowners
+            .owners(&repo_root.join_components(&["other", "index.ts"]))
+            .is_empty());
+    }
+}
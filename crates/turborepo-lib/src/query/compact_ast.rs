@@ -0,0 +1,199 @@
+//! A small, stable representation of a module's AST. Unlike the serialized SWC
+//! `Module`, its shape doesn't depend on SWC's internal representation, so it
+//! doesn't change when we upgrade SWC.
+
+use serde::Serialize;
+use swc_common::{BytePos, Span};
+use swc_ecma_ast::*;
+use swc_ecma_visit::{Visit, VisitWith};
+
+#[derive(Debug, Serialize)]
+pub struct CompactNode {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<String>,
+    /// Start and end byte offsets into the file
+    span: [usize; 2],
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    children: Vec<CompactNode>,
+}
+
+impl CompactNode {
+    /// Builds the compact tree for `module`. `file_start` is the position of
+    /// the start of the file in the `SourceMap` used to parse it, so that spans
+    /// are reported relative to the file.
+    pub fn from_module(module: &Module, file_start: BytePos) -> Self {
+        let mut builder = Builder {
+            file_start,
+            stack: Vec::new(),
+        };
+        builder.push("Module", module.span, None, None);
+        module.visit_children_with(&mut builder);
+
+        builder
+            .stack
+            .pop()
+            .expect("module node is never popped while visiting")
+    }
+}
+
+struct Builder {
+    file_start: BytePos,
+    // The nodes currently being visited, each one is a child of the one before
+    stack: Vec<CompactNode>,
+}
+
+impl Builder {
+    fn push(
+        &mut self,
+        kind: &'static str,
+        span: Span,
+        name: Option<String>,
+        value: Option<String>,
+    ) {
+        let (start, end) = turbo_trace::span_offsets(span, self.file_start);
+        self.stack.push(CompactNode {
+            kind,
+            name,
+            value,
+            span: [start, end],
+            children: Vec::new(),
+        });
+    }
+
+    fn pop(&mut self) {
+        let node = self.stack.pop().expect("pop is always paired with a push");
+        self.stack
+            .last_mut()
+            .expect("module node is always at the bottom of the stack")
+            .children
+            .push(node);
+    }
+
+    /// Records a node and everything below it
+    fn node<N: VisitWith<Self>>(&mut self, kind: &'static str, span: Span, node: &N) {
+        self.push(kind, span, None, None);
+        node.visit_children_with(self);
+        self.pop();
+    }
+
+    fn leaf(
+        &mut self,
+        kind: &'static str,
+        span: Span,
+        name: Option<String>,
+        value: Option<String>,
+    ) {
+        self.push(kind, span, name, value);
+        self.pop();
+    }
+}
+
+macro_rules! compact_nodes {
+    ($($method:ident($ty:ty) => $kind:literal,)*) => {
+        $(
+            fn $method(&mut self, node: &$ty) {
+                self.node($kind, node.span, node);
+            }
+        )*
+    };
+}
+
+// Only nodes that are meaningful to clients are recorded. Everything else is
+// skipped over, with its children attached to the nearest recorded ancestor.
+impl Visit for Builder {
+    compact_nodes! {
+        visit_import_decl(ImportDecl) => "ImportDeclaration",
+        visit_import_named_specifier(ImportNamedSpecifier) => "ImportSpecifier",
+        visit_import_default_specifier(ImportDefaultSpecifier) => "ImportDefaultSpecifier",
+        visit_import_star_as_specifier(ImportStarAsSpecifier) => "ImportNamespaceSpecifier",
+        visit_export_decl(ExportDecl) => "ExportDeclaration",
+        visit_named_export(NamedExport) => "ExportNamedDeclaration",
+        visit_export_default_decl(ExportDefaultDecl) => "ExportDefaultDeclaration",
+        visit_export_default_expr(ExportDefaultExpr) => "ExportDefaultExpression",
+        visit_export_all(ExportAll) => "ExportAllDeclaration",
+        visit_var_declarator(VarDeclarator) => "VariableDeclarator",
+        visit_ts_interface_decl(TsInterfaceDecl) => "InterfaceDeclaration",
+        visit_ts_type_alias_decl(TsTypeAliasDecl) => "TypeAliasDeclaration",
+        visit_ts_enum_decl(TsEnumDecl) => "EnumDeclaration",
+        visit_ts_module_decl(TsModuleDecl) => "ModuleDeclaration",
+        visit_call_expr(CallExpr) => "CallExpression",
+        visit_new_expr(NewExpr) => "NewExpression",
+        visit_arrow_expr(ArrowExpr) => "ArrowFunctionExpression",
+        visit_member_expr(MemberExpr) => "MemberExpression",
+        visit_expr_stmt(ExprStmt) => "ExpressionStatement",
+        visit_return_stmt(ReturnStmt) => "ReturnStatement",
+        visit_if_stmt(IfStmt) => "IfStatement",
+        visit_jsx_element(JSXElement) => "JSXElement",
+    }
+
+    fn visit_fn_decl(&mut self, node: &FnDecl) {
+        self.node("FunctionDeclaration", node.function.span, node);
+    }
+
+    fn visit_fn_expr(&mut self, node: &FnExpr) {
+        self.node("FunctionExpression", node.function.span, node);
+    }
+
+    fn visit_class_decl(&mut self, node: &ClassDecl) {
+        self.node("ClassDeclaration", node.class.span, node);
+    }
+
+    fn visit_class_expr(&mut self, node: &ClassExpr) {
+        self.node("ClassExpression", node.class.span, node);
+    }
+
+    fn visit_var_decl(&mut self, node: &VarDecl) {
+        let kind = match node.kind {
+            VarDeclKind::Var => "var",
+            VarDeclKind::Let => "let",
+            VarDeclKind::Const => "const",
+        };
+        self.push(
+            "VariableDeclaration",
+            node.span,
+            None,
+            Some(kind.to_string()),
+        );
+        node.visit_children_with(self);
+        self.pop();
+    }
+
+    fn visit_ident(&mut self, node: &Ident) {
+        self.leaf("Identifier", node.span, Some(node.sym.to_string()), None);
+    }
+
+    fn visit_ident_name(&mut self, node: &IdentName) {
+        self.leaf("Identifier", node.span, Some(node.sym.to_string()), None);
+    }
+
+    fn visit_str(&mut self, node: &Str) {
+        self.leaf(
+            "StringLiteral",
+            node.span,
+            None,
+            Some(node.value.to_string()),
+        );
+    }
+
+    fn visit_number(&mut self, node: &Number) {
+        self.leaf(
+            "NumericLiteral",
+            node.span,
+            None,
+            Some(node.value.to_string()),
+        );
+    }
+
+    fn visit_bool(&mut self, node: &Bool) {
+        self.leaf(
+            "BooleanLiteral",
+            node.span,
+            None,
+            Some(node.value.to_string()),
+        );
+    }
+}
@@ -1,9 +1,19 @@
 use std::sync::Arc;
 
-use async_graphql::Object;
+use async_graphql::{Object, SimpleObject};
 
 use crate::run::Run;
 
+/// An external package that a workspace package depends on
+#[derive(SimpleObject)]
+pub struct ExternalDependency {
+    #[graphql(flatten)]
+    pub package: ExternalPackage,
+    /// Whether the package is listed in the workspace package's
+    /// `package.json`, rather than being a dependency of a dependency
+    pub direct: bool,
+}
+
 #[derive(Clone)]
 pub struct ExternalPackage {
     run: Arc<Run>,
@@ -15,6 +25,12 @@ impl ExternalPackage {
         Self { run, package }
     }
 
+    /// This uses a different naming convention because we already have a
+    /// `version` resolver defined for GraphQL
+    pub fn get_version(&self) -> &str {
+        &self.package.version
+    }
+
     /// Converts the lockfile key to a human friendly name
     pub fn human_name(&self) -> String {
         self.run
@@ -30,4 +46,9 @@ impl ExternalPackage {
     async fn name(&self) -> String {
         self.human_name().to_string()
     }
+
+    /// The version of the package that's resolved in the lockfile
+    async fn version(&self) -> &str {
+        &self.package.version
+    }
 }
@@ -1,18 +1,50 @@
-use std::sync::Arc;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::Arc,
+};
 
-use async_graphql::{Enum, Object, SimpleObject};
+use async_graphql::{ComplexObject, Context, Enum, InputObject, Object, SimpleObject};
+use base64::Engine;
 use camino::Utf8PathBuf;
 use miette::SourceCode;
-use swc_ecma_ast::EsVersion;
+use oxc_resolver::Resolver;
+use swc_common::{sync::Lrc, BytePos, SourceFile, SourceMap, Span, Spanned};
+use swc_ecma_ast::{EsVersion, ModuleDecl, ModuleItem};
 use swc_ecma_parser::{EsSyntax, Syntax, TsSyntax};
-use turbo_trace::Tracer;
-use turbopath::AbsoluteSystemPathBuf;
+use swc_ecma_visit::VisitWith;
+use turbo_trace::{
+    content_hash, extract_script, is_single_file_component, span_offsets, BundlerAliases,
+    ExportFinder, ImportFinder, ImportGraph, ImportedNames, RuntimeResolvers, Tracer,
+    WorkspacePackages,
+};
+use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
+use turborepo_repository::{
+    change_mapper::{
+        ChangeMapper, GlobalDepsPackageChangeMapper, PackageChanges, PackageInclusionReason,
+    },
+    package_graph::PackageName,
+};
+use turborepo_scm::package_deps::INPUT_INCLUDE_DEFAULT_FILES;
+use wax::Program;
 
 use crate::{
-    query::{Array, Diagnostic, Error},
+    engine::TaskNode,
+    query::{
+        cache::{ImportGraphKey, QueryCache, TraceKey},
+        compact_ast::CompactNode,
+        limits::TRACE_COMPLEXITY,
+        null_on_error,
+        package::Package,
+        task::RepositoryTask,
+        Array, Diagnostic, Error, PackageChangeReason, RepositoryQuery,
+    },
     run::Run,
 };
 
+/// Maps files to the packages that are affected when they change
+pub type FileChangeMapper<'a> = ChangeMapper<'a, GlobalDepsPackageChangeMapper<'a>>;
+
+#[derive(Clone)]
 pub struct File {
     run: Arc<Run>,
     path: AbsoluteSystemPathBuf,
@@ -37,15 +69,217 @@ impl File {
         self
     }
 
-    fn parse_file(&self) -> Result<swc_ecma_ast::Module, Error> {
-        let contents = self.path.read_to_string()?;
-        let source_map = swc_common::SourceMap::default();
+    pub fn get_path(&self) -> &AbsoluteSystemPath {
+        &self.path
+    }
+
+    /// The files this file imports from or re-exports from, along with the
+    /// exports of each file that it uses. Imports that don't resolve to a file
+    /// are skipped.
+    pub fn imported_exports(
+        &self,
+        resolver: &Resolver,
+    ) -> Result<Vec<(AbsoluteSystemPathBuf, ImportedNames)>, Error> {
+        let parsed = self.parse_file()?;
+        let mut import_finder = ImportFinder::default();
+        parsed.module.visit_with(&mut import_finder);
+
+        let file_resolver = Tracer::infer_resolver_with_ts_config(&self.path, resolver);
+        let resolver = file_resolver.as_ref().unwrap_or(resolver);
+        let Some(dir) = self.path.parent() else {
+            return Ok(Vec::new());
+        };
+
+        // Re-exports are found as imports too
+        Ok(import_finder
+            .imports()
+            .iter()
+            .filter_map(|import| {
+                let path = Tracer::resolve_import(resolver, dir, &import.specifier).ok()??;
+                Some((path, import.names.clone()))
+            })
+            .collect())
+    }
+
+    /// The top level exports of the file, in the order they're declared
+    pub fn get_exports(&self) -> Result<Vec<Export>, Error> {
+        let parsed = self.parse_file()?;
+        let mut finder = ExportFinder::default();
+        parsed.module.visit_with(&mut finder);
+
+        Ok(finder
+            .exports()
+            .iter()
+            .map(|export| {
+                let (start, end) = parsed.offsets(export.span);
+                Export {
+                    name: export.name.clone(),
+                    kind: export.kind.into(),
+                    start,
+                    end,
+                }
+            })
+            .collect())
+    }
+
+    /// Finds the packages that are affected when a file changes, using the
+    /// same rules as `--affected`. Building one compiles the globs in
+    /// `turbo.json`, so it should be reused for every file in a query.
+    pub fn change_mapper(run: &Run) -> Result<FileChangeMapper<'_>, Error> {
+        let package_detector = GlobalDepsPackageChangeMapper::new(
+            run.pkg_dep_graph(),
+            run.root_turbo_json()
+                .global_deps
+                .iter()
+                .map(|dep| dep.as_str()),
+        )?
+        .with_shared_files(run.root_turbo_json().shared_files())?;
+        Ok(ChangeMapper::new(
+            run.pkg_dep_graph(),
+            vec![],
+            package_detector,
+        ))
+    }
+
+    /// The packages that are affected when this file changes
+    fn package_changes(&self, change_mapper: &FileChangeMapper) -> Result<PackageChanges, Error> {
+        let path = self.run.repo_root().anchor(&self.path)?;
+        Ok(change_mapper.changed_packages(HashSet::from([path]), None)?)
+    }
+
+    /// Whether changing the file doesn't affect any workspace package, i.e. it
+    /// isn't in a workspace package and doesn't match `globalDependencies`
+    pub fn get_is_orphan(&self, change_mapper: &FileChangeMapper) -> Result<bool, Error> {
+        // Changes to the lockfile are mapped to the packages whose external
+        // dependencies changed, which the change mapper can't see here
+        let package_manager = self.run.pkg_dep_graph().package_manager();
+        if self.path == package_manager.lockfile_path(self.run.repo_root()) {
+            return Ok(false);
+        }
+
+        Ok(match self.package_changes(change_mapper)? {
+            PackageChanges::All(_) => false,
+            PackageChanges::Some(packages) => packages
+                .keys()
+                .all(|package| package.name == PackageName::Root),
+        })
+    }
+
+    /// Whether the file is hashed as an input of a task with the given
+    /// `inputs`, following the same rules as task hashing. The file must be in
+    /// the task's package.
+    fn is_task_input(&self, package_dir: &AbsoluteSystemPath, inputs: &TaskInputs) -> bool {
+        let Ok(path) = package_dir.anchor(&self.path) else {
+            return false;
+        };
+        inputs.is_match(path.to_unix().as_str())
+    }
+
+    /// The packages that are affected when the file changes, sorted by name
+    fn file_packages(&self) -> Result<Vec<FilePackage>, Error> {
+        let run = &self.run;
+        let query = RepositoryQuery::new(run.clone());
+        match self.package_changes(&Self::change_mapper(run)?)? {
+            PackageChanges::All(reason) => Ok(vec![FilePackage {
+                package: None,
+                package_json_path: None,
+                all_packages: true,
+                reason: query.convert_change_reason(PackageInclusionReason::All(reason)),
+            }]),
+            PackageChanges::Some(packages) => {
+                let mut packages = packages.into_iter().collect::<Vec<_>>();
+                packages.sort_by(|(a, _), (b, _)| a.name.cmp(&b.name));
+                packages
+                    .into_iter()
+                    .map(|(package, reason)| {
+                        let package_json_path = run
+                            .pkg_dep_graph()
+                            .package_info(&package.name)
+                            .map(|info| info.package_json_path.to_unix().to_string());
+                        Ok(FilePackage {
+                            package: Some(Package::new(run.clone(), package.name)?),
+                            package_json_path,
+                            all_packages: false,
+                            reason: query.convert_change_reason(reason),
+                        })
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    fn parse_file(&self) -> Result<ParsedFile, Error> {
+        let (parse, source) = self.parse()?;
+        let ParseOutcome { module, mut errors } = Arc::unwrap_or_clone(parse);
+        let Some(module) = module else {
+            let error = errors
+                .pop()
+                .expect("a parse that failed has the error that stopped it");
+            return Err(Error::Parse(error));
+        };
+        Ok(ParsedFile { module, source })
+    }
+
+    /// Parses the file, or reuses the parse from earlier in the query if the
+    /// file hasn't changed. Every parse uses a new source map, so spans in a
+    /// cached AST line up with the returned file.
+    fn parse(&self) -> Result<(Arc<ParseOutcome>, Source), Error> {
+        let (source, syntax) = self.source()?;
+        let Source { file, .. } = &source;
+        let hash = content_hash(file.src.as_bytes());
+
+        let cache = QueryCache::current();
+        if let Some(parse) = cache.get_parse(&self.path, hash) {
+            return Ok((parse, source));
+        }
+
+        let comments = swc_common::comments::SingleThreadedComments::default();
+        let mut errors = Vec::new();
+        let module = match swc_ecma_parser::parse_file_as_module(
+            file,
+            syntax,
+            EsVersion::EsNext,
+            Some(&comments),
+            &mut errors,
+        ) {
+            Ok(module) => Some(module),
+            Err(error) => {
+                errors.push(error);
+                None
+            }
+        };
+        let parse = Arc::new(ParseOutcome { module, errors });
+        cache.insert_parse(&self.path, hash, parse.clone());
+
+        Ok((parse, source))
+    }
+
+    /// Reads the file's script into a new source map without parsing it, so
+    /// that offsets can be converted even if the file doesn't parse
+    fn source(&self) -> Result<(Source, Syntax), Error> {
+        let (contents, syntax) = self.script()?;
+        let source_map: Lrc<SourceMap> = Default::default();
         let file = source_map.new_source_file(
             swc_common::FileName::Custom(self.path.to_string()).into(),
-            contents.clone(),
+            contents,
         );
-        let syntax = if self.path.extension() == Some("ts") || self.path.extension() == Some("tsx")
-        {
+        Ok((Source { source_map, file }, syntax))
+    }
+
+    /// Reads the script in the file along with the syntax to parse it with.
+    /// For Vue and Svelte components this is only their `<script>` blocks.
+    fn script(&self) -> Result<(String, Syntax), Error> {
+        let contents = self.path.read_to_string()?;
+        if is_single_file_component(&self.path) {
+            let script = extract_script(&contents);
+            let syntax = script.syntax();
+            return Ok((script.source, syntax));
+        }
+        Ok((contents, self.syntax()))
+    }
+
+    fn syntax(&self) -> Syntax {
+        if matches!(self.path.extension(), Some("ts" | "tsx" | "mts" | "cts")) {
             Syntax::Typescript(TsSyntax {
                 tsx: self.path.extension() == Some("tsx"),
                 decorators: true,
@@ -56,22 +290,153 @@ impl File {
                 jsx: self.path.ends_with(".jsx"),
                 ..Default::default()
             })
+        }
+    }
+
+    /// Parses the file and returns every syntax error, including the ones the
+    /// parser recovers from. An error that stops the parser is always last.
+    fn syntax_errors(&self) -> Result<Vec<Diagnostic>, Error> {
+        let (parse, Source { file, .. }) = self.parse()?;
+        let path = self.run.repo_root().anchor(&self.path)?.to_string();
+        Ok(parse
+            .errors
+            .iter()
+            .map(|error| {
+                let (start, end) = span_offsets(error.span(), file.start_pos);
+                Diagnostic {
+                    message: error.kind().msg().to_string(),
+                    path: Some(path.clone()),
+                    start: Some(start),
+                    end: Some(end),
+                    ..Default::default()
+                }
+            })
+            .collect())
+    }
+}
+
+/// The `inputs` of a task with their globs compiled, so they can be matched
+/// against many files
+pub struct TaskInputs {
+    /// Every file is an input if there aren't any globs
+    all: bool,
+    /// Whether the inputs include `$TURBO_DEFAULT$`
+    default_files: bool,
+    includes: Vec<wax::Glob<'static>>,
+    excludes: Vec<wax::Glob<'static>>,
+}
+
+impl TaskInputs {
+    pub fn new(inputs: &[String]) -> Result<Self, Error> {
+        let mut task_inputs = Self {
+            all: inputs.is_empty(),
+            default_files: false,
+            includes: Vec::new(),
+            excludes: Vec::new(),
         };
-        let comments = swc_common::comments::SingleThreadedComments::default();
-        let mut errors = Vec::new();
-        let module = swc_ecma_parser::parse_file_as_module(
-            &file,
-            syntax,
-            EsVersion::EsNext,
-            Some(&comments),
-            &mut errors,
-        )
-        .map_err(Error::Parse)?;
+        for input in inputs {
+            if input == INPUT_INCLUDE_DEFAULT_FILES {
+                task_inputs.default_files = true;
+            } else if let Some(exclusion) = input.strip_prefix('!') {
+                task_inputs
+                    .excludes
+                    .push(wax::Glob::new(exclusion)?.into_owned());
+            } else {
+                task_inputs
+                    .includes
+                    .push(wax::Glob::new(input)?.into_owned());
+            }
+        }
+        Ok(task_inputs)
+    }
+
+    /// Whether a path relative to the task's package is an input
+    fn is_match(&self, path: &str) -> bool {
+        // `package.json` and `turbo.json` are always inputs
+        if self.all || path == "package.json" || path == "turbo.json" {
+            return true;
+        }
+        if self.excludes.iter().any(|glob| glob.is_match(path)) {
+            return false;
+        }
+        self.default_files || self.includes.iter().any(|glob| glob.is_match(path))
+    }
+}
+
+/// The result of parsing a file, which is shared by every resolver that needs
+/// the file's AST or syntax errors
+#[derive(Clone)]
+pub struct ParseOutcome {
+    /// This is `None` if an error stopped the parser, in which case it's the
+    /// last of `errors`
+    module: Option<swc_ecma_ast::Module>,
+    /// Every syntax error, including the ones the parser recovered from
+    errors: Vec<swc_ecma_parser::error::Error>,
+}
+
+/// A file's source, which is added to a `SourceMap` of its own
+struct Source {
+    source_map: Lrc<SourceMap>,
+    file: Lrc<SourceFile>,
+}
+
+/// A parsed module along with the source it was parsed from, which is
+/// needed to translate spans back into the original source.
+struct ParsedFile {
+    module: swc_ecma_ast::Module,
+    source: Source,
+}
 
-        Ok(module)
+impl ParsedFile {
+    /// The start and end byte offsets of `span` in the file
+    fn offsets(&self, span: Span) -> (usize, usize) {
+        span_offsets(span, self.source.file.start_pos)
     }
 }
 
+impl Source {
+    /// Converts a byte offset into the file into a line and column. Anything
+    /// reporting a location in a file should go through this so that they all
+    /// agree on how positions are counted.
+    fn position(&self, offset: usize) -> Option<Position> {
+        if !self.file.src.is_char_boundary(offset) {
+            return None;
+        }
+        let loc = self
+            .source_map
+            .lookup_char_pos(self.file.start_pos + BytePos(offset as u32));
+        Some(Position {
+            line: loc.line,
+            column: loc.col.0 + 1,
+        })
+    }
+
+    /// Builds a source map that maps every line of the file to itself. There
+    /// are no transforms applied to the source yet, so this is exact.
+    fn identity_source_map(&self, source: &str) -> serde_json::Value {
+        let line_count = self.file.count_lines();
+        let mappings = (0..line_count)
+            .map(|line| if line == 0 { "AAAA" } else { "AACA" })
+            .collect::<Vec<_>>()
+            .join(";");
+        serde_json::json!({
+            "version": 3,
+            "sources": [source],
+            "sourcesContent": [self.file.src.as_str()],
+            "names": [],
+            "mappings": mappings,
+        })
+    }
+}
+
+/// A position in a file, where both the line and column start at 1. Columns
+/// are counted in characters, not bytes.
+#[derive(SimpleObject, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
 impl From<turbo_trace::TraceError> for Diagnostic {
     fn from(error: turbo_trace::TraceError) -> Self {
         let message = error.to_string();
@@ -120,33 +485,516 @@ impl From<turbo_trace::TraceError> for Diagnostic {
                     end: Some(span.offset() + span.len()),
                 }
             }
+            turbo_trace::TraceError::CaseMismatch {
+                span,
+                text,
+                file_path,
+                actual_path,
+                ..
+            } => {
+                let import = text
+                    .read_span(&span, 1, 1)
+                    .ok()
+                    .map(|s| String::from_utf8_lossy(s.data()).to_string());
+
+                Diagnostic {
+                    message,
+                    import,
+                    reason: Some(format!("the file on disk is `{actual_path}`")),
+                    path: Some(file_path),
+                    start: Some(span.offset()),
+                    end: Some(span.offset() + span.len()),
+                }
+            }
         }
     }
 }
 
-#[derive(SimpleObject)]
-struct TraceResult {
+#[derive(SimpleObject, Clone)]
+#[graphql(complex)]
+pub struct TraceResult {
     files: Array<File>,
     errors: Array<Diagnostic>,
+    /// Import cycles between the traced files, as lists of paths relative to
+    /// the repository root. Each file in a cycle imports the next one, and
+    /// the last one imports the first.
+    cycles: Vec<Vec<String>>,
+    /// Dynamic imports and requires in the traced files that couldn't be
+    /// followed because their argument isn't a string literal. When this isn't
+    /// empty, the traced files may be incomplete.
+    unresolved_dynamic_imports: Array<UnresolvedDynamicImport>,
+    /// Whether files were left out because they're more than `depth`
+    /// imports away
+    truncated: bool,
+    /// The files that were left out because they're more than `depth`
+    /// imports away. Their own imports aren't traced.
+    truncated_files: Array<File>,
+    #[graphql(skip)]
+    graph: TraceGraph,
+}
+
+/// A dynamic import or require whose argument isn't a string literal, e.g.
+/// `import(`./locales/${locale}`)`
+#[derive(SimpleObject, Clone)]
+pub struct UnresolvedDynamicImport {
+    /// The path of the file containing the import, relative to the repository
+    /// root
+    path: String,
+    kind: ImportKind,
+    /// Byte offset of the start of the import
+    start: usize,
+    /// Byte offset of the end of the import
+    end: usize,
+}
+
+impl UnresolvedDynamicImport {
+    /// Collects the unresolved dynamic imports of every traced file, sorted
+    /// by path and position
+    fn from_trace(result: &turbo_trace::TraceResult, run: &Run) -> Result<Array<Self>, Error> {
+        let mut imports = Vec::new();
+        for (path, file) in &result.files {
+            let path = run.repo_root().anchor(path)?.to_unix().to_string();
+            for import in &file.unresolved_dynamic_imports {
+                imports.push(Self {
+                    path: path.clone(),
+                    kind: import.kind.into(),
+                    start: import.start,
+                    end: import.end,
+                });
+            }
+        }
+        imports.sort_by(|a, b| (&a.path, a.start).cmp(&(&b.path, b.start)));
+        Ok(Array::from(imports))
+    }
+}
+
+/// An import of one traced file by another, with paths relative to the
+/// repository root
+#[derive(SimpleObject, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct TraceEdge {
+    from: String,
+    to: String,
+    /// Whether `from` only imports `to` with `import type`, so `to` isn't
+    /// needed at runtime
+    type_only: bool,
+    /// Each import of `to` in `from`, in the order they appear
+    imports: Vec<EdgeImport>,
+}
+
+/// One of the imports that make up an edge
+#[derive(SimpleObject, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct EdgeImport {
+    kind: ImportKind,
+    /// Byte offset of the start of the import in `from`
+    start: usize,
+    /// Byte offset of the end of the import in `from`
+    end: usize,
+}
+
+/// How to render the graph of a trace
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Enum)]
+pub enum GraphFormat {
+    /// A Graphviz digraph
+    Dot,
+    /// A Mermaid flowchart
+    Mermaid,
+    /// An object with a list of `nodes` and a list of `edges`
+    Json,
+}
+
+/// The files in a trace and the imports between them, with paths relative to
+/// the repository root
+#[derive(Debug, Default, Clone)]
+struct TraceGraph {
+    nodes: Vec<String>,
+    edges: Vec<TraceEdge>,
+}
+
+impl TraceGraph {
+    fn new(
+        result: &turbo_trace::TraceResult,
+        repo_root: &AbsoluteSystemPath,
+    ) -> Result<Self, Error> {
+        let relative = |path: &AbsoluteSystemPath| -> Result<String, Error> {
+            Ok(repo_root.anchor(path)?.to_unix().to_string())
+        };
+        let mut nodes = result
+            .files
+            .keys()
+            .map(|path| relative(path))
+            .collect::<Result<Vec<_>, Error>>()?;
+        let mut edges: Vec<TraceEdge> = Vec::new();
+        for (path, edge) in result.edges() {
+            let from = relative(path)?;
+            let to = relative(&edge.to)?;
+            let import = EdgeImport {
+                kind: match edge.import_type {
+                    turbo_trace::ImportType::Type => ImportKind::TypeOnly,
+                    turbo_trace::ImportType::Value => edge.kind.into(),
+                },
+                start: edge.start,
+                end: edge.end,
+            };
+            // Each file's imports are next to each other
+            match edges
+                .iter_mut()
+                .rev()
+                .take_while(|file_edge| file_edge.from == from)
+                .find(|file_edge| file_edge.to == to)
+            {
+                Some(file_edge) => file_edge.imports.push(import),
+                None => edges.push(TraceEdge {
+                    from,
+                    type_only: result.files[path].type_only_imports.contains(&edge.to),
+                    to,
+                    imports: vec![import],
+                }),
+            }
+        }
+        nodes.sort();
+        edges.sort();
+
+        Ok(Self { nodes, edges })
+    }
+
+    fn to_dot(&self) -> String {
+        let escape = |path: &str| path.replace('"', "\\\"");
+        let mut dot = String::from("digraph {\n");
+        for node in &self.nodes {
+            dot.push_str(&format!("  \"{}\";\n", escape(node)));
+        }
+        for TraceEdge {
+            from,
+            to,
+            type_only,
+            ..
+        } in &self.edges
+        {
+            let style = if *type_only { " [style=dashed]" } else { "" };
+            dot.push_str(&format!(
+                "  \"{}\" -> \"{}\"{style};\n",
+                escape(from),
+                escape(to)
+            ));
+        }
+        dot.push('}');
+        dot
+    }
+
+    fn to_mermaid(&self) -> String {
+        // Mermaid node ids can't contain most punctuation, so nodes are
+        // numbered and labelled with their path
+        let ids = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node.as_str(), format!("n{i}")))
+            .collect::<HashMap<_, _>>();
+        let mut mermaid = String::from("graph LR\n");
+        for node in &self.nodes {
+            mermaid.push_str(&format!(
+                "  {}[\"{}\"]\n",
+                ids[node.as_str()],
+                node.replace('"', "#quot;")
+            ));
+        }
+        for TraceEdge {
+            from,
+            to,
+            type_only,
+            ..
+        } in &self.edges
+        {
+            let arrow = if *type_only { "-.->" } else { "-->" };
+            mermaid.push_str(&format!(
+                "  {} {arrow} {}\n",
+                ids[from.as_str()],
+                ids[to.as_str()]
+            ));
+        }
+        mermaid.truncate(mermaid.trim_end().len());
+        mermaid
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "nodes": self.nodes,
+            "edges": self
+                .edges
+                .iter()
+                .map(|edge| {
+                    serde_json::json!({ "from": edge.from, "to": edge.to, "typeOnly": edge.type_only })
+                })
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+#[ComplexObject]
+impl TraceResult {
+    /// The graph of imports between the traced files, including the file the
+    /// trace started from. DOT and Mermaid graphs are returned as strings.
+    async fn graph(&self, format: GraphFormat) -> serde_json::Value {
+        match format {
+            GraphFormat::Dot => self.graph.to_dot().into(),
+            GraphFormat::Mermaid => self.graph.to_mermaid().into(),
+            GraphFormat::Json => self.graph.to_json(),
+        }
+    }
+
+    /// The imports between the traced files, including the file the trace
+    /// started from. `typeOnly` keeps only the edges whose `typeOnly` matches
+    /// it, so `typeOnly: false` is the edges that are needed at runtime. Use
+    /// `importType: VALUES` when tracing to leave out type-only imports
+    /// entirely.
+    async fn edges(&self, type_only: Option<bool>) -> Vec<TraceEdge> {
+        self.graph
+            .edges
+            .iter()
+            .filter(|edge| type_only.map_or(true, |type_only| edge.type_only == type_only))
+            .cloned()
+            .collect()
+    }
+}
+
+/// A file found by tracing several entrypoints at once
+#[derive(SimpleObject)]
+pub struct TracedFile {
+    #[graphql(flatten)]
+    pub file: File,
+    /// The entrypoints that import the file, directly or indirectly, as paths
+    /// relative to the repository root. An entrypoint is included in its own
+    /// entries.
+    entries: Vec<String>,
+}
+
+/// The result of tracing several entrypoints at once
+#[derive(SimpleObject)]
+pub struct BatchTraceResult {
+    /// Every traced file, including the entrypoints
+    pub files: Array<TracedFile>,
+    pub errors: Array<Diagnostic>,
+    pub unresolved_dynamic_imports: Array<UnresolvedDynamicImport>,
+    /// Whether files were left out because they're more than `depth`
+    /// imports away from every entrypoint
+    pub truncated: bool,
+    /// The files that were left out because they're more than `depth`
+    /// imports away from every entrypoint
+    pub truncated_files: Array<File>,
+}
+
+impl BatchTraceResult {
+    /// Traces the dependencies of every path, which are relative to the
+    /// repository root
+    pub async fn trace(
+        run: Arc<Run>,
+        paths: Vec<String>,
+        depth: Option<usize>,
+        ts_config: Option<String>,
+        import_type: Option<ImportType>,
+        follow_workspace_dependencies: bool,
+    ) -> Result<Self, Error> {
+        let cache = QueryCache::current();
+        let (tracer, entries) = Self::tracer(
+            &run,
+            &cache,
+            paths,
+            ts_config,
+            import_type,
+            follow_workspace_dependencies,
+        )?;
+        let result = tracer.trace(depth).await;
+        cache.save_trace_cache();
+        result.emit_errors();
+        Self::new(result, &entries, depth, run)
+    }
+
+    /// Creates a tracer for every path, which are relative to the repository
+    /// root, and returns it with the absolute paths of the entrypoints. The
+    /// tracer uses the trace cache of `cache`.
+    pub fn tracer(
+        run: &Run,
+        cache: &QueryCache,
+        paths: Vec<String>,
+        ts_config: Option<String>,
+        import_type: Option<ImportType>,
+        follow_workspace_dependencies: bool,
+    ) -> Result<(Tracer, Vec<AbsoluteSystemPathBuf>), Error> {
+        let entries = paths
+            .into_iter()
+            .map(|path| {
+                let abs_path = AbsoluteSystemPathBuf::from_unknown(run.repo_root(), path);
+                if !abs_path.exists() {
+                    return Err(Error::FileNotFound(abs_path.to_string()));
+                }
+                Ok(abs_path)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut tracer = Tracer::new(
+            run.repo_root().to_owned(),
+            entries.clone(),
+            ts_config.map(Utf8PathBuf::from),
+        );
+        if let Some(import_type) = import_type {
+            tracer.set_import_type(import_type.into());
+        }
+        if follow_workspace_dependencies {
+            tracer.set_workspace_packages(workspace_packages(run));
+        }
+        tracer.set_cache(cache.trace_cache(run.repo_root()));
+        Ok((tracer, entries))
+    }
+
+    /// Converts the result of tracing from each of `entries`, attributing
+    /// every file to the entries that reach it within `depth` imports
+    pub fn new(
+        result: turbo_trace::TraceResult,
+        entries: &[AbsoluteSystemPathBuf],
+        depth: Option<usize>,
+        run: Arc<Run>,
+    ) -> Result<Self, Error> {
+        let attribution = attribute_to_entries(&result.files, entries, depth, run.repo_root())?;
+
+        let unresolved_dynamic_imports = UnresolvedDynamicImport::from_trace(&result, &run)?;
+        let mut files = Vec::with_capacity(result.files.len());
+        for (path, entries) in attribution {
+            let ast = result.files.get(path).and_then(|file| file.ast.clone());
+            files.push(TracedFile {
+                file: File::new(run.clone(), path.to_owned())?.with_ast(ast),
+                entries,
+            });
+        }
+        files.sort_by(|a, b| a.file.path.cmp(&b.file.path));
+        let truncated_files = truncated_files(result.truncated_files, &run)?;
+
+        Ok(Self {
+            files: Array::from(files),
+            errors: result.errors.into_iter().map(|e| e.into()).collect(),
+            unresolved_dynamic_imports,
+            truncated: !truncated_files.is_empty(),
+            truncated_files,
+        })
+    }
+}
+
+impl TracedFile {
+    pub fn new(file: File, entries: Vec<String>) -> Self {
+        Self { file, entries }
+    }
+}
+
+/// Finds the entrypoints that reach each of `files` within `depth` imports,
+/// as paths relative to the repository root. Only the imports of `files` are
+/// followed, so files that haven't been traced yet aren't attributed.
+pub fn attribute_to_entries<'a>(
+    files: &'a HashMap<AbsoluteSystemPathBuf, turbo_trace::SeenFile>,
+    entries: &'a [AbsoluteSystemPathBuf],
+    depth: Option<usize>,
+    repo_root: &AbsoluteSystemPath,
+) -> Result<HashMap<&'a AbsoluteSystemPath, Vec<String>>, Error> {
+    let mut attribution: HashMap<&AbsoluteSystemPath, Vec<String>> = HashMap::new();
+    for entry in entries {
+        let name = repo_root.anchor(entry)?.to_unix().to_string();
+        let entry: &AbsoluteSystemPath = entry;
+        if !files.contains_key(entry) {
+            continue;
+        }
+        let mut seen = HashSet::from([entry]);
+        let mut queue = VecDeque::with_capacity(files.len());
+        queue.push_back((entry, 0));
+        while let Some((path, path_depth)) = queue.pop_front() {
+            attribution.entry(path).or_default().push(name.clone());
+            if depth.map_or(false, |depth| path_depth >= depth) {
+                continue;
+            }
+            for import in files.get(path).iter().flat_map(|file| &file.imports) {
+                let import: &AbsoluteSystemPath = import;
+                if files.contains_key(import) && seen.insert(import) {
+                    queue.push_back((import, path_depth + 1));
+                }
+            }
+        }
+    }
+    for entries in attribution.values_mut() {
+        entries.sort();
+        entries.dedup();
+    }
+    Ok(attribution)
+}
+
+fn truncated_files(
+    truncated_files: Vec<AbsoluteSystemPathBuf>,
+    run: &Arc<Run>,
+) -> Result<Array<File>, Error> {
+    truncated_files
+        .into_iter()
+        .map(|path| File::new(run.clone(), path))
+        .collect()
+}
+
+/// The directories of the workspace packages, for resolving imports of them
+/// without going through `node_modules`
+pub fn workspace_packages(run: &Run) -> turbo_trace::WorkspacePackages {
+    turbo_trace::WorkspacePackages::new(
+        run.pkg_dep_graph()
+            .packages()
+            .filter(|(name, _)| **name != PackageName::Root)
+            .map(|(name, info)| {
+                (
+                    name.to_string(),
+                    run.repo_root().resolve(info.package_path()),
+                )
+            })
+            .collect(),
+    )
 }
 
 impl TraceResult {
-    fn new(result: turbo_trace::TraceResult, run: Arc<Run>) -> Result<Self, Error> {
+    /// Converts the result of tracing from `root`. `root` is used to find
+    /// cycles, but isn't included in the files.
+    fn new(
+        mut result: turbo_trace::TraceResult,
+        root: &AbsoluteSystemPath,
+        run: Arc<Run>,
+    ) -> Result<Self, Error> {
+        let cycles = result
+            .cycles()
+            .into_iter()
+            .map(|cycle| {
+                cycle
+                    .iter()
+                    .map(|path| Ok(run.repo_root().anchor(path)?.to_unix().to_string()))
+                    .collect::<Result<Vec<_>, Error>>()
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        let graph = TraceGraph::new(&result, run.repo_root())?;
+        // The root's own dynamic imports are included, since they're just as
+        // likely to hide dependencies
+        let unresolved_dynamic_imports = UnresolvedDynamicImport::from_trace(&result, &run)?;
+        result.files.remove(root);
+
         let mut files = result
             .files
             .into_iter()
             .map(|(path, file)| Ok(File::new(run.clone(), path)?.with_ast(file.ast)))
             .collect::<Result<Vec<_>, Error>>()?;
         files.sort_by(|a, b| a.path.cmp(&b.path));
+        let truncated_files = truncated_files(result.truncated_files, &run)?;
         Ok(Self {
             files: Array::from(files),
             errors: result.errors.into_iter().map(|e| e.into()).collect(),
+            cycles,
+            unresolved_dynamic_imports,
+            truncated: !truncated_files.is_empty(),
+            truncated_files,
+            graph,
         })
     }
 }
 
 /// The type of imports to trace.
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Enum)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Enum)]
 pub enum ImportType {
     /// Trace all imports.
     All,
@@ -166,11 +1014,223 @@ impl From<ImportType> for turbo_trace::ImportTraceType {
     }
 }
 
+/// The representation of a file's AST.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Enum)]
+pub enum AstFormat {
+    /// The AST exactly as SWC produces it. Its shape can change between
+    /// versions of turbo.
+    #[default]
+    Full,
+    /// A minimal tree of node types, names, values and spans that's stable
+    /// across versions of turbo.
+    Compact,
+}
+
+/// A range of byte offsets into a file
+#[derive(InputObject, Clone, Copy, Debug)]
+pub struct SpanRange {
+    start: usize,
+    end: usize,
+}
+
+/// Limits which top level items of a module are included in its AST.
+struct AstFilter {
+    only_imports: bool,
+    span_range: Option<SpanRange>,
+}
+
+impl AstFilter {
+    fn is_empty(&self) -> bool {
+        !self.only_imports && self.span_range.is_none()
+    }
+
+    /// Removes the items that don't match the filter from `module`.
+    /// `file_start` is used to make spans relative to the file.
+    fn apply(&self, module: &mut swc_ecma_ast::Module, file_start: BytePos) {
+        module.body.retain(|item| {
+            if self.only_imports
+                && !matches!(
+                    item,
+                    ModuleItem::ModuleDecl(ModuleDecl::Import(_) | ModuleDecl::TsImportEquals(_))
+                )
+            {
+                return false;
+            }
+            self.span_range.map_or(true, |range| {
+                let (start, end) = span_offsets(item.span(), file_start);
+                start < range.end && end > range.start
+            })
+        });
+    }
+}
+
+/// What an export refers to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Enum)]
+pub enum ExportKind {
+    Function,
+    Class,
+    Variable,
+    Interface,
+    TypeAlias,
+    Enum,
+    Module,
+    /// `export default ...`
+    Default,
+    /// `export { foo }`
+    Named,
+    /// `export { foo } from "./foo"`
+    Reexport,
+    /// `export * as foo from "./foo"`
+    Namespace,
+    /// `export * from "./foo"`
+    All,
+}
+
+impl From<turbo_trace::ExportKind> for ExportKind {
+    fn from(kind: turbo_trace::ExportKind) -> Self {
+        match kind {
+            turbo_trace::ExportKind::Function => ExportKind::Function,
+            turbo_trace::ExportKind::Class => ExportKind::Class,
+            turbo_trace::ExportKind::Variable => ExportKind::Variable,
+            turbo_trace::ExportKind::Interface => ExportKind::Interface,
+            turbo_trace::ExportKind::TypeAlias => ExportKind::TypeAlias,
+            turbo_trace::ExportKind::Enum => ExportKind::Enum,
+            turbo_trace::ExportKind::Module => ExportKind::Module,
+            turbo_trace::ExportKind::Default => ExportKind::Default,
+            turbo_trace::ExportKind::Named => ExportKind::Named,
+            turbo_trace::ExportKind::Reexport => ExportKind::Reexport,
+            turbo_trace::ExportKind::Namespace => ExportKind::Namespace,
+            turbo_trace::ExportKind::All => ExportKind::All,
+        }
+    }
+}
+
+/// Whether a file can import other files
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Enum)]
+pub enum FileKind {
+    /// A script, stylesheet or component, whose imports are traced
+    Source,
+    /// A file that can only be imported, like JSON, an image or a WebAssembly
+    /// module
+    Asset,
+}
+
+impl From<turbo_trace::FileKind> for FileKind {
+    fn from(kind: turbo_trace::FileKind) -> Self {
+        match kind {
+            turbo_trace::FileKind::Source => FileKind::Source,
+            turbo_trace::FileKind::Asset => FileKind::Asset,
+        }
+    }
+}
+
+/// The syntax used to import a module.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Enum)]
+pub enum ImportKind {
+    /// `import foo from "./foo"`
+    Static,
+    /// `import("./foo")`
+    Dynamic,
+    /// `require("./foo")`
+    Require,
+    /// `export { foo } from "./foo"` or `export * from "./foo"`
+    Reexport,
+    /// `import type { Foo } from "./foo"`
+    TypeOnly,
+}
+
+impl From<turbo_trace::ImportKind> for ImportKind {
+    fn from(kind: turbo_trace::ImportKind) -> Self {
+        match kind {
+            turbo_trace::ImportKind::Static => ImportKind::Static,
+            turbo_trace::ImportKind::Dynamic => ImportKind::Dynamic,
+            turbo_trace::ImportKind::Require => ImportKind::Require,
+            turbo_trace::ImportKind::Reexport => ImportKind::Reexport,
+        }
+    }
+}
+
+impl From<&turbo_trace::Import> for ImportKind {
+    fn from(import: &turbo_trace::Import) -> Self {
+        match (import.kind, import.import_type) {
+            (_, turbo_trace::ImportType::Type) => ImportKind::TypeOnly,
+            (kind, turbo_trace::ImportType::Value) => kind.into(),
+        }
+    }
+}
+
+/// The package a file belongs to for change detection, i.e. the package that
+/// is affected when the file changes
+#[derive(SimpleObject)]
+pub struct FilePackage {
+    /// This is null when a change to the file affects every package
+    package: Option<Package>,
+    /// The path to the package's `package.json`, relative to the repository
+    /// root
+    package_json_path: Option<String>,
+    /// Whether a change to the file affects every package, e.g. because it
+    /// matches `globalDependencies`
+    all_packages: bool,
+    /// Why the file belongs to the package, or to every package
+    reason: PackageChangeReason,
+}
+
+#[derive(SimpleObject)]
+pub struct Import {
+    /// The module specifier, i.e. the string being imported
+    specifier: String,
+    kind: ImportKind,
+    /// The file the import resolves to. This is null for builtin modules and
+    /// imports that couldn't be resolved.
+    resolved: Option<File>,
+    /// Byte offset of the start of the import
+    start: usize,
+    /// Byte offset of the end of the import
+    end: usize,
+}
+
+#[derive(SimpleObject, Debug)]
+pub struct Export {
+    /// The name other files import this export as. This is `default` for
+    /// default exports and `*` for `export * from`.
+    pub name: String,
+    pub kind: ExportKind,
+    /// Byte offset of the start of the export statement
+    start: usize,
+    /// Byte offset of the end of the export statement
+    end: usize,
+}
+
+/// Builds the import graph of the repository with `key`. Every file in the
+/// repository is parsed to build it, so it's reused by the rest of the query.
+pub async fn import_graph(run: &Run, key: ImportGraphKey) -> Arc<ImportGraph> {
+    let cache = QueryCache::current();
+    if let Some(graph) = cache.get_import_graph(&key) {
+        return graph;
+    }
+
+    let mut tracer = Tracer::new(
+        run.repo_root().to_owned(),
+        Vec::new(),
+        key.ts_config.clone().map(Utf8PathBuf::from),
+    );
+    if let Some(import_type) = key.import_type {
+        tracer.set_import_type(import_type.into());
+    }
+    tracer.set_cache(cache.trace_cache(run.repo_root()));
+
+    let graph = Arc::new(tracer.import_graph().await);
+    cache.save_trace_cache();
+    cache.insert_import_graph(key, graph.clone());
+    graph
+}
+
+// Fields that read or parse the file resolve to null with an error if that
+// fails, so one bad file doesn't fail a query over many files
 #[Object]
 impl File {
-    async fn contents(&self) -> Result<String, Error> {
-        let contents = self.path.read_to_string()?;
-        Ok(contents)
+    async fn contents(&self, ctx: &Context<'_>) -> Option<String> {
+        null_on_error(ctx, || Ok(self.path.read_to_string()?))
     }
 
     async fn path(&self) -> Result<String, Error> {
@@ -185,13 +1245,177 @@ impl File {
         Ok(self.path.to_string())
     }
 
+    /// The hash turbo uses for the file when it's an input to a task. This
+    /// matches the hashes in a run summary.
+    #[graphql(complexity = "TRACE_COMPLEXITY + child_complexity")]
+    async fn content_hash(&self) -> Result<Option<String>, Error> {
+        let repo_root = self.run.repo_root();
+        let path = repo_root.anchor(&self.path)?;
+        let mut hashes = self
+            .run
+            .scm()
+            .get_hashes_for_files(repo_root, &[&path], false)?;
+        Ok(hashes.remove(&path.to_unix()))
+    }
+
+    /// Whether the file is traced for imports, or is an asset that's only
+    /// imported. Assets are included in traces without being parsed.
+    async fn kind(&self) -> FileKind {
+        turbo_trace::FileKind::of(&self.path).into()
+    }
+
+    /// The size of the file in bytes
+    async fn size_bytes(&self, ctx: &Context<'_>) -> Option<u64> {
+        null_on_error(ctx, || Ok(std::fs::metadata(&self.path)?.len()))
+    }
+
+    /// The number of lines in the file. A trailing newline doesn't start
+    /// another line.
+    async fn line_count(&self, ctx: &Context<'_>) -> Option<usize> {
+        null_on_error(ctx, || {
+            let contents = std::fs::read(&self.path)?;
+            Ok(contents.split(|b| *b == b'\n').count()
+                - usize::from(contents.is_empty() || contents.ends_with(b"\n")))
+        })
+    }
+
+    /// When the file was last modified, as an RFC 3339 timestamp
+    async fn modified_at(&self, ctx: &Context<'_>) -> Option<String> {
+        null_on_error(ctx, || {
+            let modified = std::fs::metadata(&self.path)?.modified()?;
+            Ok(chrono::DateTime::<chrono::Utc>::from(modified).to_rfc3339())
+        })
+    }
+
+    /// The SHA of the last commit that changed the file, or null if it hasn't
+    /// been committed
+    async fn last_commit_sha(&self) -> Result<Option<String>, Error> {
+        Ok(self.run.scm().last_commit_sha(&self.path)?)
+    }
+
+    /// The users and teams that own the file in the repository's CODEOWNERS
+    /// file. This is empty if the file has no owners or there's no
+    /// CODEOWNERS file.
+    async fn owners(&self) -> Result<Vec<String>, Error> {
+        let Some(codeowners) = QueryCache::current().codeowners(self.run.repo_root())? else {
+            return Ok(Vec::new());
+        };
+        Ok(codeowners.owners(&self.path).to_vec())
+    }
+
+    /// The package that's affected when the file changes, using the same
+    /// rules as `--affected`. A file matching `sharedFiles` can belong to
+    /// several packages, in which case this is the first of `packages` and
+    /// `packages` has the rest.
+    async fn package(&self) -> Result<Option<FilePackage>, Error> {
+        Ok(self.file_packages()?.into_iter().next())
+    }
+
+    /// Every package that's affected when the file changes, sorted by name.
+    /// There's more than one when the file matches `sharedFiles`.
+    async fn packages(&self) -> Result<Array<FilePackage>, Error> {
+        Ok(self.file_packages()?.into_iter().collect())
+    }
+
+    /// Whether the file isn't in any workspace package and doesn't match
+    /// `globalDependencies`, so changing it never causes a package's tasks to
+    /// run. The lockfile is never an orphan.
+    async fn is_orphan(&self) -> Result<bool, Error> {
+        self.get_is_orphan(&Self::change_mapper(&self.run)?)
+    }
+
+    /// The tasks that would have a different hash if this file changed, either
+    /// because the file is one of their inputs or because they depend on a
+    /// task that it's an input of
+    #[graphql(complexity = "TRACE_COMPLEXITY + child_complexity")]
+    async fn affected_tasks(&self) -> Result<Array<RepositoryTask>, Error> {
+        let run = &self.run;
+        let engine = run.engine();
+        let packages = match self.package_changes(&Self::change_mapper(run)?)? {
+            PackageChanges::All(_) => None,
+            PackageChanges::Some(packages) => Some(
+                packages
+                    .into_keys()
+                    .map(|package| package.name)
+                    .collect::<HashSet<_>>(),
+            ),
+        };
+
+        let cache = QueryCache::current();
+        let mut affected = HashSet::new();
+        for (task_id, definition) in engine.task_definitions() {
+            let is_input = match &packages {
+                // Every task is affected by a global change
+                None => true,
+                Some(packages) => {
+                    let name = PackageName::from(task_id.package());
+                    match run.pkg_dep_graph().package_info(&name) {
+                        Some(info) if packages.contains(&name) => self.is_task_input(
+                            &run.repo_root().resolve(info.package_path()),
+                            &*cache.task_inputs(task_id, &definition.inputs)?,
+                        ),
+                        _ => false,
+                    }
+                }
+            };
+            if !is_input {
+                continue;
+            }
+            affected.insert(task_id);
+            affected.extend(
+                engine
+                    .transitive_dependents(task_id)
+                    .into_iter()
+                    .filter_map(|node| match node {
+                        TaskNode::Root => None,
+                        TaskNode::Task(task) => Some(task),
+                    }),
+            );
+        }
+
+        let mut affected = affected.into_iter().collect::<Vec<_>>();
+        affected.sort();
+        affected
+            .into_iter()
+            .map(|task_id| RepositoryTask::new(task_id, run))
+            .collect()
+    }
+
+    /// The files this file imports, directly or through other files. With
+    /// `followWorkspaceDependencies`, imports of workspace packages are
+    /// resolved to the package's source using its `exports` or `main`, even
+    /// if the package isn't installed in `node_modules`.
+    ///
+    /// Files of external packages in `node_modules` are included, but their
+    /// imports are only traced with `followExternal`. `externalDepth` limits
+    /// how many levels into external packages are traced.
+    #[graphql(complexity = "TRACE_COMPLEXITY + child_complexity")]
+    #[allow(clippy::too_many_arguments)]
     async fn dependencies(
         &self,
         depth: Option<usize>,
         ts_config: Option<String>,
         import_type: Option<ImportType>,
         emit_errors: Option<bool>,
+        follow_workspace_dependencies: Option<bool>,
+        follow_external: Option<bool>,
+        external_depth: Option<usize>,
     ) -> Result<TraceResult, Error> {
+        let key = TraceKey {
+            path: self.path.clone(),
+            depth,
+            ts_config: ts_config.clone(),
+            import_type,
+            emit_errors: emit_errors.unwrap_or(true),
+            follow_workspace_dependencies: follow_workspace_dependencies.unwrap_or(false),
+            follow_external: follow_external.unwrap_or(false),
+            external_depth,
+        };
+        let cache = QueryCache::current();
+        if let Some(result) = cache.get_trace(&key) {
+            return Ok(result);
+        }
+
         let mut tracer = Tracer::new(
             self.run.repo_root().to_owned(),
             vec![self.path.clone()],
@@ -201,43 +1425,200 @@ impl File {
         if let Some(import_type) = import_type {
             tracer.set_import_type(import_type.into());
         }
+        if key.follow_workspace_dependencies {
+            tracer.set_workspace_packages(workspace_packages(&self.run));
+        }
+        if key.follow_external {
+            tracer.set_follow_external(external_depth);
+        }
+        tracer.set_cache(cache.trace_cache(self.run.repo_root()));
 
-        let mut result = tracer.trace(depth).await;
-        if emit_errors.unwrap_or(true) {
+        let result = tracer.trace(depth).await;
+        cache.save_trace_cache();
+        if key.emit_errors {
             result.emit_errors();
         }
-        // Remove the file itself from the result
-        result.files.remove(&self.path);
-        TraceResult::new(result, self.run.clone())
+        let result = TraceResult::new(result, &self.path, self.run.clone())?;
+        cache.insert_trace(key, result.clone());
+        Ok(result)
     }
 
+    #[graphql(complexity = "TRACE_COMPLEXITY + child_complexity")]
     async fn dependents(
         &self,
+        depth: Option<usize>,
         ts_config: Option<String>,
         import_type: Option<ImportType>,
     ) -> Result<TraceResult, Error> {
-        let mut tracer = Tracer::new(
-            self.run.repo_root().to_owned(),
-            vec![self.path.clone()],
-            ts_config.map(Utf8PathBuf::from),
-        );
-
-        if let Some(import_type) = import_type {
-            tracer.set_import_type(import_type.into());
-        }
+        let graph = import_graph(
+            &self.run,
+            ImportGraphKey {
+                ts_config,
+                import_type,
+            },
+        )
+        .await;
 
-        let mut result = tracer.reverse_trace().await;
+        let result = graph.dependents_of(&[self.path.clone()], depth);
         result.emit_errors();
-        // Remove the file itself from the result
-        result.files.remove(&self.path);
-        TraceResult::new(result, self.run.clone())
+        TraceResult::new(result, &self.path, self.run.clone())
     }
 
-    async fn ast(&self) -> Option<serde_json::Value> {
-        if let Some(ast) = &self.ast {
-            serde_json::to_value(ast).ok()
-        } else {
-            serde_json::to_value(&self.parse_file().ok()?).ok()
+    /// The AST of the file. `onlyImports` and `spanRange` limit the AST to
+    /// the top level items that are imports or overlap the range respectively.
+    #[graphql(complexity = "TRACE_COMPLEXITY + child_complexity")]
+    async fn ast(
+        &self,
+        format: Option<AstFormat>,
+        only_imports: Option<bool>,
+        span_range: Option<SpanRange>,
+    ) -> Option<serde_json::Value> {
+        let filter = AstFilter {
+            only_imports: only_imports.unwrap_or(false),
+            span_range,
+        };
+        let format = format.unwrap_or_default();
+
+        // Spans in an AST from tracing aren't relative to this file, so we can
+        // only use it when we don't need to look at spans
+        if format == AstFormat::Full && filter.span_range.is_none() {
+            if let Some(ast) = &self.ast {
+                if filter.is_empty() {
+                    return serde_json::to_value(ast).ok();
+                }
+                let mut ast = ast.clone();
+                filter.apply(&mut ast, BytePos(0));
+                return serde_json::to_value(&ast).ok();
+            }
+        }
+
+        let mut parsed = self.parse_file().ok()?;
+        filter.apply(&mut parsed.module, parsed.source.file.start_pos);
+        match format {
+            AstFormat::Compact => {
+                let tree = CompactNode::from_module(&parsed.module, parsed.source.file.start_pos);
+                serde_json::to_value(&tree).ok()
+            }
+            AstFormat::Full => serde_json::to_value(&parsed.module).ok(),
         }
     }
+
+    /// The imports and requires in the file, in the order they appear, along
+    /// with the files they resolve to
+    #[graphql(complexity = "TRACE_COMPLEXITY + child_complexity")]
+    async fn imports(&self, ctx: &Context<'_>, ts_config: Option<String>) -> Option<Array<Import>> {
+        null_on_error(ctx, || {
+            let parsed = self.parse_file()?;
+            let mut finder = ImportFinder::default();
+            parsed.module.visit_with(&mut finder);
+
+            let ts_config = ts_config.map(|ts_config| {
+                AbsoluteSystemPathBuf::from_unknown(self.run.repo_root(), ts_config)
+            });
+            let resolver = Tracer::create_resolver(ts_config.as_deref());
+            let file_resolver = Tracer::infer_resolver(
+                self.run.repo_root(),
+                &self.path,
+                &resolver,
+                &BundlerAliases::default(),
+            );
+            let resolver = file_resolver.as_ref().unwrap_or(&resolver);
+            let runtime_resolvers = RuntimeResolvers::for_file(resolver, &self.path);
+            let workspace_packages = WorkspacePackages::default();
+
+            finder
+                .imports()
+                .iter()
+                .map(|import| {
+                    let resolved = self
+                        .path
+                        .parent()
+                        .and_then(|dir| {
+                            runtime_resolvers
+                                .resolve(resolver, &workspace_packages, dir, import)
+                                .ok()
+                        })
+                        .flatten()
+                        .map(|path| File::new(self.run.clone(), path))
+                        .transpose()?;
+                    let (start, end) = parsed.offsets(import.span);
+                    Ok(Import {
+                        specifier: import.specifier.clone(),
+                        kind: import.into(),
+                        resolved,
+                        start,
+                        end,
+                    })
+                })
+                .collect()
+        })
+    }
+
+    /// The top level exports of the file, in the order they're declared
+    #[graphql(complexity = "TRACE_COMPLEXITY + child_complexity")]
+    async fn exports(&self, ctx: &Context<'_>) -> Option<Array<Export>> {
+        null_on_error(ctx, || Ok(self.get_exports()?.into_iter().collect()))
+    }
+
+    /// The syntax errors in the file, with their spans as byte offsets into
+    /// the file. Unlike `ast`, this returns the errors the parser recovers
+    /// from as well as ones that stop it from parsing the file.
+    #[graphql(complexity = "TRACE_COMPLEXITY + child_complexity")]
+    async fn diagnostics(&self, ctx: &Context<'_>) -> Option<Array<Diagnostic>> {
+        null_on_error(ctx, || Ok(self.syntax_errors()?.into_iter().collect()))
+    }
+
+    /// Base64 encoded source map for the file
+    #[graphql(complexity = "TRACE_COMPLEXITY + child_complexity")]
+    async fn source_map(&self, ctx: &Context<'_>) -> Option<String> {
+        null_on_error(ctx, || {
+            let (source, _) = self.source()?;
+            let path = self.run.repo_root().anchor(&self.path)?.to_string();
+            let source_map = source.identity_source_map(&path);
+            Ok(base64::engine::general_purpose::STANDARD.encode(source_map.to_string()))
+        })
+    }
+
+    /// Converts a byte offset into the file, such as the `start` or `end` of a
+    /// diagnostic, into a line and column.
+    #[graphql(complexity = "TRACE_COMPLEXITY + child_complexity")]
+    async fn position(&self, ctx: &Context<'_>, offset: usize) -> Option<Position> {
+        null_on_error(ctx, || Ok(self.source()?.0.position(offset))).flatten()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use swc_common::{sync::Lrc, SourceMap};
+
+    use super::{Position, Source};
+
+    fn source(contents: &str) -> Source {
+        let source_map: Lrc<SourceMap> = Default::default();
+        let file = source_map.new_source_file(
+            swc_common::FileName::Custom("test.ts".to_string()).into(),
+            contents.to_string(),
+        );
+        Source { source_map, file }
+    }
+
+    #[test]
+    fn test_position_counts_characters() {
+        let source = source("const a = \"é\";\nconst b = 1;\n");
+        assert_eq!(
+            source.position(14),
+            Some(Position {
+                line: 1,
+                column: 14
+            })
+        );
+        assert_eq!(source.position(16), Some(Position { line: 2, column: 1 }));
+    }
+
+    #[test]
+    fn test_position_rejects_offset_inside_character() {
+        let source = source("const a = \"é\";\n");
+        assert_eq!(source.position(12), None);
+        assert_eq!(source.position(100), None);
+    }
 }
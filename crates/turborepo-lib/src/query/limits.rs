@@ -0,0 +1,77 @@
+use std::{sync::Arc, time::Duration};
+
+use async_graphql::{
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextExecute},
+    ObjectType, Response, SchemaBuilder, ServerError, SubscriptionType,
+};
+
+/// The complexity of resolvers that trace, parse, hash or search for files, as
+/// opposed to the default of 1 for every other field. This lets a complexity
+/// limit reject queries that trace many files while allowing large queries
+/// that don't.
+pub const TRACE_COMPLEXITY: usize = 100;
+
+/// Limits on the queries that can be run, so a server can be exposed without
+/// a single query tying it up. There are no limits by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueryLimits {
+    /// The maximum nesting depth of a query
+    pub max_depth: Option<usize>,
+    /// The maximum complexity of a query. Each field counts as 1, except for
+    /// fields that do work on files, which count as `TRACE_COMPLEXITY`.
+    pub max_complexity: Option<usize>,
+    /// How long a query can run for before it's cancelled
+    pub timeout: Option<Duration>,
+}
+
+impl QueryLimits {
+    pub fn apply<Query, Mutation, Subscription>(
+        &self,
+        mut builder: SchemaBuilder<Query, Mutation, Subscription>,
+    ) -> SchemaBuilder<Query, Mutation, Subscription>
+    where
+        Query: ObjectType + 'static,
+        Mutation: ObjectType + 'static,
+        Subscription: SubscriptionType + 'static,
+    {
+        if let Some(max_depth) = self.max_depth {
+            builder = builder.limit_depth(max_depth);
+        }
+        if let Some(max_complexity) = self.max_complexity {
+            builder = builder.limit_complexity(max_complexity);
+        }
+        if let Some(timeout) = self.timeout {
+            builder = builder.extension(Timeout(timeout));
+        }
+        builder
+    }
+}
+
+/// Cancels queries that take longer than the duration. Resolvers can only be
+/// cancelled when they yield, so a resolver that's parsing a file finishes
+/// parsing it first.
+struct Timeout(Duration);
+
+impl ExtensionFactory for Timeout {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(Timeout(self.0))
+    }
+}
+
+#[async_trait::async_trait]
+impl Extension for Timeout {
+    async fn execute(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        operation_name: Option<&str>,
+        next: NextExecute<'_>,
+    ) -> Response {
+        match tokio::time::timeout(self.0, next.run(ctx, operation_name)).await {
+            Ok(response) => response,
+            Err(_) => Response::from_errors(vec![ServerError::new(
+                format!("Query timed out after {}s", self.0.as_secs_f64()),
+                None,
+            )]),
+        }
+    }
+}
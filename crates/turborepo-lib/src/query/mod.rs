@@ -1,11 +1,19 @@
 mod boundaries;
+mod cache;
+mod codeowners;
+mod compact_ast;
 mod external_package;
 mod file;
+mod limits;
 mod package;
 mod server;
+mod subscription;
 mod task;
+mod unused_exports;
 
 use std::{
+    cmp::Reverse,
+    collections::HashMap,
     io,
     ops::{Deref, DerefMut},
     sync::Arc,
@@ -13,19 +21,32 @@ use std::{
 
 use async_graphql::{http::GraphiQLSource, *};
 use axum::{response, response::IntoResponse};
-use external_package::ExternalPackage;
-use package::Package;
-pub use server::run_server;
+pub use cache::PerQueryCache;
+use external_package::{ExternalDependency, ExternalPackage};
+pub use limits::QueryLimits;
+use limits::TRACE_COMPLEXITY;
+use package::{DependencyPath, Package};
+pub use server::{build_schema, run_server};
+pub use subscription::RepositorySubscription;
 use thiserror::Error;
 use tokio::select;
 use turbo_trace::TraceError;
 use turbopath::AbsoluteSystemPathBuf;
 use turborepo_repository::{change_mapper::AllPackageChangeReason, package_graph::PackageName};
+use wax::Program;
 
 use crate::{
     get_version,
-    query::{file::File, task::RepositoryTask},
-    run::{builder::RunBuilder, Run},
+    query::{
+        cache::ImportGraphKey,
+        file::{
+            import_graph, BatchTraceResult, Export, File, FilePackage, Import, ImportType,
+            TracedFile, UnresolvedDynamicImport,
+        },
+        task::{RepositoryTask, TaskGraph},
+        unused_exports::UnusedExport,
+    },
+    run::{builder::RunBuilder, task_id::TaskId, Run},
     signal::SignalHandler,
 };
 
@@ -43,6 +64,10 @@ pub enum Error {
     Server(#[from] io::Error),
     #[error("Package not found: {0}")]
     PackageNotFound(PackageName),
+    #[error("Task not found: {0}")]
+    TaskNotFound(String),
+    #[error("Cyclic dependency detected between tasks: {}", .0.join(", "))]
+    TaskCycle(Vec<String>),
     #[error("Failed to serialize result: {0}")]
     Serde(#[from] serde_json::Error),
     #[error(transparent)]
@@ -58,6 +83,48 @@ pub enum Error {
     Resolution(#[from] crate::run::scope::filter::ResolutionError),
     #[error("Failed to parse file: {0:?}")]
     Parse(swc_ecma_parser::error::Error),
+    #[error(transparent)]
+    Scm(#[from] turborepo_scm::Error),
+    #[error("Invalid glob: {0}")]
+    InvalidGlob(#[from] wax::BuildError),
+    #[error("Failed to walk files: {0}")]
+    Walk(#[from] ignore::Error),
+    #[error("Invalid CODEOWNERS file: {0}")]
+    CodeOwners(ignore::Error),
+    #[error("Failed to walk files: {0}")]
+    GlobWalk(#[from] globwalk::WalkError),
+    #[error(transparent)]
+    ChangeMapper(#[from] turborepo_repository::change_mapper::Error),
+    #[error(transparent)]
+    ChangeMap(#[from] turborepo_repository::change_mapper::ChangeMapError),
+    #[error("Unable to resolve the git range `{0}...{1}`.")]
+    InvalidRange(String, String),
+    #[error(transparent)]
+    Lockfile(#[from] turborepo_lockfiles::Error),
+    #[error("Failed to check the cache: {0}")]
+    Cache(#[from] turborepo_cache::CacheError),
+    #[error("Query result isn't a single list, so it can't be output as {0}.")]
+    NotAList(String),
+    #[error("Failed to write to `$GITHUB_OUTPUT`: {0}")]
+    GithubOutput(#[source] io::Error),
+    #[error(transparent)]
+    Daemon(#[from] crate::daemon::DaemonError),
+    #[error(transparent)]
+    DaemonConnector(#[from] crate::daemon::DaemonConnectorError),
+}
+
+/// Resolves a field to null if `resolve` fails, reporting the error against
+/// the field. Returning an error from a resolver nulls out the closest
+/// nullable parent instead, which for an item in a list is the whole list.
+fn null_on_error<T>(ctx: &Context<'_>, resolve: impl FnOnce() -> Result<T, Error>) -> Option<T> {
+    match resolve() {
+        Ok(value) => Some(value),
+        Err(err) => {
+            let err = async_graphql::Error::from(err).into_server_error(ctx.item.pos);
+            ctx.add_error(ctx.set_error_path(err));
+            None
+        }
+    }
 }
 
 pub struct RepositoryQuery {
@@ -75,9 +142,13 @@ impl RepositoryQuery {
     ) -> PackageChangeReason {
         match reason {
             turborepo_repository::change_mapper::PackageInclusionReason::All(
-                AllPackageChangeReason::GlobalDepsChanged { file },
+                AllPackageChangeReason::GlobalDepsChanged {
+                    file,
+                    matched_pattern,
+                },
             ) => PackageChangeReason::GlobalDepsChanged(GlobalDepsChanged {
                 file_path: file.to_string(),
+                matched_pattern,
             }),
             turborepo_repository::change_mapper::PackageInclusionReason::All(
                 AllPackageChangeReason::DefaultGlobalFileChanged { file },
@@ -134,6 +205,13 @@ impl RepositoryQuery {
                     file_path: file.to_string(),
                 })
             }
+            turborepo_repository::change_mapper::PackageInclusionReason::SharedFileChanged {
+                file,
+                pattern,
+            } => PackageChangeReason::SharedFileChanged(SharedFileChanged {
+                file_path: file.to_string(),
+                pattern,
+            }),
             turborepo_repository::change_mapper::PackageInclusionReason::InFilteredDirectory {
                 directory,
             } => PackageChangeReason::InFilteredDirectory(InFilteredDirectory {
@@ -146,18 +224,43 @@ impl RepositoryQuery {
     }
 }
 
-#[derive(Debug, SimpleObject)]
+#[derive(Debug, Clone, SimpleObject)]
+#[graphql(complex)]
 #[graphql(concrete(name = "RepositoryTasks", params(RepositoryTask)))]
 #[graphql(concrete(name = "Packages", params(Package)))]
 #[graphql(concrete(name = "ChangedPackages", params(ChangedPackage)))]
 #[graphql(concrete(name = "Files", params(File)))]
+#[graphql(concrete(name = "TracedFiles", params(TracedFile)))]
+#[graphql(concrete(name = "UnresolvedDynamicImports", params(UnresolvedDynamicImport)))]
 #[graphql(concrete(name = "ExternalPackages", params(ExternalPackage)))]
+#[graphql(concrete(name = "ExternalDependencies", params(ExternalDependency)))]
+#[graphql(concrete(name = "DependencyPaths", params(DependencyPath)))]
 #[graphql(concrete(name = "Diagnostics", params(Diagnostic)))]
+#[graphql(concrete(name = "Imports", params(Import)))]
+#[graphql(concrete(name = "FilePackages", params(FilePackage)))]
+#[graphql(concrete(name = "Exports", params(Export)))]
+#[graphql(concrete(name = "UnusedExports", params(UnusedExport)))]
 pub struct Array<T: OutputType> {
+    #[graphql(skip)]
     items: Vec<T>,
+    /// The total number of items, regardless of pagination
     length: usize,
 }
 
+#[ComplexObject]
+impl<T: OutputType> Array<T> {
+    /// The items in the array. Use `offset` and `limit` to page through large
+    /// results, e.g. `items(offset: 100, limit: 50)` returns the 101st to
+    /// 150th items.
+    async fn items(&self, offset: Option<usize>, limit: Option<usize>) -> &[T] {
+        let start = offset.unwrap_or(0).min(self.items.len());
+        let end = limit
+            .map_or(self.items.len(), |limit| start.saturating_add(limit))
+            .min(self.items.len());
+        &self.items[start..end]
+    }
+}
+
 impl<T: ObjectType> From<Vec<T>> for Array<T> {
     fn from(value: Vec<T>) -> Self {
         Self {
@@ -187,6 +290,16 @@ impl<T: OutputType> FromIterator<T> for Array<T> {
         Self { items, length }
     }
 }
+
+impl<T: OutputType> IntoIterator for Array<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter()
+    }
+}
+
 #[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
 enum PackageFields {
     Name,
@@ -221,6 +334,68 @@ struct PackagePredicate {
     less_than: Option<FieldValuePair>,
     not: Option<Box<PackagePredicate>>,
     has: Option<FieldValuePair>,
+    /// Matches packages whose name matches the glob, e.g. `@repo/*`
+    name_glob: Option<String>,
+    /// Matches packages whose path, relative to the repository root, matches
+    /// the glob, e.g. `apps/*`
+    directory_glob: Option<String>,
+    /// Matches packages that define the given task
+    has_task: Option<String>,
+    /// Matches packages whose `package.json` has a value at the path, a list
+    /// of keys separated by `.`, e.g. `scripts.build`
+    json_path: Option<String>,
+}
+
+/// The field used to sort a list of packages. Ties are broken by name.
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+enum PackageSortBy {
+    Name,
+    Path,
+    DirectDependencyCount,
+    DirectDependentCount,
+    AllDependencyCount,
+    AllDependentCount,
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug, Default)]
+enum SortOrder {
+    #[default]
+    Ascending,
+    Descending,
+}
+
+impl PackageSortBy {
+    fn sort<T>(
+        sort_by: Option<Self>,
+        order: Option<SortOrder>,
+        items: &mut [T],
+        package: impl Fn(&T) -> &Package,
+    ) {
+        let sort_by = sort_by.unwrap_or(PackageSortBy::Name);
+        let key = |item: &T| {
+            let package = package(item);
+            let count = match sort_by {
+                PackageSortBy::Name | PackageSortBy::Path => 0,
+                PackageSortBy::DirectDependencyCount => package.direct_dependencies_count(),
+                PackageSortBy::DirectDependentCount => package.direct_dependents_count(),
+                PackageSortBy::AllDependencyCount => package.all_dependencies_count(),
+                PackageSortBy::AllDependentCount => package.all_dependents_count(),
+            };
+            let path = match sort_by {
+                PackageSortBy::Path => package.get_path(),
+                _ => None,
+            };
+            let name = (sort_by == PackageSortBy::Name).then(|| package.get_name().clone());
+            (count, path, name)
+        };
+        // Both sorts are stable, so sorting by name first breaks ties by name in
+        // ascending order, whichever order the field is sorted in
+        items.sort_by_cached_key(|item| package(item).get_name().clone());
+        match order.unwrap_or_default() {
+            SortOrder::Ascending => items.sort_by_cached_key(key),
+            SortOrder::Descending => items.sort_by_cached_key(|item| Reverse(key(item))),
+        }
+    }
 }
 
 impl PackagePredicate {
@@ -359,15 +534,41 @@ impl PackagePredicate {
         }
     }
 
-    fn check(&self, pkg: &Package) -> bool {
+    fn check_glob(globs: &PredicateGlobs, glob: &str, value: Option<&str>) -> bool {
+        let Some(value) = value else {
+            return false;
+        };
+        globs[glob].is_match(value)
+    }
+
+    /// Compiles the globs in the predicate and the predicates nested in it
+    fn compile_globs<'a>(&'a self, globs: &mut PredicateGlobs<'a>) -> Result<(), Error> {
+        for glob in self.name_glob.iter().chain(&self.directory_glob) {
+            if !globs.contains_key(glob.as_str()) {
+                globs.insert(glob.as_str(), wax::Glob::new(glob)?);
+            }
+        }
+        for predicate in self
+            .and
+            .iter()
+            .chain(&self.or)
+            .flatten()
+            .chain(self.not.as_deref())
+        {
+            predicate.compile_globs(globs)?;
+        }
+        Ok(())
+    }
+
+    fn check(&self, pkg: &Package, globs: &PredicateGlobs) -> bool {
         let and = self
             .and
             .as_ref()
-            .map(|predicates| predicates.iter().all(|p| p.check(pkg)));
+            .map(|predicates| predicates.iter().all(|p| p.check(pkg, globs)));
         let or = self
             .or
             .as_ref()
-            .map(|predicates| predicates.iter().any(|p| p.check(pkg)));
+            .map(|predicates| predicates.iter().any(|p| p.check(pkg, globs)));
         let equal = self
             .equal
             .as_ref()
@@ -386,11 +587,27 @@ impl PackagePredicate {
             .less_than
             .as_ref()
             .map(|pair| Self::check_greater_than(pkg, &pair.field, &pair.value));
-        let not = self.not.as_ref().map(|predicate| !predicate.check(pkg));
+        let not = self
+            .not
+            .as_ref()
+            .map(|predicate| !predicate.check(pkg, globs));
         let has = self
             .has
             .as_ref()
             .map(|pair| Self::check_has(pkg, &pair.field, &pair.value));
+        let name_glob = self
+            .name_glob
+            .as_ref()
+            .map(|glob| Self::check_glob(globs, glob, Some(pkg.get_name().as_str())));
+        let directory_glob = self
+            .directory_glob
+            .as_ref()
+            .map(|glob| Self::check_glob(globs, glob, pkg.get_path().as_deref()));
+        let has_task = self
+            .has_task
+            .as_ref()
+            .map(|task| pkg.get_tasks().contains_key(task));
+        let json_path = self.json_path.as_ref().map(|path| pkg.has_json_path(path));
 
         and.into_iter()
             .chain(or)
@@ -400,16 +617,52 @@ impl PackagePredicate {
             .chain(less_than)
             .chain(not)
             .chain(has)
+            .chain(name_glob)
+            .chain(directory_glob)
+            .chain(has_task)
+            .chain(json_path)
             .all(|p| p)
     }
 }
 
+/// The compiled `nameGlob`s and `directoryGlob`s of a predicate, keyed by the
+/// glob
+type PredicateGlobs<'a> = HashMap<&'a str, wax::Glob<'a>>;
+
+/// A `PackagePredicate` with its globs compiled up front, so that an invalid
+/// glob is an error rather than matching nothing, and each glob is only
+/// compiled once
+struct PackageFilter<'a> {
+    predicate: &'a PackagePredicate,
+    globs: PredicateGlobs<'a>,
+}
+
+impl<'a> PackageFilter<'a> {
+    fn new(predicate: Option<&'a PackagePredicate>) -> Result<Option<Self>, Error> {
+        predicate
+            .map(|predicate| {
+                let mut globs = PredicateGlobs::new();
+                predicate.compile_globs(&mut globs)?;
+                Ok(Self { predicate, globs })
+            })
+            .transpose()
+    }
+
+    fn check(&self, pkg: &Package) -> bool {
+        self.predicate.check(pkg, &self.globs)
+    }
+}
+
 // why write few types when many work?
 #[derive(SimpleObject)]
 struct GlobalDepsChanged {
     // we're using slightly awkward names so we can reserve the nicer name for the "correct"
     // GraphQL type, e.g. a `file` field for the `File` type
     file_path: String,
+    /// The `globalDependencies` pattern that matched the file. This is empty
+    /// when there aren't any global dependencies, since then any file outside
+    /// of a package affects every package.
+    matched_pattern: Option<String>,
 }
 
 #[derive(SimpleObject)]
@@ -484,6 +737,13 @@ struct FileChanged {
     file_path: String,
 }
 
+#[derive(SimpleObject)]
+struct SharedFileChanged {
+    file_path: String,
+    /// The `sharedFiles` pattern that matched the file
+    pattern: String,
+}
+
 #[derive(SimpleObject)]
 struct InFilteredDirectory {
     directory_path: String,
@@ -505,6 +765,7 @@ enum PackageChangeReason {
     DependencyChanged(DependencyChanged),
     DependentChanged(DependentChanged),
     FileChanged(FileChanged),
+    SharedFileChanged(SharedFileChanged),
     InFilteredDirectory(InFilteredDirectory),
 }
 
@@ -515,18 +776,59 @@ struct ChangedPackage {
     package: Package,
 }
 
-#[Object]
+/// The changes between two git refs
+#[derive(SimpleObject)]
+struct Diff {
+    /// The files that changed. Use `package` on each file to see which package
+    /// it belongs to.
+    files: Array<File>,
+    /// The packages affected by the changed files, and their dependents
+    packages: Array<ChangedPackage>,
+}
+
 impl RepositoryQuery {
-    async fn affected_packages(
+    /// Every file in the repository that isn't gitignored, sorted by path
+    fn repository_files(&self) -> Result<Vec<AbsoluteSystemPathBuf>, Error> {
+        let walker = ignore::WalkBuilder::new(self.run.repo_root())
+            .hidden(false)
+            .require_git(false)
+            .filter_entry(|entry| entry.file_name() != ".git")
+            .build();
+
+        let mut paths = Vec::new();
+        for entry in walker {
+            let entry = entry?;
+            if entry
+                .file_type()
+                .map_or(false, |file_type| file_type.is_file())
+            {
+                paths.push(AbsoluteSystemPathBuf::try_from(entry.path())?);
+            }
+        }
+        paths.sort();
+        Ok(paths)
+    }
+
+    fn get_changed_packages(
         &self,
         base: Option<String>,
         head: Option<String>,
-        filter: Option<PackagePredicate>,
-    ) -> Result<Array<ChangedPackage>, Error> {
+    ) -> Result<Vec<ChangedPackage>, Error> {
+        self.filtered_packages(Some((base, head)), Vec::new())
+    }
+
+    /// Selects packages the same way `turbo run` does for `--affected` and
+    /// `--filter`, so the query and the CLI always agree
+    fn filtered_packages(
+        &self,
+        affected_range: Option<(Option<String>, Option<String>)>,
+        filter_patterns: Vec<String>,
+    ) -> Result<Vec<ChangedPackage>, Error> {
         let mut opts = self.run.opts().clone();
-        opts.scope_opts.affected_range = Some((base, head));
+        opts.scope_opts.affected_range = affected_range;
+        opts.scope_opts.filter_patterns = filter_patterns;
 
-        let mut packages = RunBuilder::calculate_filtered_packages(
+        RunBuilder::calculate_filtered_packages(
             self.run.repo_root(),
             &opts,
             self.run.pkg_dep_graph(),
@@ -540,31 +842,155 @@ impl RepositoryQuery {
                 reason: self.convert_change_reason(reason),
             })
         })
-        .filter(|package: &Result<ChangedPackage, Error>| {
-            let Ok(package) = package.as_ref() else {
-                return true;
-            };
-            filter.as_ref().map_or(true, |f| f.check(&package.package))
-        })
-        .collect::<Result<Array<_>, _>>()?;
+        .collect()
+    }
+}
+
+#[Object]
+impl RepositoryQuery {
+    async fn affected_packages(
+        &self,
+        base: Option<String>,
+        head: Option<String>,
+        filter: Option<PackagePredicate>,
+        sort_by: Option<PackageSortBy>,
+        order: Option<SortOrder>,
+    ) -> Result<Array<ChangedPackage>, Error> {
+        let filter = PackageFilter::new(filter.as_ref())?;
+        let mut packages = self
+            .get_changed_packages(base, head)?
+            .into_iter()
+            .filter(|changed| filter.as_ref().map_or(true, |f| f.check(&changed.package)))
+            .collect::<Array<_>>();
+
+        PackageSortBy::sort(sort_by, order, &mut packages, |changed| &changed.package);
+        Ok(packages)
+    }
+
+    /// Gets the packages that `turbo run --filter=<filter>` would select,
+    /// along with why each one was selected. Filters use the same syntax as
+    /// `--filter`, and every package that matches any of them is included. If
+    /// `base` or `head` are provided, the packages affected by the changes
+    /// between them are included as well, like `--affected`. Without any
+    /// filters or refs, every package is selected.
+    async fn changed_packages(
+        &self,
+        base: Option<String>,
+        head: Option<String>,
+        filter: Option<Vec<String>>,
+    ) -> Result<Array<ChangedPackage>, Error> {
+        let affected_range = (base.is_some() || head.is_some()).then_some((base, head));
+        let mut packages =
+            Array::from(self.filtered_packages(affected_range, filter.unwrap_or_default())?);
 
-        packages.sort_by(|a, b| a.package.get_name().cmp(b.package.get_name()));
+        PackageSortBy::sort(None, None, &mut packages, |changed| &changed.package);
         Ok(packages)
     }
 
+    /// Gets the files changed between `base` and `head`, along with the
+    /// packages they affect. If `head` isn't provided, uncommitted changes are
+    /// included.
+    #[graphql(complexity = "TRACE_COMPLEXITY + child_complexity")]
+    async fn diff(&self, base: String, head: Option<String>) -> Result<Diff, Error> {
+        let repo_root = self.run.repo_root();
+        let changed_files = self
+            .run
+            .scm()
+            .changed_files(repo_root, Some(&base), head.as_deref(), true, true, true)?
+            .map_err(|range| {
+                Error::InvalidRange(
+                    range.from_ref.unwrap_or_default(),
+                    range.to_ref.unwrap_or_default(),
+                )
+            })?;
+
+        let mut paths = changed_files
+            .into_iter()
+            .map(|path| repo_root.resolve(&path))
+            .collect::<Vec<_>>();
+        paths.sort();
+        let files = paths
+            .into_iter()
+            .map(|path| File::new(self.run.clone(), path))
+            .collect::<Result<_, _>>()?;
+
+        let mut packages = Array::from(self.get_changed_packages(Some(base), head)?);
+        PackageSortBy::sort(None, None, &mut packages, |changed| &changed.package);
+
+        Ok(Diff { files, packages })
+    }
+
+    /// Gets the graph of tasks that `turbo run` would run for `tasks`,
+    /// including their dependencies. Tasks can either be names, which run in
+    /// every package that matches `filter`, or `package#task`.
+    async fn task_graph(
+        &self,
+        tasks: Vec<String>,
+        filter: Option<PackagePredicate>,
+    ) -> Result<TaskGraph, Error> {
+        let filter = PackageFilter::new(filter.as_ref())?;
+        let packages = self
+            .run
+            .pkg_dep_graph()
+            .packages()
+            .map(|(name, _)| Package::new(self.run.clone(), name.clone()))
+            .filter(|package| {
+                package.as_ref().map_or(true, |package| {
+                    filter.as_ref().map_or(true, |f| f.check(package))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let engine = self.run.engine();
+        let mut roots = Vec::new();
+        for task in &tasks {
+            match task.split_once('#') {
+                Some((package, task)) => {
+                    let task_id = TaskId::new(package, task).into_owned();
+                    if engine.task_definition(&task_id).is_none() {
+                        return Err(Error::TaskNotFound(task_id.to_string()));
+                    }
+                    roots.push(task_id);
+                }
+                None => roots.extend(
+                    packages
+                        .iter()
+                        .map(|package| TaskId::new(package.get_name().as_ref(), task).into_owned())
+                        .filter(|task_id| engine.task_definition(task_id).is_some()),
+                ),
+            }
+        }
+
+        TaskGraph::new(&self.run, roots)
+    }
+
     /// Gets a single package by name
     async fn package(&self, name: String) -> Result<Package, Error> {
         let name = PackageName::from(name);
         Package::new(self.run.clone(), name)
     }
 
+    /// Gets a single task by the name of its package and the task's name,
+    /// e.g. `task(package: "web", name: "build")`
+    async fn task(&self, package: String, name: String) -> Result<RepositoryTask, Error> {
+        Package::new(self.run.clone(), PackageName::from(package))?.get_task(&name)
+    }
+
     async fn version(&self) -> &'static str {
         get_version()
     }
 
     /// Check boundaries for all packages.
     async fn boundaries(&self) -> Result<Array<Diagnostic>, Error> {
-        match self.run.check_boundaries().await {
+        let graph = import_graph(
+            &self.run,
+            ImportGraphKey {
+                ts_config: None,
+                import_type: None,
+            },
+        )
+        .await;
+        match self.run.check_boundaries(&graph).await {
             Ok(result) => {
                 result.emit();
 
@@ -584,47 +1010,137 @@ impl RepositoryQuery {
         File::new(self.run.clone(), abs_path)
     }
 
-    /// Gets a list of packages that match the given filter
-    async fn packages(&self, filter: Option<PackagePredicate>) -> Result<Array<Package>, Error> {
-        let Some(filter) = filter else {
-            let mut packages = self
-                .run
-                .pkg_dep_graph()
-                .packages()
-                .map(|(name, _)| Package::new(self.run.clone(), name.clone()))
-                .collect::<Result<Array<_>, _>>()?;
-            packages.sort_by(|a, b| a.get_name().cmp(b.get_name()));
-            return Ok(packages);
-        };
+    /// Traces the dependencies of several files in one pass. Each file in the
+    /// result lists the entrypoints it was found from.
+    /// `followWorkspaceDependencies` works like it does for
+    /// `File.dependencies`.
+    #[graphql(complexity = "TRACE_COMPLEXITY + child_complexity")]
+    async fn trace(
+        &self,
+        paths: Vec<String>,
+        depth: Option<usize>,
+        ts_config: Option<String>,
+        import_type: Option<ImportType>,
+        follow_workspace_dependencies: Option<bool>,
+    ) -> Result<BatchTraceResult, Error> {
+        BatchTraceResult::trace(
+            self.run.clone(),
+            paths,
+            depth,
+            ts_config,
+            import_type,
+            follow_workspace_dependencies.unwrap_or(false),
+        )
+        .await
+    }
+
+    /// Gets the files in the repository that match any of the globs, which are
+    /// relative to the repository root. Globs starting with `!` exclude files.
+    /// Gitignored files are never included.
+    #[graphql(complexity = "TRACE_COMPLEXITY + child_complexity")]
+    async fn files(&self, globs: Vec<String>) -> Result<Array<File>, Error> {
+        let (exclusions, inclusions): (Vec<_>, Vec<_>) =
+            globs.iter().partition(|glob| glob.starts_with('!'));
+        let inclusions = inclusions
+            .into_iter()
+            .map(|glob| wax::Glob::new(glob.trim_start_matches('/')))
+            .collect::<Result<Vec<_>, _>>()?;
+        let exclusions = exclusions
+            .into_iter()
+            .map(|glob| wax::Glob::new(glob.trim_start_matches('!').trim_start_matches('/')))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let repo_root = self.run.repo_root();
+        let mut paths = Vec::new();
+        for path in self.repository_files()? {
+            let relative_path = repo_root.anchor(&path)?.to_unix();
+            let is_match = |glob: &wax::Glob| glob.is_match(relative_path.as_str());
+            if inclusions.iter().any(is_match) && !exclusions.iter().any(is_match) {
+                paths.push(path);
+            }
+        }
+
+        paths
+            .into_iter()
+            .map(|path| File::new(self.run.clone(), path))
+            .collect()
+    }
+
+    /// Gets the files in the repository that aren't in any workspace package
+    /// and don't match `globalDependencies`, so changing them never causes a
+    /// package's tasks to run. Files in the root package are included, since
+    /// only root tasks can depend on them. Gitignored files are never
+    /// included.
+    #[graphql(complexity = "TRACE_COMPLEXITY + child_complexity")]
+    async fn orphan_files(&self) -> Result<Array<File>, Error> {
+        let change_mapper = File::change_mapper(&self.run)?;
+        let mut files = Vec::new();
+        for path in self.repository_files()? {
+            let file = File::new(self.run.clone(), path)?;
+            if file.get_is_orphan(&change_mapper)? {
+                files.push(file);
+            }
+        }
+        Ok(Array::from(files))
+    }
 
+    /// Gets a list of packages that match the given filter
+    async fn packages(
+        &self,
+        filter: Option<PackagePredicate>,
+        sort_by: Option<PackageSortBy>,
+        order: Option<SortOrder>,
+    ) -> Result<Array<Package>, Error> {
+        let filter = PackageFilter::new(filter.as_ref())?;
         let mut packages = self
             .run
             .pkg_dep_graph()
             .packages()
             .map(|(name, _)| Package::new(self.run.clone(), name.clone()))
-            .filter(|pkg| pkg.as_ref().map_or(false, |pkg| filter.check(pkg)))
+            .filter(|pkg| {
+                pkg.as_ref().map_or(filter.is_none(), |pkg| {
+                    filter.as_ref().map_or(true, |f| f.check(pkg))
+                })
+            })
             .collect::<Result<Array<_>, _>>()?;
-        packages.sort_by(|a, b| a.get_name().cmp(b.get_name()));
+        PackageSortBy::sort(sort_by, order, &mut packages, |package| package);
 
         Ok(packages)
     }
 }
 
 pub async fn graphiql() -> impl IntoResponse {
-    response::Html(GraphiQLSource::build().endpoint("/").finish())
+    response::Html(
+        GraphiQLSource::build()
+            .endpoint("/")
+            .subscription_endpoint("/ws")
+            .finish(),
+    )
 }
 
-pub async fn run_query_server(run: Run, signal: SignalHandler) -> Result<(), Error> {
+/// The port the GraphQL server listens on by default
+pub const DEFAULT_PORT: u16 = 8000;
+
+pub async fn run_query_server(
+    run: Run,
+    signal: SignalHandler,
+    port: u16,
+    open_browser: bool,
+    limits: QueryLimits,
+) -> Result<(), Error> {
     let subscriber = signal.subscribe().ok_or(Error::NoSignalHandler)?;
-    println!("GraphiQL IDE: http://localhost:8000");
-    webbrowser::open("http://localhost:8000")?;
+    let url = format!("http://localhost:{port}");
+    println!("GraphiQL IDE: {url}");
+    if open_browser {
+        webbrowser::open(&url)?;
+    }
     select! {
         biased;
         _ = subscriber.listen() => {
             println!("Shutting down GraphQL server");
             return Ok(());
         }
-        result = server::run_server(None, Arc::new(run)) => {
+        result = server::run_server(None, Arc::new(run), port, limits) => {
             result?;
         }
     }
@@ -632,7 +1148,7 @@ pub async fn run_query_server(run: Run, signal: SignalHandler) -> Result<(), Err
     Ok(())
 }
 
-#[derive(SimpleObject, Debug, Default)]
+#[derive(SimpleObject, Debug, Default, Clone)]
 pub struct Diagnostic {
     pub message: String,
     pub reason: Option<String>,
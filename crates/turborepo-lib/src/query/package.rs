@@ -1,15 +1,115 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
-use async_graphql::Object;
+use async_graphql::{Object, SimpleObject};
 use itertools::Itertools;
+use turbopath::AbsoluteSystemPathBuf;
 use turborepo_errors::Spanned;
 use turborepo_repository::package_graph::{PackageName, PackageNode};
 
 use crate::{
-    query::{task::RepositoryTask, Array, Error},
-    run::Run,
+    cli::EnvMode,
+    query::{
+        external_package::{ExternalDependency, ExternalPackage},
+        file::File,
+        limits::TRACE_COMPLEXITY,
+        task::RepositoryTask,
+        unused_exports::{find_unused_exports, UnusedExport},
+        Array, Error,
+    },
+    run::{task_id::TaskId, Run},
 };
 
+/// The environment variables that a task depends on
+#[derive(SimpleObject)]
+struct TaskEnvVars {
+    task: String,
+    /// Either `strict` or `loose`. In strict mode only the variables listed
+    /// here are available to the task.
+    env_mode: String,
+    /// Variables from `globalEnv`, which are hashed as inputs to every task
+    global_env: Vec<String>,
+    /// Variables that are hashed as inputs to the task
+    env: Vec<String>,
+    /// Variables that are available to the task but aren't hashed, including
+    /// `globalPassThroughEnv`. This is null in loose mode, where every
+    /// variable is available.
+    pass_through_env: Option<Vec<String>>,
+}
+
+/// A file that other packages can import through the package's
+/// `package.json`
+#[derive(SimpleObject)]
+struct EntryPoint {
+    /// The field of `package.json` the entry point is from, i.e. `main`,
+    /// `module`, `types`, `typings` or `exports`
+    field: String,
+    /// The subpath that importers use, e.g. `.` for the package itself or
+    /// `./button`
+    subpath: String,
+    /// The `exports` conditions that select the entry point, outermost first,
+    /// e.g. `["import", "types"]`
+    conditions: Vec<String>,
+    /// The path as it's written in `package.json`, relative to the package
+    target: String,
+    /// The file the entry point refers to. This is null if the file doesn't
+    /// exist or the target is a pattern containing `*`.
+    file: Option<File>,
+}
+
+/// A direct dependency between two packages
+#[derive(SimpleObject)]
+struct DependencyEdge {
+    dependent: Package,
+    dependency: Package,
+    /// The fields of the dependent's `package.json` that list the dependency,
+    /// e.g. `dependencies` or `devDependencies`
+    fields: Vec<String>,
+}
+
+/// A chain of dependencies from one package to another
+#[derive(SimpleObject)]
+pub struct DependencyPath {
+    edges: Vec<DependencyEdge>,
+}
+
+/// The number of dependency paths that are returned unless `maxPaths` is set
+const DEFAULT_MAX_DEPENDENCY_PATHS: usize = 10;
+
+/// The fields of `package.json` that point at a single entry point
+const ENTRY_POINT_FIELDS: &[&str] = &["main", "module", "types", "typings"];
+
+/// Collects the targets of an `exports` value for `subpath`, following nested
+/// conditions and fallback arrays
+fn collect_exports(
+    value: &serde_json::Value,
+    subpath: &str,
+    conditions: &mut Vec<String>,
+    targets: &mut Vec<(String, Vec<String>, String)>,
+) {
+    match value {
+        serde_json::Value::String(target) => {
+            targets.push((subpath.to_string(), conditions.clone(), target.clone()))
+        }
+        serde_json::Value::Array(fallbacks) => {
+            for fallback in fallbacks {
+                collect_exports(fallback, subpath, conditions, targets);
+            }
+        }
+        serde_json::Value::Object(branches) => {
+            for (condition, value) in branches {
+                conditions.push(condition.clone());
+                collect_exports(value, subpath, conditions, targets);
+                conditions.pop();
+            }
+        }
+        // `null` excludes a subpath
+        _ => {}
+    }
+}
+
 #[derive(Clone)]
 pub struct Package {
     run: Arc<Run>,
@@ -35,6 +135,15 @@ impl Package {
         &self.name
     }
 
+    /// The path to the package, relative to the repository root, with `/` as
+    /// the separator
+    pub fn get_path(&self) -> Option<String> {
+        self.run
+            .pkg_dep_graph()
+            .package_info(&self.name)
+            .map(|info| info.package_path().to_unix().to_string())
+    }
+
     pub fn get_tasks(&self) -> HashMap<String, Spanned<String>> {
         self.run
             .pkg_dep_graph()
@@ -48,6 +157,35 @@ impl Package {
             .unwrap_or_default()
     }
 
+    /// Whether the package's `package.json` has a non-null value at `path`,
+    /// a list of keys separated by `.`, e.g. `scripts.build`
+    pub fn has_json_path(&self, path: &str) -> bool {
+        let Some(json) = self
+            .run
+            .pkg_dep_graph()
+            .package_json(&self.name)
+            .and_then(|json| serde_json::to_value(json).ok())
+        else {
+            return false;
+        };
+
+        path.split('.')
+            .try_fold(&json, |value, key| match value {
+                serde_json::Value::Object(fields) => fields.get(key),
+                serde_json::Value::Array(items) => items.get(key.parse::<usize>().ok()?),
+                _ => None,
+            })
+            .map_or(false, |value| !value.is_null())
+    }
+
+    pub fn get_task(&self, name: &str) -> Result<RepositoryTask, Error> {
+        let task_id = TaskId::new(self.name.as_ref(), name).into_owned();
+        if self.run.engine().task_definition(&task_id).is_none() {
+            return Err(Error::TaskNotFound(task_id.to_string()));
+        }
+        RepositoryTask::new(&task_id, &self.run)
+    }
+
     pub fn direct_dependents_count(&self) -> usize {
         self.run
             .pkg_dep_graph()
@@ -221,11 +359,209 @@ impl Package {
         self.get_tasks()
             .into_iter()
             .sorted_by(|a, b| a.0.cmp(&b.0))
-            .map(|(name, script)| RepositoryTask {
-                name,
-                package: self.clone(),
-                script: Some(script),
+            .map(|(name, script)| RepositoryTask::from_parts(name, self.clone(), Some(script)))
+            .collect()
+    }
+
+    /// The environment variables each task in the package depends on
+    async fn env_vars(&self) -> Vec<TaskEnvVars> {
+        let root_turbo_json = self.run.root_turbo_json();
+        self.run
+            .engine()
+            .task_definitions()
+            .iter()
+            .filter(|(task_id, _)| task_id.package() == self.name.as_ref())
+            .sorted_by(|a, b| a.0.task().cmp(b.0.task()))
+            .map(|(task_id, definition)| {
+                let env_mode = definition
+                    .env_mode
+                    .unwrap_or(self.run.opts().run_opts.env_mode);
+                let pass_through_env = (env_mode == EnvMode::Strict).then(|| {
+                    root_turbo_json
+                        .global_pass_through_env
+                        .iter()
+                        .flatten()
+                        .chain(definition.pass_through_env.iter().flatten())
+                        .cloned()
+                        .sorted()
+                        .dedup()
+                        .collect()
+                });
+                TaskEnvVars {
+                    task: task_id.task().to_string(),
+                    env_mode: env_mode.to_string(),
+                    global_env: root_turbo_json.global_env.clone(),
+                    env: definition.env.clone(),
+                    pass_through_env,
+                }
             })
             .collect()
     }
+
+    /// The shortest chains of dependencies from this package to `to`, which
+    /// explain why this package depends on it. This is empty if it doesn't.
+    /// At most `maxPaths` paths are returned, which defaults to 10.
+    async fn dependency_paths(
+        &self,
+        to: String,
+        max_paths: Option<usize>,
+    ) -> Result<Array<DependencyPath>, Error> {
+        let to = Package::new(self.run.clone(), PackageName::from(to))?;
+        let package = |name: PackageName| Package {
+            run: self.run.clone(),
+            name,
+        };
+        Ok(self
+            .run
+            .pkg_dep_graph()
+            .dependency_paths(
+                &self.name,
+                &to.name,
+                max_paths.unwrap_or(DEFAULT_MAX_DEPENDENCY_PATHS),
+            )
+            .into_iter()
+            .map(|path| DependencyPath {
+                edges: path
+                    .into_iter()
+                    .map(|edge| DependencyEdge {
+                        dependent: package(edge.dependent),
+                        dependency: package(edge.dependency),
+                        fields: edge.fields.into_iter().map(String::from).collect(),
+                    })
+                    .collect(),
+            })
+            .collect())
+    }
+
+    /// Gets a single task in the package by name
+    async fn task(&self, name: String) -> Result<RepositoryTask, Error> {
+        self.get_task(&name)
+    }
+
+    /// The external packages the package depends on, with the versions
+    /// resolved from the lockfile. Only direct dependencies are included
+    /// unless `transitive` is true. This is empty if there's no lockfile.
+    async fn external_dependencies(
+        &self,
+        transitive: Option<bool>,
+    ) -> Result<Array<ExternalDependency>, Error> {
+        let pkg_graph = self.run.pkg_dep_graph();
+        let info = pkg_graph
+            .package_info(&self.name)
+            .ok_or_else(|| Error::PackageNotFound(self.name.clone()))?;
+        let Some(lockfile) = pkg_graph.lockfile() else {
+            return Ok(Array::from(Vec::new()));
+        };
+
+        let workspace_path = info.package_path().to_unix();
+        let mut direct = HashSet::new();
+        for (name, version) in info.unresolved_external_dependencies.iter().flatten() {
+            if let Some(package) =
+                lockfile.resolve_package(workspace_path.as_str(), name, version)?
+            {
+                direct.insert(package);
+            }
+        }
+
+        let packages = if transitive.unwrap_or(false) {
+            info.transitive_dependencies
+                .iter()
+                .flatten()
+                .chain(direct.iter())
+                .cloned()
+                .collect::<HashSet<_>>()
+        } else {
+            direct.clone()
+        };
+
+        Ok(packages
+            .into_iter()
+            .map(|package| ExternalDependency {
+                direct: direct.contains(&package),
+                package: ExternalPackage::new(self.run.clone(), package),
+            })
+            .map(|dependency| (dependency.package.human_name(), dependency))
+            .sorted_by(|(a_name, a), (b_name, b)| {
+                a_name
+                    .cmp(b_name)
+                    .then_with(|| a.package.get_version().cmp(b.package.get_version()))
+            })
+            .map(|(_, dependency)| dependency)
+            .collect())
+    }
+
+    /// The entry points of the package from the `main`, `module`, `types`
+    /// and `typings` fields of its `package.json`, followed by every target in
+    /// its `exports` map
+    async fn entry_points(&self) -> Result<Vec<EntryPoint>, Error> {
+        let Some(info) = self.run.pkg_dep_graph().package_info(&self.name) else {
+            return Err(Error::PackageNotFound(self.name.clone()));
+        };
+        let package_dir = self.run.repo_root().resolve(info.package_path());
+        let json = &info.package_json.other;
+
+        let mut targets = ENTRY_POINT_FIELDS
+            .iter()
+            .filter_map(|field| {
+                let target = json.get(*field)?.as_str()?;
+                Some((
+                    field.to_string(),
+                    ".".to_string(),
+                    Vec::new(),
+                    target.to_string(),
+                ))
+            })
+            .collect::<Vec<_>>();
+
+        let mut exports = Vec::new();
+        match json.get("exports") {
+            // Keys starting with `.` are subpaths, otherwise they're conditions
+            // for the package itself
+            Some(serde_json::Value::Object(subpaths))
+                if subpaths.keys().any(|key| key.starts_with('.')) =>
+            {
+                for (subpath, value) in subpaths {
+                    collect_exports(value, subpath, &mut Vec::new(), &mut exports);
+                }
+            }
+            Some(value) => collect_exports(value, ".", &mut Vec::new(), &mut exports),
+            None => {}
+        }
+        targets.extend(exports.into_iter().map(|(subpath, conditions, target)| {
+            ("exports".to_string(), subpath, conditions, target)
+        }));
+
+        targets
+            .into_iter()
+            .map(|(field, subpath, conditions, target)| {
+                let path = AbsoluteSystemPathBuf::from_unknown(&package_dir, &target);
+                let file = if !target.contains('*') && path.as_std_path().is_file() {
+                    Some(File::new(self.run.clone(), path)?)
+                } else {
+                    None
+                };
+                Ok(EntryPoint {
+                    field,
+                    subpath,
+                    conditions,
+                    target,
+                    file,
+                })
+            })
+            .collect()
+    }
+
+    /// Exports of the files in the package that aren't imported anywhere in
+    /// the repository
+    #[graphql(complexity = "TRACE_COMPLEXITY + child_complexity")]
+    async fn unused_exports(&self) -> Result<Array<UnusedExport>, Error> {
+        let package_dir = self.run.repo_root().resolve(
+            self.run
+                .pkg_dep_graph()
+                .package_info(&self.name)
+                .ok_or_else(|| Error::PackageNotFound(self.name.clone()))?
+                .package_path(),
+        );
+        find_unused_exports(&self.run, &package_dir)
+    }
 }
@@ -1,20 +1,56 @@
 use std::sync::Arc;
 
-use async_graphql::{EmptyMutation, EmptySubscription, MergedObject, Schema};
-use async_graphql_axum::GraphQL;
+use async_graphql::{EmptyMutation, MergedObject, MergedSubscription, Schema};
+use async_graphql_axum::{GraphQL, GraphQLSubscription};
 use axum::{http::Method, routing::get, Router};
 use tokio::net::TcpListener;
 use tower_http::cors::{Any, CorsLayer};
 use turborepo_ui::wui::query::SharedState;
 
-use crate::{query, query::graphiql, run::Run};
+use crate::{
+    query,
+    query::{graphiql, QueryLimits},
+    run::Run,
+};
 
 #[derive(MergedObject)]
-struct Query(turborepo_ui::wui::RunQuery, query::RepositoryQuery);
+pub struct Query(turborepo_ui::wui::RunQuery, query::RepositoryQuery);
+
+#[derive(MergedSubscription)]
+pub struct Subscription(
+    turborepo_ui::wui::RunSubscription,
+    query::RepositorySubscription,
+);
+
+pub type QuerySchema = Schema<Query, EmptyMutation, Subscription>;
+
+/// Builds the schema that's served by `turbo query` and the web UI, with
+/// `limits` applied
+pub fn build_schema(state: Option<SharedState>, run: Arc<Run>, limits: QueryLimits) -> QuerySchema {
+    let web_ui_query = turborepo_ui::wui::RunQuery::new(state.clone());
+    let turbo_query = query::RepositoryQuery::new(run.clone());
+    let combined_query = Query(web_ui_query, turbo_query);
+
+    let combined_subscription = Subscription(
+        turborepo_ui::wui::RunSubscription::new(state),
+        query::RepositorySubscription::new(run),
+    );
+
+    limits
+        .apply(Schema::build(
+            combined_query,
+            EmptyMutation,
+            combined_subscription,
+        ))
+        .extension(query::PerQueryCache)
+        .finish()
+}
 
 pub async fn run_server(
     state: Option<SharedState>,
     run: Arc<Run>,
+    port: u16,
+    limits: QueryLimits,
 ) -> Result<(), turborepo_ui::Error> {
     let cors = CorsLayer::new()
         // allow `GET` and `POST` when accessing the resource
@@ -23,17 +59,17 @@ pub async fn run_server(
         // allow requests from any origin
         .allow_origin(Any);
 
-    let web_ui_query = turborepo_ui::wui::RunQuery::new(state.clone());
-    let turbo_query = query::RepositoryQuery::new(run);
-    let combined_query = Query(web_ui_query, turbo_query);
-
-    let schema = Schema::new(combined_query, EmptyMutation, EmptySubscription);
+    let schema = build_schema(state, run, limits);
     let app = Router::new()
-        .route("/", get(graphiql).post_service(GraphQL::new(schema)))
+        .route(
+            "/",
+            get(graphiql).post_service(GraphQL::new(schema.clone())),
+        )
+        .route_service("/ws", GraphQLSubscription::new(schema))
         .layer(cors);
 
     axum::serve(
-        TcpListener::bind("127.0.0.1:8000")
+        TcpListener::bind(("127.0.0.1", port))
             .await
             .map_err(turborepo_ui::wui::Error::Server)?,
         app,
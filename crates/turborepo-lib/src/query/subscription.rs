@@ -0,0 +1,295 @@
+use std::{collections::HashMap, sync::Arc};
+
+use async_graphql::{SimpleObject, Subscription, Union};
+use futures::{future, Stream, StreamExt};
+use tokio::{sync::mpsc, task::JoinHandle};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use turbo_trace::TracedLevel;
+use turbopath::AbsoluteSystemPathBuf;
+
+use crate::{
+    daemon::{proto, DaemonConnector, DaemonError},
+    query::{
+        cache::QueryCache,
+        file::{
+            attribute_to_entries, BatchTraceResult, File, ImportType, TracedFile,
+            UnresolvedDynamicImport,
+        },
+        limits::TRACE_COMPLEXITY,
+        package::Package,
+        Array, Diagnostic, Error,
+    },
+    run::Run,
+};
+
+/// Subscriptions for changes to the repository. Changes are watched by the
+/// daemon, which is started if it isn't already running.
+pub struct RepositorySubscription {
+    run: Arc<Run>,
+}
+
+impl RepositorySubscription {
+    pub fn new(run: Arc<Run>) -> Self {
+        Self { run }
+    }
+}
+
+#[derive(SimpleObject)]
+struct PackageChanged {
+    package_name: String,
+    /// The package that changed. This is null if the package no longer exists
+    package: Option<Package>,
+}
+
+/// The daemon lost track of which packages changed, or the set of packages in
+/// the repository changed, so every package should be considered changed.
+#[derive(SimpleObject)]
+struct RediscoverPackages {
+    // GraphQL objects need at least one field
+    all: bool,
+}
+
+#[derive(Union)]
+enum PackageChangeEvent {
+    PackageChanged(PackageChanged),
+    RediscoverPackages(RediscoverPackages),
+}
+
+/// Tracing has finished and every file has been sent
+#[derive(SimpleObject)]
+struct TraceFinished {
+    errors: Array<Diagnostic>,
+    unresolved_dynamic_imports: Array<UnresolvedDynamicImport>,
+}
+
+#[derive(Union)]
+enum TraceEvent {
+    TracedFile(TracedFile),
+    TraceFinished(TraceFinished),
+}
+
+impl RepositorySubscription {
+    fn convert_event(
+        run: &Arc<Run>,
+        event: proto::package_change_event::Event,
+    ) -> Result<PackageChangeEvent, Error> {
+        match event {
+            proto::package_change_event::Event::PackageChanged(proto::PackageChanged {
+                package_name,
+            }) => Ok(PackageChangeEvent::PackageChanged(PackageChanged {
+                package: Package::new(run.clone(), package_name.as_str().into()).ok(),
+                package_name,
+            })),
+            proto::package_change_event::Event::RediscoverPackages(_) => {
+                Ok(PackageChangeEvent::RediscoverPackages(RediscoverPackages {
+                    all: true,
+                }))
+            }
+            proto::package_change_event::Event::Error(proto::PackageChangeError { message }) => {
+                Err(DaemonError::Unavailable(message).into())
+            }
+        }
+    }
+
+    /// Sends the files of each level of a running trace as they arrive, then
+    /// `TraceFinished` once `trace` is done
+    async fn send_trace_events(
+        run: Arc<Run>,
+        entries: Vec<AbsoluteSystemPathBuf>,
+        depth: Option<usize>,
+        mut levels: mpsc::UnboundedReceiver<TracedLevel>,
+        trace: JoinHandle<turbo_trace::TraceResult>,
+        events: &mpsc::UnboundedSender<Result<TraceEvent, Error>>,
+    ) -> Result<(), Error> {
+        let mut files = HashMap::new();
+        while let Some(level) = levels.recv().await {
+            let level_paths = level
+                .iter()
+                .map(|(path, _)| path.clone())
+                .collect::<Vec<_>>();
+            files.extend(level);
+            let mut attribution = attribute_to_entries(&files, &entries, depth, run.repo_root())?;
+            for path in level_paths {
+                // Files past `depth` from every entrypoint aren't included
+                let Some(file_entries) = attribution.remove(&*path) else {
+                    continue;
+                };
+                let ast = files.get(&path).and_then(|file| file.ast.clone());
+                let file = File::new(run.clone(), path)?.with_ast(ast);
+                let event = TraceEvent::TracedFile(TracedFile::new(file, file_entries));
+                // The client stopped listening
+                if events.send(Ok(event)).is_err() {
+                    return Ok(());
+                }
+            }
+        }
+
+        let result = trace.await.expect("trace task panicked");
+        let _ = events.send(Ok(TraceEvent::TraceFinished(TraceFinished {
+            unresolved_dynamic_imports: UnresolvedDynamicImport::from_trace(&result, &run)?,
+            errors: result.errors.into_iter().map(|e| e.into()).collect(),
+        })));
+        Ok(())
+    }
+}
+
+#[Subscription]
+impl RepositorySubscription {
+    /// Emits an event whenever the files in a package change
+    async fn package_changed(
+        &self,
+    ) -> Result<impl Stream<Item = async_graphql::Result<PackageChangeEvent>>, Error> {
+        let mut client = match self.run.daemon() {
+            Some(client) => client.clone(),
+            None => {
+                DaemonConnector::new(true, false, self.run.repo_root())
+                    .connect()
+                    .await?
+            }
+        };
+        let events = client.package_changes().await?;
+
+        let run = self.run.clone();
+        Ok(events.filter_map(move |event| {
+            let event = match event {
+                Ok(event) => event.event.map(|event| Self::convert_event(&run, event)),
+                Err(status) => Some(Err(DaemonError::from(status).into())),
+            };
+            // Subscription errors have to be cloneable, which ours aren't
+            future::ready(event.map(|event| event.map_err(async_graphql::Error::from)))
+        }))
+    }
+
+    /// Traces the dependencies of several files like the `trace` query, but
+    /// sends each level of files as soon as it's been traced, followed by the
+    /// errors once the trace has finished. Clients can start rendering files
+    /// before a large trace has finished, and fields of a file, such as its
+    /// `ast`, are resolved as it's sent.
+    ///
+    /// A file's `entries` are the entrypoints that reach it through the files
+    /// traced by the time it's sent. An entrypoint that only reaches it
+    /// through files that are traced later isn't included.
+    #[graphql(complexity = "TRACE_COMPLEXITY + child_complexity")]
+    async fn trace(
+        &self,
+        paths: Vec<String>,
+        depth: Option<usize>,
+        ts_config: Option<String>,
+        import_type: Option<ImportType>,
+        follow_workspace_dependencies: Option<bool>,
+    ) -> Result<impl Stream<Item = async_graphql::Result<TraceEvent>>, Error> {
+        let cache = QueryCache::current();
+        let (mut tracer, entries) = BatchTraceResult::tracer(
+            &self.run,
+            &cache,
+            paths,
+            ts_config,
+            import_type,
+            follow_workspace_dependencies.unwrap_or(false),
+        )?;
+        let (progress, levels) = mpsc::unbounded_channel();
+        tracer.set_progress(progress);
+        let trace = tokio::spawn(async move {
+            let result = tracer.trace(depth).await;
+            cache.save_trace_cache();
+            result.emit_errors();
+            result
+        });
+
+        let (events, events_rx) = mpsc::unbounded_channel();
+        let run = self.run.clone();
+        tokio::spawn(async move {
+            if let Err(err) =
+                Self::send_trace_events(run, entries, depth, levels, trace, &events).await
+            {
+                let _ = events.send(Err(err));
+            }
+        });
+
+        // Subscription errors have to be cloneable, which ours aren't
+        Ok(UnboundedReceiverStream::new(events_rx)
+            .map(|event| event.map_err(async_graphql::Error::from)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use futures::StreamExt;
+    use serde_json::json;
+    use tempfile::tempdir;
+    use turbopath::AbsoluteSystemPathBuf;
+    use turborepo_telemetry::events::command::CommandEventBuilder;
+    use turborepo_ui::ColorConfig;
+
+    use crate::{
+        cli::Args,
+        commands::CommandBase,
+        query::{build_schema, QueryLimits},
+        run::builder::RunBuilder,
+        signal::SignalHandler,
+    };
+
+    #[tokio::test]
+    async fn test_trace_sends_files_before_finishing() -> Result<(), anyhow::Error> {
+        let tmp = tempdir()?;
+        let repo_root = AbsoluteSystemPathBuf::try_from(tmp.path())?.to_realpath()?;
+        for (path, contents) in [
+            (
+                "package.json",
+                r#"{"name": "root", "packageManager": "npm@10.5.0"}"#,
+            ),
+            (
+                "package-lock.json",
+                r#"{"name": "root", "lockfileVersion": 3, "packages": {"": {"name": "root"}}}"#,
+            ),
+            ("turbo.json", r#"{"tasks": {}}"#),
+            ("main.ts", r#"import "./foo";"#),
+            ("foo.ts", r#"import "./bar";"#),
+            ("bar.ts", ""),
+        ] {
+            repo_root
+                .join_component(path)
+                .create_with_contents(contents)?;
+        }
+
+        let base = CommandBase::new(Args::default(), repo_root, "1.0.0", ColorConfig::new(false))?;
+        let handler = SignalHandler::new(std::future::pending::<Option<()>>());
+        let run = RunBuilder::new(base)?
+            .add_all_tasks()
+            .do_not_validate_engine()
+            .build(&handler, CommandEventBuilder::new("query"))
+            .await?;
+        let schema = build_schema(None, Arc::new(run), QueryLimits::default());
+
+        let events = schema
+            .execute_stream(
+                r#"subscription {
+                    trace(paths: ["main.ts"]) {
+                        __typename
+                        ... on TracedFile { path entries }
+                    }
+                }"#,
+            )
+            .map(|response| response.data.into_json().unwrap())
+            .collect::<Vec<_>>()
+            .await;
+        let traced_file = |path: &str| {
+            json!({
+                "trace": { "__typename": "TracedFile", "path": path, "entries": ["main.ts"] }
+            })
+        };
+        assert_eq!(
+            events,
+            vec![
+                traced_file("main.ts"),
+                traced_file("foo.ts"),
+                traced_file("bar.ts"),
+                json!({ "trace": { "__typename": "TraceFinished" } }),
+            ]
+        );
+
+        Ok(())
+    }
+}
@@ -1,18 +1,227 @@
-use std::sync::Arc;
+use std::{
+    collections::{BTreeSet, HashSet},
+    sync::{Arc, OnceLock},
+};
 
-use async_graphql::Object;
+use async_graphql::{Enum, Object, SimpleObject};
 use turborepo_errors::Spanned;
 
 use crate::{
     engine::TaskNode,
     query::{package::Package, Array, Error},
     run::{task_id::TaskId, Run},
+    task_graph::TaskDefinition,
+    task_hash::TaskHashTracker,
 };
 
 pub struct RepositoryTask {
     pub name: String,
     pub package: Package,
     pub script: Option<Spanned<String>>,
+    // Hashing is expensive, so we only do it once per task, and only if a
+    // resolver needs it
+    hashes: OnceLock<TaskHashTracker>,
+}
+
+/// Where a task's outputs would be restored from
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+enum CacheSource {
+    Local,
+    Remote,
+}
+
+impl From<turborepo_cache::CacheSource> for CacheSource {
+    fn from(source: turborepo_cache::CacheSource) -> Self {
+        match source {
+            turborepo_cache::CacheSource::Local => CacheSource::Local,
+            turborepo_cache::CacheSource::Remote => CacheSource::Remote,
+        }
+    }
+}
+
+/// Whether the outputs of a task are cached for its current hash
+#[derive(SimpleObject)]
+struct TaskCacheStatus {
+    /// Whether `turbo run` would restore the task's outputs instead of
+    /// running it
+    hit: bool,
+    /// This is null on a cache miss
+    source: Option<CacheSource>,
+    /// How long the task took to run when it was cached, in milliseconds. This
+    /// is 0 on a cache miss.
+    time_saved: u64,
+}
+
+/// A file that's an input to a task, along with its hash
+#[derive(SimpleObject)]
+struct TaskInput {
+    /// The path to the file, relative to the package
+    path: String,
+    hash: String,
+}
+
+/// A task's configuration after `extends` and package configurations are
+/// applied
+#[derive(SimpleObject)]
+#[graphql(name = "TaskDefinition")]
+struct ResolvedTaskDefinition {
+    /// The globs for the inputs of the task. If there are none, every file in
+    /// the package that's checked into git is an input.
+    inputs: Vec<String>,
+    /// The globs for the outputs of the task. Exclusions are prefixed with `!`.
+    outputs: Vec<String>,
+    cache: bool,
+    env: Vec<String>,
+    pass_through_env: Option<Vec<String>>,
+    /// The tasks this task depends on. Dependencies on tasks in the package's
+    /// dependencies are prefixed with `^`.
+    depends_on: Vec<String>,
+    persistent: bool,
+    interactive: bool,
+    interruptible: bool,
+    output_logs: String,
+    /// The env mode for the task, if it's different to the one for the run
+    env_mode: Option<String>,
+}
+
+impl From<&TaskDefinition> for ResolvedTaskDefinition {
+    fn from(definition: &TaskDefinition) -> Self {
+        let depends_on = definition
+            .topological_dependencies
+            .iter()
+            .map(|task| format!("^{}", task.value))
+            .chain(
+                definition
+                    .task_dependencies
+                    .iter()
+                    .map(|task| task.value.to_string()),
+            )
+            .collect();
+        Self {
+            inputs: definition.inputs.clone(),
+            outputs: definition
+                .outputs
+                .inclusions
+                .iter()
+                .cloned()
+                .chain(
+                    definition
+                        .outputs
+                        .exclusions
+                        .iter()
+                        .map(|glob| format!("!{glob}")),
+                )
+                .collect(),
+            cache: definition.cache,
+            env: definition.env.clone(),
+            pass_through_env: definition.pass_through_env.clone(),
+            depends_on,
+            persistent: definition.persistent,
+            interactive: definition.interactive,
+            interruptible: definition.interruptible,
+            output_logs: definition.output_logs.to_string(),
+            env_mode: definition.env_mode.map(|mode| mode.to_string()),
+        }
+    }
+}
+
+/// A task and one of the tasks it depends on, as `package#task`
+#[derive(SimpleObject)]
+struct TaskGraphEdge {
+    task: String,
+    dependency: String,
+}
+
+/// The tasks that run for a set of tasks, including their dependencies
+#[derive(SimpleObject)]
+pub struct TaskGraph {
+    tasks: Array<RepositoryTask>,
+    edges: Vec<TaskGraphEdge>,
+    /// The tasks grouped into layers, where every dependency of a task is in
+    /// an earlier layer. Tasks in the same layer can run in parallel.
+    layers: Vec<Vec<String>>,
+}
+
+impl TaskGraph {
+    /// Builds the graph of `roots` and everything they depend on
+    pub fn new(run: &Arc<Run>, roots: Vec<TaskId<'static>>) -> Result<Self, Error> {
+        let engine = run.engine();
+        let mut task_ids = BTreeSet::new();
+        for root in &roots {
+            task_ids.insert(root.clone());
+            task_ids.extend(
+                engine
+                    .transitive_dependencies(root)
+                    .into_iter()
+                    .filter_map(|node| match node {
+                        TaskNode::Root => None,
+                        TaskNode::Task(task) => Some(task.clone()),
+                    }),
+            );
+        }
+
+        let dependencies = |task_id: &TaskId| -> BTreeSet<TaskId<'static>> {
+            engine
+                .dependencies(task_id)
+                .into_iter()
+                .flatten()
+                .filter_map(|node| match node {
+                    TaskNode::Root => None,
+                    TaskNode::Task(task) => Some(task.clone()),
+                })
+                .collect()
+        };
+
+        let mut edges = Vec::new();
+        for task_id in &task_ids {
+            for dependency in dependencies(task_id) {
+                edges.push(TaskGraphEdge {
+                    task: task_id.to_string(),
+                    dependency: dependency.to_string(),
+                });
+            }
+        }
+
+        // Each layer is the tasks whose dependencies are all in earlier layers
+        let mut layers = Vec::new();
+        let mut placed = HashSet::new();
+        let mut remaining = task_ids.clone();
+        while !remaining.is_empty() {
+            let layer = remaining
+                .iter()
+                .filter(|task_id| {
+                    dependencies(task_id)
+                        .iter()
+                        .all(|dependency| placed.contains(dependency))
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+            if layer.is_empty() {
+                return Err(Error::TaskCycle(
+                    remaining
+                        .iter()
+                        .map(|task_id| task_id.to_string())
+                        .collect(),
+                ));
+            }
+            for task_id in &layer {
+                remaining.remove(task_id);
+            }
+            layers.push(layer.iter().map(|task_id| task_id.to_string()).collect());
+            placed.extend(layer);
+        }
+
+        let tasks = task_ids
+            .iter()
+            .map(|task_id| RepositoryTask::new(task_id, run))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            tasks,
+            edges,
+            layers,
+        })
+    }
 }
 
 impl RepositoryTask {
@@ -20,11 +229,41 @@ impl RepositoryTask {
         let package = Package::new(run.clone(), task_id.package().into())?;
         let script = package.get_tasks().get(task_id.task()).cloned();
 
-        Ok(RepositoryTask {
-            name: task_id.task().to_string(),
+        Ok(RepositoryTask::from_parts(
+            task_id.task().to_string(),
             package,
             script,
-        })
+        ))
+    }
+
+    pub fn from_parts(name: String, package: Package, script: Option<Spanned<String>>) -> Self {
+        RepositoryTask {
+            name,
+            package,
+            script,
+            hashes: OnceLock::new(),
+        }
+    }
+
+    fn task_id(&self) -> TaskId<'static> {
+        TaskId::from_static(self.package.get_name().to_string(), self.name.clone())
+    }
+
+    fn hashes(&self) -> Result<&TaskHashTracker, Error> {
+        if let Some(hashes) = self.hashes.get() {
+            return Ok(hashes);
+        }
+        let hashes = self.package.run().calculate_task_hashes(&self.task_id())?;
+        Ok(self.hashes.get_or_init(|| hashes))
+    }
+
+    fn task_definition(&self) -> Result<&TaskDefinition, Error> {
+        let task_id = self.task_id();
+        self.package
+            .run()
+            .engine()
+            .task_definition(&task_id)
+            .ok_or_else(|| Error::TaskNotFound(task_id.to_string()))
     }
 
     fn collect_and_sort<'a>(
@@ -68,6 +307,91 @@ impl RepositoryTask {
         self.script.as_ref().map(|script| script.value.to_string())
     }
 
+    /// The hash of the task, as it would be calculated for `turbo run`
+    async fn hash(&self) -> Result<String, Error> {
+        let task_id = self.task_id();
+        self.hashes()?
+            .hash(&task_id)
+            .ok_or_else(|| Error::TaskNotFound(task_id.to_string()))
+    }
+
+    /// Whether the task's outputs are in the local or remote cache for its
+    /// current hash. Tasks with `cache: false` are always a miss.
+    async fn cache_status(&self) -> Result<TaskCacheStatus, Error> {
+        let hit = if self.task_definition()?.cache {
+            let task_id = self.task_id();
+            let hash = self
+                .hashes()?
+                .hash(&task_id)
+                .ok_or_else(|| Error::TaskNotFound(task_id.to_string()))?;
+            self.package.run().run_cache().exists(&hash).await?
+        } else {
+            None
+        };
+
+        Ok(match hit {
+            Some(hit) => TaskCacheStatus {
+                hit: true,
+                source: Some(hit.source.into()),
+                time_saved: hit.time_saved,
+            },
+            None => TaskCacheStatus {
+                hit: false,
+                source: None,
+                time_saved: 0,
+            },
+        })
+    }
+
+    /// The files that are hashed as inputs to the task
+    async fn inputs(&self) -> Result<Vec<TaskInput>, Error> {
+        let mut inputs = self
+            .hashes()?
+            .get_expanded_inputs(&self.task_id())
+            .map(|inputs| inputs.0)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(path, hash)| TaskInput {
+                path: path.to_string(),
+                hash,
+            })
+            .collect::<Vec<_>>();
+        inputs.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(inputs)
+    }
+
+    /// The globs for the outputs of the task. Exclusions are prefixed with `!`.
+    async fn outputs(&self) -> Result<Vec<String>, Error> {
+        let outputs = self.task_definition()?.hashable_outputs(&self.task_id());
+        Ok(outputs
+            .inclusions
+            .into_iter()
+            .chain(
+                outputs
+                    .exclusions
+                    .into_iter()
+                    .map(|glob| format!("!{glob}")),
+            )
+            .collect())
+    }
+
+    /// The environment variables that contribute to the hash of the task, as
+    /// `NAME=<hash of value>` pairs
+    async fn env_vars(&self) -> Result<Vec<String>, Error> {
+        let mut env_vars = self
+            .hashes()?
+            .env_vars(&self.task_id())
+            .map(|env_vars| env_vars.all.to_secret_hashable())
+            .unwrap_or_default();
+        env_vars.sort();
+        Ok(env_vars)
+    }
+
+    /// The configuration for the task from `turbo.json`
+    async fn definition(&self) -> Result<ResolvedTaskDefinition, Error> {
+        Ok(self.task_definition()?.into())
+    }
+
     async fn direct_dependents(&self) -> Result<Array<RepositoryTask>, Error> {
         let task_id = TaskId::from_static(self.package.get_name().to_string(), self.name.clone());
 
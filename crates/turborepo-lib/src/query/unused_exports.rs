@@ -0,0 +1,141 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use async_graphql::SimpleObject;
+use tracing::debug;
+use turbo_trace::{ImportedNames, Tracer};
+use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
+
+use crate::{
+    query::{
+        file::{Export, ExportKind, File},
+        Array, Error,
+    },
+    run::Run,
+};
+
+/// An export that isn't imported or re-exported by any file in the
+/// repository.
+#[derive(SimpleObject)]
+pub struct UnusedExport {
+    file: File,
+    export: Export,
+}
+
+/// Which exports of a file are used by the rest of the repository
+#[derive(Debug, Default)]
+enum Usage {
+    All,
+    #[default]
+    None,
+    Named(HashSet<String>),
+}
+
+impl Usage {
+    fn add(&mut self, names: ImportedNames) {
+        match (self, names) {
+            (Usage::All, _) => {}
+            (usage, ImportedNames::All) => *usage = Usage::All,
+            (Usage::Named(used), ImportedNames::Named(names)) => used.extend(names),
+            (usage @ Usage::None, ImportedNames::Named(names)) => {
+                *usage = Usage::Named(names.into_iter().collect())
+            }
+        }
+    }
+
+    fn is_used(&self, export: &Export) -> bool {
+        match self {
+            Usage::All => true,
+            Usage::None => false,
+            Usage::Named(names) => names.contains(&export.name),
+        }
+    }
+}
+
+fn source_files(root: &AbsoluteSystemPath) -> Result<HashSet<AbsoluteSystemPathBuf>, Error> {
+    Ok(globwalk::globwalk(
+        root,
+        &[
+            "**/*.js".parse().expect("valid glob"),
+            "**/*.jsx".parse().expect("valid glob"),
+            "**/*.ts".parse().expect("valid glob"),
+            "**/*.tsx".parse().expect("valid glob"),
+            "**/*.vue".parse().expect("valid glob"),
+            "**/*.svelte".parse().expect("valid glob"),
+        ],
+        &[
+            "**/node_modules/**".parse().expect("valid glob"),
+            "**/.next/**".parse().expect("valid glob"),
+        ],
+        globwalk::WalkType::Files,
+    )?)
+}
+
+/// Finds the exports of the files in `package_dir` that aren't imported by
+/// any file in the repository.
+///
+/// This is conservative: namespace imports, dynamic imports and requires
+/// count as using every export of a file, and `export * from` re-exports are
+/// never reported since we don't know which names they export. Files in
+/// packages nested inside `package_dir` are not included.
+pub fn find_unused_exports(
+    run: &Arc<Run>,
+    package_dir: &AbsoluteSystemPath,
+) -> Result<Array<UnusedExport>, Error> {
+    let repo_root = run.repo_root();
+    let files = source_files(repo_root)?;
+    let resolver = Tracer::create_resolver(None);
+
+    let mut usages: HashMap<AbsoluteSystemPathBuf, Usage> = HashMap::new();
+    for path in &files {
+        let file = File::new(run.clone(), path.clone())?;
+        // Files that don't parse can't tell us anything about usages, so we
+        // skip them rather than failing the whole query
+        let imported = match file.imported_exports(&resolver) {
+            Ok(imported) => imported,
+            Err(err) => {
+                debug!("skipping {}: {}", path, err);
+                continue;
+            }
+        };
+        for (imported_path, names) in imported {
+            usages.entry(imported_path).or_default().add(names);
+        }
+    }
+
+    let nested_packages = run
+        .pkg_dep_graph()
+        .packages()
+        .map(|(_, info)| repo_root.resolve(info.package_path()))
+        .filter(|dir| &**dir != package_dir && dir.starts_with(package_dir))
+        .collect::<Vec<_>>();
+
+    let mut package_files = files
+        .into_iter()
+        .filter(|path| path.starts_with(package_dir))
+        .filter(|path| !nested_packages.iter().any(|dir| path.starts_with(dir)))
+        .collect::<Vec<_>>();
+    package_files.sort();
+
+    let mut unused = Vec::new();
+    for path in package_files {
+        let usage = usages.get(&path);
+        let file = File::new(run.clone(), path.clone())?;
+        let Ok(exports) = file.get_exports() else {
+            continue;
+        };
+        for export in exports {
+            if export.kind == ExportKind::All || usage.is_some_and(|usage| usage.is_used(&export)) {
+                continue;
+            }
+            unused.push(UnusedExport {
+                file: File::new(run.clone(), path.clone())?,
+                export,
+            });
+        }
+    }
+
+    Ok(unused.into_iter().collect())
+}
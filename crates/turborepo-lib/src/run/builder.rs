@@ -69,6 +69,9 @@ pub struct RunBuilder {
     should_validate_engine: bool,
     // If true, we will add all tasks to the graph, even if they are not specified
     add_all_tasks: bool,
+    // A long lived process, like `turbo query --serve`, uses the daemon even when
+    // it isn't attached to a terminal, so its packages come from the daemon
+    is_long_lived: bool,
 }
 
 impl RunBuilder {
@@ -83,6 +86,9 @@ impl RunBuilder {
             // We currently only use a pty if the following are met:
             // - we're attached to a tty
             atty::is(atty::Stream::Stdout) &&
+            // - we aren't using the accessible UI, which shouldn't get cursor movement
+            //   from tasks
+            !matches!(opts.run_opts.ui_mode, UIMode::Accessible) &&
             // - if we're on windows, we're using the UI
             (!cfg!(windows) || matches!(opts.run_opts.ui_mode, UIMode::Tui)),
         );
@@ -93,6 +99,12 @@ impl RunBuilder {
             opts,
             ..
         } = base;
+        // Colors are read out by screen readers as escape codes
+        let ui = if matches!(opts.run_opts.ui_mode, UIMode::Accessible) {
+            ColorConfig::new(true)
+        } else {
+            ui
+        };
 
         Ok(Self {
             processes,
@@ -107,6 +119,7 @@ impl RunBuilder {
             should_print_prelude_override: None,
             should_validate_engine: true,
             add_all_tasks: false,
+            is_long_lived: false,
         })
     }
 
@@ -130,6 +143,11 @@ impl RunBuilder {
         self
     }
 
+    pub fn long_lived(mut self) -> Self {
+        self.is_long_lived = true;
+        self
+    }
+
     fn connect_process_manager(&self, signal_subscriber: SignalSubscriber) {
         let manager = self.processes.clone();
         tokio::spawn(async move {
@@ -245,7 +263,8 @@ impl RunBuilder {
             RepoType::Monorepo
         });
 
-        let is_ci_or_not_tty = turborepo_ci::is_ci() || !std::io::stdout().is_terminal();
+        let is_ci_or_not_tty =
+            !self.is_long_lived && (turborepo_ci::is_ci() || !std::io::stdout().is_terminal());
         run_telemetry.track_ci(turborepo_ci::Vendor::get_name());
 
         // Remove allow when daemon is flagged back on
@@ -289,7 +308,8 @@ impl RunBuilder {
         let mut pkg_dep_graph = {
             let builder = PackageGraph::builder(&self.repo_root, root_package_json.clone())
                 .with_single_package_mode(self.opts.run_opts.single_package)
-                .with_allow_no_package_manager(self.opts.repo_opts.allow_no_package_manager);
+                .with_allow_no_package_manager(self.opts.repo_opts.allow_no_package_manager)
+                .with_peer_dependencies(self.opts.repo_opts.include_peer_dependencies);
 
             // Daemon package discovery depends on packageManager existing in package.json
             let graph = if cfg!(feature = "daemon-package-discovery")
@@ -416,7 +436,13 @@ impl RunBuilder {
 
         let root_turbo_json = turbo_json_loader.load(&PackageName::Root)?.clone();
 
-        pkg_dep_graph.validate()?;
+        let package_cycles = pkg_dep_graph.cycles();
+        pkg_dep_graph.validate_with_allowed_cycles(
+            &package_cycles,
+            &root_turbo_json.allowed_package_cycles(),
+        )?;
+        root_turbo_json.validate_allowed_package_cycles(&package_cycles)?;
+        root_turbo_json.validate_shared_files(&pkg_dep_graph)?;
 
         let filtered_pkgs = Self::calculate_filtered_packages(
             &self.repo_root,
@@ -473,7 +499,7 @@ impl RunBuilder {
             api_client: self.api_client,
             api_auth: self.api_auth,
             env_at_execution_start,
-            filtered_pkgs: filtered_pkgs.keys().cloned().collect(),
+            filtered_pkgs,
             pkg_dep_graph: Arc::new(pkg_dep_graph),
             root_turbo_json,
             scm,
@@ -1,7 +1,7 @@
 use std::{
     io::Write,
     sync::{Arc, Mutex},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use itertools::Itertools;
@@ -64,6 +64,9 @@ pub trait CacheOutput {
     fn status(&mut self, message: &str, result: CacheResult);
     fn error(&mut self, message: &str);
     fn replay_logs(&mut self, log_file: &AbsoluteSystemPath) -> Result<(), turborepo_ui::Error>;
+    /// Called on a cache hit with where it came from and how long restoring
+    /// the outputs took
+    fn cache_restored(&mut self, source: CacheSource, duration: Duration);
 }
 
 impl RunCache {
@@ -133,6 +136,12 @@ impl RunCache {
         }
     }
 
+    /// Checks whether the outputs for `hash` are in the cache, without
+    /// restoring them
+    pub async fn exists(&self, hash: &str) -> Result<Option<CacheHitMetadata>, CacheError> {
+        self.cache.exists(hash).await
+    }
+
     pub async fn shutdown_cache(
         &self,
     ) -> Result<(Arc<Mutex<UploadMap>>, oneshot::Receiver<()>), CacheError> {
@@ -260,6 +269,7 @@ impl TaskCache {
 
         let has_changed_outputs = changed_output_count > 0;
 
+        let restore_start = Instant::now();
         let cache_status = if has_changed_outputs {
             // Note that we currently don't use the output globs when restoring, but we
             // could in the future to avoid doing unnecessary file I/O. We also
@@ -318,6 +328,10 @@ impl TaskCache {
             })
         };
 
+        if let Some(cache_hit_metadata) = &cache_status {
+            terminal_output.cache_restored(cache_hit_metadata.source, restore_start.elapsed());
+        }
+
         let more_context = if has_changed_outputs {
             ""
         } else {
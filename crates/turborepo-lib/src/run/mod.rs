@@ -14,7 +14,7 @@ mod ui;
 pub mod watch;
 
 use std::{
-    collections::{BTreeMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     io::Write,
     sync::Arc,
     time::Duration,
@@ -29,25 +29,44 @@ use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
 use turborepo_api_client::{APIAuth, APIClient};
 use turborepo_ci::Vendor;
 use turborepo_env::EnvironmentVariableMap;
-use turborepo_repository::package_graph::{PackageGraph, PackageName, PackageNode};
+use turborepo_repository::{
+    change_mapper::PackageInclusionReason,
+    package_graph::{PackageGraph, PackageName, PackageNode},
+};
 use turborepo_scm::SCM;
-use turborepo_telemetry::events::generic::GenericEventBuilder;
+use turborepo_telemetry::events::{
+    generic::GenericEventBuilder, task::PackageTaskEventBuilder, EventBuilder,
+};
 use turborepo_ui::{
-    cprint, cprintln, sender::UISender, tui, tui::TuiSender, wui::sender::WebUISender, ColorConfig,
-    BOLD_GREY, GREY,
+    accessible::{self, AccessibleSender},
+    cprint, cprintln,
+    sender::UISender,
+    tui,
+    tui::TuiSender,
+    wui::sender::WebUISender,
+    ColorConfig, BOLD_GREY, GREY,
 };
 
 pub use crate::run::error::Error;
 use crate::{
     cli::EnvMode,
-    engine::Engine,
+    engine::{Engine, TaskNode},
     microfrontends::MicrofrontendsConfigs,
     opts::Opts,
     process::ProcessManager,
-    run::{global_hash::get_global_hash_inputs, summary::RunTracker, task_access::TaskAccess},
+    run::{
+        global_hash::{get_global_hash_inputs, GlobalHashableInputs},
+        summary::RunTracker,
+        task_access::TaskAccess,
+        task_id::TaskId,
+    },
     signal::SignalHandler,
     task_graph::Visitor,
-    task_hash::{get_external_deps_hash, get_internal_deps_hash, PackageInputsHashes},
+    task_hash,
+    task_hash::{
+        get_external_deps_hash, get_internal_deps_hash, PackageInputsHashes, TaskHashTracker,
+        TaskHasher,
+    },
     turbo_json::{TurboJson, UIMode},
     DaemonClient, DaemonConnector,
 };
@@ -64,7 +83,7 @@ pub struct Run {
     api_client: APIClient,
     api_auth: Option<APIAuth>,
     env_at_execution_start: EnvironmentVariableMap,
-    filtered_pkgs: HashSet<PackageName>,
+    filtered_pkgs: HashMap<PackageName, PackageInclusionReason>,
     pkg_dep_graph: Arc<PackageGraph>,
     root_turbo_json: TurboJson,
     scm: SCM,
@@ -81,6 +100,7 @@ type UIResult<T> = Result<Option<(T, JoinHandle<Result<(), turborepo_ui::Error>>
 
 type WuiResult = UIResult<WebUISender>;
 type TuiResult = UIResult<TuiSender>;
+type AccessibleResult = UIResult<AccessibleSender>;
 
 impl Run {
     fn has_non_interruptible_tasks(&self) -> bool {
@@ -94,7 +114,7 @@ impl Run {
         } else {
             let mut packages = self
                 .filtered_pkgs
-                .iter()
+                .keys()
                 .map(|workspace_name| workspace_name.to_string())
                 .collect::<Vec<String>>();
             packages.sort();
@@ -134,6 +154,15 @@ impl Run {
         &self.scm
     }
 
+    pub fn run_cache(&self) -> &Arc<RunCache> {
+        &self.run_cache
+    }
+
+    /// The connection to the daemon, if the run is using it
+    pub fn daemon(&self) -> Option<&DaemonClient<DaemonConnector>> {
+        self.daemon.as_ref()
+    }
+
     pub fn root_turbo_json(&self) -> &TurboJson {
         &self.root_turbo_json
     }
@@ -160,13 +189,30 @@ impl Run {
         new_run
     }
 
+    /// Creates a run of only `task`, and the tasks that depend on it if
+    /// `include_dependents` is set, so it can be re-run in watch mode.
+    /// Returns `None` if the task can't be re-run.
+    pub fn create_run_for_task(
+        &self,
+        task: &TaskId<'static>,
+        include_dependents: bool,
+    ) -> Option<Self> {
+        let mut new_run = self.clone();
+        let new_engine = self
+            .engine
+            .create_engine_for_task(task, include_dependents)?;
+        new_run.engine = Arc::new(new_engine);
+
+        Some(new_run)
+    }
+
     // Produces the transitive closure of the filtered packages,
     // i.e. the packages relevant for this run.
     #[instrument(skip(self), ret)]
     pub fn get_relevant_packages(&self) -> HashSet<PackageName> {
         let packages: Vec<_> = self
             .filtered_pkgs
-            .iter()
+            .keys()
             .map(|pkg| PackageNode::Workspace(pkg.clone()))
             .collect();
         self.pkg_dep_graph
@@ -184,7 +230,7 @@ impl Run {
     pub fn get_potential_tasks(&self) -> Result<BTreeMap<String, Vec<String>>, Error> {
         let mut tasks = BTreeMap::new();
         for (name, info) in self.pkg_dep_graph.packages() {
-            if !self.filtered_pkgs.contains(name) {
+            if !self.filtered_pkgs.contains_key(name) {
                 continue;
             }
             for task_name in info.package_json.scripts.keys() {
@@ -206,7 +252,8 @@ impl Run {
         &self.engine
     }
 
-    pub fn filtered_pkgs(&self) -> &HashSet<PackageName> {
+    /// The packages selected for the run, and why each one was selected
+    pub fn filtered_pkgs(&self) -> &HashMap<PackageName, PackageInclusionReason> {
         &self.filtered_pkgs
     }
 
@@ -238,7 +285,31 @@ impl Run {
             UIMode::Web => self
                 .start_web_ui()
                 .map(|res| res.map(|(sender, handle)| (UISender::Wui(sender), handle))),
+            UIMode::Accessible => self
+                .start_accessible_ui()
+                .map(|res| res.map(|(sender, handle)| (UISender::Accessible(sender), handle))),
+        }
+    }
+
+    fn start_accessible_ui(&self) -> AccessibleResult {
+        if self.opts.run_opts.dry_run.is_some() {
+            return Ok(None);
+        }
+
+        let task_names = self.engine.tasks_with_command(&self.pkg_dep_graph);
+        // If there aren't any tasks to run, then shouldn't start the UI
+        if task_names.is_empty() {
+            return Ok(None);
         }
+
+        let (sender, receiver) = AccessibleSender::new();
+        let handle = tokio::task::spawn(accessible::run_accessible_ui(
+            task_names,
+            receiver,
+            std::io::stdout(),
+        ));
+
+        Ok(Some((sender, handle)))
     }
     fn start_web_ui(self: &Arc<Self>) -> WuiResult {
         let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
@@ -277,6 +348,176 @@ impl Run {
         }
     }
 
+    /// Hashes of the root package's external and internal dependencies. These
+    /// are only used in monorepos.
+    fn root_dependencies_hashes(&self) -> Result<(Option<String>, Option<String>), Error> {
+        let root_workspace = self
+            .pkg_dep_graph
+            .package_info(&PackageName::Root)
+            .expect("must have root workspace");
+
+        let is_monorepo = !self.opts.run_opts.single_package;
+
+        let root_external_dependencies_hash =
+            is_monorepo.then(|| get_external_deps_hash(&root_workspace.transitive_dependencies));
+
+        let root_internal_dependencies_hash = is_monorepo
+            .then(|| {
+                get_internal_deps_hash(
+                    &self.scm,
+                    &self.repo_root,
+                    self.pkg_dep_graph
+                        .root_internal_package_dependencies_paths(),
+                )
+            })
+            .transpose()?;
+
+        Ok((
+            root_external_dependencies_hash,
+            root_internal_dependencies_hash,
+        ))
+    }
+
+    fn global_hash_inputs<'a>(
+        &'a self,
+        root_external_dependencies_hash: Option<&'a str>,
+        root_internal_dependencies_hash: Option<&'a str>,
+    ) -> Result<GlobalHashableInputs<'a>, Error> {
+        let root_workspace = self
+            .pkg_dep_graph
+            .package_info(&PackageName::Root)
+            .expect("must have root workspace");
+
+        let env_mode = self.opts.run_opts.env_mode;
+        let pass_through_env = match env_mode {
+            EnvMode::Loose => {
+                // Remove the passthroughs from hash consideration if we're explicitly loose.
+                None
+            }
+            EnvMode::Strict => self.root_turbo_json.global_pass_through_env.as_deref(),
+        };
+
+        Ok(get_global_hash_inputs(
+            root_external_dependencies_hash,
+            root_internal_dependencies_hash,
+            root_workspace,
+            &self.repo_root,
+            self.pkg_dep_graph.package_manager(),
+            self.pkg_dep_graph.lockfile(),
+            &self.root_turbo_json.global_deps,
+            &self.env_at_execution_start,
+            &self.root_turbo_json.global_env,
+            pass_through_env,
+            env_mode,
+            self.opts.run_opts.framework_inference,
+            &self.scm,
+        )?)
+    }
+
+    fn global_env(
+        &self,
+        global_hash_inputs: &GlobalHashableInputs,
+    ) -> Result<EnvironmentVariableMap, Error> {
+        let mut env = self
+            .env_at_execution_start
+            .from_wildcards(global_hash_inputs.pass_through_env.unwrap_or_default())
+            .map_err(Error::Env)?;
+        if let Some(resolved_global) = &global_hash_inputs.resolved_env_vars {
+            env.union(&resolved_global.all);
+        }
+        Ok(env)
+    }
+
+    /// Calculates the hash of `task_id` without running anything, along with
+    /// the hashes of the tasks it depends on. These are the same hashes a dry
+    /// run would report.
+    pub fn calculate_task_hashes(
+        &self,
+        task_id: &TaskId<'static>,
+    ) -> Result<TaskHashTracker, Error> {
+        let tasks = self.engine.transitive_dependencies(task_id);
+        let workspaces = self.pkg_dep_graph.packages().collect();
+        let package_inputs_hashes = PackageInputsHashes::calculate_file_hashes(
+            &self.scm,
+            tasks.iter().copied().par_bridge(),
+            workspaces,
+            self.engine.task_definitions(),
+            &self.repo_root,
+            &self.run_telemetry,
+            &self.daemon,
+        )?;
+
+        let (root_external_dependencies_hash, root_internal_dependencies_hash) =
+            self.root_dependencies_hashes()?;
+        let global_hash_inputs = self.global_hash_inputs(
+            root_external_dependencies_hash.as_deref(),
+            root_internal_dependencies_hash.as_deref(),
+        )?;
+        let global_hash = global_hash_inputs.calculate_global_hash();
+        let global_env = self.global_env(&global_hash_inputs)?;
+
+        let task_hasher = TaskHasher::new(
+            package_inputs_hashes,
+            &self.opts.run_opts,
+            &self.env_at_execution_start,
+            &global_hash,
+            global_env,
+        );
+
+        let mut hashed = HashSet::new();
+        self.calculate_task_hash(&task_hasher, task_id, &mut hashed)?;
+
+        Ok(task_hasher.task_hash_tracker())
+    }
+
+    // A task's hash depends on the hashes of its dependencies, so they have to
+    // be hashed first
+    fn calculate_task_hash(
+        &self,
+        task_hasher: &TaskHasher,
+        task_id: &TaskId<'static>,
+        hashed: &mut HashSet<TaskId<'static>>,
+    ) -> Result<(), Error> {
+        if !hashed.insert(task_id.clone()) {
+            return Ok(());
+        }
+
+        let dependencies = self
+            .engine
+            .dependencies(task_id)
+            .ok_or_else(|| task_hash::Error::MissingPipelineEntry(task_id.clone()))?;
+        for dependency in &dependencies {
+            if let TaskNode::Task(dependency) = dependency {
+                self.calculate_task_hash(task_hasher, dependency, hashed)?;
+            }
+        }
+
+        let task_definition = self
+            .engine
+            .task_definition(task_id)
+            .ok_or_else(|| task_hash::Error::MissingPipelineEntry(task_id.clone()))?;
+        let package_name = PackageName::from(task_id.package());
+        let workspace = self
+            .pkg_dep_graph
+            .package_info(&package_name)
+            .ok_or_else(|| task_hash::Error::MissingPackageJson(package_name.to_string()))?;
+        let task_env_mode = task_definition
+            .env_mode
+            .unwrap_or(self.opts.run_opts.env_mode);
+
+        task_hasher.calculate_task_hash(
+            task_id,
+            task_definition,
+            task_env_mode,
+            workspace,
+            dependencies,
+            PackageTaskEventBuilder::new(task_id.package(), task_id.task())
+                .with_parent(&self.run_telemetry),
+        )?;
+
+        Ok(())
+    }
+
     pub async fn run(&self, ui_sender: Option<UISender>, is_watch: bool) -> Result<i32, Error> {
         let skip_cache_writes = self.opts.cache_opts.cache.skip_writes();
         if let Some(subscriber) = self.signal_handler.subscribe() {
@@ -376,65 +617,14 @@ impl Run {
             &self.daemon,
         )?;
 
-        let root_workspace = self
-            .pkg_dep_graph
-            .package_info(&PackageName::Root)
-            .expect("must have root workspace");
-
-        let is_monorepo = !self.opts.run_opts.single_package;
-
-        let root_external_dependencies_hash =
-            is_monorepo.then(|| get_external_deps_hash(&root_workspace.transitive_dependencies));
-
-        let root_internal_dependencies_hash = is_monorepo
-            .then(|| {
-                get_internal_deps_hash(
-                    &self.scm,
-                    &self.repo_root,
-                    self.pkg_dep_graph
-                        .root_internal_package_dependencies_paths(),
-                )
-            })
-            .transpose()?;
-
-        let global_hash_inputs = {
-            let env_mode = self.opts.run_opts.env_mode;
-            let pass_through_env = match env_mode {
-                EnvMode::Loose => {
-                    // Remove the passthroughs from hash consideration if we're explicitly loose.
-                    None
-                }
-                EnvMode::Strict => self.root_turbo_json.global_pass_through_env.as_deref(),
-            };
-
-            get_global_hash_inputs(
-                root_external_dependencies_hash.as_deref(),
-                root_internal_dependencies_hash.as_deref(),
-                root_workspace,
-                &self.repo_root,
-                self.pkg_dep_graph.package_manager(),
-                self.pkg_dep_graph.lockfile(),
-                &self.root_turbo_json.global_deps,
-                &self.env_at_execution_start,
-                &self.root_turbo_json.global_env,
-                pass_through_env,
-                env_mode,
-                self.opts.run_opts.framework_inference,
-                &self.scm,
-            )?
-        };
+        let (root_external_dependencies_hash, root_internal_dependencies_hash) =
+            self.root_dependencies_hashes()?;
+        let global_hash_inputs = self.global_hash_inputs(
+            root_external_dependencies_hash.as_deref(),
+            root_internal_dependencies_hash.as_deref(),
+        )?;
         let global_hash = global_hash_inputs.calculate_global_hash();
-
-        let global_env = {
-            let mut env = self
-                .env_at_execution_start
-                .from_wildcards(global_hash_inputs.pass_through_env.unwrap_or_default())
-                .map_err(Error::Env)?;
-            if let Some(resolved_global) = &global_hash_inputs.resolved_env_vars {
-                env.union(&resolved_global.all);
-            }
-            env
-        };
+        let global_env = self.global_env(&global_hash_inputs)?;
 
         let run_tracker = RunTracker::new(
             self.start_at,
@@ -38,9 +38,11 @@ impl<'a> ScopeChangeDetector<'a> {
         scm: &'a SCM,
         pkg_graph: &'a PackageGraph,
         global_deps: impl Iterator<Item = &'a str>,
+        shared_files: impl IntoIterator<Item = (&'a str, Vec<&'a PackageName>)>,
         ignore_patterns: Vec<String>,
     ) -> Result<Self, Error> {
-        let pkg_detector = GlobalDepsPackageChangeMapper::new(pkg_graph, global_deps)?;
+        let pkg_detector = GlobalDepsPackageChangeMapper::new(pkg_graph, global_deps)?
+            .with_shared_files(shared_files)?;
         let change_mapper = ChangeMapper::new(pkg_graph, ignore_patterns, pkg_detector);
 
         Ok(Self {
@@ -11,7 +11,11 @@ mod scm;
 mod spaces;
 mod task;
 mod task_factory;
-use std::{collections::HashSet, io, io::Write};
+use std::{
+    collections::{BTreeMap, HashMap},
+    io,
+    io::Write,
+};
 
 use chrono::{DateTime, Local};
 pub use duration::TurboDuration;
@@ -27,7 +31,10 @@ use tracing::{error, log::warn};
 use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf, AnchoredSystemPath};
 use turborepo_api_client::{spaces::CreateSpaceRunPayload, APIAuth, APIClient};
 use turborepo_env::EnvironmentVariableMap;
-use turborepo_repository::package_graph::{PackageGraph, PackageName};
+use turborepo_repository::{
+    change_mapper::PackageInclusionReason,
+    package_graph::{PackageGraph, PackageName},
+};
 use turborepo_scm::SCM;
 use turborepo_ui::{color, cprintln, cwriteln, ColorConfig, BOLD, BOLD_CYAN, GREY};
 
@@ -95,6 +102,9 @@ pub struct RunSummary<'a> {
     #[serde(skip_serializing_if = "Option::is_none")]
     execution: Option<ExecutionSummary<'a>>,
     packages: Vec<&'a PackageName>,
+    /// Why each of the `packages` was selected
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    package_reasons: BTreeMap<&'a PackageName, &'a PackageInclusionReason>,
     env_mode: EnvMode,
     framework_inference: bool,
     tasks: Vec<TaskSummary>,
@@ -182,7 +192,7 @@ impl RunTracker {
         exit_code: i32,
         end_time: DateTime<Local>,
         run_opts: &'a RunOpts,
-        packages: &'a HashSet<PackageName>,
+        packages: &'a HashMap<PackageName, PackageInclusionReason>,
         global_hash_summary: GlobalHashSummary<'a>,
         global_env_mode: EnvMode,
         task_factory: TaskSummaryFactory<'a>,
@@ -217,7 +227,8 @@ impl RunTracker {
             id: Ksuid::new(None, None),
             version: RUN_SUMMARY_SCHEMA_VERSION.to_string(),
             turbo_version: self.version,
-            packages: packages.iter().sorted().collect(),
+            packages: packages.keys().sorted().collect(),
+            package_reasons: packages.iter().collect(),
             execution: Some(execution_summary),
             env_mode: global_env_mode,
             framework_inference: run_opts.framework_inference,
@@ -252,7 +263,7 @@ impl RunTracker {
         repo_root: &'a AbsoluteSystemPath,
         package_inference_root: Option<&AnchoredSystemPath>,
         run_opts: &'a RunOpts,
-        packages: &'a HashSet<PackageName>,
+        packages: &'a HashMap<PackageName, PackageInclusionReason>,
         global_hash_summary: GlobalHashSummary<'a>,
         global_env_mode: cli::EnvMode,
         engine: &'a Engine,
@@ -762,6 +773,7 @@ impl<'a> RunSummary<'a> {
         // and each task summary needs some cleaning
         if !self.monorepo {
             self.packages.clear();
+            self.package_reasons.clear();
         }
 
         self.tasks.sort_by(|a, b| a.task_id.cmp(&b.task_id));
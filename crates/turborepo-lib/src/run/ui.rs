@@ -12,7 +12,13 @@ pub async fn start_web_ui_server(
     let subscriber = turborepo_ui::wui::subscriber::Subscriber::new(rx);
     tokio::spawn(subscriber.watch(state.clone()));
 
-    query::run_server(Some(state.clone()), run).await?;
+    query::run_server(
+        Some(state.clone()),
+        run,
+        query::DEFAULT_PORT,
+        query::QueryLimits::default(),
+    )
+    .await?;
 
     Ok(())
 }
@@ -8,16 +8,20 @@ use futures::StreamExt;
 use miette::{Diagnostic, SourceSpan};
 use thiserror::Error;
 use tokio::{select, sync::Notify, task::JoinHandle};
-use tracing::{instrument, trace, warn};
+use tracing::{debug, instrument, trace, warn};
+use turborepo_cache::{CacheActions, CacheConfig};
 use turborepo_repository::package_graph::PackageName;
 use turborepo_telemetry::events::command::CommandEventBuilder;
-use turborepo_ui::sender::UISender;
+use turborepo_ui::{sender::UISender, tui::event::RerunTask};
 
 use crate::{
     commands::{self, CommandBase},
     daemon::{proto, DaemonConnectorError, DaemonError},
     get_version, opts,
-    run::{self, builder::RunBuilder, scope::target_selector::InvalidSelectorError, Run},
+    run::{
+        self, builder::RunBuilder, scope::target_selector::InvalidSelectorError, task_id::TaskId,
+        Run,
+    },
     signal::SignalHandler,
     turbo_json::CONFIG_FILE,
     DaemonConnector, DaemonPaths,
@@ -59,6 +63,90 @@ pub struct WatchClient {
 struct RunHandle {
     stopper: run::RunStopper,
     run_task: JoinHandle<Result<i32, run::Error>>,
+    // The tasks the run executes, which a re-run of any of them replaces
+    tasks: HashSet<String>,
+    kind: RunKind,
+    // Whether the run ends by itself, i.e. it has no persistent tasks
+    finishes: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RunKind {
+    /// Builds the packages that changed
+    Rebuild,
+    /// Re-runs a task the user asked to run again
+    Rerun,
+}
+
+impl RunHandle {
+    /// Starts `run` in the background, notifying `finished` once it's done
+    fn spawn(
+        run: Arc<Run>,
+        kind: RunKind,
+        ui_sender: Option<UISender>,
+        finished: &Arc<Notify>,
+    ) -> Self {
+        let finished = finished.clone();
+        Self {
+            stopper: run.stopper(),
+            tasks: run
+                .engine
+                .tasks_with_command(&run.pkg_dep_graph)
+                .into_iter()
+                .collect(),
+            kind,
+            finishes: !run
+                .engine
+                .task_definitions()
+                .values()
+                .any(|definition| definition.persistent),
+            run_task: tokio::spawn(async move {
+                let result = run.run(ui_sender, true).await;
+                finished.notify_one();
+                result
+            }),
+        }
+    }
+
+    fn is_running(&self) -> bool {
+        !self.run_task.is_finished()
+    }
+
+    async fn stop(self) {
+        // Shut down the tasks for the run
+        self.stopper.stop().await;
+        // Run should exit shortly after we stop all child tasks, wait for it to
+        // finish to ensure all messages are flushed.
+        let _ = self.run_task.await;
+    }
+}
+
+/// What to do about a re-run, given the runs in progress
+#[derive(Debug, PartialEq, Eq)]
+enum RerunPlan {
+    /// Stop the runs at these indices, which run some of the same tasks, and
+    /// start the re-run
+    Replace(Vec<usize>),
+    /// Wait for a rebuild that runs some of the same tasks to finish, so the
+    /// changes it builds aren't lost
+    Wait,
+}
+
+/// Works out how to start a re-run of `tasks`. A re-run replaces earlier
+/// re-runs of its tasks, but waits for rebuilds of its tasks that will
+/// finish, otherwise the packages that changed wouldn't be built.
+fn plan_rerun(runs: &[RunHandle], tasks: &HashSet<String>) -> RerunPlan {
+    let mut replaced = Vec::new();
+    for (index, run) in runs.iter().enumerate() {
+        if !run.is_running() || run.tasks.is_disjoint(tasks) {
+            continue;
+        }
+        if run.kind == RunKind::Rebuild && run.finishes {
+            return RerunPlan::Wait;
+        }
+        replaced.push(index);
+    }
+    RerunPlan::Replace(replaced)
 }
 
 #[derive(Debug, Error, Diagnostic)]
@@ -165,8 +253,12 @@ impl WatchClient {
         // If we used a std::sync::Mutex, we could deadlock by spinning the lock
         // and not yielding back to the tokio runtime.
         let changed_packages = Mutex::new(ChangedPackages::default());
+        // A task the user asked to run again from the UI
+        let rerun_task = Mutex::new(None);
         let notify_run = Arc::new(Notify::new());
         let notify_event = notify_run.clone();
+        let notify_rerun = notify_run.clone();
+        let rerun_requests = self.ui_sender.as_ref().and_then(UISender::rerun_requests);
 
         let event_fut = async {
             while let Some(event) = events.next().await {
@@ -178,27 +270,68 @@ impl WatchClient {
             Err(Error::ConnectionClosed)
         };
 
+        let rerun_fut = async {
+            if let Some(mut rerun_requests) = rerun_requests {
+                while let Some(request) = rerun_requests.recv().await {
+                    *rerun_task.lock().expect("poisoned lock") = Some(request);
+                    notify_rerun.notify_one();
+                }
+            }
+            // The UI can't request re-runs, or has stopped, so only changes
+            // start runs from here on
+            futures::future::pending::<()>().await;
+        };
+
         let run_fut = async {
-            let mut run_handle: Option<RunHandle> = None;
+            let mut runs: Vec<RunHandle> = Vec::new();
+            // A re-run that's waiting for a rebuild of the same tasks to finish
+            let mut queued_rerun: Option<RerunTask> = None;
             loop {
+                // Also woken up when a run finishes, so a queued re-run can start
                 notify_run.notified().await;
+                runs.retain(RunHandle::is_running);
                 let some_changed_packages = {
                     let mut changed_packages_guard =
                         changed_packages.lock().expect("poisoned lock");
                     (!changed_packages_guard.is_empty())
                         .then(|| std::mem::take(changed_packages_guard.deref_mut()))
                 };
+                if let Some(rerun) = rerun_task.lock().expect("poisoned lock").take() {
+                    // Only the latest request is kept, like changes to the same package
+                    queued_rerun = Some(rerun);
+                }
 
                 if let Some(changed_packages) = some_changed_packages {
                     // Clean up currently running tasks
-                    if let Some(RunHandle { stopper, run_task }) = run_handle.take() {
-                        // Shut down the tasks for the run
-                        stopper.stop().await;
-                        // Run should exit shortly after we stop all child tasks, wait for it to
-                        // finish to ensure all messages are flushed.
-                        let _ = run_task.await;
+                    for run in runs.drain(..) {
+                        run.stop().await;
+                    }
+                    runs.push(self.execute_run(changed_packages, &notify_run).await?);
+                }
+
+                let Some(rerun) = queued_rerun.take() else {
+                    continue;
+                };
+                let Some(run) = self.prepare_rerun(&rerun).await? else {
+                    continue;
+                };
+                let tasks = run
+                    .engine
+                    .tasks_with_command(&run.pkg_dep_graph)
+                    .into_iter()
+                    .collect();
+                match plan_rerun(&runs, &tasks) {
+                    RerunPlan::Wait => {
+                        debug!("re-running {} once changed packages are built", rerun.task);
+                        queued_rerun = Some(rerun);
+                    }
+                    RerunPlan::Replace(replaced) => {
+                        // Remove from the back so the other indices stay the same
+                        for index in replaced.into_iter().rev() {
+                            runs.remove(index).stop().await;
+                        }
+                        runs.push(self.start_rerun(run, &notify_run)?);
                     }
-                    run_handle = Some(self.execute_run(changed_packages).await?);
                 }
             }
         };
@@ -215,6 +348,9 @@ impl WatchClient {
             run_result = run_fut => {
                 run_result
             }
+            _ = rerun_fut => {
+                unreachable!("re-run requests are handled until shutdown")
+            }
         }
     }
 
@@ -255,13 +391,68 @@ impl WatchClient {
         if let Some(sender) = &self.ui_sender {
             sender.stop().await;
         }
-        if let Some(RunHandle { stopper, run_task }) = self.persistent_tasks_handle.take() {
-            // Shut down the tasks for the run
-            stopper.stop().await;
-            // Run should exit shortly after we stop all child tasks, wait for it to finish
-            // to ensure all messages are flushed.
-            let _ = run_task.await;
+        if let Some(run) = self.persistent_tasks_handle.take() {
+            run.stop().await;
+        }
+    }
+
+    /// Creates the run for a task the user asked to run again, and its
+    /// dependents if requested. It doesn't read from the cache so the task
+    /// executes even though none of its files changed. Returns `None` if the
+    /// task can't be re-run, e.g. it's persistent.
+    async fn prepare_rerun(&self, rerun: &RerunTask) -> Result<Option<Run>, Error> {
+        trace!("handling request to re-run: {rerun:?}");
+        let Ok(task_id) = TaskId::try_from(rerun.task.as_str()) else {
+            warn!("unable to re-run unknown task '{}'", rerun.task);
+            return Ok(None);
+        };
+        let task_id = task_id.into_owned();
+
+        let mut opts = self.base.opts().clone();
+        opts.cache_opts.cache = CacheConfig {
+            local: CacheActions {
+                read: false,
+                write: false,
+            },
+            remote: CacheActions {
+                read: false,
+                write: false,
+            },
+        };
+
+        let base = CommandBase::from_opts(
+            opts,
+            self.base.repo_root.clone(),
+            get_version(),
+            self.base.color_config,
+        );
+
+        let run = RunBuilder::new(base)?
+            .hide_prelude()
+            .build(&self.handler, self.telemetry.clone())
+            .await?;
+        let run = run.create_run_for_task(&task_id, rerun.include_dependents);
+        if run.is_none() {
+            warn!("unable to re-run '{task_id}', it isn't part of this run or is persistent");
+        }
+        Ok(run)
+    }
+
+    /// Starts a run made by `prepare_rerun`
+    fn start_rerun(&self, run: Run, finished: &Arc<Notify>) -> Result<RunHandle, Error> {
+        if let Some(sender) = &self.ui_sender {
+            let task_names = run.engine.tasks_with_command(&run.pkg_dep_graph);
+            sender
+                .restart_tasks(task_names)
+                .map_err(|err| Error::UISend(format!("re-running tasks: {err}")))?;
         }
+
+        Ok(RunHandle::spawn(
+            Arc::new(run),
+            RunKind::Rerun,
+            self.ui_sender.clone(),
+            finished,
+        ))
     }
 
     /// Executes a run with the given changed packages. Splits the run into two
@@ -270,8 +461,13 @@ impl WatchClient {
     /// 2. The non-persistent tasks and the persistent tasks that are allowed to
     ///    be interrupted
     ///
-    /// Returns a handle to the task running (2)
-    async fn execute_run(&mut self, changed_packages: ChangedPackages) -> Result<RunHandle, Error> {
+    /// Returns a handle to the task running (2), which notifies `finished`
+    /// once it's done
+    async fn execute_run(
+        &mut self,
+        changed_packages: ChangedPackages,
+        finished: &Arc<Notify>,
+    ) -> Result<RunHandle, Error> {
         // Should we recover here?
         trace!("handling run with changed packages: {changed_packages:?}");
         match changed_packages {
@@ -311,11 +507,12 @@ impl WatchClient {
                         .map_err(|err| Error::UISend(format!("some packages changed: {err}")))?;
                 }
 
-                let ui_sender = self.ui_sender.clone();
-                Ok(RunHandle {
-                    stopper: run.stopper(),
-                    run_task: tokio::spawn(async move { run.run(ui_sender, true).await }),
-                })
+                Ok(RunHandle::spawn(
+                    Arc::new(run),
+                    RunKind::Rebuild,
+                    self.ui_sender.clone(),
+                    finished,
+                ))
             }
             ChangedPackages::All => {
                 let mut opts = self.base.opts().clone();
@@ -339,12 +536,8 @@ impl WatchClient {
                 self.watched_packages = self.run.get_relevant_packages();
 
                 // Clean up currently running persistent tasks
-                if let Some(RunHandle { stopper, run_task }) = self.persistent_tasks_handle.take() {
-                    // Shut down the tasks for the run
-                    stopper.stop().await;
-                    // Run should exit shortly after we stop all child tasks, wait for it to finish
-                    // to ensure all messages are flushed.
-                    let _ = run_task.await;
+                if let Some(run) = self.persistent_tasks_handle.take() {
+                    run.stop().await;
                 }
                 if let Some(sender) = &self.ui_sender {
                     let task_names = self.run.engine.tasks_with_command(&self.run.pkg_dep_graph);
@@ -359,33 +552,98 @@ impl WatchClient {
                         "persistent handle should be empty before creating a new one"
                     );
                     let persistent_run = self.run.create_run_for_non_interruptible_tasks();
-                    let ui_sender = self.ui_sender.clone();
                     // If we have persistent tasks, we run them on a separate thread
                     // since persistent tasks don't finish
-                    self.persistent_tasks_handle = Some(RunHandle {
-                        stopper: persistent_run.stopper(),
-                        run_task: tokio::spawn(
-                            async move { persistent_run.run(ui_sender, true).await },
-                        ),
-                    });
+                    self.persistent_tasks_handle = Some(RunHandle::spawn(
+                        Arc::new(persistent_run),
+                        RunKind::Rebuild,
+                        self.ui_sender.clone(),
+                        finished,
+                    ));
 
                     let non_persistent_run = self.run.create_run_for_interruptible_tasks();
-                    let ui_sender = self.ui_sender.clone();
-                    Ok(RunHandle {
-                        stopper: non_persistent_run.stopper(),
-                        run_task: tokio::spawn(async move {
-                            non_persistent_run.run(ui_sender, true).await
-                        }),
-                    })
+                    Ok(RunHandle::spawn(
+                        Arc::new(non_persistent_run),
+                        RunKind::Rebuild,
+                        self.ui_sender.clone(),
+                        finished,
+                    ))
                 } else {
-                    let ui_sender = self.ui_sender.clone();
-                    let run = self.run.clone();
-                    Ok(RunHandle {
-                        stopper: run.stopper(),
-                        run_task: tokio::spawn(async move { run.run(ui_sender, true).await }),
-                    })
+                    Ok(RunHandle::spawn(
+                        self.run.clone(),
+                        RunKind::Rebuild,
+                        self.ui_sender.clone(),
+                        finished,
+                    ))
                 }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use super::{plan_rerun, RerunPlan, RunHandle, RunKind};
+    use crate::{process::ProcessManager, run::RunStopper};
+
+    fn run_handle(kind: RunKind, tasks: &[&str], finishes: bool, running: bool) -> RunHandle {
+        RunHandle {
+            stopper: RunStopper {
+                manager: ProcessManager::new(false),
+            },
+            run_task: tokio::spawn(async move {
+                if running {
+                    futures::future::pending::<()>().await;
+                }
+                Ok(0)
+            }),
+            tasks: tasks.iter().map(|task| task.to_string()).collect(),
+            kind,
+            finishes,
+        }
+    }
+
+    fn tasks(tasks: &[&str]) -> HashSet<String> {
+        tasks.iter().map(|task| task.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_rerun_waits_for_rebuild() {
+        let runs = vec![run_handle(
+            RunKind::Rebuild,
+            &["a#build", "b#build"],
+            true,
+            true,
+        )];
+        assert_eq!(plan_rerun(&runs, &tasks(&["b#build"])), RerunPlan::Wait);
+        // A re-run of other tasks runs alongside the rebuild
+        assert_eq!(
+            plan_rerun(&runs, &tasks(&["c#build"])),
+            RerunPlan::Replace(vec![])
+        );
+        runs.iter().for_each(|run| run.run_task.abort());
+    }
+
+    #[tokio::test]
+    async fn test_rerun_replaces_runs_of_its_tasks() {
+        let finished = run_handle(RunKind::Rebuild, &["a#build"], true, false);
+        // Let the finished run finish
+        while finished.is_running() {
+            tokio::task::yield_now().await;
+        }
+        let runs = vec![
+            finished,
+            run_handle(RunKind::Rerun, &["a#build"], true, true),
+            // Rebuilds with persistent tasks never finish, so they're replaced
+            run_handle(RunKind::Rebuild, &["a#build", "a#dev"], false, true),
+            run_handle(RunKind::Rerun, &["b#build"], true, true),
+        ];
+        assert_eq!(
+            plan_rerun(&runs, &tasks(&["a#build"])),
+            RerunPlan::Replace(vec![1, 2])
+        );
+        runs.iter().for_each(|run| run.run_task.abort());
+    }
+}
@@ -4,7 +4,7 @@ mod exec;
 mod output;
 use std::{
     borrow::Cow,
-    collections::HashSet,
+    collections::HashMap,
     io::Write,
     sync::{Arc, Mutex, OnceLock},
 };
@@ -24,7 +24,10 @@ use turbopath::{AbsoluteSystemPath, AnchoredSystemPath};
 use turborepo_ci::{Vendor, VendorBehavior};
 use turborepo_env::{platform::PlatformEnv, EnvironmentVariableMap};
 use turborepo_errors::TURBO_SITE;
-use turborepo_repository::package_graph::{PackageGraph, PackageName, ROOT_PKG_NAME};
+use turborepo_repository::{
+    change_mapper::PackageInclusionReason,
+    package_graph::{PackageGraph, PackageName, ROOT_PKG_NAME},
+};
 use turborepo_telemetry::events::{
     generic::GenericEventBuilder, task::PackageTaskEventBuilder, EventBuilder, TrackedErrors,
 };
@@ -378,7 +381,7 @@ impl<'a> Visitor<'a> {
     pub(crate) async fn finish(
         self,
         exit_code: i32,
-        packages: &HashSet<PackageName>,
+        packages: &HashMap<PackageName, PackageInclusionReason>,
         global_hash_inputs: GlobalHashableInputs<'_>,
         engine: &Engine,
         env_at_execution_start: &EnvironmentVariableMap,
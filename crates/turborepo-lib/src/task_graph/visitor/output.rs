@@ -1,9 +1,12 @@
-use std::io::Write;
+use std::{io::Write, time::Duration};
 
 use either::Either;
 use turbopath::AbsoluteSystemPath;
+use turborepo_cache::CacheSource;
 use turborepo_ui::{
-    sender::TaskSender, tui::event::CacheResult, OutputClient, OutputWriter, PrefixedUI,
+    sender::TaskSender,
+    tui::event::{self, CacheRestore, CacheResult},
+    OutputClient, OutputWriter, PrefixedUI,
 };
 
 use crate::run::CacheOutput;
@@ -97,6 +100,20 @@ impl<W: Write> CacheOutput for TaskCacheOutput<W> {
             TaskCacheOutput::UI(task) => turborepo_ui::replay_logs(task, log_file),
         }
     }
+
+    fn cache_restored(&mut self, source: CacheSource, duration: Duration) {
+        match self {
+            // The status line already says it was a cache hit
+            TaskCacheOutput::Direct(_) => {}
+            TaskCacheOutput::UI(task) => {
+                let source = match source {
+                    CacheSource::Local => event::CacheSource::Local,
+                    CacheSource::Remote => event::CacheSource::Remote,
+                };
+                task.cache_restored(CacheRestore { source, duration });
+            }
+        }
+    }
 }
 
 // A tiny enum that allows us to use the same type for stdout and stderr without
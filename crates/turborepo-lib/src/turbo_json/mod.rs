@@ -13,7 +13,9 @@ use serde::{Deserialize, Serialize};
 use struct_iterable::Iterable;
 use turbopath::AbsoluteSystemPath;
 use turborepo_errors::Spanned;
-use turborepo_repository::package_graph::ROOT_PKG_NAME;
+use turborepo_repository::package_graph::{
+    PackageCycle, PackageCycles, PackageGraph, PackageName, ROOT_PKG_NAME,
+};
 use turborepo_unescape::UnescapedString;
 
 use crate::{
@@ -56,6 +58,8 @@ pub struct TurboJson {
     pub(crate) global_env: Vec<String>,
     pub(crate) global_pass_through_env: Option<Vec<String>>,
     pub(crate) tasks: Pipeline,
+    pub(crate) allowed_package_cycles: Vec<Spanned<PackageCycle>>,
+    pub(crate) shared_files: BTreeMap<String, Vec<Spanned<PackageName>>>,
 }
 
 // Iterable is required to enumerate allowed keys
@@ -138,6 +142,14 @@ pub struct RawTurboJson {
     )]
     pub allow_no_package_manager: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_peer_dependencies: Option<bool>,
+    // Package dependency cycles that shouldn't fail the run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    allowed_package_cycles: Option<Vec<Spanned<Vec<UnescapedString>>>>,
+    // Files outside of any package that only affect the listed packages
+    #[serde(skip_serializing_if = "Option::is_none")]
+    shared_files: Option<BTreeMap<String, Vec<Spanned<UnescapedString>>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub daemon: Option<Spanned<bool>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub env_mode: Option<EnvMode>,
@@ -186,6 +198,9 @@ pub enum UIMode {
     Stream,
     /// Use the web user interface (experimental)
     Web,
+    /// Print task progress as plain lines, for screen readers and dumb
+    /// terminals
+    Accessible,
 }
 
 impl Default for UIMode {
@@ -200,6 +215,7 @@ impl Display for UIMode {
             UIMode::Tui => write!(f, "tui"),
             UIMode::Stream => write!(f, "stream"),
             UIMode::Web => write!(f, "web"),
+            UIMode::Accessible => write!(f, "accessible"),
         }
     }
 }
@@ -210,9 +226,9 @@ impl UIMode {
     }
 
     /// Returns true if the UI mode has a sender,
-    /// i.e. web or tui but not stream
+    /// i.e. web, tui or accessible but not stream
     pub fn has_sender(&self) -> bool {
-        matches!(self, Self::Tui | Self::Web)
+        matches!(self, Self::Tui | Self::Web | Self::Accessible)
     }
 }
 
@@ -591,12 +607,89 @@ impl TryFrom<RawTurboJson> for TurboJson {
                 .extends
                 .unwrap_or_default()
                 .map(|s| s.into_iter().map(|s| s.into()).collect()),
+            allowed_package_cycles: raw_turbo
+                .allowed_package_cycles
+                .into_iter()
+                .flatten()
+                .map(|cycle| {
+                    cycle.map(|packages| {
+                        PackageCycle::new(
+                            packages
+                                .into_iter()
+                                .map(|package| String::from(package).into()),
+                        )
+                    })
+                })
+                .collect(),
+            shared_files: raw_turbo
+                .shared_files
+                .into_iter()
+                .flatten()
+                .map(|(pattern, packages)| {
+                    (
+                        pattern,
+                        packages
+                            .into_iter()
+                            .map(|package| package.map(|package| String::from(package).into()))
+                            .collect(),
+                    )
+                })
+                .collect(),
             // Spaces and Remote Cache config is handled through layered config
         })
     }
 }
 
 impl TurboJson {
+    /// The package cycles that are permitted by `allowedPackageCycles`
+    pub fn allowed_package_cycles(&self) -> HashSet<PackageCycle> {
+        self.allowed_package_cycles
+            .iter()
+            .map(|cycle| cycle.as_inner().clone())
+            .collect()
+    }
+
+    /// Checks that every cycle in `allowedPackageCycles` is still present in
+    /// the package graph's cycles so that stale entries get cleaned up.
+    pub fn validate_allowed_package_cycles(&self, cycles: &PackageCycles) -> Result<(), Error> {
+        for cycle in &self.allowed_package_cycles {
+            if !cycles.contains(cycle.as_inner()) {
+                let (span, text) = cycle.span_and_text("turbo.json");
+                return Err(Error::StaleAllowedPackageCycle {
+                    cycle: cycle.as_inner().to_string(),
+                    span,
+                    text,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// The packages that own each `sharedFiles` pattern
+    pub fn shared_files(&self) -> impl Iterator<Item = (&str, Vec<&PackageName>)> {
+        self.shared_files.iter().map(|(pattern, packages)| {
+            (
+                pattern.as_str(),
+                packages.iter().map(|package| package.as_inner()).collect(),
+            )
+        })
+    }
+
+    /// Checks that every package listed in `sharedFiles` exists
+    pub fn validate_shared_files(&self, pkg_graph: &PackageGraph) -> Result<(), Error> {
+        for package in self.shared_files.values().flatten() {
+            if pkg_graph.package_info(package.as_inner()).is_none() {
+                let (span, text) = package.span_and_text("turbo.json");
+                return Err(Error::UnknownSharedFileOwner {
+                    package: package.as_inner().to_string(),
+                    span,
+                    text,
+                });
+            }
+        }
+        Ok(())
+    }
+
     fn has_task(&self, task_name: &TaskName) -> bool {
         for key in self.tasks.keys() {
             if key == task_name || (key.task() == task_name.task() && !task_name.is_package_task())
@@ -764,7 +857,7 @@ mod tests {
     use test_case::test_case;
     use turborepo_unescape::UnescapedString;
 
-    use super::{RawTurboJson, Spanned, TurboJson, UIMode};
+    use super::{PackageCycle, PackageName, RawTurboJson, Spanned, TurboJson, UIMode};
     use crate::{
         cli::OutputLogsMode,
         run::task_id::TaskName,
@@ -995,12 +1088,50 @@ mod tests {
 
     #[test_case(r#"{ "ui": "tui" }"#, Some(UIMode::Tui) ; "tui")]
     #[test_case(r#"{ "ui": "stream" }"#, Some(UIMode::Stream) ; "stream")]
+    #[test_case(r#"{ "ui": "accessible" }"#, Some(UIMode::Accessible) ; "accessible")]
     #[test_case(r#"{}"#, None ; "missing")]
     fn test_ui(json: &str, expected: Option<UIMode>) {
         let json = RawTurboJson::parse(json, "").unwrap();
         assert_eq!(json.ui, expected);
     }
 
+    #[test]
+    fn test_allowed_package_cycles() {
+        let raw = RawTurboJson::parse(
+            r#"{ "allowedPackageCycles": [["b", "a"], ["c", "d", "e"]] }"#,
+            "",
+        )
+        .unwrap();
+        let turbo_json = TurboJson::try_from(raw).unwrap();
+        assert_eq!(
+            turbo_json.allowed_package_cycles(),
+            [
+                PackageCycle::new(["a".into(), "b".into()]),
+                PackageCycle::new(["c".into(), "d".into(), "e".into()]),
+            ]
+            .into_iter()
+            .collect()
+        );
+    }
+
+    #[test]
+    fn test_shared_files() {
+        let raw = RawTurboJson::parse(
+            r#"{ "sharedFiles": { "shared/config/**": ["web", "docs"], "shared/ui/**": [] } }"#,
+            "turbo.json",
+        )
+        .unwrap();
+        let turbo_json = TurboJson::try_from(raw).unwrap();
+        let (web, docs) = (PackageName::from("web"), PackageName::from("docs"));
+        assert_eq!(
+            turbo_json.shared_files().collect::<Vec<_>>(),
+            vec![
+                ("shared/config/**", vec![&web, &docs]),
+                ("shared/ui/**", vec![])
+            ]
+        );
+    }
+
     #[test_case(r#"{ "daemon": true }"#, r#"{"daemon":true}"# ; "daemon_on")]
     #[test_case(r#"{ "daemon": false }"#, r#"{"daemon":false}"# ; "daemon_off")]
     fn test_daemon(json: &str, expected: &str) {
@@ -1027,6 +1158,16 @@ mod tests {
         assert_eq!(serialized, json_str);
     }
 
+    #[test_case(r#"{"includePeerDependencies":true}"#, Some(true) ; "t")]
+    #[test_case(r#"{"includePeerDependencies":false}"#, Some(false) ; "f")]
+    #[test_case(r#"{}"#, None ; "missing")]
+    fn test_include_peer_dependencies_serde(json_str: &str, expected: Option<bool>) {
+        let json = RawTurboJson::parse(json_str, "").unwrap();
+        assert_eq!(json.include_peer_dependencies, expected);
+        let serialized = serde_json::to_string(&json).unwrap();
+        assert_eq!(serialized, json_str);
+    }
+
     #[test]
     fn test_with_proxy_empty() {
         let mut json = TurboJson::default();
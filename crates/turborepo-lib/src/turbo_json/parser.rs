@@ -105,6 +105,8 @@ impl WithMetadata for RawTurboJson {
         self.global_pass_through_env.add_text(text.clone());
         self.tasks.add_text(text.clone());
         self.cache_dir.add_text(text.clone());
+        self.allowed_package_cycles.add_text(text.clone());
+        self.shared_files.add_text(text.clone());
         self.pipeline.add_text(text);
     }
 
@@ -116,6 +118,8 @@ impl WithMetadata for RawTurboJson {
         self.global_pass_through_env.add_path(path.clone());
         self.tasks.add_path(path.clone());
         self.cache_dir.add_path(path.clone());
+        self.allowed_package_cycles.add_path(path.clone());
+        self.shared_files.add_path(path.clone());
         self.pipeline.add_path(path);
     }
 }
@@ -10,6 +10,7 @@ pub use package::{
     DefaultPackageChangeMapper, Error, GlobalDepsPackageChangeMapper, PackageChangeMapper,
     PackageMapping,
 };
+use serde::Serialize;
 use tracing::debug;
 use turbopath::{AbsoluteSystemPath, AnchoredSystemPathBuf};
 use wax::Program;
@@ -29,7 +30,14 @@ pub enum LockfileChange {
     ChangedPackages(HashSet<turborepo_lockfiles::Package>),
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+/// Why a package was selected. Serialized with its variant in `type`, e.g.
+/// `{ "type": "fileChanged", "file": "apps/web/index.ts" }`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
 pub enum PackageInclusionReason {
     /// All the packages are invalidated
     All(AllPackageChangeReason),
@@ -49,6 +57,12 @@ pub enum PackageInclusionReason {
     DependentChanged { dependent: PackageName },
     /// A file contained in this package changed
     FileChanged { file: AnchoredSystemPathBuf },
+    /// A file outside of any package that this package owns changed
+    SharedFileChanged {
+        file: AnchoredSystemPathBuf,
+        /// The shared file pattern that matched the file
+        pattern: String,
+    },
     /// The filter selected a directory which contains this package
     InFilteredDirectory { directory: AnchoredSystemPathBuf },
     /// Package is automatically included because of the filter (or lack
@@ -56,10 +70,20 @@ pub enum PackageInclusionReason {
     IncludedByFilter { filters: Vec<String> },
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+/// Why every package was selected. Serialized with its variant in `reason`,
+/// alongside the `type` of the `PackageInclusionReason::All` containing it.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize)]
+#[serde(
+    tag = "reason",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
 pub enum AllPackageChangeReason {
     GlobalDepsChanged {
         file: AnchoredSystemPathBuf,
+        /// The `globalDependencies` pattern that matched the file, if the file
+        /// was matched against them
+        matched_pattern: Option<String>,
     },
     /// A file like `package.json` or `turbo.json` changed
     DefaultGlobalFileChanged {
@@ -85,6 +109,26 @@ pub fn merge_changed_packages<T: Hash + Eq>(
     }
 }
 
+/// Re-anchors changed files from the root of the source control repository to
+/// the turbo root.
+///
+/// The monorepo doesn't need to be at the root of the repository, e.g. it could
+/// be in `web/` with its own `pnpm-workspace.yaml`. Files outside of the turbo
+/// root can't belong to any package, so they're dropped.
+pub fn anchor_to_turbo_root(
+    scm_root: &AbsoluteSystemPath,
+    turbo_root: &AbsoluteSystemPath,
+    changed_files: impl IntoIterator<Item = AnchoredSystemPathBuf>,
+) -> Result<HashSet<AnchoredSystemPathBuf>, ChangeMapError> {
+    // Verify the turbo root is inside of the repository, otherwise every file
+    // would be silently dropped.
+    scm_root.anchor(turbo_root)?;
+    Ok(changed_files
+        .into_iter()
+        .filter_map(|file| turbo_root.anchor(&scm_root.resolve(&file)).ok())
+        .collect())
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum PackageChanges {
     All(AllPackageChangeReason),
@@ -207,9 +251,18 @@ impl<'a, PD: PackageChangeMapper> ChangeMapper<'a, PD> {
         let root_internal_deps = self.pkg_graph.root_internal_package_dependencies();
         let mut changed_packages = HashMap::new();
         for file in files {
-            match self.package_detector.detect_package(file) {
+            let packages = match self.package_detector.detect_package(file) {
+                PackageMapping::Package(package) => vec![package],
+                PackageMapping::Packages(packages) => packages,
+                PackageMapping::All(reason) => {
+                    debug!("all packages changed due to {file:?}");
+                    return PackageChanges::All(reason);
+                }
+                PackageMapping::None => continue,
+            };
+            for (pkg, reason) in packages {
                 // Internal root dependency changed so global hash has changed
-                PackageMapping::Package((pkg, _)) if root_internal_deps.contains(&pkg) => {
+                if root_internal_deps.contains(&pkg) {
                     debug!(
                         "{} changes root internal dependency: \"{}\"\nshortest path from root: \
                          {:?}",
@@ -221,15 +274,8 @@ impl<'a, PD: PackageChangeMapper> ChangeMapper<'a, PD> {
                         root_internal_dep: pkg.name.clone(),
                     });
                 }
-                PackageMapping::Package((pkg, reason)) => {
-                    debug!("{} changes \"{}\"", file.to_string(), pkg.name);
-                    changed_packages.insert(pkg, reason);
-                }
-                PackageMapping::All(reason) => {
-                    debug!("all packages changed due to {file:?}");
-                    return PackageChanges::All(reason);
-                }
-                PackageMapping::None => {}
+                debug!("{} changes \"{}\"", file.to_string(), pkg.name);
+                changed_packages.insert(pkg, reason);
             }
         }
 
@@ -275,6 +321,8 @@ pub enum ChangeMapError {
     NoLockfile,
     #[error("Lockfile error: {0}")]
     Lockfile(turborepo_lockfiles::Error),
+    #[error(transparent)]
+    Path(#[from] turbopath::PathError),
 }
 
 impl From<ChangedPackagesError> for ChangeMapError {
@@ -288,10 +336,13 @@ impl From<ChangedPackagesError> for ChangeMapError {
 
 #[cfg(test)]
 mod test {
+    use serde_json::json;
     use test_case::test_case;
 
-    use super::ChangeMapper;
-    use crate::change_mapper::package::DefaultPackageChangeMapper;
+    use super::{
+        anchor_to_turbo_root, AllPackageChangeReason, ChangeMapper, PackageInclusionReason,
+    };
+    use crate::{change_mapper::package::DefaultPackageChangeMapper, package_graph::PackageName};
 
     #[cfg(unix)]
     #[test_case("/a/b/c", &["package.lock"], "/a/b/c/package.lock", true ; "simple")]
@@ -342,4 +393,65 @@ mod test {
         // so simply compare the debug representations
         assert_eq!(changes, expected);
     }
+
+    #[cfg(unix)]
+    #[test_case("/repo", "/repo/web", &["web/apps/a/index.ts"], &["apps/a/index.ts"] ; "nested")]
+    #[test_case("/repo", "/repo/web", &["api/main.go", "web/package.json"], &["package.json"] ; "outside turbo root")]
+    #[test_case("/repo", "/repo", &["apps/a/index.ts"], &["apps/a/index.ts"] ; "same root")]
+    fn test_anchor_to_turbo_root(
+        scm_root: &str,
+        turbo_root: &str,
+        changed_files: &[&str],
+        expected: &[&str],
+    ) {
+        let scm_root = turbopath::AbsoluteSystemPathBuf::new(scm_root).unwrap();
+        let turbo_root = turbopath::AbsoluteSystemPathBuf::new(turbo_root).unwrap();
+        let changed_files = changed_files
+            .iter()
+            .map(|s| turbopath::AnchoredSystemPathBuf::from_raw(s).unwrap());
+        let expected = expected
+            .iter()
+            .map(|s| turbopath::AnchoredSystemPathBuf::from_raw(s).unwrap())
+            .collect();
+
+        assert_eq!(
+            anchor_to_turbo_root(&scm_root, &turbo_root, changed_files).unwrap(),
+            expected
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_anchor_to_turbo_root_outside_repo() {
+        let scm_root = turbopath::AbsoluteSystemPathBuf::new("/repo").unwrap();
+        let turbo_root = turbopath::AbsoluteSystemPathBuf::new("/other").unwrap();
+        assert!(anchor_to_turbo_root(&scm_root, &turbo_root, []).is_err());
+    }
+
+    #[test]
+    fn test_reasons_serialize_with_their_type() {
+        let reasons = [
+            PackageInclusionReason::DependencyChanged {
+                dependency: PackageName::from("util"),
+            },
+            PackageInclusionReason::ConservativeRootLockfileChanged,
+            PackageInclusionReason::All(AllPackageChangeReason::GlobalDepsChanged {
+                file: turbopath::AnchoredSystemPathBuf::from_raw("tsconfig.json").unwrap(),
+                matched_pattern: Some("*.json".to_owned()),
+            }),
+        ];
+        assert_eq!(
+            serde_json::to_value(reasons).unwrap(),
+            json!([
+                { "type": "dependencyChanged", "dependency": "util" },
+                { "type": "conservativeRootLockfileChanged" },
+                {
+                    "type": "all",
+                    "reason": "globalDepsChanged",
+                    "file": "tsconfig.json",
+                    "matchedPattern": "*.json"
+                },
+            ])
+        );
+    }
 }
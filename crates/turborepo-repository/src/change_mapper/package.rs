@@ -1,6 +1,6 @@
 use thiserror::Error;
 use turbopath::{AnchoredSystemPath, AnchoredSystemPathBuf};
-use wax::{BuildError, Program};
+use wax::{BuildError, Glob, Program};
 
 use crate::{
     change_mapper::{AllPackageChangeReason, PackageInclusionReason},
@@ -14,11 +14,13 @@ pub enum PackageMapping {
     None,
     /// This change has affected one package
     Package((WorkspacePackage, PackageInclusionReason)),
+    /// This change has affected several packages
+    Packages(Vec<(WorkspacePackage, PackageInclusionReason)>),
 }
 
 /// Maps a single file change to affected packages. This can be a single
-/// package (`Package`), none of the packages (`None`), or all of the packages
-/// (`All`).
+/// package (`Package`), several packages (`Packages`), none of the packages
+/// (`None`), or all of the packages (`All`).
 pub trait PackageChangeMapper {
     fn detect_package(&self, file: &AnchoredSystemPath) -> PackageMapping;
 }
@@ -68,6 +70,7 @@ impl PackageChangeMapper for DefaultPackageChangeMapper<'_> {
 
         PackageMapping::All(AllPackageChangeReason::GlobalDepsChanged {
             file: file.to_owned(),
+            matched_pattern: None,
         })
     }
 }
@@ -86,23 +89,107 @@ pub enum Error {
 /// which assumes that any changed file that is not in a package
 /// changes all packages. Since we have a list of global deps,
 /// we can check against that and avoid invalidating in unnecessary cases.
+///
+/// Global deps starting with `!` exclude files that the other global deps
+/// match, no matter which order they're listed in.
+///
+/// Files can also be assigned to a set of packages with
+/// [`GlobalDepsPackageChangeMapper::with_shared_files`], which takes priority
+/// over both the package directories and the global deps.
 pub struct GlobalDepsPackageChangeMapper<'a> {
     pkg_dep_graph: &'a PackageGraph,
     global_deps_matcher: wax::Any<'a>,
+    // Kept alongside the combined matcher so we can report which one matched
+    global_deps: Vec<(&'a str, Glob<'a>)>,
+    global_deps_exclusions: wax::Any<'a>,
+    shared_files: Vec<SharedFiles<'a>>,
+}
+
+/// Files matching `glob` are owned by `packages`
+struct SharedFiles<'a> {
+    pattern: &'a str,
+    glob: Glob<'a>,
+    packages: Vec<WorkspacePackage>,
 }
 
 impl<'a> GlobalDepsPackageChangeMapper<'a> {
-    pub fn new<S: wax::Pattern<'a>, I: Iterator<Item = S>>(
+    pub fn new<I: Iterator<Item = &'a str>>(
         pkg_dep_graph: &'a PackageGraph,
         global_deps: I,
     ) -> Result<Self, Error> {
-        let global_deps_matcher = wax::any(global_deps)?;
+        let (exclusions, global_deps): (Vec<_>, Vec<_>) =
+            global_deps.partition(|pattern| pattern.starts_with('!'));
+        let global_deps = global_deps
+            .into_iter()
+            .map(|pattern| Ok((pattern, Glob::new(pattern)?)))
+            .collect::<Result<Vec<_>, BuildError>>()?;
+        let global_deps_matcher = wax::any(global_deps.iter().map(|(pattern, _)| *pattern))?;
+        let global_deps_exclusions = wax::any(
+            exclusions
+                .into_iter()
+                .filter_map(|glob| glob.strip_prefix('!')),
+        )?;
 
         Ok(Self {
             pkg_dep_graph,
             global_deps_matcher,
+            global_deps,
+            global_deps_exclusions,
+            shared_files: Vec::new(),
         })
     }
+
+    /// Maps files matching each pattern to the given packages instead of the
+    /// package that contains them or the root package. Packages that aren't in
+    /// the package graph are skipped.
+    pub fn with_shared_files<P: IntoIterator<Item = &'a PackageName>>(
+        mut self,
+        shared_files: impl IntoIterator<Item = (&'a str, P)>,
+    ) -> Result<Self, Error> {
+        for (pattern, packages) in shared_files {
+            let packages = packages
+                .into_iter()
+                .filter_map(|name| {
+                    let path = self.pkg_dep_graph.package_dir(name)?;
+                    Some(WorkspacePackage {
+                        name: name.clone(),
+                        path: path.to_owned(),
+                    })
+                })
+                .collect();
+            self.shared_files.push(SharedFiles {
+                pattern,
+                glob: Glob::new(pattern)?,
+                packages,
+            });
+        }
+        Ok(self)
+    }
+
+    fn detect_shared_file(&self, path: &AnchoredSystemPath) -> Option<PackageMapping> {
+        let cleaned_path = path.clean();
+        let mut packages: Vec<(WorkspacePackage, PackageInclusionReason)> = Vec::new();
+        for shared_files in &self.shared_files {
+            if !shared_files.glob.is_match(cleaned_path.as_str()) {
+                continue;
+            }
+            for package in &shared_files.packages {
+                // A package owned through several patterns reports the first
+                if packages.iter().any(|(existing, _)| existing == package) {
+                    continue;
+                }
+                packages.push((
+                    package.clone(),
+                    PackageInclusionReason::SharedFileChanged {
+                        file: path.to_owned(),
+                        pattern: shared_files.pattern.to_string(),
+                    },
+                ));
+            }
+        }
+
+        (!packages.is_empty()).then_some(PackageMapping::Packages(packages))
+    }
 }
 
 impl PackageChangeMapper for GlobalDepsPackageChangeMapper<'_> {
@@ -112,11 +199,11 @@ impl PackageChangeMapper for GlobalDepsPackageChangeMapper<'_> {
         // dependency (this is cursed behavior but sadly possible). There's a chance
         // that we can make this more accurate by checking which package
         // manager, since not all package managers may permit root pulling from
-        // workspace package dependencies
-        if matches!(
-            path.as_str(),
-            "package.json" | "pnpm-lock.yaml" | "yarn.lock"
-        ) {
+        // workspace package dependencies. The packages whose dependencies changed
+        // are found by diffing the lockfile, so it shouldn't also be matched
+        // against the global deps.
+        let lockfile_name = self.pkg_dep_graph.package_manager().lockfile_name();
+        if path.as_str() == "package.json" || path.as_str() == lockfile_name {
             return PackageMapping::Package((
                 WorkspacePackage {
                     name: PackageName::Root,
@@ -125,17 +212,27 @@ impl PackageChangeMapper for GlobalDepsPackageChangeMapper<'_> {
                 PackageInclusionReason::ConservativeRootLockfileChanged,
             ));
         }
+        if let Some(mapping) = self.detect_shared_file(path) {
+            return mapping;
+        }
         match DefaultPackageChangeMapper::new(self.pkg_dep_graph).detect_package(path) {
             // Since `DefaultPackageChangeMapper` is overly conservative, we can check here if
             // the path is actually in globalDeps and if not, return it as
             // PackageDetection::Package(WorkspacePackage::root()).
             PackageMapping::All(_) => {
                 let cleaned_path = path.clean();
-                let in_global_deps = self.global_deps_matcher.is_match(cleaned_path.as_str());
+                let in_global_deps = self.global_deps_matcher.is_match(cleaned_path.as_str())
+                    && !self.global_deps_exclusions.is_match(cleaned_path.as_str());
 
                 if in_global_deps {
+                    let matched_pattern = self
+                        .global_deps
+                        .iter()
+                        .find(|(_, glob)| glob.is_match(cleaned_path.as_str()))
+                        .map(|(pattern, _)| pattern.to_string());
                     PackageMapping::All(AllPackageChangeReason::GlobalDepsChanged {
                         file: path.to_owned(),
+                        matched_pattern,
                     })
                 } else {
                     PackageMapping::Package((
@@ -153,8 +250,12 @@ impl PackageChangeMapper for GlobalDepsPackageChangeMapper<'_> {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::json;
     use tempfile::tempdir;
-    use turbopath::{AbsoluteSystemPath, AnchoredSystemPathBuf};
+    use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf, AnchoredSystemPathBuf};
+    use turborepo_lockfiles::Package;
 
     use super::{DefaultPackageChangeMapper, GlobalDepsPackageChangeMapper};
     use crate::{
@@ -163,7 +264,7 @@ mod tests {
         },
         discovery,
         discovery::PackageDiscovery,
-        package_graph::{PackageGraphBuilder, WorkspacePackage},
+        package_graph::{PackageGraphBuilder, PackageName, WorkspacePackage},
         package_json::PackageJson,
     };
 
@@ -216,6 +317,7 @@ mod tests {
             package_changes,
             PackageChanges::All(AllPackageChangeReason::GlobalDepsChanged {
                 file: AnchoredSystemPathBuf::from_raw("README.md")?,
+                matched_pattern: None,
             })
         );
 
@@ -248,4 +350,306 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_global_deps_reports_matched_pattern() -> Result<(), anyhow::Error> {
+        let repo_root = tempdir()?;
+        let root_package_json = PackageJson::default();
+
+        let pkg_graph = PackageGraphBuilder::new(
+            AbsoluteSystemPath::from_std_path(repo_root.path())?,
+            root_package_json,
+        )
+        .with_package_discovery(MockDiscovery)
+        .build()
+        .await?;
+
+        let turbo_package_detector =
+            GlobalDepsPackageChangeMapper::new(&pkg_graph, ["*.md", "config/**"].into_iter())?;
+        let change_mapper = ChangeMapper::new(&pkg_graph, vec![], turbo_package_detector);
+
+        let package_changes = change_mapper.changed_packages(
+            [AnchoredSystemPathBuf::from_raw("config/tsconfig.json")?]
+                .into_iter()
+                .collect(),
+            None,
+        )?;
+
+        assert_eq!(
+            package_changes,
+            PackageChanges::All(AllPackageChangeReason::GlobalDepsChanged {
+                file: AnchoredSystemPathBuf::from_raw("config/tsconfig.json")?,
+                matched_pattern: Some("config/**".to_string()),
+            })
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_global_deps_negation() -> Result<(), anyhow::Error> {
+        let repo_root = tempdir()?;
+        let root_package_json = PackageJson::default();
+
+        let pkg_graph = PackageGraphBuilder::new(
+            AbsoluteSystemPath::from_std_path(repo_root.path())?,
+            root_package_json,
+        )
+        .with_package_discovery(MockDiscovery)
+        .build()
+        .await?;
+
+        let turbo_package_detector = GlobalDepsPackageChangeMapper::new(
+            &pkg_graph,
+            ["!.github/ISSUE_TEMPLATE/**", ".github/**"].into_iter(),
+        )?;
+        let change_mapper = ChangeMapper::new(&pkg_graph, vec![], turbo_package_detector);
+
+        let package_changes = change_mapper.changed_packages(
+            [AnchoredSystemPathBuf::from_raw(".github/workflows/ci.yml")?]
+                .into_iter()
+                .collect(),
+            None,
+        )?;
+        assert_eq!(
+            package_changes,
+            PackageChanges::All(AllPackageChangeReason::GlobalDepsChanged {
+                file: AnchoredSystemPathBuf::from_raw(".github/workflows/ci.yml")?,
+                matched_pattern: Some(".github/**".to_string()),
+            })
+        );
+
+        // Excluded files only change the root package, like any other file
+        // outside of the global deps
+        let package_changes = change_mapper.changed_packages(
+            [AnchoredSystemPathBuf::from_raw(
+                ".github/ISSUE_TEMPLATE/bug.md",
+            )?]
+            .into_iter()
+            .collect(),
+            None,
+        )?;
+        assert_eq!(
+            package_changes,
+            PackageChanges::Some(
+                [(
+                    WorkspacePackage::root(),
+                    PackageInclusionReason::FileChanged {
+                        file: AnchoredSystemPathBuf::from_raw(".github/ISSUE_TEMPLATE/bug.md")?,
+                    }
+                )]
+                .into_iter()
+                .collect()
+            )
+        );
+
+        Ok(())
+    }
+
+    fn npm_lockfile(lodash_version: &str) -> String {
+        json!({
+            "name": "root",
+            "lockfileVersion": 3,
+            "requires": true,
+            "packages": {
+                "": { "name": "root", "workspaces": ["packages/*"] },
+                "node_modules/a": { "resolved": "packages/a", "link": true },
+                "node_modules/b": { "resolved": "packages/b", "link": true },
+                "node_modules/lodash": { "version": lodash_version },
+                "packages/a": {
+                    "version": "1.0.0",
+                    "dependencies": { "lodash": "^4.17.0" }
+                },
+                "packages/b": { "version": "1.0.0" }
+            }
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_lockfile_changes_only_affect_dependents() -> Result<(), anyhow::Error> {
+        let repo_root_tmp = tempdir()?;
+        let repo_root = AbsoluteSystemPath::from_std_path(repo_root_tmp.path())?;
+        repo_root
+            .join_component("package.json")
+            .create_with_contents(
+                json!({
+                    "name": "root",
+                    "packageManager": "npm@10.5.0",
+                    "workspaces": ["packages/*"]
+                })
+                .to_string(),
+            )?;
+        for (name, dependencies) in [("a", json!({ "lodash": "^4.17.0" })), ("b", json!({}))] {
+            let package_json = repo_root.join_components(&["packages", name, "package.json"]);
+            package_json.ensure_dir()?;
+            package_json.create_with_contents(
+                json!({ "name": name, "dependencies": dependencies }).to_string(),
+            )?;
+        }
+        repo_root
+            .join_component("package-lock.json")
+            .create_with_contents(npm_lockfile("4.17.21"))?;
+
+        let root_package_json = PackageJson::load(&repo_root.join_component("package.json"))?;
+        let pkg_graph = PackageGraphBuilder::new(repo_root, root_package_json)
+            .build()
+            .await?;
+
+        let turbo_package_detector =
+            GlobalDepsPackageChangeMapper::new(&pkg_graph, ["*.json"].into_iter())?;
+        let change_mapper = ChangeMapper::new(&pkg_graph, vec![], turbo_package_detector);
+
+        let package_changes = change_mapper.changed_packages(
+            [AnchoredSystemPathBuf::from_raw("package-lock.json")?]
+                .into_iter()
+                .collect(),
+            Some(Some(npm_lockfile("4.17.20").into_bytes())),
+        )?;
+
+        let lodash = |version: &str| Package::new("node_modules/lodash", version);
+        assert_eq!(
+            package_changes,
+            PackageChanges::Some(
+                [
+                    (
+                        WorkspacePackage::root(),
+                        PackageInclusionReason::ConservativeRootLockfileChanged,
+                    ),
+                    (
+                        WorkspacePackage {
+                            name: PackageName::from("a"),
+                            path: AnchoredSystemPathBuf::from_raw("packages/a")?,
+                        },
+                        PackageInclusionReason::LockfileChanged {
+                            added: vec![lodash("4.17.21")],
+                            removed: vec![lodash("4.17.20")],
+                        },
+                    ),
+                ]
+                .into_iter()
+                .collect()
+            )
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_lockfile_is_not_a_global_dep() -> Result<(), anyhow::Error> {
+        let repo_root = tempdir()?;
+        let root_package_json = PackageJson::default();
+
+        let pkg_graph = PackageGraphBuilder::new(
+            AbsoluteSystemPath::from_std_path(repo_root.path())?,
+            root_package_json,
+        )
+        .with_package_discovery(MockDiscovery)
+        .build()
+        .await?;
+
+        let turbo_package_detector =
+            GlobalDepsPackageChangeMapper::new(&pkg_graph, ["*.json"].into_iter())?;
+        let change_mapper = ChangeMapper::new(&pkg_graph, vec![], turbo_package_detector);
+
+        let package_changes = change_mapper.changed_packages(
+            [AnchoredSystemPathBuf::from_raw("package-lock.json")?]
+                .into_iter()
+                .collect(),
+            None,
+        )?;
+
+        assert_eq!(
+            package_changes,
+            PackageChanges::Some(
+                [(
+                    WorkspacePackage::root(),
+                    PackageInclusionReason::ConservativeRootLockfileChanged,
+                )]
+                .into_iter()
+                .collect()
+            )
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_shared_files() -> Result<(), anyhow::Error> {
+        let repo_root =
+            AbsoluteSystemPathBuf::new(if cfg!(windows) { r"C:\repo" } else { "/repo" })?;
+        let pkg_graph = PackageGraphBuilder::new(&repo_root, PackageJson::default())
+            .with_package_discovery(MockDiscovery)
+            .with_package_jsons(Some(HashMap::from_iter(["web", "docs", "api"].map(
+                |name| {
+                    (
+                        repo_root.join_components(&["apps", name, "package.json"]),
+                        PackageJson::from_value(json!({ "name": name })).unwrap(),
+                    )
+                },
+            ))))
+            .build()
+            .await?;
+
+        let web = PackageName::from("web");
+        let docs = PackageName::from("docs");
+        let turbo_package_detector =
+            GlobalDepsPackageChangeMapper::new(&pkg_graph, ["shared/**"].into_iter())?
+                .with_shared_files([
+                    ("shared/config/**", vec![&web, &docs]),
+                    ("shared/config/web.json", vec![&web]),
+                ])?;
+        let change_mapper = ChangeMapper::new(&pkg_graph, vec![], turbo_package_detector);
+
+        let package_changes = change_mapper.changed_packages(
+            [AnchoredSystemPathBuf::from_raw("shared/config/web.json")?]
+                .into_iter()
+                .collect(),
+            None,
+        )?;
+        let reason = PackageInclusionReason::SharedFileChanged {
+            file: AnchoredSystemPathBuf::from_raw("shared/config/web.json")?,
+            pattern: "shared/config/**".to_string(),
+        };
+        assert_eq!(
+            package_changes,
+            PackageChanges::Some(
+                [
+                    (
+                        WorkspacePackage {
+                            name: web.clone(),
+                            path: AnchoredSystemPathBuf::from_raw("apps/web")?,
+                        },
+                        reason.clone(),
+                    ),
+                    (
+                        WorkspacePackage {
+                            name: docs.clone(),
+                            path: AnchoredSystemPathBuf::from_raw("apps/docs")?,
+                        },
+                        reason,
+                    ),
+                ]
+                .into_iter()
+                .collect()
+            )
+        );
+
+        // Files that aren't shared still fall back to the global deps
+        let package_changes = change_mapper.changed_packages(
+            [AnchoredSystemPathBuf::from_raw("shared/utils.ts")?]
+                .into_iter()
+                .collect(),
+            None,
+        )?;
+        assert_eq!(
+            package_changes,
+            PackageChanges::All(AllPackageChangeReason::GlobalDepsChanged {
+                file: AnchoredSystemPathBuf::from_raw("shared/utils.ts")?,
+                matched_pattern: Some("shared/**".to_string()),
+            })
+        );
+
+        Ok(())
+    }
 }
@@ -25,6 +25,7 @@ pub struct PackageGraphBuilder<'a, T> {
     repo_root: &'a AbsoluteSystemPath,
     root_package_json: PackageJson,
     is_single_package: bool,
+    include_peer_dependencies: bool,
     package_jsons: Option<HashMap<AbsoluteSystemPathBuf, PackageJson>>,
     lockfile: Option<Box<dyn Lockfile>>,
     package_discovery: T,
@@ -70,6 +71,7 @@ impl<'a> PackageGraphBuilder<'a, LocalPackageDiscoveryBuilder> {
             repo_root,
             root_package_json,
             is_single_package: false,
+            include_peer_dependencies: false,
             package_jsons: None,
             lockfile: None,
         }
@@ -88,6 +90,13 @@ impl<'a, P> PackageGraphBuilder<'a, P> {
         self
     }
 
+    /// Also add graph edges for workspace packages that are only listed as
+    /// `peerDependencies`.
+    pub fn with_peer_dependencies(mut self, include_peer_dependencies: bool) -> Self {
+        self.include_peer_dependencies = include_peer_dependencies;
+        self
+    }
+
     pub fn with_package_jsons(
         mut self,
         package_jsons: Option<HashMap<AbsoluteSystemPathBuf, PackageJson>>,
@@ -112,6 +121,7 @@ impl<'a, P> PackageGraphBuilder<'a, P> {
             repo_root: self.repo_root,
             root_package_json: self.root_package_json,
             is_single_package: self.is_single_package,
+            include_peer_dependencies: self.include_peer_dependencies,
             package_jsons: self.package_jsons,
             lockfile: self.lockfile,
             package_discovery: discovery,
@@ -145,6 +155,7 @@ where
 struct BuildState<'a, S, T> {
     repo_root: &'a AbsoluteSystemPath,
     single: bool,
+    include_peer_dependencies: bool,
     workspaces: HashMap<PackageName, PackageInfo>,
     workspace_graph: Graph<PackageNode, ()>,
     node_lookup: HashMap<PackageNode, NodeIndex>,
@@ -194,7 +205,7 @@ where
             repo_root,
             root_package_json,
             is_single_package: single,
-
+            include_peer_dependencies,
             package_jsons,
             lockfile,
             package_discovery,
@@ -212,7 +223,7 @@ where
         Ok(BuildState {
             repo_root,
             single,
-
+            include_peer_dependencies,
             workspaces,
             lockfile,
             package_jsons,
@@ -298,6 +309,7 @@ impl<'a, T: PackageDiscovery> BuildState<'a, ResolvedPackageManager, T> {
         let Self {
             repo_root,
             single,
+            include_peer_dependencies,
             workspaces,
             workspace_graph,
             node_lookup,
@@ -308,6 +320,7 @@ impl<'a, T: PackageDiscovery> BuildState<'a, ResolvedPackageManager, T> {
         Ok(BuildState {
             repo_root,
             single,
+            include_peer_dependencies,
             workspaces,
             workspace_graph,
             node_lookup,
@@ -355,6 +368,18 @@ impl<'a, T: PackageDiscovery> BuildState<'a, ResolvedWorkspaces, T> {
             .workspaces
             .iter()
             .map(|(name, entry)| {
+                // Peer dependencies are provided by the consumer, so they only
+                // contribute workspace edges and never external dependencies.
+                let peer_deps = self.include_peer_dependencies.then(|| {
+                    Dependencies::new(
+                        self.repo_root,
+                        &entry.package_json_path,
+                        &self.workspaces,
+                        package_manager,
+                        entry.package_json.peer_dependencies.iter().flatten(),
+                    )
+                    .internal
+                });
                 // TODO avoid clone
                 (
                     name.clone(),
@@ -365,15 +390,20 @@ impl<'a, T: PackageDiscovery> BuildState<'a, ResolvedWorkspaces, T> {
                         package_manager,
                         entry.package_json.all_dependencies(),
                     ),
+                    peer_deps.unwrap_or_default(),
                 )
             })
             .collect::<Vec<_>>();
-        for (name, deps) in split_deps {
+        for (name, deps, peer_deps) in split_deps {
             let entry = self
                 .workspaces
                 .get_mut(&name)
                 .expect("workspace present in ");
-            let Dependencies { internal, external } = deps;
+            let Dependencies {
+                mut internal,
+                external,
+            } = deps;
+            internal.extend(peer_deps);
             let node_idx = self
                 .node_lookup
                 .get(&PackageNode::Workspace(name))
@@ -449,6 +479,7 @@ impl<'a, T: PackageDiscovery> BuildState<'a, ResolvedWorkspaces, T> {
         let Self {
             repo_root,
             single,
+            include_peer_dependencies,
             workspaces,
             workspace_graph,
             node_lookup,
@@ -458,6 +489,7 @@ impl<'a, T: PackageDiscovery> BuildState<'a, ResolvedWorkspaces, T> {
         Ok(BuildState {
             repo_root,
             single,
+            include_peer_dependencies,
             workspaces,
             workspace_graph,
             node_lookup,
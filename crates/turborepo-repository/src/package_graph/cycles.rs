@@ -0,0 +1,177 @@
+use std::{collections::HashSet, fmt};
+
+use itertools::Itertools;
+use turborepo_graph_utils as graph;
+
+use super::{Error, PackageGraph, PackageName, PackageNode};
+
+/// An ordered cycle of packages in the package graph.
+///
+/// `a -> b -> a` is represented as `[a, b]`. Cycles are rotated to start at
+/// their smallest package name so the same cycle always compares equal no
+/// matter which package it was written from.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PackageCycle(Vec<PackageName>);
+
+impl PackageCycle {
+    pub fn new(packages: impl IntoIterator<Item = PackageName>) -> Self {
+        let mut packages: Vec<_> = packages.into_iter().collect();
+        if let Some(start) = packages.iter().position_min() {
+            packages.rotate_left(start);
+        }
+        Self(packages)
+    }
+
+    pub fn packages(&self) -> &[PackageName] {
+        &self.0
+    }
+
+    /// Each edge of the cycle as a (dependent, dependency) pair
+    fn edges(&self) -> impl Iterator<Item = (&PackageName, &PackageName)> {
+        self.0.iter().circular_tuple_windows()
+    }
+}
+
+impl fmt::Display for PackageCycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for package in &self.0 {
+            write!(f, "{package} -> ")?;
+        }
+        match self.0.first() {
+            Some(first) => write!(f, "{first}"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The cycles in the package graph
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PackageCycles {
+    cycles: HashSet<PackageCycle>,
+    /// Groups of packages that have too many cycles between them to list
+    tangled: Vec<Vec<PackageName>>,
+}
+
+impl PackageCycles {
+    /// Returns true if the cycle is in the package graph, or might be because
+    /// its packages are tangled together
+    pub fn contains(&self, cycle: &PackageCycle) -> bool {
+        self.cycles.contains(cycle)
+            || self.tangled.iter().any(|packages| {
+                cycle
+                    .packages()
+                    .iter()
+                    .all(|package| packages.contains(package))
+            })
+    }
+
+    /// The cycles in the package graph in sorted order, without the cycles of
+    /// tangled packages
+    pub fn cycles(&self) -> Vec<&PackageCycle> {
+        self.cycles.iter().sorted().collect()
+    }
+
+    /// Groups of packages that have too many cycles between them to list
+    pub fn tangled(&self) -> &[Vec<PackageName>] {
+        &self.tangled
+    }
+}
+
+impl PackageGraph {
+    /// Returns every cycle in the package graph
+    pub fn cycles(&self) -> PackageCycles {
+        let package_names = |indices: Vec<_>| {
+            indices
+                .into_iter()
+                .filter_map(|idx| match self.graph.node_weight(idx) {
+                    Some(PackageNode::Workspace(package)) => Some(package.clone()),
+                    // The root node has no dependencies so it can't be part of a cycle
+                    Some(PackageNode::Root) | None => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        let graph::Cycles { cycles, components } = graph::cycles(&self.graph);
+        PackageCycles {
+            cycles: cycles
+                .into_iter()
+                .map(|cycle| PackageCycle::new(package_names(cycle)))
+                .collect(),
+            tangled: components
+                .into_iter()
+                .map(|component| package_names(component).into_iter().sorted().collect())
+                .sorted()
+                .collect(),
+        }
+    }
+
+    pub(super) fn validate_cycles(
+        &self,
+        cycles: &PackageCycles,
+        allowed_cycles: &HashSet<PackageCycle>,
+    ) -> Result<(), Error> {
+        let report = cycles
+            .cycles()
+            .into_iter()
+            .filter(|cycle| !allowed_cycles.contains(cycle))
+            .map(|cycle| self.describe_cycle(cycle))
+            .chain(cycles.tangled().iter().map(|packages| {
+                format!(
+                    "\t{}\n\t  these packages have too many cycles between them to list",
+                    packages.iter().format(", ")
+                )
+            }))
+            .join("\n");
+        if !report.is_empty() {
+            return Err(Error::InvalidPackageGraph(
+                graph::Error::CyclicDependencies(report),
+            ));
+        }
+
+        graph::validate_no_self_dependencies(&self.graph).map_err(Error::InvalidPackageGraph)
+    }
+
+    fn describe_cycle(&self, cycle: &PackageCycle) -> String {
+        let mut lines = vec![format!("\t{cycle}")];
+        for (dependent, dependency) in cycle.edges() {
+            let fields = self.dependency_fields(dependent, dependency);
+            if fields.is_empty() {
+                lines.push(format!("\t  \"{dependent}\" depends on \"{dependency}\""));
+            } else {
+                lines.push(format!(
+                    "\t  \"{dependent}\" lists \"{dependency}\" in {}",
+                    fields.join(", ")
+                ));
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cycle_rotation() {
+        let cycle = PackageCycle::new(["c".into(), "a".into(), "b".into()]);
+        assert_eq!(
+            cycle,
+            PackageCycle::new(["a".into(), "b".into(), "c".into()])
+        );
+        assert_ne!(
+            cycle,
+            PackageCycle::new(["a".into(), "c".into(), "b".into()])
+        );
+        assert_eq!(cycle.to_string(), "a -> b -> c -> a");
+    }
+
+    #[test]
+    fn test_tangled_packages_may_contain_cycle() {
+        let cycles = PackageCycles {
+            cycles: HashSet::new(),
+            tangled: vec![vec!["a".into(), "b".into(), "c".into()]],
+        };
+        assert!(cycles.contains(&PackageCycle::new(["b".into(), "a".into()])));
+        assert!(!cycles.contains(&PackageCycle::new(["a".into(), "d".into()])));
+    }
+}
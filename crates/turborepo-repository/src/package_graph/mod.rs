@@ -9,7 +9,6 @@ use tracing::debug;
 use turbopath::{
     AbsoluteSystemPath, AbsoluteSystemPathBuf, AnchoredSystemPath, AnchoredSystemPathBuf,
 };
-use turborepo_graph_utils as graph;
 use turborepo_lockfiles::Lockfile;
 
 use crate::{
@@ -18,9 +17,13 @@ use crate::{
 };
 
 pub mod builder;
+mod cycles;
 mod dep_splitter;
+mod paths;
 
 pub use builder::{Error, PackageGraphBuilder};
+pub use cycles::{PackageCycle, PackageCycles};
+pub use paths::DependencyEdge;
 
 pub const ROOT_PKG_NAME: &str = "//";
 
@@ -149,8 +152,18 @@ impl PackageGraph {
         PackageGraphBuilder::new(repo_root, root_package_json)
     }
 
-    #[tracing::instrument(skip(self))]
     pub fn validate(&self) -> Result<(), Error> {
+        self.validate_with_allowed_cycles(&self.cycles(), &HashSet::new())
+    }
+
+    /// Validates the package graph, permitting any of the given cycles.
+    /// `cycles` are the graph's cycles from `cycles`.
+    #[tracing::instrument(skip(self, cycles))]
+    pub fn validate_with_allowed_cycles(
+        &self,
+        cycles: &PackageCycles,
+        allowed_cycles: &HashSet<PackageCycle>,
+    ) -> Result<(), Error> {
         for (package_name, info) in self.packages.iter() {
             if matches!(package_name, PackageName::Root) {
                 continue;
@@ -164,9 +177,7 @@ impl PackageGraph {
                 Some(_) => continue,
             }
         }
-        graph::validate_graph(&self.graph).map_err(Error::InvalidPackageGraph)?;
-
-        Ok(())
+        self.validate_cycles(cycles, allowed_cycles)
     }
 
     pub fn remove_package_dependencies(&mut self) {
@@ -603,6 +614,8 @@ mod test {
     use std::assert_matches::assert_matches;
 
     use serde_json::json;
+    use test_case::test_case;
+    use turborepo_graph_utils as graph;
 
     use super::*;
     use crate::discovery::PackageDiscovery;
@@ -726,6 +739,59 @@ mod test {
         );
     }
 
+    #[test_case(false ; "peer dependencies excluded")]
+    #[test_case(true ; "peer dependencies included")]
+    #[tokio::test]
+    async fn test_peer_dependency_edges(include_peer_dependencies: bool) {
+        let root =
+            AbsoluteSystemPathBuf::new(if cfg!(windows) { r"C:\repo" } else { "/repo" }).unwrap();
+        let pkg_graph = PackageGraph::builder(
+            &root,
+            PackageJson::from_value(json!({ "name": "root" })).unwrap(),
+        )
+        .with_package_discovery(MockDiscovery)
+        .with_peer_dependencies(include_peer_dependencies)
+        .with_package_jsons(Some({
+            let mut map = HashMap::new();
+            map.insert(
+                root.join_component("package_a"),
+                PackageJson::from_value(json!({
+                    "name": "a",
+                    "peerDependencies": {
+                        "design-system": "*",
+                        "react": "^18.0.0"
+                    }
+                }))
+                .unwrap(),
+            );
+            map.insert(
+                root.join_component("design_system"),
+                PackageJson::from_value(json!({ "name": "design-system" })).unwrap(),
+            );
+            map
+        }))
+        .build()
+        .await
+        .unwrap();
+
+        let a = PackageNode::Workspace("a".into());
+        let design_system = PackageNode::Workspace("design-system".into());
+        let dependencies = pkg_graph.immediate_dependencies(&a).unwrap();
+        assert_eq!(
+            dependencies.contains(&design_system),
+            include_peer_dependencies
+        );
+        // Peer dependencies are never tracked as external dependencies
+        let a_external = pkg_graph
+            .packages
+            .get(&PackageName::from("a"))
+            .unwrap()
+            .unresolved_external_dependencies
+            .as_ref()
+            .unwrap();
+        assert!(a_external.is_empty());
+    }
+
     #[derive(Debug)]
     struct MockLockfile {}
     impl turborepo_lockfiles::Lockfile for MockLockfile {
@@ -911,6 +977,138 @@ mod test {
         );
     }
 
+    #[tokio::test]
+    async fn test_dependency_paths() {
+        let root =
+            AbsoluteSystemPathBuf::new(if cfg!(windows) { r"C:\repo" } else { "/repo" }).unwrap();
+        let pkg_graph = PackageGraph::builder(
+            &root,
+            PackageJson::from_value(json!({ "name": "root" })).unwrap(),
+        )
+        .with_package_discovery(MockDiscovery)
+        .with_package_jsons(Some(HashMap::from_iter(
+            [
+                json!({
+                    "name": "app",
+                    "dependencies": { "ui": "*", "legacy": "*" },
+                    "devDependencies": { "utils": "*" }
+                }),
+                json!({ "name": "ui", "dependencies": { "core": "*" } }),
+                json!({
+                    "name": "utils",
+                    "peerDependencies": { "core": "*" },
+                    "devDependencies": { "core": "*" }
+                }),
+                json!({ "name": "legacy", "dependencies": { "shim": "*" } }),
+                json!({ "name": "shim", "dependencies": { "core": "*" } }),
+                json!({ "name": "core" }),
+            ]
+            .map(|package_json| {
+                (
+                    root.join_component(package_json["name"].as_str().unwrap()),
+                    PackageJson::from_value(package_json).unwrap(),
+                )
+            }),
+        )))
+        .with_lockfile(Some(Box::new(MockLockfile {})))
+        .build()
+        .await
+        .unwrap();
+
+        let edge = |dependent: &str, dependency: &str, fields: &[&'static str]| DependencyEdge {
+            dependent: dependent.into(),
+            dependency: dependency.into(),
+            fields: fields.to_vec(),
+        };
+        let (app, core) = (PackageName::from("app"), PackageName::from("core"));
+        assert_eq!(
+            pkg_graph.dependency_paths(&app, &core, 10),
+            vec![
+                vec![
+                    edge("app", "ui", &["dependencies"]),
+                    edge("ui", "core", &["dependencies"])
+                ],
+                vec![
+                    edge("app", "utils", &["devDependencies"]),
+                    edge("utils", "core", &["devDependencies", "peerDependencies"])
+                ],
+            ]
+        );
+        assert_eq!(pkg_graph.dependency_paths(&app, &core, 1).len(), 1);
+        assert!(pkg_graph.dependency_paths(&core, &app, 10).is_empty());
+        assert!(pkg_graph.dependency_paths(&app, &app, 10).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cycle_report_and_allowed_cycles() {
+        let root =
+            AbsoluteSystemPathBuf::new(if cfg!(windows) { r"C:\repo" } else { "/repo" }).unwrap();
+        let pkg_graph = PackageGraph::builder(
+            &root,
+            PackageJson::from_value(json!({ "name": "root" })).unwrap(),
+        )
+        .with_package_discovery(MockDiscovery)
+        .with_package_jsons(Some({
+            let mut map = HashMap::new();
+            map.insert(
+                root.join_component("package_a"),
+                PackageJson::from_value(json!({
+                    "name": "foo",
+                    "dependencies": { "bar": "*" },
+                    "devDependencies": { "bar": "*" }
+                }))
+                .unwrap(),
+            );
+            map.insert(
+                root.join_component("package_b"),
+                PackageJson::from_value(json!({
+                    "name": "bar",
+                    "dependencies": { "baz": "*" },
+                    "devDependencies": { "foo": "*" }
+                }))
+                .unwrap(),
+            );
+            map.insert(
+                root.join_component("package_c"),
+                PackageJson::from_value(json!({
+                    "name": "baz",
+                    "dependencies": { "bar": "*" }
+                }))
+                .unwrap(),
+            );
+            map
+        }))
+        .with_lockfile(Some(Box::new(MockLockfile {})))
+        .build()
+        .await
+        .unwrap();
+
+        let bar_baz = PackageCycle::new(["baz".into(), "bar".into()]);
+        let bar_foo = PackageCycle::new(["foo".into(), "bar".into()]);
+        let cycles = pkg_graph.cycles();
+        assert_eq!(cycles.cycles(), vec![&bar_baz, &bar_foo]);
+        assert!(cycles.tangled().is_empty());
+
+        let err = pkg_graph.validate().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid package dependency graph: Cyclic dependency detected:\n\tbar -> baz -> \
+             bar\n\t  \"bar\" lists \"baz\" in dependencies\n\t  \"baz\" lists \"bar\" in \
+             dependencies\n\tbar -> foo -> bar\n\t  \"bar\" lists \"foo\" in devDependencies\n\t  \
+             \"foo\" lists \"bar\" in dependencies, devDependencies"
+        );
+
+        let err = pkg_graph
+            .validate_with_allowed_cycles(&cycles, &[bar_baz.clone()].into_iter().collect())
+            .unwrap_err();
+        assert!(!err.to_string().contains("bar -> baz"));
+        assert!(err.to_string().contains("bar -> foo -> bar"));
+
+        assert!(pkg_graph
+            .validate_with_allowed_cycles(&cycles, &[bar_baz, bar_foo].into_iter().collect())
+            .is_ok());
+    }
+
     #[tokio::test]
     async fn test_self_dependency() {
         let root =
@@ -0,0 +1,140 @@
+use std::collections::{HashMap, VecDeque};
+
+use itertools::Itertools;
+use petgraph::{graph::NodeIndex, Direction};
+
+use super::{PackageGraph, PackageName, PackageNode};
+
+/// A direct dependency between two packages in the package graph
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DependencyEdge {
+    pub dependent: PackageName,
+    pub dependency: PackageName,
+    /// The `package.json` fields of the dependent that list the dependency,
+    /// e.g. `dependencies` or `devDependencies`
+    pub fields: Vec<&'static str>,
+}
+
+impl PackageGraph {
+    /// Returns the shortest paths of dependencies from `from` to `to`, or
+    /// nothing if `from` doesn't depend on `to`.
+    ///
+    /// There can be exponentially many shortest paths, so at most `limit` are
+    /// returned. Paths are ordered by the names of the packages along them.
+    pub fn dependency_paths(
+        &self,
+        from: &PackageName,
+        to: &PackageName,
+        limit: usize,
+    ) -> Vec<Vec<DependencyEdge>> {
+        let (Some(&from), Some(&to)) = (
+            self.node_lookup.get(&PackageNode::Workspace(from.clone())),
+            self.node_lookup.get(&PackageNode::Workspace(to.clone())),
+        ) else {
+            return Vec::new();
+        };
+        if from == to {
+            return Vec::new();
+        }
+
+        // The number of edges from each package to `to`, found by walking up
+        // the graph from `to`
+        let mut distances = HashMap::from([(to, 0)]);
+        let mut queue = VecDeque::from([to]);
+        while let Some(node) = queue.pop_front() {
+            let distance = distances[&node];
+            for dependent in self.graph.neighbors_directed(node, Direction::Incoming) {
+                distances.entry(dependent).or_insert_with(|| {
+                    queue.push_back(dependent);
+                    distance + 1
+                });
+            }
+        }
+        if !distances.contains_key(&from) {
+            return Vec::new();
+        }
+
+        let mut paths = Vec::new();
+        self.collect_shortest_paths(&distances, &mut vec![from], &mut paths, limit);
+        paths
+            .into_iter()
+            .map(|path| {
+                path.into_iter()
+                    .tuple_windows()
+                    .map(|(dependent, dependency)| {
+                        let dependent = self.graph[dependent].as_package_name();
+                        let dependency = self.graph[dependency].as_package_name();
+                        DependencyEdge {
+                            dependent: dependent.clone(),
+                            dependency: dependency.clone(),
+                            fields: self.dependency_fields(dependent, dependency),
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn collect_shortest_paths(
+        &self,
+        distances: &HashMap<NodeIndex, usize>,
+        path: &mut Vec<NodeIndex>,
+        paths: &mut Vec<Vec<NodeIndex>>,
+        limit: usize,
+    ) {
+        let node = *path.last().expect("path should start with a package");
+        let distance = distances[&node];
+        if distance == 0 {
+            paths.push(path.clone());
+            return;
+        }
+        // Only follow dependencies that are one step closer to the target
+        let next = self
+            .graph
+            .neighbors_directed(node, Direction::Outgoing)
+            .filter(|dependency| distances.get(dependency) == Some(&(distance - 1)))
+            .sorted_by(|a, b| {
+                self.graph[*a]
+                    .as_package_name()
+                    .cmp(self.graph[*b].as_package_name())
+            })
+            .dedup();
+        for dependency in next {
+            if paths.len() >= limit {
+                return;
+            }
+            path.push(dependency);
+            self.collect_shortest_paths(distances, path, paths, limit);
+            path.pop();
+        }
+    }
+
+    /// The `package.json` fields of `dependent` that list `dependency`
+    pub fn dependency_fields(
+        &self,
+        dependent: &PackageName,
+        dependency: &PackageName,
+    ) -> Vec<&'static str> {
+        let Some(package_json) = self.package_json(dependent) else {
+            return Vec::new();
+        };
+        let dependency_name = self
+            .package_json(dependency)
+            .and_then(|package_json| package_json.name.as_deref())
+            .unwrap_or(dependency.as_str());
+        [
+            ("dependencies", &package_json.dependencies),
+            ("devDependencies", &package_json.dev_dependencies),
+            ("optionalDependencies", &package_json.optional_dependencies),
+            ("peerDependencies", &package_json.peer_dependencies),
+        ]
+        .into_iter()
+        .filter(|(_, dependencies)| {
+            dependencies
+                .as_ref()
+                .is_some_and(|dependencies| dependencies.contains_key(dependency_name))
+        })
+        .map(|(field, _)| field)
+        .collect()
+    }
+}
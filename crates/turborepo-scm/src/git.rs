@@ -100,6 +100,15 @@ impl SCM {
             Self::Manual => Err(Error::GitRequired(file_path.to_owned())),
         }
     }
+
+    /// Gets the SHA of the last commit that changed `file_path`, or `None` if
+    /// the file has never been committed
+    pub fn last_commit_sha(&self, file_path: &AbsoluteSystemPath) -> Result<Option<String>, Error> {
+        match self {
+            Self::Git(git) => git.last_commit_sha(file_path),
+            Self::Manual => Err(Error::GitRequired(file_path.to_owned())),
+        }
+    }
 }
 
 const UNKNOWN_SHA: &str = "0000000000000000000000000000000000000000";
@@ -291,7 +300,7 @@ impl Git {
         let mut args = vec![
             "diff-tree",
             "-r",
-            "--name-only",
+            "--name-status",
             "--no-commit-id",
             &valid_from,
             to_commit,
@@ -302,7 +311,7 @@ impl Git {
         }
 
         let output = self.execute_git_command(&args, pathspec)?;
-        self.add_files_from_stdout(&mut files, turbo_root, output);
+        self.add_files_from_name_status(&mut files, turbo_root, output);
 
         // We only care about non-tracked files if we haven't specified both ends up the
         // comparison
@@ -314,10 +323,12 @@ impl Git {
                 pathspec,
             )?;
             self.add_files_from_stdout(&mut files, turbo_root, ls_files_output);
-            // Include any files that have been staged, but not committed
+            // Include any files that have been staged, but not committed. Unlike
+            // `diff-tree`, `diff` detects renames, so we need the status to
+            // include the path a file was moved from.
             let diff_output =
-                self.execute_git_command(&["diff", "--name-only", "--cached"], pathspec)?;
-            self.add_files_from_stdout(&mut files, turbo_root, diff_output);
+                self.execute_git_command(&["diff", "--name-status", "--cached", "-M"], pathspec)?;
+            self.add_files_from_name_status(&mut files, turbo_root, diff_output);
         }
 
         Ok(files)
@@ -360,6 +371,36 @@ impl Git {
         }
     }
 
+    /// Adds the paths from `--name-status` output. Renames are listed as
+    /// `R<score>\t<source>\t<destination>` and change both paths, while
+    /// copies only change their destination.
+    fn add_files_from_name_status(
+        &self,
+        files: &mut HashSet<AnchoredSystemPathBuf>,
+        turbo_root: &AbsoluteSystemPath,
+        stdout: Vec<u8>,
+    ) {
+        let stdout = String::from_utf8(stdout).unwrap();
+        for line in stdout.lines() {
+            let Some((status, paths)) = line.split_once('\t') else {
+                continue;
+            };
+            let paths = match status.chars().next() {
+                Some('C') => paths.split('\t').skip(1).collect::<Vec<_>>(),
+                _ => paths.split('\t').collect(),
+            };
+            for path in paths {
+                let path = RelativeUnixPath::new(path).unwrap();
+                // Renames are only detected between paths that match the
+                // pathspec, so both sides are always inside of the turbo root
+                let anchored_to_turbo_root_file_path = self
+                    .reanchor_path_from_git_root_to_turbo_root(turbo_root, path)
+                    .unwrap();
+                files.insert(anchored_to_turbo_root_file_path);
+            }
+        }
+    }
+
     fn reanchor_path_from_git_root_to_turbo_root(
         &self,
         turbo_root: &AbsoluteSystemPath,
@@ -381,6 +422,17 @@ impl Git {
 
         self.execute_git_command(&["show", &arg], "")
     }
+
+    fn last_commit_sha(&self, file_path: &AbsoluteSystemPath) -> Result<Option<String>, Error> {
+        let anchored_file_path = self.root.anchor(file_path)?;
+        let output = self.execute_git_command(
+            &["log", "-1", "--format=%H"],
+            anchored_file_path.to_unix().as_str(),
+        )?;
+        let sha = String::from_utf8(output)?.trim().to_owned();
+
+        Ok((!sha.is_empty()).then_some(sha))
+    }
 }
 
 /// Finds the content of a file at a previous commit. Assumes file is in a git
@@ -644,6 +696,57 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn test_staged_renamed_files() -> Result<(), Error> {
+        let (repo_root, repo) = setup_repository(None)?;
+        let turbo_root = repo_root.path().join("web");
+        fs::create_dir_all(turbo_root.join("packages/a"))?;
+        fs::create_dir_all(turbo_root.join("packages/b"))?;
+        let file = turbo_root.join("packages/a/foo.js");
+        fs::write(&file, "let z = 0;")?;
+        commit_file(&repo, Path::new("web/packages/a/foo.js"), None);
+
+        // Stage the move without committing it, which `git diff --cached`
+        // reports as a rename
+        fs::rename(file, turbo_root.join("packages/b/foo.js"))?;
+        let mut index = repo.index().unwrap();
+        index
+            .remove_path(Path::new("web/packages/a/foo.js"))
+            .unwrap();
+        index.add_path(Path::new("web/packages/b/foo.js")).unwrap();
+        index.write().unwrap();
+
+        let files = changed_files(
+            repo_root.path().to_path_buf(),
+            turbo_root,
+            Some("HEAD"),
+            None,
+            true,
+        )?;
+        #[cfg(unix)]
+        {
+            assert_eq!(
+                files,
+                HashSet::from([
+                    "packages/a/foo.js".to_string(),
+                    "packages/b/foo.js".to_string()
+                ])
+            );
+        }
+        #[cfg(windows)]
+        {
+            assert_eq!(
+                files,
+                HashSet::from([
+                    "packages\\a\\foo.js".to_string(),
+                    "packages\\b\\foo.js".to_string()
+                ])
+            );
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_merge_base() -> Result<(), Error> {
         let (repo_root, repo) = setup_repository(None)?;
@@ -956,6 +1059,35 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_last_commit_sha() -> Result<(), Error> {
+        let (repo_root, repo) = setup_repository(None)?;
+        let root = AbsoluteSystemPathBuf::try_from(repo_root.path()).unwrap();
+        let foo = root.join_component("foo.js");
+        foo.create_with_contents("let z = 0;")?;
+        let bar = root.join_component("bar.js");
+        bar.create_with_contents("let y = 0;")?;
+
+        let first_commit_oid = commit_file(&repo, Path::new("foo.js"), None);
+        let second_commit_oid = commit_file(&repo, Path::new("bar.js"), Some(first_commit_oid));
+
+        let scm = SCM::new(&root);
+        assert_eq!(
+            scm.last_commit_sha(&foo)?,
+            Some(first_commit_oid.to_string())
+        );
+        assert_eq!(
+            scm.last_commit_sha(&bar)?,
+            Some(second_commit_oid.to_string())
+        );
+
+        let baz = root.join_component("baz.js");
+        baz.create_with_contents("let x = 0;")?;
+        assert_eq!(scm.last_commit_sha(&baz)?, None);
+
+        Ok(())
+    }
+
     #[test]
     fn test_revparse() -> Result<(), Error> {
         let (repo_root, repo) = setup_repository(None)?;
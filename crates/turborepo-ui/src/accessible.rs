@@ -0,0 +1,290 @@
+//! A UI for screen readers and dumb terminals.
+//!
+//! Instead of drawing to an alternate screen, it prints task progress and
+//! output as plain lines, one after another, each starting with the name of
+//! the task it's about.
+
+use std::{
+    collections::{HashMap, HashSet},
+    io::Write,
+};
+
+use tokio::sync::mpsc;
+
+use crate::{
+    sender::{TaskSender, UISender},
+    tui::event::{CacheResult, OutputLogs, TaskResult},
+    Error,
+};
+
+#[derive(Debug)]
+enum Event {
+    StartTask { task: String },
+    RestartTasks { tasks: Vec<String> },
+    EndTask { task: String, result: TaskResult },
+    Status { task: String, status: String },
+    Output { task: String, output: Vec<u8> },
+    UpdateTasks { tasks: Vec<String> },
+    Stop,
+}
+
+#[derive(Debug, Clone)]
+pub struct AccessibleSender {
+    tx: mpsc::UnboundedSender<Event>,
+}
+
+pub struct AccessibleReceiver {
+    rx: mpsc::UnboundedReceiver<Event>,
+}
+
+impl AccessibleSender {
+    pub fn new() -> (Self, AccessibleReceiver) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        (Self { tx }, AccessibleReceiver { rx })
+    }
+
+    pub fn start_task(&self, task: String, _output_logs: OutputLogs) {
+        self.tx.send(Event::StartTask { task }).ok();
+    }
+
+    pub fn restart_tasks(&self, tasks: Vec<String>) -> Result<(), crate::Error> {
+        self.tx.send(Event::RestartTasks { tasks }).ok();
+        Ok(())
+    }
+
+    pub fn end_task(&self, task: String, result: TaskResult) {
+        self.tx.send(Event::EndTask { task, result }).ok();
+    }
+
+    pub fn status(&self, task: String, status: String, _result: CacheResult) {
+        self.tx.send(Event::Status { task, status }).ok();
+    }
+
+    pub fn set_stdin(&self, _: String, _: Box<dyn Write + Send>) {
+        // There's nowhere to type into a task, so its stdin is left unused
+    }
+
+    pub fn output(&self, task: String, output: Vec<u8>) -> Result<(), crate::Error> {
+        self.tx.send(Event::Output { task, output }).ok();
+        Ok(())
+    }
+
+    pub fn task(&self, task: String) -> TaskSender {
+        TaskSender {
+            name: task,
+            handle: UISender::Accessible(self.clone()),
+            logs: Default::default(),
+        }
+    }
+
+    pub fn stop(&self) {
+        self.tx.send(Event::Stop).ok();
+    }
+
+    pub fn update_tasks(&self, tasks: Vec<String>) -> Result<(), crate::Error> {
+        self.tx.send(Event::UpdateTasks { tasks }).ok();
+        Ok(())
+    }
+}
+
+/// Prints the events from `receiver` to `writer` until the UI is stopped
+pub async fn run_accessible_ui(
+    tasks: Vec<String>,
+    mut receiver: AccessibleReceiver,
+    writer: impl Write,
+) -> Result<(), Error> {
+    let mut progress = Progress::new(tasks, writer);
+    while let Some(event) = receiver.rx.recv().await {
+        if matches!(event, Event::Stop) {
+            break;
+        }
+        progress.handle(event).map_err(Error::CannotWriteLogs)?;
+    }
+    progress.flush().map_err(Error::CannotWriteLogs)
+}
+
+struct Progress<W> {
+    writer: W,
+    tasks: Vec<String>,
+    finished: HashSet<String>,
+    // Output that hasn't reached the end of a line yet
+    partial_lines: HashMap<String, Vec<u8>>,
+}
+
+impl<W: Write> Progress<W> {
+    fn new(tasks: Vec<String>, writer: W) -> Self {
+        Self {
+            writer,
+            tasks,
+            finished: HashSet::new(),
+            partial_lines: HashMap::new(),
+        }
+    }
+
+    fn handle(&mut self, event: Event) -> std::io::Result<()> {
+        match event {
+            Event::StartTask { task } => writeln!(self.writer, "{task}: started")?,
+            Event::RestartTasks { tasks } => {
+                for task in tasks {
+                    self.finished.remove(&task);
+                    writeln!(self.writer, "{task}: restarting")?;
+                }
+            }
+            Event::EndTask { task, result } => {
+                self.flush_task(&task)?;
+                let result = match result {
+                    TaskResult::Success => "succeeded",
+                    TaskResult::Failure => "failed",
+                    TaskResult::CacheHit => "restored from cache",
+                };
+                self.finished.insert(task.clone());
+                writeln!(
+                    self.writer,
+                    "{task}: {result}, {} of {} tasks done",
+                    self.finished.len(),
+                    self.tasks.len()
+                )?;
+            }
+            Event::Status { task, status } => writeln!(self.writer, "{task}: {status}")?,
+            Event::Output { task, output } => {
+                let partial = self.partial_lines.entry(task.clone()).or_default();
+                partial.extend_from_slice(&output);
+                if let Some(end) = partial.iter().rposition(|byte| *byte == b'\n') {
+                    let lines = partial.drain(..=end).collect::<Vec<_>>();
+                    self.write_lines(&task, &lines)?;
+                }
+            }
+            Event::UpdateTasks { tasks } => {
+                self.finished.retain(|task| tasks.contains(task));
+                self.tasks = tasks;
+            }
+            Event::Stop => (),
+        }
+        self.writer.flush()
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        let tasks = self.partial_lines.keys().cloned().collect::<Vec<_>>();
+        for task in tasks {
+            self.flush_task(&task)?;
+        }
+        self.writer.flush()
+    }
+
+    fn flush_task(&mut self, task: &str) -> std::io::Result<()> {
+        if let Some(partial) = self.partial_lines.remove(task) {
+            self.write_lines(task, &partial)?;
+        }
+        Ok(())
+    }
+
+    fn write_lines(&mut self, task: &str, output: &[u8]) -> std::io::Result<()> {
+        let output = String::from_utf8_lossy(output);
+        let output = console::strip_ansi_codes(&output);
+        for line in output.lines() {
+            // A carriage return redraws the line, so only the last redraw is
+            // worth reading out
+            let line = line.rsplit('\r').next().unwrap_or_default();
+            if !line.trim().is_empty() {
+                writeln!(self.writer, "{task}: {line}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn progress() -> Progress<Vec<u8>> {
+        Progress::new(
+            vec!["web#build".to_owned(), "docs#build".to_owned()],
+            Vec::new(),
+        )
+    }
+
+    fn output(progress: &Progress<Vec<u8>>) -> &str {
+        std::str::from_utf8(&progress.writer).unwrap()
+    }
+
+    #[test]
+    fn test_prints_progress_and_output() {
+        let mut progress = progress();
+        for event in [
+            Event::StartTask {
+                task: "web#build".to_owned(),
+            },
+            Event::Status {
+                task: "web#build".to_owned(),
+                status: "cache miss, executing 1234".to_owned(),
+            },
+            Event::Output {
+                task: "web#build".to_owned(),
+                output: b"\x1b[32mcompiled\x1b[0m\r\nbuilding 10%\rbuilding".to_vec(),
+            },
+            Event::Output {
+                task: "web#build".to_owned(),
+                output: b" 100%\n\n".to_vec(),
+            },
+            Event::EndTask {
+                task: "web#build".to_owned(),
+                result: TaskResult::Success,
+            },
+        ] {
+            progress.handle(event).unwrap();
+        }
+        assert_eq!(
+            output(&progress),
+            "web#build: started\nweb#build: cache miss, executing 1234\nweb#build: \
+             compiled\nweb#build: building 100%\nweb#build: succeeded, 1 of 2 tasks done\n"
+        );
+    }
+
+    #[test]
+    fn test_partial_lines_are_printed_when_the_task_ends() {
+        let mut progress = progress();
+        progress
+            .handle(Event::Output {
+                task: "docs#build".to_owned(),
+                output: b"no newline".to_vec(),
+            })
+            .unwrap();
+        assert_eq!(output(&progress), "");
+        progress
+            .handle(Event::EndTask {
+                task: "docs#build".to_owned(),
+                result: TaskResult::Failure,
+            })
+            .unwrap();
+        assert_eq!(
+            output(&progress),
+            "docs#build: no newline\ndocs#build: failed, 1 of 2 tasks done\n"
+        );
+    }
+
+    #[test]
+    fn test_restarted_tasks_are_no_longer_done() {
+        let mut progress = progress();
+        for event in [
+            Event::EndTask {
+                task: "web#build".to_owned(),
+                result: TaskResult::CacheHit,
+            },
+            Event::RestartTasks {
+                tasks: vec!["web#build".to_owned()],
+            },
+            Event::EndTask {
+                task: "web#build".to_owned(),
+                result: TaskResult::Success,
+            },
+        ] {
+            progress.handle(event).unwrap();
+        }
+        assert_eq!(
+            output(&progress),
+            "web#build: restored from cache, 1 of 2 tasks done\nweb#build: restarting\nweb#build: \
+             succeeded, 1 of 2 tasks done\n"
+        );
+    }
+}
@@ -4,6 +4,7 @@
 //! an assigned color.
 #![feature(deadline_api)]
 
+pub mod accessible;
 mod color_selector;
 mod line;
 mod logs;
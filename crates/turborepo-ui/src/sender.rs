@@ -1,16 +1,21 @@
 use std::sync::{Arc, Mutex};
 
+use tokio::sync::mpsc;
+
 use crate::{
+    accessible::AccessibleSender,
     tui,
-    tui::event::{CacheResult, OutputLogs, PaneSize, TaskResult},
+    tui::event::{CacheRestore, CacheResult, OutputLogs, PaneSize, RerunTask, TaskResult},
     wui::sender,
 };
 
-/// Enum to abstract over sending events to either the Tui or the Web UI
+/// Enum to abstract over sending events to the Tui, the Web UI or the
+/// accessible UI
 #[derive(Debug, Clone)]
 pub enum UISender {
     Tui(tui::TuiSender),
     Wui(sender::WebUISender),
+    Accessible(AccessibleSender),
 }
 
 impl UISender {
@@ -18,6 +23,7 @@ impl UISender {
         match self {
             UISender::Tui(sender) => sender.start_task(task, output_logs),
             UISender::Wui(sender) => sender.start_task(task, output_logs),
+            UISender::Accessible(sender) => sender.start_task(task, output_logs),
         }
     }
 
@@ -25,6 +31,7 @@ impl UISender {
         match self {
             UISender::Tui(sender) => sender.restart_tasks(tasks),
             UISender::Wui(sender) => sender.restart_tasks(tasks),
+            UISender::Accessible(sender) => sender.restart_tasks(tasks),
         }
     }
 
@@ -32,6 +39,7 @@ impl UISender {
         match self {
             UISender::Tui(sender) => sender.end_task(task, result),
             UISender::Wui(sender) => sender.end_task(task, result),
+            UISender::Accessible(sender) => sender.end_task(task, result),
         }
     }
 
@@ -39,12 +47,30 @@ impl UISender {
         match self {
             UISender::Tui(sender) => sender.status(task, status, result),
             UISender::Wui(sender) => sender.status(task, status, result),
+            UISender::Accessible(sender) => sender.status(task, status, result),
+        }
+    }
+    pub fn cache_restored(&self, task: String, restore: CacheRestore) {
+        match self {
+            UISender::Tui(sender) => sender.cache_restored(task, restore),
+            // The web UI only shows the cache status
+            UISender::Wui(_) | UISender::Accessible(_) => {}
         }
     }
+
+    /// Requests to re-run a task, if the UI can make them
+    pub fn rerun_requests(&self) -> Option<mpsc::UnboundedReceiver<RerunTask>> {
+        match self {
+            UISender::Tui(sender) => Some(sender.rerun_requests()),
+            UISender::Wui(_) | UISender::Accessible(_) => None,
+        }
+    }
+
     fn set_stdin(&self, task: String, stdin: Box<dyn std::io::Write + Send>) {
         match self {
             UISender::Tui(sender) => sender.set_stdin(task, stdin),
             UISender::Wui(sender) => sender.set_stdin(task, stdin),
+            UISender::Accessible(sender) => sender.set_stdin(task, stdin),
         }
     }
 
@@ -52,6 +78,7 @@ impl UISender {
         match self {
             UISender::Tui(sender) => sender.output(task, output),
             UISender::Wui(sender) => sender.output(task, output),
+            UISender::Accessible(sender) => sender.output(task, output),
         }
     }
 
@@ -60,26 +87,29 @@ impl UISender {
         match self {
             UISender::Tui(sender) => sender.task(task),
             UISender::Wui(sender) => sender.task(task),
+            UISender::Accessible(sender) => sender.task(task),
         }
     }
     pub async fn stop(&self) {
         match self {
             UISender::Tui(sender) => sender.stop().await,
             UISender::Wui(sender) => sender.stop(),
+            UISender::Accessible(sender) => sender.stop(),
         }
     }
     pub fn update_tasks(&self, tasks: Vec<String>) -> Result<(), crate::Error> {
         match self {
             UISender::Tui(sender) => sender.update_tasks(tasks),
             UISender::Wui(sender) => sender.update_tasks(tasks),
+            UISender::Accessible(sender) => sender.update_tasks(tasks),
         }
     }
 
     pub async fn pane_size(&self) -> Option<PaneSize> {
         match self {
             UISender::Tui(sender) => sender.pane_size().await,
-            // Not applicable to the web UI
-            UISender::Wui(_) => None,
+            // Not applicable to the web or accessible UI
+            UISender::Wui(_) | UISender::Accessible(_) => None,
         }
     }
 }
@@ -132,6 +162,11 @@ impl TaskSender {
         let status = console::strip_ansi_codes(status).into_owned();
         self.handle.status(self.name.clone(), status, result);
     }
+
+    /// Records where the task's outputs were restored from on a cache hit
+    pub fn cache_restored(&self, restore: CacheRestore) {
+        self.handle.cache_restored(self.name.clone(), restore);
+    }
 }
 
 impl std::io::Write for TaskSender {
@@ -1,5 +1,5 @@
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, HashSet},
     io::{self, Stdout, Write},
     mem,
     time::Duration,
@@ -8,9 +8,11 @@ use std::{
 use ratatui::{
     backend::{Backend, CrosstermBackend},
     layout::{Constraint, Layout},
-    widgets::{Clear, TableState},
+    style::{Modifier, Style},
+    widgets::{Block, Borders, Clear, TableState},
     Frame, Terminal,
 };
+use serde::{Deserialize, Serialize};
 use tokio::{
     sync::{mpsc, oneshot},
     time::Instant,
@@ -18,21 +20,37 @@ use tokio::{
 use tracing::{debug, trace};
 use turbopath::AbsoluteSystemPathBuf;
 
-use crate::tui::popup::{popup, popup_area};
+use crate::tui::popup::{bind_list, popup, popup_area};
 
 pub const FRAMERATE: Duration = Duration::from_millis(3);
 const RESIZE_DEBOUNCE_DELAY: Duration = Duration::from_millis(10);
+// How often the elapsed time of running tasks is redrawn, they're shown to a
+// tenth of a second
+const DURATION_REFRESH: Duration = Duration::from_millis(100);
+// The layout is kept between runs so it doesn't need to be redone every time
+const TASK_LIST_WIDTH: PreferenceKey<u16> = PreferenceKey::new("task_list_width");
+const SPLIT_LAYOUT: PreferenceKey<Split> = PreferenceKey::new("split_layout");
+const STATUS_FILTER: PreferenceKey<StatusFilter> = PreferenceKey::new("status_filter");
+const GROUP_BY_PACKAGE: PreferenceKey<bool> = PreferenceKey::new("group_by_package");
+const COLLAPSED_PACKAGES: PreferenceKey<Vec<String>> = PreferenceKey::new("collapsed_packages");
 
 use super::{
-    event::{CacheResult, Direction, OutputLogs, PaneSize, TaskResult},
+    diagnostics::DiagnosticPatterns,
+    durations::TaskDurations,
+    event::{CacheRestore, CacheResult, Direction, OutputLogs, PaneSize, RerunTask, TaskResult},
     input,
-    preferences::PreferenceLoader,
+    keybindings::Keybindings,
+    notification::{CompletionNotification, RunOutcome},
+    preferences::{user_preferences_path, PreferenceKey, PreferenceLoader},
+    progress::{ProgressBar, RunProgress},
     search::SearchResults,
+    theme::Theme,
+    vim::{Motion, VimState, VimTarget},
     AppReceiver, Debouncer, Error, Event, InputOptions, SizeInfo, TaskTable, TerminalPane,
 };
 use crate::{
     tui::{
-        task::{Task, TasksByStatus},
+        task::{RowKey, StatusFilter, Task, TasksByStatus},
         term_output::TerminalOutput,
     },
     ColorConfig,
@@ -46,6 +64,17 @@ pub enum LayoutSections {
         previous_selection: String,
         results: SearchResults,
     },
+    LogSearch {
+        query: String,
+    },
+}
+
+/// A task whose logs are shown next to the selected task's
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Split {
+    task: String,
+    // Whether this task is in the left pane, the selected task is in the other
+    on_left: bool,
 }
 
 pub struct App<W> {
@@ -57,14 +86,43 @@ pub struct App<W> {
     selected_task_index: usize,
     is_task_selection_pinned: bool,
     showing_help_popup: bool,
+    split: Option<Split>,
+    // Set while the task list's border is being dragged
+    is_resizing_task_list: bool,
     done: bool,
     preferences: PreferenceLoader,
+    keybindings: Keybindings,
+    theme: Theme,
+    completion_notification: CompletionNotification,
+    // Set once every task of the run has finished, until another task starts
+    has_notified_completion: bool,
+    // The tasks started since the last notification, which the next one
+    // reports on. In watch mode, that's the tasks of the current rebuild.
+    run_tasks: HashSet<String>,
+    // Written to the terminal on the next update
+    pending_notification: Option<String>,
+    // Set when navigating with vim-style keys
+    vim: Option<VimState>,
+    // Where requests to re-run a task go, only set in watch mode
+    rerun_sender: Option<mpsc::UnboundedSender<RerunTask>>,
+    // How long each task took the last time it succeeded without a cache hit
+    task_durations: TaskDurations,
+    // Which lines of task output are counted as warnings and errors
+    diagnostic_patterns: DiagnosticPatterns,
+    // Where copied text goes, the system clipboard outside of tests
+    clipboard: Box<dyn Fn(&str) + Send>,
 }
 
 impl<W> App<W> {
-    pub fn new(rows: u16, cols: u16, tasks: Vec<String>, preferences: PreferenceLoader) -> Self {
+    pub fn new(
+        rows: u16,
+        cols: u16,
+        tasks: Vec<String>,
+        mut preferences: PreferenceLoader,
+    ) -> Self {
         debug!("tasks: {tasks:?}");
-        let size = SizeInfo::new(rows, cols, tasks.iter().map(|s| s.as_str()));
+        let size = SizeInfo::new(rows, cols, tasks.iter().map(|s| s.as_str()))
+            .with_task_list_width(preferences.get(&TASK_LIST_WIDTH));
 
         // Initializes with the planned tasks
         // and will mutate as tasks change
@@ -73,39 +131,77 @@ impl<W> App<W> {
         task_list.sort_unstable();
         task_list.dedup();
 
-        let tasks_by_status = TasksByStatus {
+        let mut tasks_by_status = TasksByStatus {
             planned: task_list,
             finished: Vec::new(),
             running: Vec::new(),
+            status_filter: StatusFilter::All,
+            group_by_package: false,
+            collapsed_packages: HashSet::new(),
         };
 
         let pane_rows = size.pane_rows();
         let pane_cols = size.pane_cols();
+        let max_log_lines = preferences.max_log_lines();
+        let diagnostic_patterns = preferences.diagnostic_patterns();
+
+        preferences.prune_last_task_by_filter(tasks_by_status.task_names_in_displayed_order());
+        // Every task needs an output, so the filter is only applied once
+        // they've been created
+        let task_outputs = tasks_by_status
+            .task_names_in_displayed_order()
+            .map(|task_name| {
+                (
+                    task_name.to_owned(),
+                    TerminalOutput::new(pane_rows, pane_cols, None)
+                        .with_max_lines(max_log_lines)
+                        .with_follow(preferences.follow_logs(task_name))
+                        .with_diagnostics(diagnostic_patterns.clone()),
+                )
+            })
+            .collect();
+        tasks_by_status.status_filter = preferences.get(&STATUS_FILTER).unwrap_or_default();
+        tasks_by_status.group_by_package = preferences.get(&GROUP_BY_PACKAGE).unwrap_or_default();
+        tasks_by_status.collapsed_packages = preferences
+            .get(&COLLAPSED_PACKAGES)
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
 
         // Attempt to load previous selection. If there isn't one, go to index 0.
         let selected_task_index = preferences
             .active_task()
             .and_then(|active_task| tasks_by_status.active_index(active_task))
             .unwrap_or(0);
+        // The split is only restored if both of its tasks are still there
+        let split = preferences.get(&SPLIT_LAYOUT).filter(|split| {
+            tasks_by_status.active_index(&split.task).is_some()
+                && tasks_by_status.task_name(selected_task_index).ok() != Some(split.task.as_str())
+        });
 
         Self {
             size,
             done: false,
             section_focus: LayoutSections::TaskList,
-            tasks: tasks_by_status
-                .task_names_in_displayed_order()
-                .map(|task_name| {
-                    (
-                        task_name.to_owned(),
-                        TerminalOutput::new(pane_rows, pane_cols, None),
-                    )
-                })
-                .collect(),
+            tasks: task_outputs,
             selected_task_index,
             tasks_by_status,
             task_list_scroll: TableState::default().with_selected(selected_task_index),
             showing_help_popup: false,
-            is_task_selection_pinned: preferences.active_task().is_some(),
+            is_resizing_task_list: false,
+            is_task_selection_pinned: preferences.active_task().is_some() || split.is_some(),
+            split,
+            keybindings: preferences.keybindings(),
+            theme: preferences.theme(),
+            completion_notification: preferences.completion_notification(),
+            has_notified_completion: false,
+            run_tasks: HashSet::new(),
+            pending_notification: None,
+            vim: preferences.vim_mode().then(VimState::default),
+            rerun_sender: None,
+            task_durations: TaskDurations::load(preferences.repo_root()),
+            diagnostic_patterns,
+            clipboard: Box::new(super::copy_to_clipboard),
             preferences,
         }
     }
@@ -114,7 +210,9 @@ impl<W> App<W> {
     fn is_focusing_pane(&self) -> bool {
         match self.section_focus {
             LayoutSections::Pane => true,
-            LayoutSections::TaskList | LayoutSections::Search { .. } => false,
+            LayoutSections::TaskList
+            | LayoutSections::Search { .. }
+            | LayoutSections::LogSearch { .. } => false,
         }
     }
 
@@ -123,11 +221,19 @@ impl<W> App<W> {
     }
 
     fn input_options(&self) -> Result<InputOptions, Error> {
-        let has_selection = self.get_full_task()?.has_selection();
+        let task = self.get_full_task()?;
         Ok(InputOptions {
             focus: &self.section_focus,
-            has_selection,
+            has_selection: task.has_selection(),
+            is_selecting_lines: task.is_selecting_lines(),
             is_help_popup_open: self.showing_help_popup,
+            is_log_pane_focused: !self.preferences.is_task_list_visible()
+                || self
+                    .vim
+                    .as_ref()
+                    .is_some_and(|vim| vim.target() == VimTarget::Logs),
+            keybindings: &self.keybindings,
+            vim_mode: self.vim.is_some(),
         })
     }
 
@@ -136,6 +242,12 @@ impl<W> App<W> {
         self.preferences.set_is_task_list_visible(Some(value));
     }
 
+    fn resize_sidebar(&mut self, grow: bool) -> Result<(), Error> {
+        let width = self.size.resize_task_list(grow);
+        self.preferences.set(&TASK_LIST_WIDTH, Some(width))?;
+        Ok(())
+    }
+
     fn update_task_selection_pinned_state(&mut self) -> Result<(), Error> {
         // Preferences assume a pinned state when there is an active task.
         // This `None` creates "un-pinned-ness" on the next TUI startup.
@@ -143,6 +255,109 @@ impl<W> App<W> {
         Ok(())
     }
 
+    /// Splits the logs so the selected task stays in view next to the
+    /// next task that's selected, or goes back to showing one task
+    fn toggle_split(&mut self) -> Result<(), Error> {
+        if self.split.take().is_none() {
+            self.split = Some(Split {
+                task: self.active_task()?.to_owned(),
+                on_left: true,
+            });
+            self.is_task_selection_pinned = true;
+            self.persist_active_task()?;
+        }
+        self.persist_split()
+    }
+
+    fn persist_split(&mut self) -> Result<(), Error> {
+        self.preferences.set(&SPLIT_LAYOUT, self.split.clone())?;
+        Ok(())
+    }
+
+    /// Moves focus to the other split pane by selecting its task
+    fn switch_split_focus(&mut self) -> Result<(), Error> {
+        let Some(split) = &self.split else {
+            return Ok(());
+        };
+        let active_task = self.active_task()?.to_owned();
+        let task = split.task.clone();
+        self.is_task_selection_pinned = true;
+        if self.select_task(&task).is_err() {
+            self.split = None;
+            return Ok(());
+        }
+        if let Some(split) = &mut self.split {
+            split.task = active_task;
+            split.on_left = !split.on_left;
+        }
+        self.persist_active_task().ok();
+        self.persist_split()
+    }
+
+    /// Drops the split if its task is no longer in the task list
+    fn prune_split(&mut self) {
+        if let Some(split) = &self.split {
+            if !self.tasks.contains_key(&split.task) {
+                self.split = None;
+            }
+        }
+    }
+
+    /// Resizes task outputs to the width they're shown at. The split tasks
+    /// are shown in half of the pane, every other task in all of it.
+    fn fit_task_outputs(&mut self) {
+        let rows = self.size.pane_rows();
+        let cols = self.size.pane_cols();
+        let (left_cols, right_cols) = self.size.split_pane_cols();
+        let active_task = self.active_task().ok().map(str::to_owned);
+        for (task, output) in &mut self.tasks {
+            let cols = match &self.split {
+                Some(split) if Some(task) == active_task.as_ref() => {
+                    if split.on_left {
+                        right_cols
+                    } else {
+                        left_cols
+                    }
+                }
+                Some(split) if *task == split.task => {
+                    if split.on_left {
+                        left_cols
+                    } else {
+                        right_cols
+                    }
+                }
+                _ => cols,
+            };
+            output.resize(rows, cols);
+        }
+    }
+
+    /// The width of the task list, or 0 if it's hidden
+    fn visible_task_list_width(&self) -> u16 {
+        if self.preferences.is_task_list_visible() {
+            self.size.task_list_width()
+        } else {
+            0
+        }
+    }
+
+    /// The columns of the pane that has focus, relative to the whole terminal
+    fn focused_pane_columns(&self) -> std::ops::Range<u16> {
+        let table_width = self.visible_task_list_width();
+        match &self.split {
+            None => table_width..u16::MAX,
+            Some(split) => {
+                let (left_cols, right_cols) = self.size.split_pane_cols();
+                if split.on_left {
+                    let start = table_width + left_cols + 1;
+                    start..start + right_cols
+                } else {
+                    table_width..table_width + left_cols
+                }
+            }
+        }
+    }
+
     pub fn get_full_task(&self) -> Result<&TerminalOutput<W>, Error> {
         let active_task = self.active_task()?;
         self.tasks
@@ -174,10 +389,7 @@ impl<W> App<W> {
     pub fn next(&mut self) {
         let num_rows = self.tasks_by_status.count_all();
         if num_rows > 0 {
-            self.selected_task_index = (self.selected_task_index + 1) % num_rows;
-            self.task_list_scroll.select(Some(self.selected_task_index));
-            self.is_task_selection_pinned = true;
-            self.persist_active_task().ok();
+            self.select_index((self.selected_task_index + 1) % num_rows);
         }
     }
 
@@ -185,14 +397,58 @@ impl<W> App<W> {
     pub fn previous(&mut self) {
         let num_rows = self.tasks_by_status.count_all();
         if num_rows > 0 {
-            self.selected_task_index = self
-                .selected_task_index
-                .checked_sub(1)
-                .unwrap_or(num_rows - 1);
-            self.task_list_scroll.select(Some(self.selected_task_index));
-            self.is_task_selection_pinned = true;
-            self.persist_active_task().ok();
+            self.select_index(
+                self.selected_task_index
+                    .checked_sub(1)
+                    .unwrap_or(num_rows - 1),
+            );
+        }
+    }
+
+    /// Selects the row at `index` as if the user moved to it
+    fn select_index(&mut self, index: usize) {
+        self.selected_task_index = index;
+        self.task_list_scroll.select(Some(index));
+        self.is_task_selection_pinned = true;
+        self.persist_active_task().ok();
+    }
+
+    /// Handles a key press in vim mode. Motions move the selected task, or
+    /// scroll its logs after `l`, without wrapping around.
+    fn vim_key(&mut self, c: char) -> Result<(), Error> {
+        let Some(vim) = &mut self.vim else {
+            return Ok(());
+        };
+        let target = vim.target();
+        let Some(motion) = vim.key(c) else {
+            return Ok(());
+        };
+        match (target, motion) {
+            // Switching targets only changes what later motions move
+            (_, Motion::Target(_)) => {}
+            (VimTarget::TaskList, motion) => {
+                let last = self.tasks_by_status.count_all().saturating_sub(1);
+                let index = match motion {
+                    Motion::Down(count) => self.selected_task_index.saturating_add(count),
+                    Motion::Up(count) => self.selected_task_index.saturating_sub(count),
+                    Motion::Line(line) => line.saturating_sub(1),
+                    Motion::First => 0,
+                    Motion::Last | Motion::Target(_) => last,
+                };
+                self.select_index(index.min(last));
+            }
+            (VimTarget::Logs, motion) => {
+                let task = self.get_full_task_mut()?;
+                match motion {
+                    Motion::Down(count) => task.scroll_by(Direction::Down, count),
+                    Motion::Up(count) => task.scroll_by(Direction::Up, count),
+                    Motion::Line(line) => task.scroll_to_line(line),
+                    Motion::First => task.scroll_to_line(1),
+                    Motion::Last | Motion::Target(_) => task.scroll_by(Direction::Down, usize::MAX),
+                }
+            }
         }
+        Ok(())
     }
 
     #[tracing::instrument(skip_all)]
@@ -215,9 +471,18 @@ impl<W> App<W> {
         let mut prev_focus = LayoutSections::TaskList;
         mem::swap(&mut self.section_focus, &mut prev_focus);
         if let LayoutSections::Search {
-            previous_selection, ..
+            previous_selection,
+            results,
         } = prev_focus
         {
+            // Remember the selection for the next time this filter is used
+            if !restore_scroll && results.has_matches() {
+                if let Ok(active_task) = self.active_task() {
+                    let active_task = active_task.to_owned();
+                    self.preferences
+                        .set_last_task_for_filter(results.query().to_owned(), active_task);
+                }
+            }
             if restore_scroll && self.select_task(&previous_selection).is_err() {
                 // If the task that was selected is no longer in the task list we reset
                 // scrolling.
@@ -231,18 +496,22 @@ impl<W> App<W> {
             debug!("scrolling search while not searching");
             return Ok(());
         };
+        // A package's header shows the same task as the row after it
+        let active_task = self.active_task()?;
         let new_selection = match direction {
             Direction::Up => results.first_match(
                 self.tasks_by_status
                     .task_names_in_displayed_order()
                     .rev()
                     // We skip all of the tasks that are at or after the current selection
-                    .skip(self.tasks_by_status.count_all() - self.selected_task_index),
+                    .skip(self.tasks_by_status.count_all() - self.selected_task_index)
+                    .filter(|task| *task != active_task),
             ),
             Direction::Down => results.first_match(
                 self.tasks_by_status
                     .task_names_in_displayed_order()
-                    .skip(self.selected_task_index + 1),
+                    .skip(self.selected_task_index + 1)
+                    .filter(|task| *task != active_task),
             ),
         };
         if let Some(new_selection) = new_selection {
@@ -284,14 +553,19 @@ impl<W> App<W> {
             return;
         };
 
+        // if a task was previously chosen for this filter go back to it
         // if currently selected task is in results stay on it
         // if not we go forward looking for a task in results
+        let last_task = self.preferences.last_task_for_filter(results.query());
         if let Some(result) = results
-            .first_match(
-                self.tasks_by_status
-                    .task_names_in_displayed_order()
-                    .skip(self.selected_task_index),
-            )
+            .first_match(last_task.into_iter())
+            .or_else(|| {
+                results.first_match(
+                    self.tasks_by_status
+                        .task_names_in_displayed_order()
+                        .skip(self.selected_task_index),
+                )
+            })
             .or_else(|| results.first_match(self.tasks_by_status.task_names_in_displayed_order()))
         {
             let new_selection = result.to_owned();
@@ -300,18 +574,75 @@ impl<W> App<W> {
         }
     }
 
+    pub fn enter_log_search(&mut self) {
+        self.section_focus = LayoutSections::LogSearch {
+            query: String::new(),
+        };
+    }
+
+    /// Leaves the log search prompt, searching the selected task's logs for
+    /// the query if `apply` is set
+    pub fn exit_log_search(&mut self, apply: bool) -> Result<(), Error> {
+        let LayoutSections::LogSearch { query } =
+            mem::replace(&mut self.section_focus, LayoutSections::TaskList)
+        else {
+            debug!("exiting log search while not searching logs");
+            return Ok(());
+        };
+        if apply && !query.is_empty() {
+            // Stay on the task whose logs are being searched
+            self.is_task_selection_pinned = true;
+            self.get_full_task_mut()?.search_logs(query);
+        }
+        Ok(())
+    }
+
+    pub fn log_search_enter_char(&mut self, c: char) {
+        let LayoutSections::LogSearch { query } = &mut self.section_focus else {
+            debug!("modifying log search query while not searching logs");
+            return;
+        };
+        query.push(c);
+    }
+
+    pub fn log_search_remove_char(&mut self) -> Result<(), Error> {
+        let LayoutSections::LogSearch { query } = &mut self.section_focus else {
+            debug!("modifying log search query while not searching logs");
+            return Ok(());
+        };
+        if query.pop().is_none() {
+            self.exit_log_search(false)?;
+        }
+        Ok(())
+    }
+
+    pub fn scroll_to_log_match(&mut self, direction: Direction) -> Result<(), Error> {
+        self.get_full_task_mut()?.scroll_to_log_match(direction);
+        Ok(())
+    }
+
+    /// Highlights the next warning or error in the selected task's logs
+    pub fn scroll_to_diagnostic(&mut self, direction: Direction) -> Result<(), Error> {
+        // Stay on the task whose logs are being looked through
+        self.is_task_selection_pinned = true;
+        self.get_full_task_mut()?.scroll_to_diagnostic(direction);
+        Ok(())
+    }
+
+    pub fn clear_log_search(&mut self) -> Result<(), Error> {
+        self.get_full_task_mut()?.clear_log_search();
+        Ok(())
+    }
+
     /// Mark the given task as started.
     /// If planned, pulls it from planned tasks and starts it.
     /// If finished, removes from finished and starts again as new task.
     #[tracing::instrument(skip(self, output_logs))]
     pub fn start_task(&mut self, task: &str, output_logs: OutputLogs) -> Result<(), Error> {
         debug!("starting {task}");
-        // Name of currently highlighted task.
+        // Currently highlighted row.
         // We will use this after the order switches.
-        let highlighted_task = self
-            .tasks_by_status
-            .task_name(self.selected_task_index)?
-            .to_string();
+        let highlighted_row = self.tasks_by_status.row_key(self.selected_task_index)?;
 
         let mut found_task = false;
 
@@ -335,9 +666,13 @@ impl<W> App<W> {
             .get_mut(task)
             .ok_or_else(|| Error::TaskNotFound { name: task.into() })?
             .output_logs = Some(output_logs);
+        if mem::take(&mut self.has_notified_completion) {
+            self.run_tasks.clear();
+        }
+        self.run_tasks.insert(task.to_owned());
 
         // If user hasn't interacted, keep highlighting top-most task in list.
-        self.select_task(&highlighted_task)?;
+        self.reselect_row(&highlighted_row);
 
         Ok(())
     }
@@ -347,12 +682,9 @@ impl<W> App<W> {
     #[tracing::instrument(skip(self, result))]
     pub fn finish_task(&mut self, task: &str, result: TaskResult) -> Result<(), Error> {
         debug!("finishing task {task}");
-        // Name of currently highlighted task.
+        // Currently highlighted row.
         // We will use this after the order switches.
-        let highlighted_task = self
-            .tasks_by_status
-            .task_name(self.selected_task_index)?
-            .to_string();
+        let highlighted_row = self.tasks_by_status.row_key(self.selected_task_index)?;
 
         let running_idx = self
             .tasks_by_status
@@ -362,20 +694,61 @@ impl<W> App<W> {
             .ok_or_else(|| Error::TaskNotFound { name: task.into() })?;
 
         let running = self.tasks_by_status.running.remove(running_idx);
-        self.tasks_by_status
-            .insert_finished_task(running.finish(result));
+        let finished = running.finish(result);
+        // Failures can end early, so they don't say how long a task takes
+        if result == TaskResult::Success {
+            self.task_durations
+                .record(task, finished.end() - finished.start());
+        }
+        self.tasks_by_status.insert_finished_task(finished);
 
         self.tasks
             .get_mut(task)
             .ok_or_else(|| Error::TaskNotFound { name: task.into() })?
             .task_result = Some(result);
 
-        // Find the highlighted task from before the list movement in the new list.
-        self.select_task(&highlighted_task)?;
+        // Find the highlighted row from before the list movement in the new list.
+        self.reselect_row(&highlighted_row);
+
+        if self.tasks_by_status.running.is_empty() && self.tasks_by_status.planned.is_empty() {
+            self.save_task_durations();
+            self.notify_run_complete();
+        }
 
         Ok(())
     }
 
+    /// Saves the durations of the tasks that finished. Failing to save only
+    /// makes later estimates worse, so it's not an error.
+    fn save_task_durations(&mut self) {
+        if let Err(err) = self.task_durations.save() {
+            debug!("unable to save task durations: {err}");
+        }
+    }
+
+    /// Notifies the user that the run, or a rebuild in watch mode, finished,
+    /// if they've asked to be and haven't been already
+    fn notify_run_complete(&mut self) {
+        let finished = self
+            .tasks_by_status
+            .finished
+            .iter()
+            .filter(|task| self.run_tasks.contains(task.name()))
+            .collect::<Vec<_>>();
+        if self.has_notified_completion || finished.is_empty() {
+            return;
+        }
+        let outcome = RunOutcome {
+            tasks: finished.len(),
+            failed: finished
+                .iter()
+                .filter(|task| matches!(task.result(), TaskResult::Failure))
+                .count(),
+        };
+        self.has_notified_completion = true;
+        self.pending_notification = self.completion_notification.escape_sequence(outcome);
+    }
+
     pub fn has_stdin(&self) -> Result<bool, Error> {
         if let Some(term) = self.tasks.get(self.active_task()?) {
             Ok(term.stdin.is_some())
@@ -405,10 +778,18 @@ impl<W> App<W> {
         for task in &tasks {
             self.tasks.entry(task.clone()).or_insert_with(|| {
                 TerminalOutput::new(self.size.pane_rows(), self.size.pane_cols(), None)
+                    .with_max_lines(self.preferences.max_log_lines())
+                    .with_follow(self.preferences.follow_logs(task))
+                    .with_diagnostics(self.diagnostic_patterns.clone())
             });
         }
         // Trim the terminal output to only tasks that exist in new list
         self.tasks.retain(|name, _| tasks.contains(name));
+        for task in self.tasks.values_mut() {
+            task.save_scroll();
+            task.reset_diagnostic_counts();
+        }
+        self.prune_split();
         // Update task list
         let mut task_list = tasks.into_iter().map(Task::new).collect::<Vec<_>>();
         task_list.sort_unstable();
@@ -417,6 +798,9 @@ impl<W> App<W> {
             planned: task_list,
             running: Default::default(),
             finished: Default::default(),
+            status_filter: self.tasks_by_status.status_filter,
+            group_by_package: self.tasks_by_status.group_by_package,
+            collapsed_packages: mem::take(&mut self.tasks_by_status.collapsed_packages),
         };
 
         // Task that was selected may have been removed, go back to top if this happens
@@ -439,9 +823,16 @@ impl<W> App<W> {
         let highlighted_task = self.active_task()?.to_owned();
         // Make sure all tasks have a terminal output
         for task in &tasks {
-            self.tasks.entry(task.clone()).or_insert_with(|| {
+            let output = self.tasks.entry(task.clone()).or_insert_with(|| {
                 TerminalOutput::new(self.size.pane_rows(), self.size.pane_cols(), None)
+                    .with_max_lines(self.preferences.max_log_lines())
+                    .with_follow(self.preferences.follow_logs(task))
+                    .with_diagnostics(self.diagnostic_patterns.clone())
             });
+            output.save_scroll();
+            output.reset_diagnostic_counts();
+            // The new run restores its own cache hit, if it has one
+            output.cache_restore = None;
         }
 
         self.tasks_by_status
@@ -484,19 +875,93 @@ impl<W> App<W> {
             })?;
         task.status = Some(status);
         task.cache_result = Some(result);
+        if result == CacheResult::Miss {
+            task.cache_restore = None;
+        }
+        Ok(())
+    }
+
+    /// Records where a running task's cache hit was restored from
+    #[tracing::instrument(skip(self))]
+    pub fn set_cache_restore(&mut self, task: &str, restore: CacheRestore) -> Result<(), Error> {
+        self.tasks_by_status
+            .running
+            .iter_mut()
+            .find(|running| running.name() == task)
+            .ok_or_else(|| Error::TaskNotFound { name: task.into() })?
+            .set_cache_restore(restore);
+        self.tasks
+            .get_mut(task)
+            .ok_or_else(|| Error::TaskNotFound { name: task.into() })?
+            .cache_restore = Some(restore);
         Ok(())
     }
 
     pub fn handle_mouse(&mut self, mut event: crossterm::event::MouseEvent) -> Result<(), Error> {
-        let table_width = self.size.task_list_width();
-        debug!("original mouse event: {event:?}, table_width: {table_width}");
-        // Only handle mouse event if it happens inside of pane
+        let table_width = self.visible_task_list_width();
+        // Changing the selected task is left to the keyboard while
+        // interacting with a task or searching
+        let can_select = matches!(self.section_focus, LayoutSections::TaskList);
+        match event.kind {
+            crossterm::event::MouseEventKind::Down(crossterm::event::MouseButton::Left) => {
+                // The task list's right border can be dragged to resize it
+                self.is_resizing_task_list = table_width.checked_sub(1) == Some(event.column);
+                if self.is_resizing_task_list {
+                    return Ok(());
+                }
+                if event.column < table_width {
+                    return if can_select {
+                        self.select_task_at_row(event.row)
+                    } else {
+                        Ok(())
+                    };
+                }
+                if can_select
+                    && self.split.is_some()
+                    && !self.focused_pane_columns().contains(&event.column)
+                {
+                    self.switch_split_focus()?;
+                }
+            }
+            crossterm::event::MouseEventKind::Drag(crossterm::event::MouseButton::Left)
+                if self.is_resizing_task_list =>
+            {
+                let width = self.size.set_task_list_width(event.column + 1);
+                self.preferences.set(&TASK_LIST_WIDTH, Some(width))?;
+                return Ok(());
+            }
+            crossterm::event::MouseEventKind::ScrollUp
+            | crossterm::event::MouseEventKind::ScrollDown => {
+                let is_up = matches!(event.kind, crossterm::event::MouseEventKind::ScrollUp);
+                if event.column < table_width {
+                    match (can_select, is_up) {
+                        (false, _) => {}
+                        (true, true) => self.previous(),
+                        (true, false) => self.next(),
+                    }
+                    return Ok(());
+                }
+                return self.scroll_pane_at(
+                    event.column,
+                    if is_up {
+                        Direction::Up
+                    } else {
+                        Direction::Down
+                    },
+                );
+            }
+            _ => {}
+        }
+
+        let pane_columns = self.focused_pane_columns();
+        debug!("original mouse event: {event:?}, pane_columns: {pane_columns:?}");
+        // Only handle mouse event if it happens inside of the focused pane
         // We give a 1 cell buffer to make it easier to select the first column of a row
-        if event.row > 0 && event.column >= table_width {
+        if event.row > 0 && pane_columns.contains(&event.column) {
             // Subtract 1 from the y axis due to the title of the pane
             event.row -= 1;
-            // Subtract the width of the table
-            event.column -= table_width;
+            // Subtract the columns to the left of the pane
+            event.column -= pane_columns.start;
             debug!("translated mouse event: {event:?}");
 
             let task = self.get_full_task_mut()?;
@@ -506,12 +971,121 @@ impl<W> App<W> {
         Ok(())
     }
 
-    pub fn copy_selection(&self) -> Result<(), Error> {
-        let task = self.get_full_task()?;
-        let Some(text) = task.copy_selection() else {
+    /// Writes the selected task's logs to a file in the export directory and
+    /// copies the file's path, so the logs can be shared
+    pub fn export_logs(&mut self) -> Result<(), Error> {
+        let path = self
+            .preferences
+            .log_export_dir()
+            .join_component(&log_file_name(self.active_task()?));
+        let exported = self.get_full_task_mut()?.export_logs(&path);
+        let notice = match exported {
+            Ok(()) => {
+                (self.clipboard)(path.as_str());
+                format!("Logs saved to {path} (path copied)")
+            }
+            Err(err) => {
+                debug!("unable to export logs to {path}: {err}");
+                format!("Unable to save logs: {err}")
+            }
+        };
+        self.get_full_task_mut()?.set_export_notice(notice);
+        Ok(())
+    }
+
+    /// Toggles whether the selected task's logs scroll to show new output, or
+    /// every task's logs if `all_tasks` is set
+    pub fn toggle_follow(&mut self, all_tasks: bool) -> Result<(), Error> {
+        let active_task = self.active_task()?.to_owned();
+        let follow = !self.preferences.follow_logs(&active_task);
+        if all_tasks {
+            self.preferences.set_follow_all_logs(follow);
+            for task in self.tasks.values_mut() {
+                task.set_follow(follow);
+            }
+        } else {
+            self.preferences.set_follow_logs(&active_task, follow);
+            self.get_full_task_mut()?.set_follow(follow);
+        }
+        Ok(())
+    }
+
+    /// Asks watch mode to run the selected task again, and its dependents if
+    /// `include_dependents` is set. Does nothing outside of watch mode.
+    pub fn rerun_task(&mut self, include_dependents: bool) -> Result<(), Error> {
+        let Some(sender) = &self.rerun_sender else {
+            debug!("ignoring request to re-run a task outside of watch mode");
+            return Ok(());
+        };
+        let request = RerunTask {
+            task: self.active_task()?.to_owned(),
+            include_dependents,
+        };
+        if sender.send(request).is_err() {
+            // Watch mode has stopped listening for requests
+            self.rerun_sender = None;
+        }
+        Ok(())
+    }
+
+    /// Selects the task shown at `row` of the task list, if there is one
+    fn select_task_at_row(&mut self, row: u16) -> Result<(), Error> {
+        // The table has a one row header and a two row footer
+        if row == 0 || row + 2 >= self.size.content_rows() {
+            return Ok(());
+        }
+        let index = self.task_list_scroll.offset() + usize::from(row - 1);
+        if index >= self.tasks_by_status.count_all() {
+            return Ok(());
+        }
+        self.selected_task_index = index;
+        self.task_list_scroll.select(Some(index));
+        self.is_task_selection_pinned = true;
+        self.persist_active_task()
+    }
+
+    /// Scrolls the logs in the pane at `column`, which is the other task's
+    /// logs when the logs are split and the column isn't in the focused pane
+    fn scroll_pane_at(&mut self, column: u16, direction: Direction) -> Result<(), Error> {
+        let task = match &self.split {
+            Some(split) if !self.focused_pane_columns().contains(&column) => split.task.clone(),
+            _ => self.active_task()?.to_owned(),
+        };
+        self.tasks
+            .get_mut(&task)
+            .ok_or(Error::TaskNotFound { name: task.clone() })?
+            .scroll(direction)
+    }
+
+    pub fn copy_selection(&mut self) -> Result<(), Error> {
+        let Some(text) = self.get_full_task_mut()?.copy_selection() else {
             return Ok(());
         };
-        super::copy_to_clipboard(&text);
+        (self.clipboard)(&text);
+        let task = self.get_full_task_mut()?;
+        // Like yanking in vim, copying the selected lines stops selecting them
+        if task.is_selecting_lines() {
+            task.clear_selection();
+        }
+        Ok(())
+    }
+
+    /// Starts selecting whole lines of the selected task's logs, which copies
+    /// them without the columns a terminal's own selection would pick up
+    pub fn select_lines(&mut self) -> Result<(), Error> {
+        // Stay on the task whose logs are being selected
+        self.is_task_selection_pinned = true;
+        self.get_full_task_mut()?.start_line_selection();
+        Ok(())
+    }
+
+    pub fn extend_line_selection(&mut self, direction: Direction) -> Result<(), Error> {
+        self.get_full_task_mut()?.extend_line_selection(direction);
+        Ok(())
+    }
+
+    pub fn clear_selection(&mut self) -> Result<(), Error> {
+        self.get_full_task_mut()?.clear_selection();
         Ok(())
     }
 
@@ -520,11 +1094,7 @@ impl<W> App<W> {
             return Ok(());
         }
 
-        let Some(new_index_to_highlight) = self
-            .tasks_by_status
-            .task_names_in_displayed_order()
-            .position(|task| task == task_name)
-        else {
+        let Some(new_index_to_highlight) = self.tasks_by_status.active_index(task_name) else {
             return Err(Error::TaskNotFound {
                 name: task_name.to_owned(),
             });
@@ -535,6 +1105,95 @@ impl<W> App<W> {
         Ok(())
     }
 
+    /// Selects `row` again after the task list changed, like `select_task`.
+    /// If the status filter hides it now, the selection stays in the same row
+    /// of the list, or moves up to the last one.
+    fn reselect_row(&mut self, row: &RowKey) {
+        if self.is_task_selection_pinned {
+            match self.tasks_by_status.row_index(row) {
+                Some(index) => {
+                    self.selected_task_index = index;
+                    self.task_list_scroll.select(Some(index));
+                }
+                None => debug!("{row:?} is hidden by the status filter"),
+            }
+        }
+        let last_index = self.tasks_by_status.count_all().saturating_sub(1);
+        if self.selected_task_index > last_index {
+            self.selected_task_index = last_index;
+            self.task_list_scroll.select(Some(last_index));
+        }
+    }
+
+    /// Shows only the tasks that match `filter`, or every task if it's
+    /// already the filter
+    fn toggle_status_filter(&mut self, filter: StatusFilter) -> Result<(), Error> {
+        let highlighted_row = self.tasks_by_status.row_key(self.selected_task_index)?;
+        let filter = if self.tasks_by_status.status_filter == filter {
+            StatusFilter::All
+        } else {
+            filter
+        };
+        self.tasks_by_status.status_filter = filter;
+        self.preferences.set(
+            &STATUS_FILTER,
+            (filter != StatusFilter::All).then_some(filter),
+        )?;
+        self.reselect_row(&highlighted_row);
+        Ok(())
+    }
+
+    /// Switches between grouping the task list by package and a flat list
+    fn toggle_grouping(&mut self) -> Result<(), Error> {
+        let highlighted_row = self.tasks_by_status.row_key(self.selected_task_index)?;
+        let group_by_package = !self.tasks_by_status.group_by_package;
+        self.tasks_by_status.group_by_package = group_by_package;
+        self.preferences
+            .set(&GROUP_BY_PACKAGE, group_by_package.then_some(true))?;
+        self.reselect_row(&highlighted_row);
+        Ok(())
+    }
+
+    /// Collapses or expands the package of the selected row. Collapsing
+    /// selects the package's header, since its tasks are hidden.
+    fn set_package_collapsed(&mut self, collapsed: bool) -> Result<(), Error> {
+        let Some(package) = self
+            .tasks_by_status
+            .package_at(self.selected_task_index)
+            .map(ToOwned::to_owned)
+        else {
+            return Ok(());
+        };
+        let highlighted_row = self.tasks_by_status.row_key(self.selected_task_index)?;
+        let highlighted_task = self.active_task()?.to_owned();
+        let collapsed_packages = &mut self.tasks_by_status.collapsed_packages;
+        let changed = if collapsed {
+            collapsed_packages.insert(package.clone())
+        } else {
+            collapsed_packages.remove(&package)
+        };
+        if !changed {
+            return Ok(());
+        }
+        let mut persisted = collapsed_packages.iter().cloned().collect::<Vec<_>>();
+        persisted.sort();
+        self.preferences.set(
+            &COLLAPSED_PACKAGES,
+            (!persisted.is_empty()).then_some(persisted),
+        )?;
+
+        self.is_task_selection_pinned = true;
+        if collapsed {
+            self.reselect_row(&RowKey::Package {
+                package,
+                task: highlighted_task,
+            });
+        } else {
+            self.reselect_row(&highlighted_row);
+        }
+        self.persist_active_task()
+    }
+
     /// Resets scroll state
     pub fn reset_scroll(&mut self) {
         self.is_task_selection_pinned = false;
@@ -607,7 +1266,7 @@ pub async fn run_app(
 ) -> Result<(), Error> {
     let mut terminal = startup(color_config)?;
     let size = terminal.size()?;
-    let preferences = PreferenceLoader::new(repo_root)?;
+    let preferences = PreferenceLoader::with_user_preferences(repo_root, user_preferences_path())?;
 
     let mut app: App<Box<dyn io::Write + Send>> =
         App::new(size.height, size.width, tasks, preferences);
@@ -648,6 +1307,11 @@ async fn run_app_inner<B: Backend + std::io::Write>(
         if !matches!(event, Event::Tick) {
             needs_rerender = true;
         }
+        // Running tasks show how long they've taken so far, which changes
+        // without any events
+        if !app.tasks_by_status.running.is_empty() && DURATION_REFRESH <= last_render.elapsed() {
+            needs_rerender = true;
+        }
 
         let mut event = Some(event);
         let mut resize_event = None;
@@ -665,9 +1329,16 @@ async fn run_app_inner<B: Backend + std::io::Write>(
         }
         if let Some(event) = event {
             callback = update(app, event)?;
+            if let Some(notification) = app.pending_notification.take() {
+                terminal.backend_mut().write_all(notification.as_bytes())?;
+                Backend::flush(terminal.backend_mut())?;
+            }
             if app.done {
                 break;
             }
+            if let Err(err) = app.preferences.flush_if_due() {
+                debug!("unable to write preferences: {err}");
+            }
             if FRAMERATE <= last_render.elapsed() && needs_rerender {
                 terminal.draw(|f| view(app, f))?;
                 last_render = Instant::now();
@@ -679,6 +1350,22 @@ async fn run_app_inner<B: Backend + std::io::Write>(
     Ok(callback)
 }
 
+/// The name of the file a task's logs are exported to. Task names like
+/// `@scope/web#build` have characters that can't be in file names.
+fn log_file_name(task: &str) -> String {
+    let name = task
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || "#@-_.".contains(c) {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    format!("{name}.log")
+}
+
 /// Blocking poll for events, will only return None if app handle has been
 /// dropped
 async fn poll<'a>(
@@ -763,6 +1450,7 @@ fn cleanup<B: Backend + io::Write>(
     let tasks_started = app.tasks_by_status.tasks_started();
     app.persist_tasks(tasks_started)?;
     app.preferences.flush_to_disk().ok();
+    app.save_task_durations();
     crossterm::terminal::disable_raw_mode()?;
     terminal.show_cursor()?;
     // We can close the channel now that terminal is back restored to a normal state
@@ -794,6 +1482,11 @@ fn update(
         }
         Event::Stop(callback) => {
             debug!("shutting down due to message");
+            // Tasks that depend on a failed task never start, so the run
+            // can end without every task finishing
+            if app.tasks_by_status.running.is_empty() {
+                app.notify_run_complete();
+            }
             app.done = true;
             return Ok(Some(callback));
         }
@@ -803,6 +1496,9 @@ fn update(
         Event::EndTask { task, result } => {
             app.finish_task(&task, result)?;
         }
+        Event::CacheRestored { task, restore } => {
+            app.set_cache_restore(&task, restore)?;
+        }
         Event::Up => {
             app.previous();
         }
@@ -828,9 +1524,30 @@ fn update(
         Event::TogglePinnedTask => {
             app.update_task_selection_pinned_state()?;
         }
+        Event::ToggleSplit => {
+            app.toggle_split()?;
+        }
+        Event::SwitchSplitFocus => {
+            app.switch_split_focus()?;
+        }
         Event::ToggleSidebar => {
             app.update_sidebar_toggle();
         }
+        Event::ResizeSidebar { grow } => {
+            app.resize_sidebar(grow)?;
+        }
+        Event::VimKey(c) => {
+            app.vim_key(c)?;
+        }
+        Event::ToggleGrouping => {
+            app.toggle_grouping()?;
+        }
+        Event::SetPackageCollapsed { collapsed } => {
+            app.set_package_collapsed(collapsed)?;
+        }
+        Event::ToggleStatusFilter(filter) => {
+            app.toggle_status_filter(filter)?;
+        }
         Event::ToggleHelpPopup => {
             app.showing_help_popup = !app.showing_help_popup;
         }
@@ -846,9 +1563,33 @@ fn update(
         Event::Mouse(m) => {
             app.handle_mouse(m)?;
         }
+        Event::ExportLogs => {
+            app.export_logs()?;
+        }
+        Event::ToggleFollow { all_tasks } => {
+            app.toggle_follow(all_tasks)?;
+        }
+        Event::EnableRerun(sender) => {
+            app.rerun_sender = Some(sender);
+        }
+        Event::RerunTask { include_dependents } => {
+            app.rerun_task(include_dependents)?;
+        }
         Event::CopySelection => {
             app.copy_selection()?;
         }
+        Event::DiagnosticScroll { direction } => {
+            app.scroll_to_diagnostic(direction)?;
+        }
+        Event::SelectLines => {
+            app.select_lines()?;
+        }
+        Event::ExtendLineSelection { direction } => {
+            app.extend_line_selection(direction)?;
+        }
+        Event::ClearSelection => {
+            app.clear_selection()?;
+        }
         Event::RestartTasks { tasks } => {
             app.restart_tasks(tasks)?;
         }
@@ -870,6 +1611,24 @@ fn update(
         Event::SearchBackspace => {
             app.search_remove_char()?;
         }
+        Event::LogSearchEnter => {
+            app.enter_log_search();
+        }
+        Event::LogSearchExit { apply } => {
+            app.exit_log_search(apply)?;
+        }
+        Event::LogSearchEnterChar(c) => {
+            app.log_search_enter_char(c);
+        }
+        Event::LogSearchBackspace => {
+            app.log_search_remove_char()?;
+        }
+        Event::LogSearchScroll { direction } => {
+            app.scroll_to_log_match(direction)?;
+        }
+        Event::LogSearchClear => {
+            app.clear_log_search()?;
+        }
         Event::PaneSizeQuery(callback) => {
             // If caller has already hung up do nothing
             callback
@@ -890,8 +1649,11 @@ fn view<W>(app: &mut App<W>, f: &mut Frame) {
     } else {
         Layout::horizontal([Constraint::Max(0), Constraint::Length(cols)])
     };
-    let [table, pane] = horizontal.areas(f.size());
+    let [content, progress] =
+        Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(f.size());
+    let [table, pane] = horizontal.areas(content);
 
+    app.fit_task_outputs();
     let active_task = app.active_task().unwrap().to_string();
 
     let output_logs = app.tasks.get(&active_task).unwrap();
@@ -900,28 +1662,90 @@ fn view<W>(app: &mut App<W>, f: &mut Frame) {
         &active_task,
         &app.section_focus,
         app.preferences.is_task_list_visible(),
-    );
-
-    let table_to_render = TaskTable::new(&app.tasks_by_status);
+        &app.keybindings,
+    )
+    .with_vim(app.vim.as_ref());
+
+    let table_to_render = TaskTable::new(&app.tasks_by_status)
+        .with_theme(app.theme)
+        .with_diagnostics(
+            app.tasks
+                .iter()
+                .map(|(task, output)| (task.as_str(), output.diagnostic_counts())),
+        );
 
     f.render_stateful_widget(&table_to_render, table, &mut app.task_list_scroll);
-    f.render_widget(&pane_to_render, pane);
-
+    let progress_bar = ProgressBar::new(
+        RunProgress::new(
+            &app.tasks_by_status,
+            app.task_durations.durations(),
+            std::time::Instant::now(),
+        ),
+        app.theme,
+    );
+    f.render_widget(&progress_bar, progress);
+    match app
+        .split
+        .as_ref()
+        .and_then(|split| Some((split, app.tasks.get(&split.task)?)))
+    {
+        Some((split, split_output)) => {
+            let (left_cols, right_cols) = app.size.split_pane_cols();
+            let [left, divider, right] = Layout::horizontal([
+                Constraint::Length(left_cols),
+                Constraint::Length(1),
+                Constraint::Length(right_cols),
+            ])
+            .areas(pane);
+            let split_pane: TerminalPane<W> = TerminalPane::new(
+                split_output,
+                &split.task,
+                &app.section_focus,
+                app.preferences.is_task_list_visible(),
+                &app.keybindings,
+            )
+            .in_split(false);
+            let (split_area, active_area) = if split.on_left {
+                (left, right)
+            } else {
+                (right, left)
+            };
+            f.render_widget(&split_pane, split_area);
+            f.render_widget(
+                Block::new().borders(Borders::LEFT).border_style(
+                    Style::default()
+                        .fg(app.theme.border)
+                        .add_modifier(Modifier::DIM),
+                ),
+                divider,
+            );
+            f.render_widget(&pane_to_render.in_split(true), active_area);
+        }
+        None => f.render_widget(&pane_to_render, pane),
+    }
+
     if app.showing_help_popup {
-        let area = popup_area(*f.buffer_mut().area());
+        let bind_list = bind_list(&app.keybindings, app.vim.is_some());
+        let area = popup_area(*f.buffer_mut().area(), &bind_list);
         let area = area.intersection(*f.buffer_mut().area());
         f.render_widget(Clear, area); // Clears background underneath popup
-        f.render_widget(popup(area), area);
+        f.render_widget(popup(area, &bind_list, app.theme), area);
     }
 }
 
 #[cfg(test)]
 mod test {
+    use std::sync::{Arc, Mutex};
+
+    use crossterm::event::{KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
     use tempfile::tempdir;
     use turbopath::AbsoluteSystemPathBuf;
 
     use super::*;
-    use crate::tui::event::CacheResult;
+    use crate::tui::{
+        event::{CacheResult, CacheSource},
+        Preferences,
+    };
 
     #[test]
     fn test_scroll() -> Result<(), Error> {
@@ -1218,6 +2042,281 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_cache_restore() -> Result<(), Error> {
+        let repo_root_tmp = tempdir()?;
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+
+        let mut app: App<()> = App::new(
+            100,
+            100,
+            vec!["a".to_string(), "b".to_string()],
+            PreferenceLoader::new(&repo_root)?,
+        );
+        let restore = CacheRestore {
+            source: CacheSource::Remote,
+            duration: Duration::from_millis(1200),
+        };
+        assert!(
+            app.set_cache_restore("a", restore).is_err(),
+            "only running tasks are restored"
+        );
+        app.start_task("a", OutputLogs::Full)?;
+        app.set_cache_restore("a", restore)?;
+        app.finish_task("a", TaskResult::CacheHit)?;
+
+        let finished = app.tasks_by_status.finished.first().unwrap();
+        assert_eq!(finished.cache_restore(), Some(restore));
+        assert_eq!(
+            app.tasks.get("a").unwrap().title("a"),
+            " a > (remote cache, restored in 1.2s) "
+        );
+        assert!(app.tasks.get("b").unwrap().cache_restore.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_cache_restore_is_cleared() -> Result<(), Error> {
+        let repo_root_tmp = tempdir()?;
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+
+        let mut app: App<()> = App::new(
+            100,
+            100,
+            vec!["a".to_string()],
+            PreferenceLoader::new(&repo_root)?,
+        );
+        let restore = CacheRestore {
+            source: CacheSource::Local,
+            duration: Duration::from_millis(300),
+        };
+        app.start_task("a", OutputLogs::Full)?;
+        app.set_cache_restore("a", restore)?;
+        app.finish_task("a", TaskResult::CacheHit)?;
+
+        app.restart_tasks(vec!["a".to_string()])?;
+        assert!(app.tasks.get("a").unwrap().cache_restore.is_none());
+        assert_eq!(app.tasks.get("a").unwrap().title("a"), " a > ");
+
+        // A cache miss means nothing was restored
+        app.start_task("a", OutputLogs::Full)?;
+        app.set_cache_restore("a", restore)?;
+        app.set_status("a".to_string(), "executing".to_string(), CacheResult::Miss)?;
+        assert!(app.tasks.get("a").unwrap().cache_restore.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_completion_notification() -> Result<(), Error> {
+        let repo_root_tmp = tempdir()?;
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+        Preferences::import(&repo_root, r#"{ "completion_notification": "desktop" }"#)?;
+
+        let mut app: App<()> = App::new(
+            100,
+            100,
+            vec!["a".to_string(), "b".to_string()],
+            PreferenceLoader::new(&repo_root)?,
+        );
+        app.start_task("a", OutputLogs::Full)?;
+        app.start_task("b", OutputLogs::Full)?;
+        app.finish_task("a", TaskResult::Success)?;
+        assert_eq!(app.pending_notification, None, "b is still running");
+        app.finish_task("b", TaskResult::Failure)?;
+        assert_eq!(
+            app.pending_notification.take().as_deref(),
+            Some("\x1b]9;turbo: 1 of 2 tasks failed\x07")
+        );
+
+        // A rebuild in watch mode notifies again once it finishes, about only
+        // the tasks it ran
+        app.restart_tasks(vec!["b".to_string()])?;
+        app.start_task("b", OutputLogs::Full)?;
+        app.finish_task("b", TaskResult::Success)?;
+        assert_eq!(
+            app.pending_notification.take().as_deref(),
+            Some("\x1b]9;turbo: 1 task passed\x07")
+        );
+        app.notify_run_complete();
+        assert_eq!(app.pending_notification, None, "only notified once");
+        Ok(())
+    }
+
+    #[test]
+    fn test_group_by_package() -> Result<(), Error> {
+        let repo_root_tmp = tempdir()?;
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+        let tasks = vec![
+            "docs#build".to_string(),
+            "web#build".to_string(),
+            "web#lint".to_string(),
+        ];
+
+        let mut app: App<()> =
+            App::new(100, 100, tasks.clone(), PreferenceLoader::new(&repo_root)?);
+        app.toggle_grouping()?;
+        assert_eq!(app.active_task()?, "docs#build", "docs header is selected");
+        app.next();
+        app.next();
+        app.next();
+        assert_eq!(app.active_task()?, "web#build");
+        app.set_package_collapsed(true)?;
+        assert_eq!(app.selected_task_index, 2, "web header is selected");
+        assert_eq!(app.tasks_by_status.count_all(), 3);
+
+        // The header stays selected as its tasks change order
+        app.start_task("web#lint", OutputLogs::Full)?;
+        assert_eq!(app.selected_task_index, 0, "web moved to the top");
+        assert_eq!(app.active_task()?, "web#lint");
+        app.preferences.flush_to_disk()?;
+
+        let mut app: App<()> = App::new(100, 100, tasks, PreferenceLoader::new(&repo_root)?);
+        assert!(app.tasks_by_status.group_by_package);
+        assert_eq!(app.tasks_by_status.count_all(), 3, "web is still collapsed");
+        app.set_package_collapsed(false)?;
+        assert_eq!(app.tasks_by_status.count_all(), 5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_vim_mode() -> Result<(), Error> {
+        let repo_root_tmp = tempdir()?;
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+        Preferences::import(&repo_root, r#"{ "vim_mode": true }"#)?;
+
+        let tasks = (0..10).map(|i| format!("task{i}")).collect();
+        let mut app: App<Vec<u8>> = App::new(20, 100, tasks, PreferenceLoader::new(&repo_root)?);
+        assert!(app.input_options()?.vim_mode);
+        let keys = |app: &mut App<Vec<u8>>, keys: &str| -> Result<(), Error> {
+            keys.chars().try_for_each(|c| app.vim_key(c))
+        };
+        keys(&mut app, "3j")?;
+        assert_eq!(app.active_task()?, "task3");
+        keys(&mut app, "20j")?;
+        assert_eq!(app.active_task()?, "task9", "doesn't wrap around");
+        keys(&mut app, "gg")?;
+        assert_eq!(app.active_task()?, "task0");
+        keys(&mut app, "5G")?;
+        assert_eq!(app.active_task()?, "task4");
+
+        app.process_output("task4", "line\r\n".repeat(50).as_bytes())?;
+        keys(&mut app, "l4k")?;
+        assert_eq!(app.get_full_task()?.parser.screen().scrollback(), 4);
+        assert_eq!(app.active_task()?, "task4", "motions scroll the logs");
+        keys(&mut app, "G")?;
+        assert_eq!(app.get_full_task()?.parser.screen().scrollback(), 0);
+        keys(&mut app, "hk")?;
+        assert_eq!(app.active_task()?, "task3");
+        Ok(())
+    }
+
+    #[test]
+    fn test_follow_is_persisted() -> Result<(), Error> {
+        let repo_root_tmp = tempdir()?;
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+        let tasks = vec!["a".to_string(), "b".to_string()];
+
+        let mut app: App<()> =
+            App::new(100, 100, tasks.clone(), PreferenceLoader::new(&repo_root)?);
+        app.toggle_follow(false)?;
+        assert!(!app.tasks["a"].follows());
+        assert!(app.tasks["b"].follows());
+        app.preferences.flush_to_disk()?;
+
+        let mut app: App<()> =
+            App::new(100, 100, tasks.clone(), PreferenceLoader::new(&repo_root)?);
+        assert!(!app.tasks["a"].follows(), "kept for the next run");
+        app.toggle_follow(true)?;
+        assert!(app.tasks["a"].follows());
+        assert!(app.tasks["b"].follows());
+        app.next();
+        app.toggle_follow(true)?;
+        assert!(!app.tasks["a"].follows());
+        assert!(!app.tasks["b"].follows());
+        Ok(())
+    }
+
+    #[test]
+    fn test_task_durations_are_kept_for_estimates() -> Result<(), Error> {
+        let repo_root_tmp = tempdir()?;
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+        let tasks = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+
+        let mut app: App<()> =
+            App::new(100, 100, tasks.clone(), PreferenceLoader::new(&repo_root)?);
+        app.start_task("a", OutputLogs::Full)?;
+        app.start_task("b", OutputLogs::Full)?;
+        app.start_task("c", OutputLogs::Full)?;
+        app.finish_task("a", TaskResult::Success)?;
+        app.finish_task("b", TaskResult::CacheHit)?;
+        app.finish_task("c", TaskResult::Failure)?;
+        let durations = app.task_durations.durations();
+        assert!(durations.contains_key("a"));
+        assert!(
+            !durations.contains_key("b"),
+            "cache hits don't say how long a task takes"
+        );
+        assert!(!durations.contains_key("c"), "failures can end early");
+        app.preferences.flush_to_disk()?;
+        let preferences = repo_root
+            .join_components(&[".turbo", "preferences", "tui.json"])
+            .read_existing_to_string()?
+            .unwrap_or_default();
+        assert!(!preferences.contains("durations"), "{preferences}");
+
+        // Finishing the run saved the durations
+        let app: App<()> = App::new(100, 100, tasks, PreferenceLoader::new(&repo_root)?);
+        assert_eq!(app.task_durations.durations().len(), 1);
+        let progress = RunProgress::new(
+            &app.tasks_by_status,
+            app.task_durations.durations(),
+            std::time::Instant::now(),
+        );
+        assert_eq!(progress.pending, 3);
+        assert!(progress.eta.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_rerun_task() -> Result<(), Error> {
+        let repo_root_tmp = tempdir()?;
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+
+        let mut app: App<()> = App::new(
+            100,
+            100,
+            vec!["a".to_string(), "b".to_string()],
+            PreferenceLoader::new(&repo_root)?,
+        );
+        // Outside of watch mode there's nowhere to send the request
+        app.rerun_task(false)?;
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        app.rerun_sender = Some(tx);
+        app.next();
+        app.rerun_task(true)?;
+        assert_eq!(
+            rx.try_recv().ok(),
+            Some(RerunTask {
+                task: "b".to_string(),
+                include_dependents: true,
+            })
+        );
+
+        drop(rx);
+        app.rerun_task(false)?;
+        assert!(app.rerun_sender.is_none(), "watch mode stopped listening");
+        Ok(())
+    }
+
     #[test]
     fn test_restarting_task_no_scroll() -> Result<(), Error> {
         let repo_root_tmp = tempdir()?;
@@ -1395,6 +2494,368 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_log_search() -> Result<(), Error> {
+        let repo_root_tmp = tempdir()?;
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+
+        let mut app: App<Vec<u8>> = App::new(
+            100,
+            100,
+            vec!["a".to_string(), "b".to_string()],
+            PreferenceLoader::new(&repo_root)?,
+        );
+        app.process_output("a", b"building\r\nerror: oops\r\n")?;
+        app.enter_log_search();
+        for c in "err".chars() {
+            app.log_search_enter_char(c);
+        }
+        app.log_search_remove_char()?;
+        app.log_search_enter_char('r');
+        assert!(matches!(
+            &app.section_focus,
+            LayoutSections::LogSearch { query } if query == "err"
+        ));
+        app.exit_log_search(true)?;
+        assert!(matches!(app.section_focus, LayoutSections::TaskList));
+        let task = app.get_full_task()?;
+        assert_eq!(
+            task.log_search().map(|search| search.position()),
+            Some((Some(1), 1))
+        );
+        assert_eq!(task.copy_selection().as_deref(), Some("err"));
+
+        app.clear_log_search()?;
+        assert!(app.get_full_task()?.log_search().is_none());
+
+        app.enter_log_search();
+        app.log_search_enter_char('e');
+        app.exit_log_search(false)?;
+        assert!(matches!(app.section_focus, LayoutSections::TaskList));
+        assert!(
+            app.get_full_task()?.log_search().is_none(),
+            "search was cancelled"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_logs() -> Result<(), Error> {
+        let repo_root_tmp = tempdir()?;
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+
+        let mut app: App<()> = App::new(
+            100,
+            100,
+            vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            PreferenceLoader::new(&repo_root)?,
+        );
+        let cols = app.size.pane_cols();
+        let (left_cols, right_cols) = app.size.split_pane_cols();
+        assert_eq!(left_cols + 1 + right_cols, cols);
+
+        app.toggle_split()?;
+        app.next();
+        app.fit_task_outputs();
+        assert_eq!(app.active_task()?, "b");
+        assert_eq!(app.tasks["a"].size().1, left_cols);
+        assert_eq!(app.tasks["b"].size().1, right_cols);
+        assert_eq!(app.tasks["c"].size().1, cols);
+
+        // Switching focus keeps each task on its side
+        app.switch_split_focus()?;
+        app.fit_task_outputs();
+        assert_eq!(app.active_task()?, "a");
+        assert_eq!(app.tasks["a"].size().1, left_cols);
+        assert_eq!(app.tasks["b"].size().1, right_cols);
+        let table_width = app.size.task_list_width();
+        assert_eq!(
+            app.focused_pane_columns(),
+            table_width..table_width + left_cols
+        );
+
+        app.toggle_split()?;
+        app.fit_task_outputs();
+        assert!(app.split.is_none());
+        assert_eq!(app.tasks["b"].size().1, cols);
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_is_dropped_with_its_task() -> Result<(), Error> {
+        let repo_root_tmp = tempdir()?;
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+
+        let mut app: App<()> = App::new(
+            100,
+            100,
+            vec!["a".to_string(), "b".to_string()],
+            PreferenceLoader::new(&repo_root)?,
+        );
+        app.toggle_split()?;
+        app.next();
+        app.update_tasks(vec!["b".to_string(), "c".to_string()])?;
+        assert!(app.split.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_status_filter() -> Result<(), Error> {
+        let repo_root_tmp = tempdir()?;
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+        let tasks = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+
+        let mut app: App<()> =
+            App::new(100, 100, tasks.clone(), PreferenceLoader::new(&repo_root)?);
+        app.start_task("a", OutputLogs::Full)?;
+        app.start_task("b", OutputLogs::Full)?;
+        app.finish_task("a", TaskResult::Failure)?;
+        app.next();
+        assert_eq!(app.active_task()?, "c");
+
+        // The selected task is hidden, so the selection stays in the same row
+        app.toggle_status_filter(StatusFilter::Running)?;
+        assert_eq!(app.tasks_by_status.count_all(), 1);
+        assert_eq!(app.active_task()?, "b");
+        // Finishing the last running task leaves nothing to filter to
+        app.finish_task("b", TaskResult::CacheHit)?;
+        assert_eq!(app.tasks_by_status.count_all(), 3);
+        assert_eq!(app.active_task()?, "b");
+
+        app.toggle_status_filter(StatusFilter::Failed)?;
+        assert_eq!(app.active_task()?, "a");
+        app.preferences.flush_to_disk()?;
+        let restored: App<()> = App::new(100, 100, tasks, PreferenceLoader::new(&repo_root)?);
+        assert_eq!(restored.tasks_by_status.status_filter, StatusFilter::Failed);
+        assert_eq!(restored.tasks.len(), 3, "filtered tasks still have outputs");
+
+        // Toggling the same filter shows every task again
+        app.toggle_status_filter(StatusFilter::Failed)?;
+        assert_eq!(app.tasks_by_status.status_filter, StatusFilter::All);
+        assert_eq!(app.tasks_by_status.count_all(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_export_logs() -> Result<(), Error> {
+        let repo_root_tmp = tempdir()?;
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+
+        let mut app: App<Vec<u8>> = App::new(
+            100,
+            100,
+            vec!["@scope/web#build".to_string()],
+            PreferenceLoader::new(&repo_root)?,
+        );
+        let copied = Arc::new(Mutex::new(Vec::new()));
+        app.clipboard = Box::new({
+            let copied = copied.clone();
+            move |text| copied.lock().unwrap().push(text.to_owned())
+        });
+        app.process_output(
+            "@scope/web#build",
+            b"\x1b[1mbuilding\x1b[0m\r\nerror: oops\r\n",
+        )?;
+        app.export_logs()?;
+
+        let path = repo_root.join_components(&[".turbo", "logs", "@scope_web#build.log"]);
+        assert_eq!(path.read_to_string()?, "building\nerror: oops\n");
+        assert_eq!(*copied.lock().unwrap(), vec![path.to_string()]);
+        let notice = app.get_full_task()?.export_notice().unwrap_or_default();
+        assert!(notice.contains(path.as_str()), "{notice}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_layout_is_restored() -> Result<(), Error> {
+        let repo_root_tmp = tempdir()?;
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+        let tasks = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+
+        let mut app: App<()> =
+            App::new(100, 100, tasks.clone(), PreferenceLoader::new(&repo_root)?);
+        let task_list_width = app.size.task_list_width();
+        app.resize_sidebar(true)?;
+        app.resize_sidebar(true)?;
+        app.resize_sidebar(false)?;
+        assert_eq!(app.size.task_list_width(), task_list_width + 2);
+        app.toggle_split()?;
+        app.next();
+        app.preferences.flush_to_disk()?;
+
+        let app: App<()> = App::new(100, 100, tasks.clone(), PreferenceLoader::new(&repo_root)?);
+        assert_eq!(app.size.task_list_width(), task_list_width + 2);
+        assert_eq!(app.active_task()?, "b");
+        let split = app.split.as_ref().expect("split is restored");
+        assert_eq!(split.task, "a");
+        assert!(split.on_left);
+
+        // A split with a task that's no longer there isn't restored
+        let app: App<()> = App::new(
+            100,
+            100,
+            vec!["b".to_string(), "c".to_string()],
+            PreferenceLoader::new(&repo_root)?,
+        );
+        assert!(app.split.is_none());
+        Ok(())
+    }
+
+    fn mouse(kind: MouseEventKind, column: u16, row: u16) -> MouseEvent {
+        MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    #[test]
+    fn test_click_selects_task() -> Result<(), Error> {
+        let repo_root_tmp = tempdir()?;
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+
+        let mut app: App<()> = App::new(
+            20,
+            100,
+            vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            PreferenceLoader::new(&repo_root)?,
+        );
+        let click = MouseEventKind::Down(MouseButton::Left);
+        app.handle_mouse(mouse(click, 2, 2))?;
+        assert_eq!(app.active_task()?, "b");
+        assert_eq!(app.preferences.active_task(), Some("b"));
+        // The header, the footer and the rows past the last task aren't tasks
+        for row in [0, 5, 19] {
+            app.handle_mouse(mouse(click, 2, row))?;
+            assert_eq!(app.active_task()?, "b");
+        }
+
+        app.enter_search()?;
+        app.handle_mouse(mouse(click, 2, 1))?;
+        assert_eq!(
+            app.active_task()?,
+            "b",
+            "clicks don't select while searching"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_scroll_wheel() -> Result<(), Error> {
+        let repo_root_tmp = tempdir()?;
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+
+        let mut app: App<Vec<u8>> = App::new(
+            20,
+            100,
+            vec!["a".to_string(), "b".to_string()],
+            PreferenceLoader::new(&repo_root)?,
+        );
+        app.process_output("a", "line\r\n".repeat(50).as_bytes())?;
+        let pane_column = app.size.task_list_width() + 5;
+
+        app.handle_mouse(mouse(MouseEventKind::ScrollUp, pane_column, 5))?;
+        app.handle_mouse(mouse(MouseEventKind::ScrollUp, pane_column, 5))?;
+        assert_eq!(app.get_full_task()?.parser.screen().scrollback(), 2);
+        app.handle_mouse(mouse(MouseEventKind::ScrollDown, pane_column, 5))?;
+        assert_eq!(app.get_full_task()?.parser.screen().scrollback(), 1);
+
+        // Scrolling over the task list moves the selection
+        app.handle_mouse(mouse(MouseEventKind::ScrollDown, 2, 5))?;
+        assert_eq!(app.active_task()?, "b");
+        app.handle_mouse(mouse(MouseEventKind::ScrollUp, 2, 5))?;
+        assert_eq!(app.active_task()?, "a");
+        Ok(())
+    }
+
+    #[test]
+    fn test_drag_resizes_task_list() -> Result<(), Error> {
+        let repo_root_tmp = tempdir()?;
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+
+        let mut app: App<()> = App::new(
+            20,
+            100,
+            vec!["a".to_string()],
+            PreferenceLoader::new(&repo_root)?,
+        );
+        let border = app.size.task_list_width() - 1;
+        let drag = MouseEventKind::Drag(MouseButton::Left);
+        app.handle_mouse(mouse(MouseEventKind::Down(MouseButton::Left), border, 5))?;
+        app.handle_mouse(mouse(drag, 35, 5))?;
+        app.handle_mouse(mouse(drag, 40, 6))?;
+        assert_eq!(app.size.task_list_width(), 41);
+        assert_eq!(app.preferences.get(&TASK_LIST_WIDTH), Some(41));
+
+        // Dragging in the logs selects them instead
+        app.handle_mouse(mouse(MouseEventKind::Down(MouseButton::Left), 60, 5))?;
+        app.handle_mouse(mouse(drag, 70, 5))?;
+        assert_eq!(app.size.task_list_width(), 41);
+        Ok(())
+    }
+
+    #[test]
+    fn test_limited_task_list_width_is_kept() -> Result<(), Error> {
+        let repo_root_tmp = tempdir()?;
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+
+        let mut app: App<()> = App::new(
+            20,
+            100,
+            vec!["a".to_string()],
+            PreferenceLoader::new(&repo_root)?,
+        );
+        let border = app.size.task_list_width() - 1;
+        app.handle_mouse(mouse(MouseEventKind::Down(MouseButton::Left), border, 5))?;
+        app.handle_mouse(mouse(MouseEventKind::Drag(MouseButton::Left), 69, 5))?;
+        assert_eq!(app.size.task_list_width(), 70);
+
+        // A smaller terminal only limits the width the task list is shown at
+        app.resize(20, 60);
+        assert_eq!(app.size.task_list_width(), 40);
+        assert_eq!(app.preferences.get(&TASK_LIST_WIDTH), Some(70));
+        app.resize(20, 100);
+        assert_eq!(app.size.task_list_width(), 70);
+        Ok(())
+    }
+
+    #[test]
+    fn test_task_list_width_is_limited() -> Result<(), Error> {
+        let repo_root_tmp = tempdir()?;
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+
+        let mut app: App<()> = App::new(
+            10,
+            40,
+            vec!["a".to_string()],
+            PreferenceLoader::new(&repo_root)?,
+        );
+        for _ in 0..20 {
+            app.resize_sidebar(true)?;
+        }
+        assert_eq!(app.size.pane_cols(), 20);
+        for _ in 0..20 {
+            app.resize_sidebar(false)?;
+        }
+        assert_eq!(app.size.task_list_width(), 10);
+        // Growing starts from the limit instead of the width past it
+        app.resize_sidebar(true)?;
+        assert_eq!(app.size.task_list_width(), 12);
+        Ok(())
+    }
+
     #[test]
     fn test_search_moves_with_typing() -> Result<(), Error> {
         let repo_root_tmp = tempdir()?;
@@ -1423,6 +2884,41 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_search_remembers_selection_per_filter() -> Result<(), Error> {
+        let repo_root_tmp = tempdir()?;
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+
+        let mut app: App<()> = App::new(
+            100,
+            100,
+            vec!["a".to_string(), "ab".to_string(), "abc".to_string()],
+            PreferenceLoader::new(&repo_root)?,
+        );
+        app.enter_search()?;
+        app.search_enter_char('a')?;
+        app.search_enter_char('b')?;
+        assert_eq!(app.active_task()?, "ab");
+        app.search_scroll(Direction::Down)?;
+        assert_eq!(app.active_task()?, "abc");
+        app.exit_search(false);
+
+        app.previous();
+        app.previous();
+        assert_eq!(app.active_task()?, "a");
+        app.enter_search()?;
+        app.search_enter_char('a')?;
+        assert_eq!(app.active_task()?, "a");
+        app.search_enter_char('b')?;
+        assert_eq!(
+            app.active_task()?,
+            "abc",
+            "should restore the task last selected with this filter"
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_search_scroll() -> Result<(), Error> {
         let repo_root_tmp = tempdir()?;
@@ -0,0 +1,106 @@
+use regex::Regex;
+use tracing::warn;
+
+// Used when the user hasn't configured `warning_pattern` or `error_pattern`
+const DEFAULT_WARNING_PATTERN: &str = r"(?i)\bwarn(ing)?\b";
+const DEFAULT_ERROR_PATTERN: &str = r"(?i)\berror\b";
+
+/// Whether a line of task output is a warning or an error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// The patterns that pick out warnings and errors in task output
+#[derive(Debug, Clone)]
+pub struct DiagnosticPatterns {
+    warning: Regex,
+    error: Regex,
+}
+
+impl DiagnosticPatterns {
+    /// Compiles the configured patterns. Patterns that aren't set, or that
+    /// aren't valid regexes, are replaced by the defaults.
+    pub fn new(warning: Option<&str>, error: Option<&str>) -> Self {
+        Self {
+            warning: compile("warning_pattern", warning, DEFAULT_WARNING_PATTERN),
+            error: compile("error_pattern", error, DEFAULT_ERROR_PATTERN),
+        }
+    }
+
+    /// A line matching both patterns is an error
+    pub fn classify(&self, line: &str) -> Option<Severity> {
+        if self.error.is_match(line) {
+            Some(Severity::Error)
+        } else if self.warning.is_match(line) {
+            Some(Severity::Warning)
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for DiagnosticPatterns {
+    fn default() -> Self {
+        Self::new(None, None)
+    }
+}
+
+fn compile(field: &str, pattern: Option<&str>, default: &str) -> Regex {
+    let default = || Regex::new(default).expect("default pattern is valid");
+    let Some(pattern) = pattern else {
+        return default();
+    };
+    Regex::new(pattern).unwrap_or_else(|err| {
+        warn!("ignoring invalid {field} '{pattern}': {err}");
+        default()
+    })
+}
+
+/// How many lines of a task's output were warnings and errors
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiagnosticCounts {
+    pub warnings: usize,
+    pub errors: usize,
+}
+
+impl DiagnosticCounts {
+    pub fn count(&mut self, severity: Severity) {
+        match severity {
+            Severity::Warning => self.warnings += 1,
+            Severity::Error => self.errors += 1,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.warnings == 0 && self.errors == 0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test_case("warning: unused variable", Some(Severity::Warning) ; "warning")]
+    #[test_case("WARN deprecated option", Some(Severity::Warning) ; "uppercase warn")]
+    #[test_case("error[E0308]: mismatched types", Some(Severity::Error) ; "error")]
+    #[test_case("warning: this will be an error", Some(Severity::Error) ; "error wins")]
+    #[test_case("no errors, forewarned", None ; "words containing the patterns")]
+    fn test_default_patterns(line: &str, expected: Option<Severity>) {
+        assert_eq!(DiagnosticPatterns::default().classify(line), expected);
+    }
+
+    #[test]
+    fn test_invalid_pattern_uses_default() {
+        let patterns = DiagnosticPatterns::new(Some("("), Some("^FAIL"));
+        assert_eq!(patterns.classify("warning: x"), Some(Severity::Warning));
+        assert_eq!(
+            patterns.classify("FAIL src/app.test.ts"),
+            Some(Severity::Error)
+        );
+        assert_eq!(patterns.classify("error: not matched"), None);
+    }
+}
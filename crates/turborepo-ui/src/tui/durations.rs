@@ -0,0 +1,165 @@
+use std::{
+    collections::HashMap,
+    io,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
+
+const TASK_DURATIONS_PATH_COMPONENTS: &[&str] = &[".turbo", "task-durations.json"];
+// Tasks that haven't succeeded for this long are dropped, so tasks that were
+// renamed or removed don't stay in the file forever
+const MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct Recorded {
+    duration_ms: u64,
+    // When the duration was recorded, in seconds since the Unix epoch
+    recorded_at: u64,
+}
+
+/// How long each task took the last time it succeeded without a cache hit,
+/// which the time left in a run is estimated from.
+///
+/// They're kept in the repository's `.turbo/task-durations.json` rather than
+/// with the preferences, since they're history rather than something the user
+/// picks.
+pub struct TaskDurations {
+    file_path: AbsoluteSystemPathBuf,
+    durations: HashMap<String, Duration>,
+    // The durations recorded since the file was last saved
+    recorded: HashMap<String, Recorded>,
+}
+
+impl TaskDurations {
+    /// Loads the durations recorded by earlier runs. A file that can't be
+    /// read only means there's nothing to estimate from, so it's not an error.
+    pub fn load(repo_root: &AbsoluteSystemPath) -> Self {
+        let file_path = repo_root.join_components(TASK_DURATIONS_PATH_COMPONENTS);
+        let durations = read(&file_path)
+            .into_iter()
+            .map(|(task, recorded)| (task, Duration::from_millis(recorded.duration_ms)))
+            .collect();
+        Self {
+            file_path,
+            durations,
+            recorded: HashMap::new(),
+        }
+    }
+
+    pub fn durations(&self) -> &HashMap<String, Duration> {
+        &self.durations
+    }
+
+    /// Records how long a successful run of `task` took
+    pub fn record(&mut self, task: &str, duration: Duration) {
+        self.durations.insert(task.to_owned(), duration);
+        self.recorded.insert(
+            task.to_owned(),
+            Recorded {
+                duration_ms: u64::try_from(duration.as_millis()).unwrap_or(u64::MAX),
+                recorded_at: now(),
+            },
+        );
+    }
+
+    /// Writes the durations recorded since the last save over the file's,
+    /// so durations other turbo processes recorded in the meantime are kept,
+    /// and drops tasks that haven't succeeded in a while.
+    pub fn save(&mut self) -> io::Result<()> {
+        if self.recorded.is_empty() {
+            return Ok(());
+        }
+        let mut durations = read(&self.file_path);
+        durations.extend(self.recorded.drain());
+        let cutoff = now().saturating_sub(MAX_AGE.as_secs());
+        durations.retain(|_, recorded| recorded.recorded_at >= cutoff);
+
+        // Write to a temporary file first so readers never see part of it
+        self.file_path.ensure_dir()?;
+        let tmp_path = self
+            .file_path
+            .parent()
+            .expect("durations file has a parent directory")
+            .join_component("task-durations.json.tmp");
+        tmp_path.create_with_contents(serde_json::to_string_pretty(&durations)?)?;
+        tmp_path.rename(&self.file_path)
+    }
+}
+
+fn read(file_path: &AbsoluteSystemPath) -> HashMap<String, Recorded> {
+    let contents = match file_path.read_existing_to_string() {
+        Ok(Some(contents)) => contents,
+        Ok(None) => return HashMap::new(),
+        Err(err) => {
+            debug!("unable to read {file_path}: {err}");
+            return HashMap::new();
+        }
+    };
+    serde_json::from_str(&contents).unwrap_or_else(|err| {
+        debug!("unable to parse {file_path}: {err}");
+        HashMap::new()
+    })
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since_epoch| since_epoch.as_secs())
+}
+
+#[cfg(test)]
+mod test {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn test_durations_are_saved() {
+        let repo_root_tmp = tempdir().unwrap();
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path()).unwrap();
+
+        let mut durations = TaskDurations::load(&repo_root);
+        durations.record("web#build", Duration::from_millis(1500));
+        durations.save().unwrap();
+
+        // Another process's durations are kept
+        let mut other = TaskDurations::load(&repo_root);
+        other.record("docs#build", Duration::from_millis(200));
+        durations.record("web#build", Duration::from_millis(1000));
+        other.save().unwrap();
+        durations.save().unwrap();
+
+        let loaded = TaskDurations::load(&repo_root);
+        assert_eq!(
+            loaded.durations(),
+            &HashMap::from([
+                ("web#build".to_owned(), Duration::from_millis(1000)),
+                ("docs#build".to_owned(), Duration::from_millis(200)),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_old_durations_are_dropped() {
+        let repo_root_tmp = tempdir().unwrap();
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path()).unwrap();
+        let file_path = repo_root.join_components(TASK_DURATIONS_PATH_COMPONENTS);
+        file_path.ensure_dir().unwrap();
+        file_path
+            .create_with_contents(
+                r#"{ "removed#build": { "duration_ms": 100, "recorded_at": 0 } }"#,
+            )
+            .unwrap();
+
+        let mut durations = TaskDurations::load(&repo_root);
+        assert!(durations.durations().contains_key("removed#build"));
+        durations.record("web#build", Duration::from_millis(1500));
+        durations.save().unwrap();
+
+        let loaded = TaskDurations::load(&repo_root);
+        assert_eq!(loaded.durations().keys().collect::<Vec<_>>(), ["web#build"]);
+    }
+}
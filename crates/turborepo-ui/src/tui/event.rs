@@ -1,6 +1,10 @@
+use std::time::Duration;
+
 use async_graphql::Enum;
 use serde::Serialize;
-use tokio::sync::oneshot;
+use tokio::sync::{mpsc, oneshot};
+
+use super::task::StatusFilter;
 
 pub enum Event {
     StartTask {
@@ -20,6 +24,10 @@ pub enum Event {
         status: String,
         result: CacheResult,
     },
+    CacheRestored {
+        task: String,
+        restore: CacheRestore,
+    },
     PaneSizeQuery(oneshot::Sender<PaneSize>),
     Stop(oneshot::Sender<()>),
     // Stop initiated by the TUI itself
@@ -43,17 +51,45 @@ pub enum Event {
     },
     Mouse(crossterm::event::MouseEvent),
     CopySelection,
+    /// Starts selecting whole lines of the logs from the keyboard
+    SelectLines,
+    ExtendLineSelection {
+        direction: Direction,
+    },
+    ClearSelection,
+    ExportLogs,
     RestartTasks {
         tasks: Vec<String>,
     },
+    ToggleFollow {
+        all_tasks: bool,
+    },
+    /// Sent in watch mode, where tasks can be re-run from the TUI. Requests
+    /// to re-run a task are sent on the channel.
+    EnableRerun(mpsc::UnboundedSender<RerunTask>),
+    RerunTask {
+        include_dependents: bool,
+    },
     Resize {
         rows: u16,
         cols: u16,
     },
     ToggleSidebar,
+    ResizeSidebar {
+        grow: bool,
+    },
     ToggleHelpPopup,
     TogglePinnedTask,
+    ToggleSplit,
+    SwitchSplitFocus,
     SearchEnter,
+    ToggleStatusFilter(StatusFilter),
+    ToggleGrouping,
+    /// A key that vim mode handles, see `VimState`
+    VimKey(char),
+    SetPackageCollapsed {
+        collapsed: bool,
+    },
     SearchExit {
         restore_scroll: bool,
     },
@@ -62,6 +98,20 @@ pub enum Event {
     },
     SearchEnterChar(char),
     SearchBackspace,
+    LogSearchEnter,
+    LogSearchExit {
+        apply: bool,
+    },
+    LogSearchEnterChar(char),
+    LogSearchBackspace,
+    LogSearchScroll {
+        direction: Direction,
+    },
+    LogSearchClear,
+    /// Highlights the next warning or error in the logs
+    DiagnosticScroll {
+        direction: Direction,
+    },
 }
 
 pub enum Direction {
@@ -82,6 +132,27 @@ pub enum CacheResult {
     Miss,
 }
 
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Serialize, Enum)]
+pub enum CacheSource {
+    Local,
+    Remote,
+}
+
+/// Where a cache hit's outputs were restored from and how long it took
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CacheRestore {
+    pub source: CacheSource,
+    pub duration: Duration,
+}
+
+/// A request from the TUI to run a task again without any files changing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RerunTask {
+    pub task: String,
+    /// Whether the tasks that depend on the task also run again
+    pub include_dependents: bool,
+}
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Serialize, Enum)]
 pub enum OutputLogs {
     // Entire task output is persisted after run
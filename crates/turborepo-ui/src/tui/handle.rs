@@ -2,7 +2,7 @@ use tokio::sync::{mpsc, oneshot};
 
 use super::{
     app::FRAMERATE,
-    event::{CacheResult, OutputLogs, PaneSize},
+    event::{CacheRestore, CacheResult, OutputLogs, PaneSize, RerunTask},
     Error, Event, TaskResult,
 };
 use crate::sender::{TaskSender, UISender};
@@ -67,6 +67,12 @@ impl TuiSender {
             .ok();
     }
 
+    pub fn cache_restored(&self, task: String, restore: CacheRestore) {
+        self.primary
+            .send(Event::CacheRestored { task, restore })
+            .ok();
+    }
+
     pub fn set_stdin(&self, task: String, stdin: Box<dyn std::io::Write + Send>) {
         self.primary.send(Event::SetStdin { task, stdin }).ok();
     }
@@ -113,6 +119,15 @@ impl TuiSender {
             .map_err(|err| Error::Mpsc(err.to_string()))?)
     }
 
+    /// Lets tasks be re-run from the TUI, returning the requests to re-run a
+    /// task. Used in watch mode, where there's a run to re-run them in.
+    pub fn rerun_requests(&self) -> mpsc::UnboundedReceiver<RerunTask> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        // If the TUI has stopped the receiver won't get any requests
+        self.primary.send(Event::EnableRerun(tx)).ok();
+        rx
+    }
+
     /// Fetches the size of the terminal pane
     pub async fn pane_size(&self) -> Option<PaneSize> {
         let (callback_tx, callback_rx) = oneshot::channel();
@@ -6,13 +6,21 @@ use tracing::debug;
 use super::{
     app::LayoutSections,
     event::{Direction, Event},
+    keybindings::{Action, Keybindings},
+    vim::VimState,
 };
 
 #[derive(Debug, Clone, Copy)]
 pub struct InputOptions<'a> {
     pub focus: &'a LayoutSections,
     pub has_selection: bool,
+    pub is_selecting_lines: bool,
     pub is_help_popup_open: bool,
+    // Whether the logs have focus rather than the task list, i.e. the task
+    // list is hidden or vim motions move through the logs
+    pub is_log_pane_focused: bool,
+    pub keybindings: &'a Keybindings,
+    pub vim_mode: bool,
 }
 
 pub fn start_crossterm_stream(tx: mpsc::Sender<crossterm::event::Event>) -> Option<JoinHandle<()>> {
@@ -37,9 +45,9 @@ impl InputOptions<'_> {
         match event {
             crossterm::event::Event::Key(k) => translate_key_event(self, k),
             crossterm::event::Event::Mouse(m) => match m.kind {
-                crossterm::event::MouseEventKind::ScrollDown => Some(Event::ScrollDown),
-                crossterm::event::MouseEventKind::ScrollUp => Some(Event::ScrollUp),
-                crossterm::event::MouseEventKind::Down(crossterm::event::MouseButton::Left)
+                crossterm::event::MouseEventKind::ScrollDown
+                | crossterm::event::MouseEventKind::ScrollUp
+                | crossterm::event::MouseEventKind::Down(crossterm::event::MouseButton::Left)
                 | crossterm::event::MouseEventKind::Drag(crossterm::event::MouseButton::Left) => {
                     Some(Event::Mouse(m))
                 }
@@ -60,6 +68,10 @@ fn translate_key_event(options: InputOptions, key_event: KeyEvent) -> Option<Eve
     if key_event.kind == KeyEventKind::Release {
         return None;
     }
+    let selection_direction = options
+        .is_selecting_lines
+        .then(|| line_selection_direction(options, key_event.code))
+        .flatten();
     match key_event.code {
         KeyCode::Char('c') if key_event.modifiers == crossterm::event::KeyModifiers::CONTROL => {
             ctrl_c();
@@ -77,11 +89,27 @@ fn translate_key_event(options: InputOptions, key_event: KeyEvent) -> Option<Eve
         _ if matches!(options.focus, LayoutSections::Pane) => Some(Event::Input {
             bytes: encode_key(key_event),
         }),
-        // If we're on the list and user presses `/` enter search mode
-        KeyCode::Char('/') if matches!(options.focus, LayoutSections::TaskList) => {
-            Some(Event::SearchEnter)
-        }
         KeyCode::Esc if options.is_help_popup_open => Some(Event::ToggleHelpPopup),
+        KeyCode::Esc if matches!(options.focus, LayoutSections::LogSearch { .. }) => {
+            Some(Event::LogSearchExit { apply: false })
+        }
+        KeyCode::Enter if matches!(options.focus, LayoutSections::LogSearch { .. }) => {
+            Some(Event::LogSearchExit { apply: true })
+        }
+        KeyCode::Backspace if matches!(options.focus, LayoutSections::LogSearch { .. }) => {
+            Some(Event::LogSearchBackspace)
+        }
+        KeyCode::Char(c) if matches!(options.focus, LayoutSections::LogSearch { .. }) => {
+            Some(Event::LogSearchEnterChar(c))
+        }
+        // Selected lines are extended with the keys that move between tasks
+        _ if selection_direction.is_some() => {
+            selection_direction.map(|direction| Event::ExtendLineSelection { direction })
+        }
+        KeyCode::Esc if options.is_selecting_lines => Some(Event::ClearSelection),
+        KeyCode::Esc if matches!(options.focus, LayoutSections::TaskList) => {
+            Some(Event::LogSearchClear)
+        }
         KeyCode::Esc if matches!(options.focus, LayoutSections::Search { .. }) => {
             Some(Event::SearchExit {
                 restore_scroll: true,
@@ -108,15 +136,47 @@ fn translate_key_event(options: InputOptions, key_event: KeyEvent) -> Option<Eve
         KeyCode::Char(c) if matches!(options.focus, LayoutSections::Search { .. }) => {
             Some(Event::SearchEnterChar(c))
         }
+        KeyCode::Char(c)
+            if options.vim_mode
+                && matches!(options.focus, LayoutSections::TaskList)
+                && !key_event
+                    .modifiers
+                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+                && VimState::handles(c) =>
+        {
+            Some(Event::VimKey(c))
+        }
         // Fall through if we aren't in interactive mode
-        KeyCode::Char('h') => Some(Event::ToggleSidebar),
-        KeyCode::Char('u') => Some(Event::ScrollUp),
-        KeyCode::Char('d') => Some(Event::ScrollDown),
-        KeyCode::Char('m') => Some(Event::ToggleHelpPopup),
-        KeyCode::Char('p') => Some(Event::TogglePinnedTask),
-        KeyCode::Up | KeyCode::Char('k') => Some(Event::Up),
-        KeyCode::Down | KeyCode::Char('j') => Some(Event::Down),
-        KeyCode::Enter | KeyCode::Char('i') => Some(Event::EnterInteractive),
+        code => match options.keybindings.action(code)? {
+            Action::Quit => {
+                ctrl_c();
+                Some(Event::InternalStop)
+            }
+            // Searches can only be entered from the task list
+            Action::Search | Action::SearchLogs
+                if !matches!(options.focus, LayoutSections::TaskList) =>
+            {
+                None
+            }
+            // Searching from the logs searches them rather than the tasks
+            Action::Search if options.is_log_pane_focused => Some(Event::LogSearchEnter),
+            action => Some(action.event()),
+        },
+    }
+}
+
+/// The direction a key extends the selected lines in. They're extended with
+/// the keys that move between tasks, which in vim mode are `j` and `k` rather
+/// than the keybindings.
+fn line_selection_direction(options: InputOptions, code: KeyCode) -> Option<Direction> {
+    match code {
+        KeyCode::Char('j') if options.vim_mode => return Some(Direction::Down),
+        KeyCode::Char('k') if options.vim_mode => return Some(Direction::Up),
+        _ => {}
+    }
+    match options.keybindings.action(code)? {
+        Action::NextTask => Some(Direction::Down),
+        Action::PreviousTask => Some(Direction::Up),
         _ => None,
     }
 }
@@ -445,13 +505,88 @@ mod test {
         InputOptions {
             focus: search(),
             has_selection: false,
+            is_selecting_lines: false,
+            is_help_popup_open: false,
+            is_log_pane_focused: false,
+            keybindings: keybindings(),
+            vim_mode: false,
+        }
+    }
+
+    fn in_task_list() -> InputOptions<'static> {
+        static TASK_LIST: LayoutSections = LayoutSections::TaskList;
+        InputOptions {
+            focus: &TASK_LIST,
+            has_selection: false,
+            is_selecting_lines: false,
             is_help_popup_open: false,
+            is_log_pane_focused: false,
+            keybindings: keybindings(),
+            vim_mode: false,
         }
     }
 
+    fn in_log_search() -> InputOptions<'static> {
+        static LOG_SEARCH: OnceLock<LayoutSections> = OnceLock::new();
+        InputOptions {
+            focus: LOG_SEARCH.get_or_init(|| LayoutSections::LogSearch {
+                query: String::new(),
+            }),
+            has_selection: false,
+            is_selecting_lines: false,
+            is_help_popup_open: false,
+            is_log_pane_focused: false,
+            keybindings: keybindings(),
+            vim_mode: false,
+        }
+    }
+
+    fn in_vim_task_list() -> InputOptions<'static> {
+        InputOptions {
+            vim_mode: true,
+            ..in_task_list()
+        }
+    }
+
+    fn in_log_pane() -> InputOptions<'static> {
+        InputOptions {
+            is_log_pane_focused: true,
+            ..in_task_list()
+        }
+    }
+
+    fn selecting_lines() -> InputOptions<'static> {
+        InputOptions {
+            has_selection: true,
+            is_selecting_lines: true,
+            vim_mode: true,
+            ..in_task_list()
+        }
+    }
+
+    fn keybindings() -> &'static Keybindings {
+        static KEYBINDINGS: OnceLock<Keybindings> = OnceLock::new();
+        KEYBINDINGS.get_or_init(Keybindings::default)
+    }
+
     const H: KeyEvent = KeyEvent::new(KeyCode::Char('h'), KeyModifiers::empty());
 
     #[test_case(in_find(), H, Some(Event::SearchEnterChar('h')) ; "h while searching")]
+    #[test_case(in_task_list(), H, Some(Event::ToggleSidebar) ; "h in task list")]
+    #[test_case(in_vim_task_list(), H, Some(Event::VimKey('h')) ; "h in vim mode")]
+    #[test_case(in_vim_task_list(), KeyEvent::new(KeyCode::Char('/'), KeyModifiers::empty()), Some(Event::SearchEnter) ; "search in vim mode")]
+    #[test_case(in_task_list(), KeyEvent::new(KeyCode::Char('x'), KeyModifiers::empty()), None ; "unbound key in task list")]
+    #[test_case(in_task_list(), KeyEvent::new(KeyCode::Char('/'), KeyModifiers::empty()), Some(Event::SearchEnter) ; "search in task list")]
+    #[test_case(in_task_list(), KeyEvent::new(KeyCode::Char('f'), KeyModifiers::empty()), Some(Event::LogSearchEnter) ; "log search in task list")]
+    #[test_case(in_log_pane(), KeyEvent::new(KeyCode::Char('/'), KeyModifiers::empty()), Some(Event::LogSearchEnter) ; "search in log pane")]
+    #[test_case(in_task_list(), KeyEvent::new(KeyCode::Char('n'), KeyModifiers::empty()), Some(Event::LogSearchScroll { direction: Direction::Down }) ; "next log match in task list")]
+    #[test_case(in_log_search(), KeyEvent::new(KeyCode::Char('n'), KeyModifiers::empty()), Some(Event::LogSearchEnterChar('n')) ; "n while searching logs")]
+    #[test_case(in_log_search(), KeyEvent::new(KeyCode::Enter, KeyModifiers::empty()), Some(Event::LogSearchExit { apply: true }) ; "enter while searching logs")]
+    #[test_case(in_task_list(), KeyEvent::new(KeyCode::Char('v'), KeyModifiers::empty()), Some(Event::SelectLines) ; "select lines in task list")]
+    #[test_case(selecting_lines(), KeyEvent::new(KeyCode::Char('k'), KeyModifiers::empty()), Some(Event::ExtendLineSelection { direction: Direction::Up }) ; "k while selecting lines")]
+    #[test_case(selecting_lines(), KeyEvent::new(KeyCode::Char('y'), KeyModifiers::empty()), Some(Event::CopySelection) ; "yank while selecting lines")]
+    #[test_case(InputOptions { vim_mode: false, ..selecting_lines() }, KeyEvent::new(KeyCode::Char('j'), KeyModifiers::empty()), Some(Event::ExtendLineSelection { direction: Direction::Down }) ; "bound j while selecting lines")]
+    #[test_case(selecting_lines(), KeyEvent::new(KeyCode::Esc, KeyModifiers::empty()), Some(Event::ClearSelection) ; "esc while selecting lines")]
     // Note: This only checks event variants not any data contained in the variant
     fn test_translate_key_event_variant(
         opts: InputOptions,
@@ -467,4 +602,39 @@ mod test {
             (Some(_), None) => panic!("expected no event, got an event"),
         }
     }
+
+    #[test]
+    fn test_line_selection_follows_keybindings() {
+        let overrides = [
+            ("next_task".to_owned(), "n".to_owned()),
+            ("scroll_up".to_owned(), "k".to_owned()),
+        ]
+        .into_iter()
+        .collect();
+        let keybindings = Keybindings::with_overrides(Some(&overrides));
+        let options = InputOptions {
+            vim_mode: false,
+            keybindings: &keybindings,
+            ..selecting_lines()
+        };
+        let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::empty());
+
+        assert!(matches!(
+            translate_key_event(options, key('n')),
+            Some(Event::ExtendLineSelection {
+                direction: Direction::Down
+            })
+        ));
+        assert!(translate_key_event(options, key('j')).is_none());
+        assert!(matches!(
+            translate_key_event(options, key('k')),
+            Some(Event::ScrollUp)
+        ));
+        assert!(matches!(
+            translate_key_event(options, KeyEvent::new(KeyCode::Up, KeyModifiers::empty())),
+            Some(Event::ExtendLineSelection {
+                direction: Direction::Up
+            })
+        ));
+    }
 }
@@ -0,0 +1,467 @@
+use std::collections::{HashMap, HashSet};
+
+use crossterm::event::KeyCode;
+use tracing::warn;
+
+use super::{
+    event::{Direction, Event},
+    task::StatusFilter,
+    vim::VimState,
+};
+
+/// An action that can be triggered from the task list with a single key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    NextTask,
+    PreviousTask,
+    ToggleTaskList,
+    GrowTaskList,
+    ShrinkTaskList,
+    ToggleHelpPopup,
+    TogglePinnedTask,
+    ToggleSplit,
+    SwitchSplitFocus,
+    Search,
+    FilterFailed,
+    FilterRunning,
+    FilterCacheMisses,
+    ToggleGrouping,
+    CollapsePackage,
+    ExpandPackage,
+    SearchLogs,
+    ExportLogs,
+    RerunTask,
+    RerunWithDependents,
+    ToggleFollow,
+    ToggleFollowAll,
+    SelectLines,
+    Yank,
+    NextLogMatch,
+    PreviousLogMatch,
+    NextDiagnostic,
+    PreviousDiagnostic,
+    ScrollUp,
+    ScrollDown,
+    Interact,
+    Quit,
+}
+
+const DEFAULT_BINDINGS: &[(KeyCode, Action)] = &[
+    (KeyCode::Down, Action::NextTask),
+    (KeyCode::Char('j'), Action::NextTask),
+    (KeyCode::Up, Action::PreviousTask),
+    (KeyCode::Char('k'), Action::PreviousTask),
+    (KeyCode::Char('h'), Action::ToggleTaskList),
+    (KeyCode::Char('>'), Action::GrowTaskList),
+    (KeyCode::Char('<'), Action::ShrinkTaskList),
+    (KeyCode::Char('m'), Action::ToggleHelpPopup),
+    (KeyCode::Char('p'), Action::TogglePinnedTask),
+    (KeyCode::Char('s'), Action::ToggleSplit),
+    (KeyCode::Tab, Action::SwitchSplitFocus),
+    (KeyCode::Char('/'), Action::Search),
+    (KeyCode::Char('F'), Action::FilterFailed),
+    (KeyCode::Char('R'), Action::FilterRunning),
+    (KeyCode::Char('M'), Action::FilterCacheMisses),
+    (KeyCode::Char('g'), Action::ToggleGrouping),
+    (KeyCode::Left, Action::CollapsePackage),
+    (KeyCode::Right, Action::ExpandPackage),
+    (KeyCode::Char('f'), Action::SearchLogs),
+    (KeyCode::Char('e'), Action::ExportLogs),
+    (KeyCode::Char('r'), Action::RerunTask),
+    (KeyCode::Char('D'), Action::RerunWithDependents),
+    (KeyCode::Char('a'), Action::ToggleFollow),
+    (KeyCode::Char('A'), Action::ToggleFollowAll),
+    (KeyCode::Char('v'), Action::SelectLines),
+    (KeyCode::Char('y'), Action::Yank),
+    (KeyCode::Char('n'), Action::NextLogMatch),
+    (KeyCode::Char('N'), Action::PreviousLogMatch),
+    (KeyCode::Char('w'), Action::NextDiagnostic),
+    (KeyCode::Char('W'), Action::PreviousDiagnostic),
+    (KeyCode::Char('u'), Action::ScrollUp),
+    (KeyCode::Char('d'), Action::ScrollDown),
+    (KeyCode::Enter, Action::Interact),
+    (KeyCode::Char('i'), Action::Interact),
+];
+
+/// The keys of the actions whose default keys vim mode handles instead
+const VIM_MODE_BINDINGS: &[(KeyCode, Action)] = &[
+    (KeyCode::Char('H'), Action::ToggleTaskList),
+    (KeyCode::Char('P'), Action::ToggleGrouping),
+];
+
+/// The names of the actions in the preferences file
+const ACTION_NAMES: &[(&str, Action)] = &[
+    ("next_task", Action::NextTask),
+    ("previous_task", Action::PreviousTask),
+    ("toggle_task_list", Action::ToggleTaskList),
+    ("grow_task_list", Action::GrowTaskList),
+    ("shrink_task_list", Action::ShrinkTaskList),
+    ("toggle_help_popup", Action::ToggleHelpPopup),
+    ("toggle_pinned_task", Action::TogglePinnedTask),
+    ("toggle_split", Action::ToggleSplit),
+    ("switch_split_focus", Action::SwitchSplitFocus),
+    ("search", Action::Search),
+    ("filter_failed", Action::FilterFailed),
+    ("filter_running", Action::FilterRunning),
+    ("filter_cache_misses", Action::FilterCacheMisses),
+    ("toggle_grouping", Action::ToggleGrouping),
+    ("collapse_package", Action::CollapsePackage),
+    ("expand_package", Action::ExpandPackage),
+    ("search_logs", Action::SearchLogs),
+    ("export_logs", Action::ExportLogs),
+    ("rerun_task", Action::RerunTask),
+    ("rerun_with_dependents", Action::RerunWithDependents),
+    ("toggle_follow", Action::ToggleFollow),
+    ("toggle_follow_all", Action::ToggleFollowAll),
+    ("select_lines", Action::SelectLines),
+    ("yank", Action::Yank),
+    ("next_log_match", Action::NextLogMatch),
+    ("previous_log_match", Action::PreviousLogMatch),
+    ("next_diagnostic", Action::NextDiagnostic),
+    ("previous_diagnostic", Action::PreviousDiagnostic),
+    ("scroll_up", Action::ScrollUp),
+    ("scroll_down", Action::ScrollDown),
+    ("interact", Action::Interact),
+    ("quit", Action::Quit),
+];
+
+impl Action {
+    /// Looks up an action by the name used in the preferences file
+    fn from_name(name: &str) -> Option<Self> {
+        ACTION_NAMES
+            .iter()
+            .find(|(action_name, _)| *action_name == name)
+            .map(|(_, action)| *action)
+    }
+
+    /// The name of the action in the preferences file
+    pub fn name(&self) -> &'static str {
+        ACTION_NAMES
+            .iter()
+            .find(|(_, action)| action == self)
+            .map(|(name, _)| *name)
+            .expect("every action has a name")
+    }
+
+    /// The TUI event this action produces
+    pub fn event(&self) -> Event {
+        match self {
+            Action::NextTask => Event::Down,
+            Action::PreviousTask => Event::Up,
+            Action::ToggleTaskList => Event::ToggleSidebar,
+            Action::GrowTaskList => Event::ResizeSidebar { grow: true },
+            Action::ShrinkTaskList => Event::ResizeSidebar { grow: false },
+            Action::ToggleHelpPopup => Event::ToggleHelpPopup,
+            Action::TogglePinnedTask => Event::TogglePinnedTask,
+            Action::ToggleSplit => Event::ToggleSplit,
+            Action::SwitchSplitFocus => Event::SwitchSplitFocus,
+            Action::Search => Event::SearchEnter,
+            Action::FilterFailed => Event::ToggleStatusFilter(StatusFilter::Failed),
+            Action::FilterRunning => Event::ToggleStatusFilter(StatusFilter::Running),
+            Action::FilterCacheMisses => Event::ToggleStatusFilter(StatusFilter::CacheMiss),
+            Action::ToggleGrouping => Event::ToggleGrouping,
+            Action::CollapsePackage => Event::SetPackageCollapsed { collapsed: true },
+            Action::ExpandPackage => Event::SetPackageCollapsed { collapsed: false },
+            Action::SearchLogs => Event::LogSearchEnter,
+            Action::ExportLogs => Event::ExportLogs,
+            Action::RerunTask => Event::RerunTask {
+                include_dependents: false,
+            },
+            Action::RerunWithDependents => Event::RerunTask {
+                include_dependents: true,
+            },
+            Action::ToggleFollow => Event::ToggleFollow { all_tasks: false },
+            Action::ToggleFollowAll => Event::ToggleFollow { all_tasks: true },
+            Action::SelectLines => Event::SelectLines,
+            Action::Yank => Event::CopySelection,
+            Action::NextLogMatch => Event::LogSearchScroll {
+                direction: Direction::Down,
+            },
+            Action::PreviousLogMatch => Event::LogSearchScroll {
+                direction: Direction::Up,
+            },
+            Action::NextDiagnostic => Event::DiagnosticScroll {
+                direction: Direction::Down,
+            },
+            Action::PreviousDiagnostic => Event::DiagnosticScroll {
+                direction: Direction::Up,
+            },
+            Action::ScrollUp => Event::ScrollUp,
+            Action::ScrollDown => Event::ScrollDown,
+            Action::Interact => Event::EnterInteractive,
+            Action::Quit => Event::InternalStop,
+        }
+    }
+}
+
+/// Mapping from keys to the actions they trigger.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keybindings {
+    bindings: HashMap<KeyCode, Action>,
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        Self {
+            bindings: DEFAULT_BINDINGS.iter().copied().collect(),
+        }
+    }
+}
+
+impl Keybindings {
+    /// Creates keybindings by applying user overrides, a map from action name
+    /// to key, on top of the defaults. An override replaces every default key
+    /// for that action. Unknown actions and keys are ignored with a warning, as
+    /// are keys that another override already uses. Overriding with a key
+    /// that's bound to another action by default unbinds it from that action.
+    pub fn with_overrides(overrides: Option<&HashMap<String, String>>) -> Self {
+        let mut keybindings = Self::default();
+        let Some(overrides) = overrides else {
+            return keybindings;
+        };
+
+        // Sort so that conflicting overrides resolve the same way every time
+        let mut overrides = overrides.iter().collect::<Vec<_>>();
+        overrides.sort();
+        let mut overridden = HashSet::new();
+        for (action_name, key) in overrides {
+            let Some(action) = Action::from_name(action_name) else {
+                warn!("ignoring keybinding for unknown action '{action_name}'");
+                continue;
+            };
+            let Some(code) = parse_key(key) else {
+                warn!("ignoring keybinding for '{action_name}', unknown key '{key}'");
+                continue;
+            };
+            match keybindings.bindings.get(&code) {
+                Some(bound) if *bound != action && overridden.contains(bound) => {
+                    let bound = bound.name();
+                    warn!("ignoring keybinding for '{action_name}', '{key}' is bound to '{bound}'");
+                    continue;
+                }
+                Some(bound) if *bound != action => {
+                    let bound = bound.name();
+                    warn!("'{key}' is bound to '{action_name}' instead of '{bound}'");
+                }
+                _ => {}
+            }
+            overridden.insert(action);
+            keybindings.bindings.retain(|_, bound| *bound != action);
+            keybindings.bindings.insert(code, action);
+        }
+
+        keybindings
+    }
+
+    /// Unbinds the keys that vim mode handles, which would never reach their
+    /// action. An action left without keys is bound to its key for vim mode
+    /// if it has one, otherwise it's unbound with a warning.
+    pub fn with_vim_mode(mut self) -> Self {
+        let is_vim_key = |code: &KeyCode| matches!(code, KeyCode::Char(c) if VimState::handles(*c));
+        let mut shadowed = self
+            .bindings
+            .iter()
+            .filter(|(code, _)| is_vim_key(code))
+            .map(|(code, action)| (*action, *code))
+            .collect::<Vec<_>>();
+        // Sort so that warnings are the same every time
+        shadowed.sort_by_key(|(action, code)| (action.name(), key_name(*code)));
+        self.bindings.retain(|code, _| !is_vim_key(code));
+
+        for (action, code) in shadowed {
+            if !self.keys(action).is_empty() {
+                continue;
+            }
+            match VIM_MODE_BINDINGS.iter().find(|(_, bound)| *bound == action) {
+                Some((vim_code, _)) if !self.bindings.contains_key(vim_code) => {
+                    self.bindings.insert(*vim_code, action);
+                }
+                _ => {
+                    let key = key_name(code);
+                    let action = action.name();
+                    warn!("'{key}' is used by vim mode, so '{action}' is unbound");
+                }
+            }
+        }
+        self
+    }
+
+    pub fn action(&self, code: KeyCode) -> Option<Action> {
+        self.bindings.get(&code).copied()
+    }
+
+    /// The keys bound to `action`, with special keys before characters
+    pub fn keys(&self, action: Action) -> Vec<KeyCode> {
+        let mut keys = self
+            .bindings
+            .iter()
+            .filter(|(_, bound)| **bound == action)
+            .map(|(code, _)| *code)
+            .collect::<Vec<_>>();
+        keys.sort_by_key(|code| (matches!(code, KeyCode::Char(_)), key_name(*code)));
+        keys
+    }
+}
+
+/// How a key is shown to the user, e.g. in the help popup
+pub fn key_name(code: KeyCode) -> String {
+    match code {
+        KeyCode::Char(' ') => "Space".to_owned(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Up => "↑".to_owned(),
+        KeyCode::Down => "↓".to_owned(),
+        KeyCode::Left => "←".to_owned(),
+        KeyCode::Right => "→".to_owned(),
+        KeyCode::Enter => "Enter".to_owned(),
+        KeyCode::Tab => "Tab".to_owned(),
+        KeyCode::Home => "Home".to_owned(),
+        KeyCode::End => "End".to_owned(),
+        KeyCode::PageUp => "PageUp".to_owned(),
+        KeyCode::PageDown => "PageDown".to_owned(),
+        code => format!("{code:?}"),
+    }
+}
+
+/// Parses a key as written in the preferences file, either a single
+/// character, e.g. `j`, or the name of a special key, e.g. `enter`.
+fn parse_key(key: &str) -> Option<KeyCode> {
+    let mut chars = key.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+
+    Some(match key.to_ascii_lowercase().as_str() {
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "enter" => KeyCode::Enter,
+        "tab" => KeyCode::Tab,
+        "space" => KeyCode::Char(' '),
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test_case("j", Some(KeyCode::Char('j')) ; "character")]
+    #[test_case("J", Some(KeyCode::Char('J')) ; "uppercase character")]
+    #[test_case("PageDown", Some(KeyCode::PageDown) ; "named key")]
+    #[test_case("space", Some(KeyCode::Char(' ')) ; "space")]
+    #[test_case("ctrl+shift+nope", None ; "unknown key")]
+    #[test_case("", None ; "empty")]
+    fn test_parse_key(key: &str, expected: Option<KeyCode>) {
+        assert_eq!(parse_key(key), expected);
+    }
+
+    #[test]
+    fn test_override_replaces_defaults() {
+        let overrides = [("next_task".to_owned(), "n".to_owned())]
+            .into_iter()
+            .collect();
+        let keybindings = Keybindings::with_overrides(Some(&overrides));
+        assert_eq!(
+            keybindings.action(KeyCode::Char('n')),
+            Some(Action::NextTask)
+        );
+        assert_eq!(keybindings.action(KeyCode::Char('j')), None);
+        assert_eq!(keybindings.action(KeyCode::Down), None);
+        // Other actions keep their defaults
+        assert_eq!(
+            keybindings.action(KeyCode::Char('k')),
+            Some(Action::PreviousTask)
+        );
+    }
+
+    #[test]
+    fn test_invalid_overrides_are_ignored() {
+        let overrides = [
+            ("not_an_action".to_owned(), "x".to_owned()),
+            ("quit".to_owned(), "not a key".to_owned()),
+        ]
+        .into_iter()
+        .collect();
+        let keybindings = Keybindings::with_overrides(Some(&overrides));
+        assert_eq!(keybindings, Keybindings::default());
+    }
+
+    #[test]
+    fn test_conflicting_overrides() {
+        let overrides = [
+            ("next_task".to_owned(), "n".to_owned()),
+            ("previous_task".to_owned(), "n".to_owned()),
+            ("search".to_owned(), "p".to_owned()),
+        ]
+        .into_iter()
+        .collect();
+        let keybindings = Keybindings::with_overrides(Some(&overrides));
+        // Overrides are applied in order of their action's name
+        assert_eq!(
+            keybindings.action(KeyCode::Char('n')),
+            Some(Action::NextTask)
+        );
+        assert_eq!(
+            keybindings.keys(Action::PreviousTask),
+            [KeyCode::Up, KeyCode::Char('k')]
+        );
+        // A key that's bound by default is taken from its action
+        assert_eq!(keybindings.action(KeyCode::Char('p')), Some(Action::Search));
+        assert_eq!(keybindings.keys(Action::TogglePinnedTask), []);
+    }
+
+    #[test]
+    fn test_vim_mode_rebinds_shadowed_actions() {
+        let keybindings = Keybindings::default().with_vim_mode();
+        assert_eq!(keybindings.action(KeyCode::Char('h')), None);
+        assert_eq!(keybindings.action(KeyCode::Char('g')), None);
+        assert_eq!(
+            keybindings.keys(Action::ToggleTaskList),
+            [KeyCode::Char('H')]
+        );
+        assert_eq!(
+            keybindings.keys(Action::ToggleGrouping),
+            [KeyCode::Char('P')]
+        );
+        // Actions with other keys keep those
+        assert_eq!(keybindings.keys(Action::NextTask), [KeyCode::Down]);
+    }
+
+    #[test]
+    fn test_vim_mode_unbinds_overrides_it_shadows() {
+        let overrides = [
+            ("export_logs".to_owned(), "0".to_owned()),
+            ("search".to_owned(), "H".to_owned()),
+        ]
+        .into_iter()
+        .collect();
+        let keybindings = Keybindings::with_overrides(Some(&overrides)).with_vim_mode();
+        assert_eq!(keybindings.keys(Action::ExportLogs), []);
+        // The task list's key for vim mode is taken, so it's unbound
+        assert_eq!(keybindings.keys(Action::ToggleTaskList), []);
+        assert_eq!(keybindings.action(KeyCode::Char('H')), Some(Action::Search));
+    }
+
+    #[test]
+    fn test_keys() {
+        let keybindings = Keybindings::default();
+        assert_eq!(
+            keybindings.keys(Action::NextTask),
+            [KeyCode::Down, KeyCode::Char('j')]
+        );
+        assert_eq!(keybindings.keys(Action::Quit), []);
+        let names = keybindings
+            .keys(Action::Interact)
+            .into_iter()
+            .map(key_name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["Enter", "i"]);
+    }
+}
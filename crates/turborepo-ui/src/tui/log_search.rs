@@ -0,0 +1,190 @@
+use super::event::Direction;
+
+/// A search through the logs of a task
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogSearch {
+    query: String,
+    matches: Vec<LogMatch>,
+    // Index into `matches` of the match that's highlighted
+    current: Option<usize>,
+}
+
+/// Where a match is in the logs. The row is counted from the top of the
+/// scrollback, so it doesn't change as the logs are scrolled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LogMatch {
+    pub row: usize,
+    pub col: u16,
+    pub width: u16,
+}
+
+impl LogSearch {
+    pub fn new(query: String) -> Self {
+        Self {
+            query,
+            matches: Vec::new(),
+            current: None,
+        }
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// The position of the highlighted match, starting at 1, and the number
+    /// of matches
+    pub fn position(&self) -> (Option<usize>, usize) {
+        (self.current.map(|current| current + 1), self.matches.len())
+    }
+
+    pub fn current(&self) -> Option<LogMatch> {
+        self.matches.get(self.current?).copied()
+    }
+
+    /// Finds the matches in `rows`, the text of every row of the logs. The
+    /// highlighted match stays the same if it still matches.
+    pub fn update_matches(&mut self, rows: impl Iterator<Item = String>) {
+        let current = self.current();
+        self.matches = find_matches(rows, &self.query);
+        self.current = current.and_then(|current| {
+            self.matches
+                .iter()
+                .position(|log_match| *log_match == current)
+        });
+    }
+
+    /// Highlights the last match on or above `row`, or the first match if
+    /// there isn't one
+    pub fn select_from(&mut self, row: usize) {
+        self.current = self
+            .matches
+            .iter()
+            .rposition(|log_match| log_match.row <= row)
+            .or((!self.matches.is_empty()).then_some(0));
+    }
+
+    /// Highlights the next match in `direction`, wrapping around at the start
+    /// and end of the logs
+    pub fn select_next(&mut self, direction: Direction) {
+        let len = self.matches.len();
+        if len == 0 {
+            self.current = None;
+            return;
+        }
+        self.current = Some(match (self.current, direction) {
+            (None, Direction::Down) => 0,
+            (None, Direction::Up) => len - 1,
+            (Some(current), Direction::Down) => (current + 1) % len,
+            (Some(current), Direction::Up) => current.checked_sub(1).unwrap_or(len - 1),
+        });
+    }
+}
+
+/// Finds every occurrence of `query` in `rows`. Queries that are all
+/// lowercase ignore case.
+fn find_matches(rows: impl Iterator<Item = String>, query: &str) -> Vec<LogMatch> {
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let ignore_case = !query.chars().any(char::is_uppercase);
+    // Only ASCII is lowercased so byte offsets stay the same
+    let query = if ignore_case {
+        query.to_ascii_lowercase()
+    } else {
+        query.to_owned()
+    };
+    let width = u16::try_from(query.chars().count()).unwrap_or(u16::MAX);
+
+    let mut matches = Vec::new();
+    for (row, text) in rows.enumerate() {
+        let text = if ignore_case {
+            text.to_ascii_lowercase()
+        } else {
+            text
+        };
+        for (offset, _) in text.match_indices(&query) {
+            let col = text[..offset].chars().count();
+            matches.push(LogMatch {
+                row,
+                col: u16::try_from(col).unwrap_or(u16::MAX),
+                width,
+            });
+        }
+    }
+    matches
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn rows() -> impl Iterator<Item = String> {
+        [
+            "building...",
+            "error: oops",
+            "  Error: again",
+            "done, 1 error",
+        ]
+        .into_iter()
+        .map(String::from)
+    }
+
+    #[test]
+    fn test_finds_matches_in_order() {
+        let matches = find_matches(rows(), "error");
+        assert_eq!(
+            matches,
+            vec![
+                LogMatch {
+                    row: 1,
+                    col: 0,
+                    width: 5
+                },
+                LogMatch {
+                    row: 2,
+                    col: 2,
+                    width: 5
+                },
+                LogMatch {
+                    row: 3,
+                    col: 8,
+                    width: 5
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_uppercase_matches_case() {
+        let matches = find_matches(rows(), "Error");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].row, 2);
+        assert!(find_matches(rows(), "").is_empty());
+    }
+
+    #[test]
+    fn test_navigation_wraps() {
+        let mut search = LogSearch::new("error".into());
+        search.update_matches(rows());
+        search.select_from(2);
+        assert_eq!(search.position(), (Some(2), 3));
+        search.select_next(Direction::Down);
+        assert_eq!(search.position(), (Some(3), 3));
+        search.select_next(Direction::Down);
+        assert_eq!(search.position(), (Some(1), 3));
+        search.select_next(Direction::Up);
+        assert_eq!(search.position(), (Some(3), 3));
+    }
+
+    #[test]
+    fn test_keeps_current_match_when_logs_change() {
+        let mut search = LogSearch::new("error".into());
+        search.update_matches(rows());
+        search.select_from(0);
+        assert_eq!(search.position(), (Some(1), 3), "first match if none above");
+        search.update_matches(rows().chain(["another error".to_owned()]));
+        assert_eq!(search.position(), (Some(1), 4));
+        search.update_matches(rows().skip(2));
+        assert_eq!(search.position(), (None, 2));
+    }
+}
@@ -1,18 +1,26 @@
 mod app;
 mod clipboard;
 mod debouncer;
+mod diagnostics;
+mod durations;
 pub mod event;
 mod handle;
 mod input;
+mod keybindings;
+mod log_search;
+mod notification;
 mod pane;
 mod popup;
 mod preferences;
+mod progress;
 mod search;
 mod size;
 mod spinner;
 mod table;
 mod task;
 mod term_output;
+mod theme;
+mod vim;
 
 pub use app::{run_app, terminal_big_enough};
 use clipboard::copy_to_clipboard;
@@ -21,6 +29,7 @@ use event::{Event, TaskResult};
 pub use handle::{AppReceiver, TuiSender};
 use input::InputOptions;
 pub use pane::TerminalPane;
+pub use preferences::{user_preferences_path, PreferenceLoader, Preferences};
 use size::SizeInfo;
 pub use table::TaskTable;
 pub use term_output::TerminalOutput;
@@ -0,0 +1,83 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+// Desktop notification escape sequence understood by iTerm2, WezTerm, kitty
+// and Windows Terminal. Other terminals ignore it.
+const DESKTOP_NOTIFICATION_START: &str = "\x1b]9;";
+const BELL: &str = "\x07";
+
+/// How to let the user know that a run, or a rebuild in watch mode, finished
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CompletionNotification {
+    #[default]
+    Off,
+    /// Rings the terminal bell
+    Bell,
+    /// Shows a desktop notification with whether the run passed
+    Desktop,
+}
+
+/// How the tasks of a finished run went
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunOutcome {
+    pub tasks: usize,
+    pub failed: usize,
+}
+
+impl RunOutcome {
+    pub fn message(&self) -> String {
+        let Self { tasks, failed } = *self;
+        let plural = if tasks == 1 { "" } else { "s" };
+        match failed {
+            0 => format!("turbo: {tasks} task{plural} passed"),
+            failed => format!("turbo: {failed} of {tasks} task{plural} failed"),
+        }
+    }
+}
+
+impl CompletionNotification {
+    /// What to write to the terminal to notify the user of `outcome`, if
+    /// anything
+    pub fn escape_sequence(self, outcome: RunOutcome) -> Option<String> {
+        match self {
+            CompletionNotification::Off => None,
+            CompletionNotification::Bell => Some(BELL.to_owned()),
+            CompletionNotification::Desktop => Some(format!(
+                "{DESKTOP_NOTIFICATION_START}{}{BELL}",
+                outcome.message()
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_escape_sequence() {
+        let passed = RunOutcome {
+            tasks: 3,
+            failed: 0,
+        };
+        let failed = RunOutcome {
+            tasks: 1,
+            failed: 1,
+        };
+        assert_eq!(CompletionNotification::Off.escape_sequence(passed), None);
+        assert_eq!(
+            CompletionNotification::Bell
+                .escape_sequence(failed)
+                .as_deref(),
+            Some("\x07")
+        );
+        assert_eq!(
+            CompletionNotification::Desktop
+                .escape_sequence(passed)
+                .as_deref(),
+            Some("\x1b]9;turbo: 3 tasks passed\x07")
+        );
+        assert_eq!(failed.message(), "turbo: 1 of 1 task failed");
+    }
+}
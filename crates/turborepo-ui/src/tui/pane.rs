@@ -1,3 +1,4 @@
+use itertools::Itertools;
 use ratatui::{
     style::{Modifier, Style, Stylize},
     text::Line,
@@ -5,19 +6,34 @@ use ratatui::{
 };
 use tui_term::widget::PseudoTerminal;
 
-use super::{app::LayoutSections, TerminalOutput};
+use super::{
+    app::LayoutSections,
+    keybindings::{key_name, Action, Keybindings},
+    vim::{VimState, VimTarget},
+    TerminalOutput,
+};
 
 const EXIT_INTERACTIVE_HINT: &str = "Ctrl-z - Stop interacting";
 const ENTER_INTERACTIVE_HINT: &str = "i - Interact";
 const HAS_SELECTION: &str = "c - Copy selection";
+const SELECTING_LINES_HINT: &str = "j/k - Select more lines   y - Copy   Esc - Cancel";
 const SCROLL_LOGS: &str = "u/d - Scroll logs";
-const TASK_LIST_HIDDEN: &str = "h - Show task list";
+const LOG_SEARCH_HINT: &str = "n/N - Next/previous match";
+const SWITCH_SPLIT_HINT: &str = "Tab - Switch pane";
+const NOT_FOLLOWING_HINT: &str = "a - Follow new logs";
+const VIM_TASK_LIST_HINT: &str = "l - Move through logs";
+const VIM_LOGS_HINT: &str = "j/k - Scroll logs   h - Move through tasks";
 
 pub struct TerminalPane<'a, W> {
     terminal_output: &'a TerminalOutput<W>,
     task_name: &'a str,
     section: &'a LayoutSections,
     has_sidebar: bool,
+    keybindings: &'a Keybindings,
+    // Whether this pane has focus when it's one of the split panes, `None`
+    // when the logs aren't split
+    split_focus: Option<bool>,
+    vim: Option<&'a VimState>,
 }
 
 impl<'a, W> TerminalPane<'a, W> {
@@ -26,28 +42,88 @@ impl<'a, W> TerminalPane<'a, W> {
         task_name: &'a str,
         section: &'a LayoutSections,
         has_sidebar: bool,
+        keybindings: &'a Keybindings,
     ) -> Self {
         Self {
             terminal_output,
             section,
             task_name,
             has_sidebar,
+            keybindings,
+            split_focus: None,
+            vim: None,
         }
     }
 
+    /// Shows hints for vim mode instead of the keybindings it replaces
+    pub fn with_vim(mut self, vim: Option<&'a VimState>) -> Self {
+        self.vim = vim;
+        self
+    }
+
+    /// Shows the pane as one of the split panes. Only the focused pane has
+    /// hints in its footer.
+    pub fn in_split(mut self, is_focused: bool) -> Self {
+        self.split_focus = Some(is_focused);
+        self
+    }
+
+    /// A hint for the keys bound to `action`, or nothing if it's unbound
+    fn key_hint(&self, action: Action, description: &str) -> String {
+        let keys = self.keybindings.keys(action);
+        if keys.is_empty() {
+            return String::new();
+        }
+        format!(
+            "{} - {description}",
+            keys.into_iter().map(key_name).join("/")
+        )
+    }
+
     fn footer(&self) -> Line {
+        // The search key searches the logs when they have focus
+        let search_logs_hint = self.key_hint(Action::Search, "Search logs");
+        let task_list_hidden_hint = self.key_hint(Action::ToggleTaskList, "Show task list");
+        let log_search = self.terminal_output.log_search().map(|search| {
+            let (current, total) = search.position();
+            let current = current.map_or("-".to_owned(), |current| current.to_string());
+            format!("{current}/{total} matches for '{}'", search.query())
+        });
         let build_message_vec = |footer_text: &[&str]| -> Line {
             let mut messages = Vec::new();
-            messages.extend_from_slice(footer_text);
+            messages.extend(footer_text.iter().filter(|text| !text.is_empty()));
 
             if !self.has_sidebar {
-                messages.push(TASK_LIST_HIDDEN);
+                messages.extend(
+                    [task_list_hidden_hint.as_str(), search_logs_hint.as_str()]
+                        .into_iter()
+                        .filter(|hint| !hint.is_empty()),
+                );
+            }
+
+            if self.split_focus.is_some() {
+                messages.push(SWITCH_SPLIT_HINT);
             }
 
-            if self.terminal_output.has_selection() {
+            if self.terminal_output.is_selecting_lines() {
+                messages.push(SELECTING_LINES_HINT);
+            } else if self.terminal_output.has_selection() {
                 messages.push(HAS_SELECTION);
             }
 
+            if !self.terminal_output.follows() {
+                messages.push(NOT_FOLLOWING_HINT);
+            }
+
+            if let Some(notice) = self.terminal_output.export_notice() {
+                messages.push(notice);
+            }
+
+            if let Some(log_search) = &log_search {
+                messages.push(LOG_SEARCH_HINT);
+                messages.push(log_search);
+            }
+
             // Spaces are used to pad the footer text for aesthetics
             let formatted_messages = format!("   {}", messages.join("   "));
 
@@ -58,12 +134,31 @@ impl<'a, W> TerminalPane<'a, W> {
             .left_aligned()
         };
 
+        if self.split_focus == Some(false) {
+            return Line::default();
+        }
         match self.section {
             LayoutSections::Pane => build_message_vec(&[EXIT_INTERACTIVE_HINT]),
-            LayoutSections::TaskList => build_message_vec(&[ENTER_INTERACTIVE_HINT, SCROLL_LOGS]),
+            LayoutSections::TaskList => match self.vim {
+                Some(vim) => {
+                    let pending_keys = vim.pending_keys();
+                    let hints: &[&str] = match vim.target() {
+                        VimTarget::TaskList => &[VIM_TASK_LIST_HINT],
+                        // Already shown when the task list is hidden
+                        VimTarget::Logs if !self.has_sidebar => &[VIM_LOGS_HINT],
+                        VimTarget::Logs => &[VIM_LOGS_HINT, &search_logs_hint],
+                    };
+                    let messages = [&[ENTER_INTERACTIVE_HINT], hints, &[&pending_keys]].concat();
+                    build_message_vec(&messages)
+                }
+                None => build_message_vec(&[ENTER_INTERACTIVE_HINT, SCROLL_LOGS]),
+            },
             LayoutSections::Search { results, .. } => {
                 Line::from(format!("/ {}", results.query())).left_aligned()
             }
+            LayoutSections::LogSearch { query } => {
+                Line::from(format!("Search logs: {query}")).left_aligned()
+            }
         }
     }
 }
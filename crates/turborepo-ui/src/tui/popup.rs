@@ -2,35 +2,183 @@ use std::cmp::min;
 
 use ratatui::{
     layout::{Constraint, Flex, Layout, Rect},
+    style::Style,
     text::Line,
     widgets::{Block, List, ListItem, Padding},
 };
 
-const BIND_LIST: [&str; 12] = [
-    "m      - Toggle this help popup",
-    "↑ or j - Select previous task",
-    "↓ or k - Select next task",
-    "h      - Toggle task list",
-    "p      - Toggle pinned task selection",
-    "/      - Filter tasks to search term",
-    "ESC    - Clear filter",
-    "i      - Interact with task",
-    "Ctrl+z - Stop interacting with task",
-    "c      - Copy logs selection (Only when logs are selected)",
-    "u      - Scroll logs up",
-    "d      - Scroll logs down",
+use super::{
+    keybindings::{key_name, Action, Keybindings},
+    theme::Theme,
+};
+
+/// The binds shown in the popup, either a remappable action or a key that's
+/// always the same
+const BIND_LIST: [(Bind, &str); 35] = [
+    (
+        Bind::Action(Action::ToggleHelpPopup),
+        "Toggle this help popup",
+    ),
+    (Bind::Action(Action::PreviousTask), "Select previous task"),
+    (Bind::Action(Action::NextTask), "Select next task"),
+    (Bind::Action(Action::ToggleTaskList), "Toggle task list"),
+    (Bind::Action(Action::GrowTaskList), "Widen task list"),
+    (Bind::Action(Action::ShrinkTaskList), "Narrow task list"),
+    (
+        Bind::Action(Action::TogglePinnedTask),
+        "Toggle pinned task selection",
+    ),
+    (
+        Bind::Action(Action::Search),
+        "Filter tasks to search term, or search logs when they have focus",
+    ),
+    (
+        Bind::Fixed("ESC"),
+        "Clear filter, log search or selected lines",
+    ),
+    (Bind::Action(Action::FilterFailed), "Show only failed tasks"),
+    (
+        Bind::Action(Action::FilterRunning),
+        "Show only running tasks",
+    ),
+    (
+        Bind::Action(Action::FilterCacheMisses),
+        "Show only cache misses",
+    ),
+    (
+        Bind::Action(Action::ToggleGrouping),
+        "Group tasks by package",
+    ),
+    (Bind::Action(Action::CollapsePackage), "Collapse package"),
+    (Bind::Action(Action::ExpandPackage), "Expand package"),
+    (
+        Bind::Action(Action::ToggleSplit),
+        "Split logs with another task",
+    ),
+    (
+        Bind::Action(Action::SwitchSplitFocus),
+        "Switch between split logs",
+    ),
+    (Bind::Action(Action::SearchLogs), "Search task logs"),
+    (Bind::Action(Action::NextLogMatch), "Next match in logs"),
+    (
+        Bind::Action(Action::PreviousLogMatch),
+        "Previous match in logs",
+    ),
+    (
+        Bind::Action(Action::NextDiagnostic),
+        "Next warning or error in logs",
+    ),
+    (
+        Bind::Action(Action::PreviousDiagnostic),
+        "Previous warning or error in logs",
+    ),
+    (Bind::Action(Action::ExportLogs), "Save task logs to a file"),
+    (
+        Bind::Action(Action::RerunTask),
+        "Re-run task (Only in watch mode)",
+    ),
+    (
+        Bind::Action(Action::RerunWithDependents),
+        "Re-run task and its dependents (Only in watch mode)",
+    ),
+    (Bind::Action(Action::Interact), "Interact with task"),
+    (Bind::Fixed("Ctrl+z"), "Stop interacting with task"),
+    (
+        Bind::Fixed("c"),
+        "Copy logs selection (Only when logs are selected)",
+    ),
+    (Bind::Action(Action::SelectLines), "Select lines of logs"),
+    (
+        Bind::Fixed("j/k"),
+        "Extend selected lines (Only when selecting lines)",
+    ),
+    (Bind::Action(Action::Yank), "Copy logs selection"),
+    (Bind::Action(Action::ScrollUp), "Scroll logs up"),
+    (Bind::Action(Action::ScrollDown), "Scroll logs down"),
+    (
+        Bind::Action(Action::ToggleFollow),
+        "Toggle following new logs of task",
+    ),
+    (
+        Bind::Action(Action::ToggleFollowAll),
+        "Toggle following new logs of every task",
+    ),
 ];
 
-pub fn popup_area(area: Rect) -> Rect {
+/// The keys vim mode adds, shown when it's on
+const VIM_BIND_LIST: [(&str, &str); 5] = [
+    ("j/k", "Select next/previous task, or scroll logs"),
+    ("gg/G", "Jump to the first/last task or line"),
+    ("5j", "Repeat a movement, e.g. 5 times"),
+    ("l", "Move through the logs"),
+    ("h", "Move through the task list"),
+];
+
+enum Bind {
+    Action(Action),
+    Fixed(&'static str),
+}
+
+/// The lines of the help popup, showing the keys that are currently bound to
+/// each action
+pub fn bind_list(keybindings: &Keybindings, vim_mode: bool) -> Vec<String> {
+    let mut binds = BIND_LIST
+        .iter()
+        .map(|(bind, description)| {
+            let keys = match bind {
+                Bind::Action(action) => keybindings
+                    .keys(*action)
+                    .into_iter()
+                    .map(key_name)
+                    .collect::<Vec<_>>()
+                    .join(" or "),
+                Bind::Fixed(key) => key.to_string(),
+            };
+            (keys, *description)
+        })
+        .collect::<Vec<_>>();
+    // Quit isn't bound by default, but users can bind it
+    let quit = keybindings.keys(Action::Quit);
+    if !quit.is_empty() {
+        let keys = quit.into_iter().map(key_name).collect::<Vec<_>>();
+        binds.push((keys.join(" or "), "Quit"));
+    }
+    if vim_mode {
+        binds.extend(
+            VIM_BIND_LIST
+                .iter()
+                .map(|(keys, description)| (keys.to_string(), *description)),
+        );
+    }
+
+    let width = binds
+        .iter()
+        .map(|(keys, _)| keys.chars().count())
+        .max()
+        .unwrap_or(0);
+    binds
+        .into_iter()
+        .map(|(keys, description)| {
+            if keys.is_empty() {
+                format!("{:width$} - {description} (Unbound)", "-")
+            } else {
+                format!("{keys:width$} - {description}")
+            }
+        })
+        .collect()
+}
+
+pub fn popup_area(area: Rect, bind_list: &[String]) -> Rect {
     let screen_width = area.width;
     let screen_height = area.height;
 
-    let popup_width = BIND_LIST
+    let popup_width = bind_list
         .iter()
-        .map(|s| s.len().saturating_add(4))
+        .map(|s| s.chars().count().saturating_add(4))
         .max()
         .unwrap_or(0) as u16;
-    let popup_height = min((BIND_LIST.len().saturating_add(4)) as u16, screen_height);
+    let popup_height = min((bind_list.len().saturating_add(4)) as u16, screen_height);
 
     let x = screen_width.saturating_sub(popup_width) / 2;
     let y = screen_height.saturating_sub(popup_height) / 2;
@@ -50,17 +198,17 @@ pub fn popup_area(area: Rect) -> Rect {
     area
 }
 
-pub fn popup(area: Rect) -> List<'static> {
+pub fn popup(area: Rect, bind_list: &[String], theme: Theme) -> List<'static> {
     let available_height = area.height.saturating_sub(4) as usize;
 
-    let items: Vec<ListItem> = BIND_LIST
+    let items: Vec<ListItem> = bind_list
         .iter()
         .take(available_height)
-        .map(|item| ListItem::new(Line::from(*item)))
+        .map(|item| ListItem::new(Line::from(item.clone())))
         .collect();
 
-    let title_bottom = if available_height < BIND_LIST.len() {
-        let binds_not_visible = BIND_LIST.len().saturating_sub(available_height);
+    let title_bottom = if available_height < bind_list.len() {
+        let binds_not_visible = bind_list.len().saturating_sub(available_height);
 
         let pluralize = if binds_not_visible > 1 { "s" } else { "" };
         let message = format!(
@@ -73,9 +221,43 @@ pub fn popup(area: Rect) -> List<'static> {
     };
 
     let outer = Block::bordered()
+        .border_style(Style::default().fg(theme.border))
         .title(" Keybinds ")
         .title_bottom(title_bottom.to_string())
         .padding(Padding::uniform(1));
 
     List::new(items).block(outer)
 }
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn test_bind_list_shows_overrides() {
+        let overrides = HashMap::from([
+            ("search".to_owned(), "s".to_owned()),
+            ("quit".to_owned(), "q".to_owned()),
+            ("scroll_up".to_owned(), "d".to_owned()),
+        ]);
+        let bind_list = bind_list(&Keybindings::with_overrides(Some(&overrides)), false);
+        // Keys are padded to the widest, `Enter or i`
+        assert!(bind_list.contains(&"↓ or j     - Select next task".to_owned()));
+        assert!(bind_list.contains(
+            &"s          - Filter tasks to search term, or search logs when they have focus"
+                .to_owned()
+        ));
+        assert!(bind_list.contains(&"d          - Scroll logs up".to_owned()));
+        assert!(bind_list.contains(&"-          - Scroll logs down (Unbound)".to_owned()));
+        assert_eq!(bind_list.last(), Some(&"q          - Quit".to_owned()));
+    }
+
+    #[test]
+    fn test_bind_list_in_vim_mode() {
+        let bind_list = bind_list(&Keybindings::default().with_vim_mode(), true);
+        assert!(bind_list.contains(&"H          - Toggle task list".to_owned()));
+        assert!(bind_list.contains(&"P          - Group tasks by package".to_owned()));
+    }
+}
@@ -1,7 +1,48 @@
-use serde::{Deserialize, Serialize};
-use turbopath::AbsoluteSystemPathBuf;
+use std::{
+    collections::{HashMap, HashSet},
+    iter,
+    marker::PhantomData,
+    time::{Duration, Instant},
+};
+
+use schemars::JsonSchema;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tracing::{debug, warn};
+use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
+
+use super::{
+    diagnostics::DiagnosticPatterns,
+    keybindings::Keybindings,
+    notification::CompletionNotification,
+    theme::{Theme, ThemeConfig},
+};
 
 const TUI_PREFERENCES_PATH_COMPONENTS: &[&str] = &[".turbo", "preferences", "tui.json"];
+// Relative to the user's config directory, next to turbo's global config
+const USER_TUI_PREFERENCES_PATH_COMPONENTS: &[&str] = &["turborepo", "tui.json"];
+const TUI_PREFERENCES_SCHEMA_FILE: &str = "tui.schema.json";
+// Used when the user hasn't configured `log_export_dir`
+const DEFAULT_LOG_EXPORT_PATH_COMPONENTS: &[&str] = &[".turbo", "logs"];
+// Used when the user hasn't configured `max_log_lines`
+const DEFAULT_MAX_LOG_LINES: usize = 50_000;
+// Changes made within this window of each other are coalesced into one write
+const WRITE_DEBOUNCE_DELAY: Duration = Duration::from_millis(500);
+// How long to wait for another turbo process to finish writing preferences
+const LOCK_TIMEOUT: Duration = Duration::from_secs(2);
+const LOCK_RETRY_DELAY: Duration = Duration::from_millis(10);
+
+type Migration = fn(&mut Map<String, Value>);
+
+/// Migrates the fields of each version of `tui.json` to the next one, starting
+/// at version 0. Renaming or removing a field needs a migration that moves or
+/// drops it, so preferences written by older versions of turbo still parse.
+const MIGRATIONS: &[Migration] = &[
+    // Version 0 is every file written before preferences were versioned, which
+    // has the same fields as version 1
+    |_| {},
+];
+const PREFERENCES_VERSION: u32 = MIGRATIONS.len() as u32;
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -9,31 +50,125 @@ pub enum Error {
     Io(#[from] std::io::Error),
     #[error(transparent)]
     Serde(#[from] serde_json::Error),
+    #[error("Preferences profile must be a JSON object.")]
+    InvalidProfile,
+    #[error("Unable to lock preferences file: {0}")]
+    Lock(#[from] pidlock::PidlockError),
 }
 
+/// Loads and saves the TUI's preferences.
+///
+/// Preferences come from two files, and each one is taken from the first of
+/// these that sets it:
+/// 1. The repository's `.turbo/preferences/tui.json`
+/// 2. The user's `turborepo/tui.json` in their config directory, which applies
+///    to every repository and worktree
+/// 3. The defaults
+///
+/// The active task, the last task for each filter and which tasks' logs
+/// follow new output only make sense for one repository, so they're only kept
+/// in the repository's file.
 pub struct PreferenceLoader {
+    repo_root: AbsoluteSystemPathBuf,
+    file_path: AbsoluteSystemPathBuf,
+    config: Preferences,
+    // The preferences as they were last read from or written to the file, so
+    // only the changes made here are written over other processes' changes
+    saved: Preferences,
+    user: Option<UserPreferences>,
+    // Set when there are changes that haven't been written to disk yet
+    pending_write: Option<Instant>,
+}
+
+struct UserPreferences {
     file_path: AbsoluteSystemPathBuf,
     config: Preferences,
+    saved: Preferences,
+}
+
+/// A preference a TUI component keeps in `tui.json` under `name`, without
+/// needing a field in `Preferences`.
+///
+/// Keys are declared as constants next to the component that uses them, and
+/// their names mustn't be the same as a field of `Preferences`.
+pub struct PreferenceKey<T> {
+    name: &'static str,
+    value: PhantomData<fn() -> T>,
+}
+
+impl<T> PreferenceKey<T> {
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            value: PhantomData,
+        }
+    }
+}
+
+/// The path of the user's preferences, or `None` if there's no config
+/// directory
+pub fn user_preferences_path() -> Option<AbsoluteSystemPathBuf> {
+    let config_dir = turborepo_dirs::config_dir().ok()??;
+    Some(config_dir.join_components(USER_TUI_PREFERENCES_PATH_COMPONENTS))
 }
 
 impl PreferenceLoader {
+    /// Loads only the repository's preferences
     pub fn new(repo_root: &AbsoluteSystemPathBuf) -> Result<Self, Error> {
         let file_path = repo_root.join_components(TUI_PREFERENCES_PATH_COMPONENTS);
-        let contents = file_path.read_existing_to_string()?;
-        let config = contents
-            .map(|string| serde_json::from_str(&string))
-            .transpose()?
-            .unwrap_or_default();
+        let config = read_layer(&file_path)?;
+
+        Ok(Self {
+            repo_root: repo_root.clone(),
+            file_path,
+            saved: config.clone(),
+            config,
+            user: None,
+            pending_write: None,
+        })
+    }
 
-        Ok(Self { file_path, config })
+    /// Loads the repository's preferences over the user's preferences at
+    /// `user_file_path`
+    pub fn with_user_preferences(
+        repo_root: &AbsoluteSystemPathBuf,
+        user_file_path: Option<AbsoluteSystemPathBuf>,
+    ) -> Result<Self, Error> {
+        let mut loader = Self::new(repo_root)?;
+        loader.user = user_file_path
+            .map(|file_path| {
+                let config = read_layer(&file_path)?;
+                Ok::<_, Error>(UserPreferences {
+                    file_path,
+                    saved: config.clone(),
+                    config,
+                })
+            })
+            .transpose()?;
+        Ok(loader)
+    }
+
+    fn user_config(&self) -> Option<&Preferences> {
+        self.user.as_ref().map(|user| &user.config)
     }
 
     pub fn is_task_list_visible(&self) -> bool {
-        self.config.is_task_list_visible.unwrap_or(true)
+        self.config
+            .is_task_list_visible
+            .or_else(|| self.user_config()?.is_task_list_visible)
+            .unwrap_or(true)
     }
 
+    /// Changes whether the task list is visible in the file it's set in, or
+    /// in the user's preferences if it isn't set in either
     pub fn set_is_task_list_visible(&mut self, value: Option<bool>) {
-        self.config.is_task_list_visible = value;
+        match &mut self.user {
+            Some(user) if self.config.is_task_list_visible.is_none() => {
+                user.config.is_task_list_visible = value;
+            }
+            _ => self.config.is_task_list_visible = value,
+        }
+        self.schedule_write();
     }
 
     pub fn active_task(&self) -> Option<&str> {
@@ -43,38 +178,715 @@ impl PreferenceLoader {
 
     pub fn set_active_task(&mut self, value: Option<String>) -> Result<(), Error> {
         self.config.active_task = value;
+        self.schedule_write();
         Ok(())
     }
 
-    pub fn flush_to_disk(&self) -> Result<(), Error> {
-        self.file_path.ensure_dir()?;
-        self.file_path
-            .create_with_contents(serde_json::to_string_pretty(&self.config)?)?;
+    pub fn last_task_for_filter(&self, filter: &str) -> Option<&str> {
+        let last_task_by_filter = self.config.last_task_by_filter.as_ref()?;
+        last_task_by_filter.get(filter).map(|task| task.as_str())
+    }
+
+    pub fn set_last_task_for_filter(&mut self, filter: String, task: String) {
+        self.config
+            .last_task_by_filter
+            .get_or_insert_with(HashMap::new)
+            .insert(filter, task);
+        self.schedule_write();
+    }
+
+    /// Drops remembered selections for tasks that aren't in `tasks` or that the
+    /// filter no longer matches.
+    pub fn prune_last_task_by_filter<'a>(&mut self, tasks: impl IntoIterator<Item = &'a str>) {
+        let Some(last_task_by_filter) = self.config.last_task_by_filter.as_mut() else {
+            return;
+        };
+        let tasks = tasks.into_iter().collect::<HashSet<_>>();
+        let len = last_task_by_filter.len();
+        last_task_by_filter
+            .retain(|filter, task| tasks.contains(task.as_str()) && task.contains(filter.as_str()));
+        if last_task_by_filter.len() != len {
+            self.schedule_write();
+        }
+    }
+
+    pub fn palette(&self) -> Palette {
+        let palette = self
+            .config
+            .palette
+            .as_deref()
+            .or_else(|| self.user_config()?.palette.as_deref());
+        match palette {
+            Some("colorblind") => Palette::Colorblind,
+            _ => Palette::Default,
+        }
+    }
 
+    /// The theme to draw the TUI with. Themes defined in the repository's
+    /// preferences replace the user's themes of the same name. Without a
+    /// theme, the palette picks between the dark and colorblind themes.
+    pub fn theme(&self) -> Theme {
+        let Some(name) = self
+            .config
+            .theme
+            .as_deref()
+            .or_else(|| self.user_config()?.theme.as_deref())
+        else {
+            return match self.palette() {
+                Palette::Default => Theme::DARK,
+                Palette::Colorblind => Theme::COLORBLIND,
+            };
+        };
+        let user_theme = iter::once(&self.config)
+            .chain(self.user_config())
+            .find_map(|config| config.themes.as_ref()?.get(name));
+        match user_theme {
+            Some(config) => Theme::from_config(name, config),
+            None => Theme::built_in(name).unwrap_or_else(|| {
+                warn!("unknown theme '{name}', using 'dark'");
+                Theme::DARK
+            }),
+        }
+    }
+
+    /// The maximum number of log lines kept per task, `None` if unlimited
+    pub fn max_log_lines(&self) -> Option<usize> {
+        let max_log_lines = self
+            .config
+            .max_log_lines
+            .or_else(|| self.user_config()?.max_log_lines);
+        match max_log_lines {
+            None => Some(DEFAULT_MAX_LOG_LINES),
+            Some(0) => None,
+            Some(max_log_lines) => Some(max_log_lines),
+        }
+    }
+
+    /// Whether `task`'s logs scroll to show new output, either because it's
+    /// set for the task or for every task
+    pub fn follow_logs(&self, task: &str) -> bool {
+        self.config
+            .follow_logs_by_task
+            .as_ref()
+            .and_then(|follow_logs_by_task| follow_logs_by_task.get(task).copied())
+            .unwrap_or_else(|| self.follow_all_logs())
+    }
+
+    fn follow_all_logs(&self) -> bool {
+        self.config
+            .follow_logs
+            .or_else(|| self.user_config()?.follow_logs)
+            .unwrap_or(true)
+    }
+
+    /// Sets whether `task`'s logs follow new output, overriding the setting
+    /// for every task
+    pub fn set_follow_logs(&mut self, task: &str, follow: bool) {
+        let follow_all_logs = self.follow_all_logs();
+        let follow_logs_by_task = self
+            .config
+            .follow_logs_by_task
+            .get_or_insert_with(HashMap::new);
+        if follow == follow_all_logs {
+            follow_logs_by_task.remove(task);
+        } else {
+            follow_logs_by_task.insert(task.to_owned(), follow);
+        }
+        if follow_logs_by_task.is_empty() {
+            self.config.follow_logs_by_task = None;
+        }
+        self.schedule_write();
+    }
+
+    /// Sets whether every task's logs follow new output, dropping the
+    /// overrides for individual tasks. It's changed in the file it's set in,
+    /// or in the user's preferences if it isn't set in either.
+    pub fn set_follow_all_logs(&mut self, follow: bool) {
+        match &mut self.user {
+            Some(user) if self.config.follow_logs.is_none() => {
+                user.config.follow_logs = Some(follow);
+            }
+            _ => self.config.follow_logs = Some(follow),
+        }
+        self.config.follow_logs_by_task = None;
+        self.schedule_write();
+    }
+
+    /// The directory that task logs are exported to. Relative paths are
+    /// relative to the repository root.
+    pub fn log_export_dir(&self) -> AbsoluteSystemPathBuf {
+        match self
+            .config
+            .log_export_dir
+            .as_deref()
+            .or_else(|| self.user_config()?.log_export_dir.as_deref())
+        {
+            Some(dir) => AbsoluteSystemPathBuf::from_unknown(&self.repo_root, dir),
+            None => self
+                .repo_root
+                .join_components(DEFAULT_LOG_EXPORT_PATH_COMPONENTS),
+        }
+    }
+
+    /// How much of each task's output is shown when `--output-logs` isn't
+    /// passed, `None` to use each task's `outputLogs`. It's kept as written,
+    /// since `--output-logs` is what knows the values.
+    pub fn output_logs(&self) -> Option<&str> {
+        self.config
+            .output_logs
+            .as_deref()
+            .or_else(|| self.user_config()?.output_logs.as_deref())
+    }
+
+    /// Changes the default output verbosity in the file it's set in, or in
+    /// the user's preferences if it isn't set in either
+    pub fn set_output_logs(&mut self, value: String) {
+        match &mut self.user {
+            Some(user) if self.config.output_logs.is_none() => {
+                user.config.output_logs = Some(value);
+            }
+            _ => self.config.output_logs = Some(value),
+        }
+        self.schedule_write();
+    }
+
+    /// How task logs are ordered outside of the TUI when `--log-order` isn't
+    /// passed or configured
+    pub fn log_order(&self) -> Option<&str> {
+        self.config
+            .log_order
+            .as_deref()
+            .or_else(|| self.user_config()?.log_order.as_deref())
+    }
+
+    /// Changes the default log order in the file it's set in, or in the
+    /// user's preferences if it isn't set in either
+    pub fn set_log_order(&mut self, value: String) {
+        match &mut self.user {
+            Some(user) if self.config.log_order.is_none() => {
+                user.config.log_order = Some(value);
+            }
+            _ => self.config.log_order = Some(value),
+        }
+        self.schedule_write();
+    }
+
+    /// The patterns that count lines of task output as warnings and errors
+    pub fn diagnostic_patterns(&self) -> DiagnosticPatterns {
+        let warning = self
+            .config
+            .warning_pattern
+            .as_deref()
+            .or_else(|| self.user_config()?.warning_pattern.as_deref());
+        let error = self
+            .config
+            .error_pattern
+            .as_deref()
+            .or_else(|| self.user_config()?.error_pattern.as_deref());
+        DiagnosticPatterns::new(warning, error)
+    }
+
+    pub fn vim_mode(&self) -> bool {
+        self.config
+            .vim_mode
+            .or_else(|| self.user_config()?.vim_mode)
+            .unwrap_or(false)
+    }
+
+    pub fn completion_notification(&self) -> CompletionNotification {
+        self.config
+            .completion_notification
+            .or_else(|| self.user_config()?.completion_notification)
+            .unwrap_or_default()
+    }
+
+    pub fn repo_root(&self) -> &AbsoluteSystemPath {
+        &self.repo_root
+    }
+
+    /// The default keybindings with any user overrides applied. The
+    /// repository's keybindings override the user's one action at a time. In
+    /// vim mode, the keys it handles are moved off of their actions.
+    pub fn keybindings(&self) -> Keybindings {
+        let mut overrides = self
+            .user_config()
+            .and_then(|user| user.keybindings.clone())
+            .unwrap_or_default();
+        overrides.extend(
+            self.config
+                .keybindings
+                .iter()
+                .flatten()
+                .map(|(action, key)| (action.clone(), key.clone())),
+        );
+        let keybindings = Keybindings::with_overrides(Some(&overrides));
+        if self.vim_mode() {
+            keybindings.with_vim_mode()
+        } else {
+            keybindings
+        }
+    }
+
+    /// Gets the preference stored under `key`, or `None` if it isn't set or
+    /// isn't a `T`. Like other preferences, the repository's value is used
+    /// over the user's.
+    pub fn get<T: DeserializeOwned>(&self, key: &PreferenceKey<T>) -> Option<T> {
+        iter::once(&self.config)
+            .chain(self.user_config())
+            .filter_map(|config| config.unknown_fields.get(key.name))
+            .find_map(|value| serde_json::from_value(value.clone()).ok())
+    }
+
+    /// Sets the preference stored under `key` in the repository's
+    /// preferences, or removes it if `value` is `None`. The change is written
+    /// along with any other changes made before the next write.
+    pub fn set<T: Serialize>(
+        &mut self,
+        key: &PreferenceKey<T>,
+        value: Option<T>,
+    ) -> Result<(), Error> {
+        match value {
+            Some(value) => {
+                self.config
+                    .unknown_fields
+                    .insert(key.name.to_owned(), serde_json::to_value(value)?);
+            }
+            None => {
+                self.config.unknown_fields.remove(key.name);
+            }
+        }
+        self.schedule_write();
+        Ok(())
+    }
+
+    fn schedule_write(&mut self) {
+        self.pending_write.get_or_insert_with(Instant::now);
+    }
+
+    /// Writes pending changes to disk once they've had time to settle. Should
+    /// be called regularly, a final `flush_to_disk` is still needed on
+    /// shutdown.
+    ///
+    /// This is called from the render loop, so it never waits for another
+    /// process's lock. The write stays pending and is retried on the next call
+    /// instead.
+    pub fn flush_if_due(&mut self) -> Result<(), Error> {
+        if self
+            .pending_write
+            .map_or(false, |since| since.elapsed() >= WRITE_DEBOUNCE_DELAY)
+        {
+            match self.write_pending(Duration::ZERO) {
+                Err(Error::Lock(err)) => debug!("preferences are locked, retrying later: {err}"),
+                result => result?,
+            }
+        }
+        Ok(())
+    }
+
+    pub fn flush_to_disk(&mut self) -> Result<(), Error> {
+        self.write_pending(LOCK_TIMEOUT)
+    }
+
+    fn write_pending(&mut self, lock_timeout: Duration) -> Result<(), Error> {
+        save_changes(
+            &self.file_path,
+            lock_timeout,
+            &mut self.config,
+            &mut self.saved,
+        )?;
+        // Unlike the repository's file, the user's file is only created once
+        // something is set in it
+        if let Some(user) = self.user.as_mut().filter(|user| user.config != user.saved) {
+            save_changes(
+                &user.file_path,
+                lock_timeout,
+                &mut user.config,
+                &mut user.saved,
+            )?;
+        }
+        self.pending_write = None;
         Ok(())
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Writes the changes from `saved` to `config` over what's currently in the
+/// file, so changes other turbo processes made in the meantime are kept
+/// unless the same preference was changed here. Afterwards, both `config` and
+/// `saved` are what was written.
+fn save_changes(
+    file_path: &AbsoluteSystemPath,
+    lock_timeout: Duration,
+    config: &mut Preferences,
+    saved: &mut Preferences,
+) -> Result<(), Error> {
+    let merged = update_preferences(file_path, lock_timeout, |current| {
+        let Some(mut current) = current else {
+            return config.clone();
+        };
+        take_if_changed(
+            &mut current.is_task_list_visible,
+            &saved.is_task_list_visible,
+            &config.is_task_list_visible,
+        );
+        take_if_changed(
+            &mut current.active_task,
+            &saved.active_task,
+            &config.active_task,
+        );
+        take_if_changed(
+            &mut current.last_task_by_filter,
+            &saved.last_task_by_filter,
+            &config.last_task_by_filter,
+        );
+        take_if_changed(&mut current.palette, &saved.palette, &config.palette);
+        take_if_changed(&mut current.theme, &saved.theme, &config.theme);
+        take_if_changed(&mut current.themes, &saved.themes, &config.themes);
+        take_if_changed(
+            &mut current.max_log_lines,
+            &saved.max_log_lines,
+            &config.max_log_lines,
+        );
+        take_if_changed(
+            &mut current.follow_logs,
+            &saved.follow_logs,
+            &config.follow_logs,
+        );
+        take_if_changed(
+            &mut current.follow_logs_by_task,
+            &saved.follow_logs_by_task,
+            &config.follow_logs_by_task,
+        );
+        take_if_changed(
+            &mut current.log_export_dir,
+            &saved.log_export_dir,
+            &config.log_export_dir,
+        );
+        take_if_changed(
+            &mut current.output_logs,
+            &saved.output_logs,
+            &config.output_logs,
+        );
+        take_if_changed(&mut current.log_order, &saved.log_order, &config.log_order);
+        take_if_changed(
+            &mut current.warning_pattern,
+            &saved.warning_pattern,
+            &config.warning_pattern,
+        );
+        take_if_changed(
+            &mut current.error_pattern,
+            &saved.error_pattern,
+            &config.error_pattern,
+        );
+        take_if_changed(&mut current.vim_mode, &saved.vim_mode, &config.vim_mode);
+        take_if_changed(
+            &mut current.completion_notification,
+            &saved.completion_notification,
+            &config.completion_notification,
+        );
+        take_if_changed(
+            &mut current.keybindings,
+            &saved.keybindings,
+            &config.keybindings,
+        );
+        let names = saved
+            .unknown_fields
+            .keys()
+            .chain(config.unknown_fields.keys())
+            .cloned()
+            .collect::<HashSet<_>>();
+        for name in names {
+            let value = config.unknown_fields.get(&name);
+            if saved.unknown_fields.get(&name) == value {
+                continue;
+            }
+            match value {
+                Some(value) => current.unknown_fields.insert(name, value.clone()),
+                None => current.unknown_fields.remove(&name),
+            };
+        }
+        current
+    })?;
+    *saved = merged.clone();
+    *config = merged;
+    Ok(())
+}
+
+fn take_if_changed<T: PartialEq + Clone>(current: &mut T, saved: &T, config: &T) {
+    if config != saved {
+        *current = config.clone();
+    }
+}
+
+/// Reads the preferences in one file. Unlike `Preferences::default`, nothing
+/// is set when the file doesn't exist, so other files' preferences aren't
+/// overridden.
+fn read_layer(file_path: &AbsoluteSystemPath) -> Result<Preferences, Error> {
+    Ok(file_path
+        .read_existing_to_string()?
+        .map(|contents| parse_preferences(&contents))
+        .transpose()?
+        .unwrap_or(Preferences {
+            is_task_list_visible: None,
+            ..Default::default()
+        }))
+}
+
+/// Parses preferences written by any version of turbo, migrating them to the
+/// current version
+fn parse_preferences(contents: &str) -> Result<Preferences, Error> {
+    preferences_from_value(serde_json::from_str(contents)?)
+}
+
+fn preferences_from_value(mut value: Value) -> Result<Preferences, Error> {
+    if let Value::Object(fields) = &mut value {
+        migrate(fields, MIGRATIONS);
+    }
+    Ok(serde_json::from_value(value)?)
+}
+
+/// Runs the migrations from the version in `fields` to the latest one.
+/// Preferences from newer versions of turbo are left as they are, their new
+/// fields end up in `unknown_fields`.
+fn migrate(fields: &mut Map<String, Value>, migrations: &[Migration]) {
+    let version = fields
+        .get("version")
+        .and_then(Value::as_u64)
+        .and_then(|version| usize::try_from(version).ok())
+        .unwrap_or(0);
+    let Some(pending) = migrations
+        .get(version..)
+        .filter(|pending| !pending.is_empty())
+    else {
+        return;
+    };
+    for migration in pending {
+        migration(fields);
+    }
+    fields.insert("version".to_owned(), migrations.len().into());
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
 pub struct Preferences {
+    /// The version of this file's format, which is used to migrate
+    /// preferences written by older versions of turbo
+    pub version: Option<u32>,
     pub is_task_list_visible: Option<bool>,
     pub active_task: Option<String>,
+    /// The task that was last selected while each search filter was active
+    pub last_task_by_filter: Option<HashMap<String, String>>,
+    /// Colors used for task statuses, either "default" or "colorblind".
+    /// `theme` is used instead when it's set.
+    pub palette: Option<String>,
+    /// The theme the TUI is drawn with, either a built-in theme ("dark",
+    /// "light" or "colorblind") or one of `themes`
+    pub theme: Option<String>,
+    /// User-defined themes, by name
+    pub themes: Option<HashMap<String, ThemeConfig>>,
+    /// Maximum number of log lines kept per task, 0 for unlimited
+    pub max_log_lines: Option<usize>,
+    /// Whether task logs scroll to show new output. Defaults to true.
+    pub follow_logs: Option<bool>,
+    /// Tasks whose logs don't follow new output the way `follow_logs` says
+    pub follow_logs_by_task: Option<HashMap<String, bool>>,
+    /// Where task logs are exported to, relative to the repository root.
+    /// Defaults to `.turbo/logs`.
+    pub log_export_dir: Option<String>,
+    /// How to let you know a run, or a rebuild in watch mode, finished:
+    /// "off", "bell" or "desktop". Defaults to "off".
+    pub completion_notification: Option<CompletionNotification>,
+    /// How much of each task's output is shown when `--output-logs` isn't
+    /// passed: "full", "none", "hash-only", "new-only" or "errors-only"
+    pub output_logs: Option<String>,
+    /// How task logs are ordered outside of the TUI when `--log-order` isn't
+    /// passed: "auto", "stream" or "grouped"
+    pub log_order: Option<String>,
+    /// A regex matching the lines of task output that are counted as warnings.
+    /// Defaults to lines containing "warn" or "warning".
+    pub warning_pattern: Option<String>,
+    /// A regex matching the lines of task output that are counted as errors,
+    /// which takes precedence over `warning_pattern`. Defaults to lines
+    /// containing "error".
+    pub error_pattern: Option<String>,
+    /// Navigates with hjkl, `gg`, `G` and counts like `5j` instead of the
+    /// keybindings for those keys
+    pub vim_mode: Option<bool>,
+    /// Maps action names, e.g. `next_task`, to the key that triggers them
+    pub keybindings: Option<HashMap<String, String>>,
+    /// The preferences stored with a `PreferenceKey`, and fields this version
+    /// of turbo doesn't know about. They're kept around so that settings
+    /// written by newer versions survive a round trip.
+    #[serde(flatten)]
+    pub unknown_fields: Map<String, Value>,
+}
+
+/// The set of colors used to render task statuses
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Palette {
+    #[default]
+    Default,
+    /// Avoids distinguishing statuses by red and green
+    Colorblind,
 }
 
 impl Default for Preferences {
     fn default() -> Self {
         Self {
+            version: Some(PREFERENCES_VERSION),
             active_task: None,
             is_task_list_visible: Some(true),
+            last_task_by_filter: None,
+            palette: None,
+            theme: None,
+            themes: None,
+            max_log_lines: None,
+            follow_logs: None,
+            follow_logs_by_task: None,
+            log_export_dir: None,
+            completion_notification: None,
+            output_logs: None,
+            log_order: None,
+            warning_pattern: None,
+            error_pattern: None,
+            vim_mode: None,
+            keybindings: None,
+            unknown_fields: Map::new(),
+        }
+    }
+}
+
+impl Preferences {
+    /// JSON Schema describing `tui.json`, derived from this struct
+    pub fn json_schema() -> Value {
+        serde_json::to_value(schemars::schema_for!(Preferences))
+            .expect("JSON schema should serialize to a value")
+    }
+
+    /// Serializes the repository's current `tui.json` into a portable JSON
+    /// blob that can be shared with `Preferences::import`.
+    pub fn export(repo_root: &AbsoluteSystemPath) -> Result<String, Error> {
+        let file_path = repo_root.join_components(TUI_PREFERENCES_PATH_COMPONENTS);
+        let preferences = file_path
+            .read_existing_to_string()?
+            .map(|contents| parse_preferences(&contents))
+            .transpose()?
+            .unwrap_or_default();
+
+        Ok(serde_json::to_string_pretty(&preferences)?)
+    }
+
+    /// Validates a blob produced by `Preferences::export` and writes it to the
+    /// repository's `tui.json`, replacing the existing preferences.
+    pub fn import(repo_root: &AbsoluteSystemPath, blob: &str) -> Result<(), Error> {
+        let value: Value = serde_json::from_str(blob)?;
+        if !value.is_object() {
+            return Err(Error::InvalidProfile);
         }
+        let preferences = preferences_from_value(value)?;
+
+        let file_path = repo_root.join_components(TUI_PREFERENCES_PATH_COMPONENTS);
+        update_preferences(&file_path, LOCK_TIMEOUT, |_| preferences)?;
+        Ok(())
     }
 }
 
+/// Replaces the preferences in `file_path` with the result of `update`, which
+/// is given the current preferences, or `None` if there aren't any or they
+/// can't be read. The file is locked while it's updated, so concurrent turbo
+/// processes can't undo each other's changes. If another process holds the
+/// lock, this waits for up to `lock_timeout` for it to be released.
+///
+/// Preferences that can't be parsed are kept in `tui.json.bak` rather than
+/// being lost when they're replaced.
+fn update_preferences(
+    file_path: &AbsoluteSystemPath,
+    lock_timeout: Duration,
+    update: impl FnOnce(Option<Preferences>) -> Preferences,
+) -> Result<Preferences, Error> {
+    file_path.ensure_dir()?;
+    let _lock = lock(file_path, lock_timeout)?;
+    let contents = file_path.read_existing_to_string().ok().flatten();
+    let current = match contents.as_deref().map(parse_preferences) {
+        Some(Ok(current)) => Some(current),
+        Some(Err(err)) => {
+            let backup_path = sibling_path(file_path, "bak");
+            warn!("unable to parse {file_path}, moving it to {backup_path}: {err}");
+            write_atomically(&backup_path, contents.as_deref().unwrap_or_default())?;
+            None
+        }
+        None => None,
+    };
+    let preferences = update(current);
+    write_preferences(file_path, &preferences)?;
+    Ok(preferences)
+}
+
+/// Locks `file_path` against other turbo processes, waiting for up to
+/// `timeout` if another process holds the lock. A zero timeout only tries
+/// once. Locks held by processes that exited are taken over.
+fn lock(file_path: &AbsoluteSystemPath, timeout: Duration) -> Result<pidlock::Pidlock, Error> {
+    let lock_path = sibling_path(file_path, "lock");
+    let deadline = Instant::now() + timeout;
+    loop {
+        let mut lock = pidlock::Pidlock::new(lock_path.as_std_path().to_owned());
+        match lock.acquire() {
+            Ok(()) => return Ok(lock),
+            Err(_) if Instant::now() < deadline => std::thread::sleep(LOCK_RETRY_DELAY),
+            Err(err) => return Err(err.into()),
+        }
+    }
+}
+
+/// Writes preferences to `file_path`, alongside a schema for editors to use if
+/// this is the first time the file is being created. Callers should hold the
+/// file's lock.
+fn write_preferences(
+    file_path: &AbsoluteSystemPath,
+    preferences: &Preferences,
+) -> Result<(), Error> {
+    if !file_path.exists() {
+        let schema_path = file_path
+            .parent()
+            .expect("preferences file has a parent directory")
+            .join_component(TUI_PREFERENCES_SCHEMA_FILE);
+        write_atomically(
+            &schema_path,
+            &serde_json::to_string_pretty(&Preferences::json_schema())?,
+        )?;
+    }
+    write_atomically(file_path, &serde_json::to_string_pretty(preferences)?)
+}
+
+/// Writes to a temporary sibling file and renames it over `file_path` so
+/// readers never observe a partially written file.
+fn write_atomically(file_path: &AbsoluteSystemPath, contents: &str) -> Result<(), Error> {
+    file_path.ensure_dir()?;
+    let tmp_path = sibling_path(file_path, "tmp");
+    tmp_path.create_with_contents(contents)?;
+    tmp_path.rename(file_path)?;
+
+    Ok(())
+}
+
+/// The path next to `file_path` with `extension` appended to its name, e.g.
+/// `tui.json.lock`
+fn sibling_path(file_path: &AbsoluteSystemPath, extension: &str) -> AbsoluteSystemPathBuf {
+    file_path
+        .parent()
+        .expect("preferences file has a parent directory")
+        .join_component(&format!(
+            "{}.{extension}",
+            file_path.file_name().unwrap_or("preferences")
+        ))
+}
+
 #[cfg(test)]
 mod test {
+    use crossterm::event::KeyCode;
+    use ratatui::style::Color;
     use tempfile::tempdir;
 
     use super::*;
+    use crate::tui::keybindings::Action;
 
     fn create_loader(repo_root: AbsoluteSystemPathBuf) -> PreferenceLoader {
         PreferenceLoader::new(&repo_root).expect("Failed to create PreferenceLoader")
@@ -126,6 +938,7 @@ mod test {
         let preferences = Preferences {
             active_task: Some("web#dev".to_owned()),
             is_task_list_visible: Some(false),
+            ..Default::default()
         };
 
         loader
@@ -156,6 +969,7 @@ mod test {
         let preferences = Preferences {
             active_task: Some("web#dev".to_owned()),
             is_task_list_visible: Some(false),
+            ..Default::default()
         };
 
         loader
@@ -169,4 +983,678 @@ mod test {
         let task = PreferenceLoader::new(&repo_root).expect("Failed to create PreferenceLoader");
         assert!(!task.is_task_list_visible());
     }
+
+    #[test]
+    fn export_import_round_trip() {
+        let source_tmp = tempdir().expect("Failed to create tempdir");
+        let source_root = AbsoluteSystemPathBuf::try_from(source_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+        let mut loader = create_loader(source_root.clone());
+        loader.set_is_task_list_visible(Some(false));
+        loader
+            .set_active_task(Some("web#dev".to_owned()))
+            .expect("Failed to set active task");
+        loader.flush_to_disk().expect("Failed to flush preferences");
+
+        let blob = Preferences::export(&source_root).expect("Failed to export preferences");
+
+        let dest_tmp = tempdir().expect("Failed to create tempdir");
+        let dest_root = AbsoluteSystemPathBuf::try_from(dest_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+        Preferences::import(&dest_root, &blob).expect("Failed to import preferences");
+
+        let imported = create_loader(dest_root);
+        assert!(!imported.is_task_list_visible());
+        assert_eq!(imported.active_task(), Some("web#dev"));
+    }
+
+    #[test]
+    fn import_preserves_unknown_fields() {
+        let repo_root_tmp = tempdir().expect("Failed to create tempdir");
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+
+        Preferences::import(
+            &repo_root,
+            r#"{ "is_task_list_visible": false, "from_the_future": { "a": 1 } }"#,
+        )
+        .expect("Failed to import preferences");
+
+        let blob = Preferences::export(&repo_root).expect("Failed to export preferences");
+        let exported: Value = serde_json::from_str(&blob).expect("Export should be valid JSON");
+        assert_eq!(exported["from_the_future"], serde_json::json!({ "a": 1 }));
+        assert_eq!(exported["is_task_list_visible"], Value::Bool(false));
+    }
+
+    #[test]
+    fn import_rejects_non_object() {
+        let repo_root_tmp = tempdir().expect("Failed to create tempdir");
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+
+        assert!(matches!(
+            Preferences::import(&repo_root, "[]"),
+            Err(Error::InvalidProfile)
+        ));
+        assert!(!repo_root
+            .join_components(TUI_PREFERENCES_PATH_COMPONENTS)
+            .exists());
+    }
+
+    #[test]
+    fn keybinding_overrides_are_loaded() {
+        let repo_root_tmp = tempdir().expect("Failed to create tempdir");
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+
+        Preferences::import(
+            &repo_root,
+            r#"{ "keybindings": { "quit": "q", "toggle_task_list": "nope" } }"#,
+        )
+        .expect("Failed to import preferences");
+
+        let keybindings = create_loader(repo_root).keybindings();
+        assert_eq!(keybindings.action(KeyCode::Char('q')), Some(Action::Quit));
+        // Invalid key leaves the default binding in place
+        assert_eq!(
+            keybindings.action(KeyCode::Char('h')),
+            Some(Action::ToggleTaskList)
+        );
+    }
+
+    #[test]
+    fn repo_preferences_override_user_preferences() {
+        let repo_root_tmp = tempdir().expect("Failed to create tempdir");
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+        let user_tmp = tempdir().expect("Failed to create tempdir");
+        let user_dir = AbsoluteSystemPathBuf::try_from(user_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+
+        Preferences::import(
+            &user_dir,
+            r#"{ "palette": "colorblind", "max_log_lines": 10, "keybindings": { "quit": "x", "next_task": "n" } }"#,
+        )
+        .expect("Failed to import preferences");
+        Preferences::import(
+            &repo_root,
+            r#"{ "max_log_lines": 20, "keybindings": { "quit": "q" } }"#,
+        )
+        .expect("Failed to import preferences");
+
+        let loader = PreferenceLoader::with_user_preferences(
+            &repo_root,
+            Some(user_dir.join_components(TUI_PREFERENCES_PATH_COMPONENTS)),
+        )
+        .expect("Failed to create PreferenceLoader");
+        assert_eq!(loader.palette(), Palette::Colorblind);
+        assert_eq!(loader.max_log_lines(), Some(20));
+        let keybindings = loader.keybindings();
+        assert_eq!(keybindings.action(KeyCode::Char('q')), Some(Action::Quit));
+        assert_eq!(keybindings.action(KeyCode::Char('x')), None);
+        assert_eq!(
+            keybindings.action(KeyCode::Char('n')),
+            Some(Action::NextTask)
+        );
+    }
+
+    #[test]
+    fn task_list_visibility_is_written_where_its_set() {
+        let repo_root_tmp = tempdir().expect("Failed to create tempdir");
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+        let user_tmp = tempdir().expect("Failed to create tempdir");
+        let user_file = AbsoluteSystemPathBuf::try_from(user_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf")
+            .join_component("tui.json");
+        let load = || {
+            PreferenceLoader::with_user_preferences(&repo_root, Some(user_file.clone()))
+                .expect("Failed to create PreferenceLoader")
+        };
+
+        let mut loader = load();
+        loader.set_is_task_list_visible(Some(false));
+        loader
+            .set_active_task(Some("web#dev".to_owned()))
+            .expect("Failed to set active task");
+        loader.flush_to_disk().expect("Failed to flush preferences");
+        let user: Value =
+            serde_json::from_str(&user_file.read_to_string().expect("Failed to read file"))
+                .expect("Preferences should be valid JSON");
+        assert_eq!(user["is_task_list_visible"], Value::Bool(false));
+        assert_eq!(user["active_task"], Value::Null);
+        assert!(
+            create_loader(repo_root.clone()).is_task_list_visible(),
+            "repository's file doesn't set it"
+        );
+        assert_eq!(
+            create_loader(repo_root.clone()).active_task(),
+            Some("web#dev")
+        );
+        assert!(!load().is_task_list_visible());
+
+        Preferences::import(&repo_root, r#"{ "is_task_list_visible": false }"#)
+            .expect("Failed to import preferences");
+        let mut loader = load();
+        loader.set_is_task_list_visible(Some(true));
+        loader.flush_to_disk().expect("Failed to flush preferences");
+        assert!(create_loader(repo_root.clone()).is_task_list_visible());
+        let user: Value =
+            serde_json::from_str(&user_file.read_to_string().expect("Failed to read file"))
+                .expect("Preferences should be valid JSON");
+        assert_eq!(user["is_task_list_visible"], Value::Bool(false));
+    }
+
+    #[test]
+    fn follow_logs_can_be_set_per_task() {
+        let repo_root_tmp = tempdir().expect("Failed to create tempdir");
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+        let user_tmp = tempdir().expect("Failed to create tempdir");
+        let user_file = AbsoluteSystemPathBuf::try_from(user_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf")
+            .join_component("tui.json");
+        let load = || {
+            PreferenceLoader::with_user_preferences(&repo_root, Some(user_file.clone()))
+                .expect("Failed to create PreferenceLoader")
+        };
+
+        let mut loader = load();
+        assert!(loader.follow_logs("web#dev"), "logs follow by default");
+        loader.set_follow_logs("web#dev", false);
+        loader.flush_to_disk().expect("Failed to flush preferences");
+        let loader = load();
+        assert!(!loader.follow_logs("web#dev"));
+        assert!(loader.follow_logs("docs#dev"));
+
+        let mut loader = load();
+        loader.set_follow_all_logs(false);
+        assert!(!loader.follow_logs("docs#dev"));
+        loader.set_follow_logs("docs#dev", true);
+        loader.set_follow_logs("web#dev", false);
+        loader.flush_to_disk().expect("Failed to flush preferences");
+        let user: Value =
+            serde_json::from_str(&user_file.read_to_string().expect("Failed to read file"))
+                .expect("Preferences should be valid JSON");
+        assert_eq!(user["follow_logs"], Value::Bool(false));
+        assert_eq!(user["follow_logs_by_task"], Value::Null);
+        let loader = load();
+        assert!(loader.follow_logs("docs#dev"));
+        assert!(!loader.follow_logs("web#dev"));
+        assert_eq!(
+            loader.config.follow_logs_by_task,
+            Some([("docs#dev".to_owned(), true)].into_iter().collect()),
+            "only overrides are kept"
+        );
+    }
+
+    #[test]
+    fn output_preferences_are_saved_for_the_user() {
+        let repo_root_tmp = tempdir().expect("Failed to create tempdir");
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+        let user_tmp = tempdir().expect("Failed to create tempdir");
+        let user_file = AbsoluteSystemPathBuf::try_from(user_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf")
+            .join_component("tui.json");
+        let load = || {
+            PreferenceLoader::with_user_preferences(&repo_root, Some(user_file.clone()))
+                .expect("Failed to create PreferenceLoader")
+        };
+
+        let mut loader = load();
+        assert_eq!(loader.output_logs(), None);
+        assert_eq!(loader.log_order(), None);
+        loader.set_output_logs("errors-only".to_owned());
+        loader.set_log_order("grouped".to_owned());
+        loader.flush_to_disk().expect("Failed to flush preferences");
+        let user: Value =
+            serde_json::from_str(&user_file.read_to_string().expect("Failed to read file"))
+                .expect("Preferences should be valid JSON");
+        assert_eq!(user["output_logs"], Value::from("errors-only"));
+        assert_eq!(user["log_order"], Value::from("grouped"));
+
+        Preferences::import(&repo_root, r#"{"log_order": "stream"}"#)
+            .expect("Failed to import preferences");
+        let loader = load();
+        assert_eq!(loader.output_logs(), Some("errors-only"));
+        assert_eq!(loader.log_order(), Some("stream"));
+    }
+
+    #[test]
+    fn concurrent_writes_keep_each_others_changes() {
+        let repo_root_tmp = tempdir().expect("Failed to create tempdir");
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+        let mut watch = create_loader(repo_root.clone());
+        let mut run = create_loader(repo_root.clone());
+
+        watch
+            .set_active_task(Some("web#dev".to_owned()))
+            .expect("Failed to set active task");
+        watch.flush_to_disk().expect("Failed to flush preferences");
+        run.set_is_task_list_visible(Some(false));
+        run.flush_to_disk().expect("Failed to flush preferences");
+
+        let written = create_loader(repo_root);
+        assert_eq!(written.active_task(), Some("web#dev"));
+        assert!(!written.is_task_list_visible());
+        assert_eq!(run.active_task(), Some("web#dev"));
+    }
+
+    #[test]
+    fn flush_if_due_does_not_wait_for_lock() {
+        let repo_root_tmp = tempdir().expect("Failed to create tempdir");
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+        let mut loader = create_loader(repo_root.clone());
+        let lock_path = repo_root.join_components(&[".turbo", "preferences", "tui.json.lock"]);
+        lock_path.ensure_dir().expect("Failed to create directory");
+        // This process is alive, so the lock is held
+        lock_path
+            .create_with_contents(std::process::id().to_string())
+            .expect("Failed to create lock");
+
+        loader
+            .set_active_task(Some("web#dev".to_owned()))
+            .expect("Failed to set active task");
+        std::thread::sleep(WRITE_DEBOUNCE_DELAY);
+        let start = Instant::now();
+        loader.flush_if_due().expect("Failed to flush preferences");
+        assert!(
+            start.elapsed() < LOCK_TIMEOUT,
+            "should not wait for the lock"
+        );
+        assert!(!loader.file_path.exists(), "write should be blocked");
+        assert!(loader.pending_write.is_some(), "write should stay pending");
+
+        lock_path.remove_file().expect("Failed to remove lock");
+        loader.flush_if_due().expect("Failed to flush preferences");
+        assert_eq!(create_loader(repo_root).active_task(), Some("web#dev"));
+        assert!(loader.pending_write.is_none());
+    }
+
+    #[test]
+    fn takes_over_stale_lock() {
+        let repo_root_tmp = tempdir().expect("Failed to create tempdir");
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+        let mut loader = create_loader(repo_root.clone());
+        let lock_path = repo_root.join_components(&[".turbo", "preferences", "tui.json.lock"]);
+        lock_path.ensure_dir().expect("Failed to create directory");
+        // No process has this pid, so the lock is stale
+        lock_path
+            .create_with_contents(i32::MAX.to_string())
+            .expect("Failed to create lock");
+
+        loader
+            .set_active_task(Some("web#dev".to_owned()))
+            .expect("Failed to set active task");
+        loader.flush_to_disk().expect("Failed to flush preferences");
+        assert_eq!(create_loader(repo_root).active_task(), Some("web#dev"));
+        assert!(!lock_path.exists(), "lock is released");
+    }
+
+    #[test]
+    fn prunes_stale_last_task_by_filter() {
+        let repo_root_tmp = tempdir().expect("Failed to create tempdir");
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+        let mut loader = create_loader(repo_root);
+
+        loader.set_last_task_for_filter("web".to_owned(), "web#dev".to_owned());
+        loader.set_last_task_for_filter("docs".to_owned(), "docs#dev".to_owned());
+        loader.set_last_task_for_filter("lint".to_owned(), "web#dev".to_owned());
+        loader.prune_last_task_by_filter(["web#dev", "web#build"]);
+
+        assert_eq!(loader.last_task_for_filter("web"), Some("web#dev"));
+        assert_eq!(loader.last_task_for_filter("docs"), None, "task is gone");
+        assert_eq!(
+            loader.last_task_for_filter("lint"),
+            None,
+            "filter no longer matches"
+        );
+    }
+
+    #[test]
+    fn palette_defaults_when_unrecognized() {
+        let repo_root_tmp = tempdir().expect("Failed to create tempdir");
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+
+        assert_eq!(create_loader(repo_root.clone()).palette(), Palette::Default);
+
+        Preferences::import(&repo_root, r#"{ "palette": "colorblind" }"#)
+            .expect("Failed to import preferences");
+        assert_eq!(
+            create_loader(repo_root.clone()).palette(),
+            Palette::Colorblind
+        );
+
+        Preferences::import(&repo_root, r#"{ "palette": "sepia" }"#)
+            .expect("Failed to import preferences");
+        assert_eq!(create_loader(repo_root).palette(), Palette::Default);
+    }
+
+    #[test]
+    fn log_export_dir_is_relative_to_the_repo() {
+        let repo_root_tmp = tempdir().expect("Failed to create tempdir");
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+
+        assert_eq!(
+            create_loader(repo_root.clone()).log_export_dir(),
+            repo_root.join_components(&[".turbo", "logs"])
+        );
+
+        Preferences::import(&repo_root, r#"{ "log_export_dir": "tmp/logs" }"#)
+            .expect("Failed to import preferences");
+        assert_eq!(
+            create_loader(repo_root.clone()).log_export_dir(),
+            repo_root.join_components(&["tmp", "logs"])
+        );
+
+        let elsewhere = repo_root.join_component("elsewhere");
+        Preferences::import(
+            &repo_root,
+            &serde_json::json!({ "log_export_dir": elsewhere.as_str() }).to_string(),
+        )
+        .expect("Failed to import preferences");
+        assert_eq!(create_loader(repo_root).log_export_dir(), elsewhere);
+    }
+
+    #[test]
+    fn themes_come_from_either_file() {
+        let repo_root_tmp = tempdir().expect("Failed to create tempdir");
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+        let user_file_path = repo_root.join_components(&["config", "turborepo", "tui.json"]);
+        user_file_path
+            .ensure_dir()
+            .expect("Failed to create directory");
+        user_file_path
+            .create_with_contents(
+                r#"{ "theme": "mine", "themes": { "mine": { "extends": "light", "success": "cyan" } } }"#,
+            )
+            .expect("Failed to create file");
+        let load = || {
+            PreferenceLoader::with_user_preferences(&repo_root, Some(user_file_path.clone()))
+                .expect("Failed to create PreferenceLoader")
+        };
+
+        let theme = load().theme();
+        assert_eq!(theme.success, Color::Cyan);
+        assert_eq!(theme.failure, Theme::LIGHT.failure);
+
+        // A theme of the same name in the repository replaces the user's
+        Preferences::import(
+            &repo_root,
+            r#"{ "palette": "colorblind", "themes": { "mine": { "failure": "magenta" } } }"#,
+        )
+        .expect("Failed to import preferences");
+        let theme = load().theme();
+        assert_eq!(theme.success, Theme::DARK.success);
+        assert_eq!(theme.failure, Color::Magenta);
+
+        Preferences::import(&repo_root, r#"{ "theme": "light" }"#)
+            .expect("Failed to import preferences");
+        assert_eq!(load().theme(), Theme::LIGHT);
+        Preferences::import(&repo_root, r#"{ "theme": "sepia" }"#)
+            .expect("Failed to import preferences");
+        assert_eq!(load().theme(), Theme::DARK);
+        Preferences::import(&repo_root, r#"{ "palette": "colorblind" }"#)
+            .expect("Failed to import preferences");
+        assert_eq!(create_loader(repo_root.clone()).theme(), Theme::COLORBLIND);
+    }
+
+    #[test]
+    fn writes_are_debounced() {
+        let repo_root_tmp = tempdir().expect("Failed to create tempdir");
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+        let mut loader = create_loader(repo_root.clone());
+
+        loader.flush_if_due().expect("Failed to flush preferences");
+        assert!(!loader.file_path.exists(), "nothing to write");
+
+        loader.set_is_task_list_visible(Some(false));
+        loader
+            .set_active_task(Some("web#dev".to_owned()))
+            .expect("Failed to set active task");
+        loader.flush_if_due().expect("Failed to flush preferences");
+        assert!(
+            !loader.file_path.exists(),
+            "write should wait for the delay"
+        );
+
+        std::thread::sleep(WRITE_DEBOUNCE_DELAY);
+        loader.flush_if_due().expect("Failed to flush preferences");
+        let written = create_loader(repo_root);
+        assert!(!written.is_task_list_visible());
+        assert_eq!(written.active_task(), Some("web#dev"));
+        assert!(loader.pending_write.is_none());
+    }
+
+    #[test]
+    fn max_log_lines_zero_is_unlimited() {
+        let repo_root_tmp = tempdir().expect("Failed to create tempdir");
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+
+        assert_eq!(
+            create_loader(repo_root.clone()).max_log_lines(),
+            Some(DEFAULT_MAX_LOG_LINES)
+        );
+
+        Preferences::import(&repo_root, r#"{ "max_log_lines": 0 }"#)
+            .expect("Failed to import preferences");
+        assert_eq!(create_loader(repo_root.clone()).max_log_lines(), None);
+
+        Preferences::import(&repo_root, r#"{ "max_log_lines": 100 }"#)
+            .expect("Failed to import preferences");
+        assert_eq!(create_loader(repo_root).max_log_lines(), Some(100));
+    }
+
+    #[test]
+    fn json_schema_describes_fields() {
+        let schema = Preferences::json_schema();
+        let properties = schema["properties"]
+            .as_object()
+            .expect("schema should have properties");
+        assert!(properties.contains_key("is_task_list_visible"));
+        assert!(properties.contains_key("keybindings"));
+        assert!(!properties.contains_key("unknown_fields"));
+    }
+
+    #[test]
+    fn writes_schema_on_first_write() {
+        let repo_root_tmp = tempdir().expect("Failed to create tempdir");
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+        let mut loader = create_loader(repo_root.clone());
+        loader.flush_to_disk().expect("Failed to flush preferences");
+
+        let schema_path =
+            repo_root.join_components(&[".turbo", "preferences", TUI_PREFERENCES_SCHEMA_FILE]);
+        let schema: Value =
+            serde_json::from_str(&schema_path.read_to_string().expect("Failed to read schema"))
+                .expect("Schema should be valid JSON");
+        assert_eq!(schema, Preferences::json_schema());
+    }
+
+    #[test]
+    fn versions_unversioned_preferences() {
+        let repo_root_tmp = tempdir().expect("Failed to create tempdir");
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+        let file_path = repo_root.join_components(TUI_PREFERENCES_PATH_COMPONENTS);
+        file_path.ensure_dir().expect("Failed to create directory");
+        file_path
+            .create_with_contents(r#"{ "active_task": "web#dev" }"#)
+            .expect("Failed to create file");
+
+        let mut loader = create_loader(repo_root.clone());
+        assert_eq!(loader.active_task(), Some("web#dev"));
+        loader.flush_to_disk().expect("Failed to flush preferences");
+
+        let written: Value =
+            serde_json::from_str(&file_path.read_to_string().expect("Failed to read file"))
+                .expect("Preferences should be valid JSON");
+        assert_eq!(written["version"], PREFERENCES_VERSION);
+        assert_eq!(written["active_task"], "web#dev");
+    }
+
+    #[test]
+    fn migrates_from_the_files_version() {
+        let migrations: &[Migration] = &[
+            |fields| {
+                fields.insert("added_in_v1".to_owned(), true.into());
+            },
+            |fields| {
+                if let Some(colors) = fields.remove("colors") {
+                    fields.insert("palette".to_owned(), colors);
+                }
+            },
+        ];
+        let mut fields = serde_json::json!({ "version": 1, "colors": "colorblind" })
+            .as_object()
+            .cloned()
+            .expect("fields are an object");
+
+        migrate(&mut fields, migrations);
+        assert_eq!(
+            Value::Object(fields.clone()),
+            serde_json::json!({ "version": 2, "palette": "colorblind" })
+        );
+
+        // Preferences that are already migrated are left alone
+        let migrated = fields.clone();
+        migrate(&mut fields, migrations);
+        assert_eq!(fields, migrated);
+    }
+
+    #[test]
+    fn keeps_preferences_from_newer_versions() {
+        let repo_root_tmp = tempdir().expect("Failed to create tempdir");
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+        let newer_version = PREFERENCES_VERSION + 1;
+        Preferences::import(
+            &repo_root,
+            &format!(r#"{{ "version": {newer_version}, "layout": "split" }}"#),
+        )
+        .expect("Failed to import preferences");
+
+        let mut loader = create_loader(repo_root.clone());
+        loader
+            .set_active_task(Some("web#dev".to_owned()))
+            .expect("Failed to set active task");
+        loader.flush_to_disk().expect("Failed to flush preferences");
+
+        let written = create_loader(repo_root).config;
+        assert_eq!(written.version, Some(newer_version));
+        assert_eq!(written.active_task.as_deref(), Some("web#dev"));
+        assert_eq!(written.unknown_fields["layout"], "split");
+    }
+
+    #[test]
+    fn backs_up_unparseable_preferences() {
+        let repo_root_tmp = tempdir().expect("Failed to create tempdir");
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+        let file_path = repo_root.join_components(TUI_PREFERENCES_PATH_COMPONENTS);
+        file_path.ensure_dir().expect("Failed to create directory");
+        let unparseable = r#"{ "max_log_lines": "lots" }"#;
+        file_path
+            .create_with_contents(unparseable)
+            .expect("Failed to create file");
+
+        Preferences::import(&repo_root, r#"{ "max_log_lines": 100 }"#)
+            .expect("Failed to import preferences");
+        assert_eq!(create_loader(repo_root).max_log_lines(), Some(100));
+        assert_eq!(
+            sibling_path(&file_path, "bak")
+                .read_to_string()
+                .expect("Failed to read backup"),
+            unparseable
+        );
+    }
+
+    const FOLLOW_OUTPUT: PreferenceKey<bool> = PreferenceKey::new("follow_output");
+    const COLUMN_WIDTHS: PreferenceKey<Vec<u16>> = PreferenceKey::new("column_widths");
+
+    #[test]
+    fn stores_preferences_by_key() {
+        let repo_root_tmp = tempdir().expect("Failed to create tempdir");
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+        let mut loader = create_loader(repo_root.clone());
+        assert_eq!(loader.get(&FOLLOW_OUTPUT), None);
+
+        loader
+            .set(&FOLLOW_OUTPUT, Some(false))
+            .expect("Failed to set preference");
+        loader
+            .set(&COLUMN_WIDTHS, Some(vec![20, 80]))
+            .expect("Failed to set preference");
+        assert_eq!(loader.get(&FOLLOW_OUTPUT), Some(false));
+        loader.flush_to_disk().expect("Failed to flush preferences");
+
+        let mut written = create_loader(repo_root.clone());
+        assert_eq!(written.get(&FOLLOW_OUTPUT), Some(false));
+        assert_eq!(written.get(&COLUMN_WIDTHS), Some(vec![20, 80]));
+
+        written
+            .set(&COLUMN_WIDTHS, None)
+            .expect("Failed to set preference");
+        written
+            .flush_to_disk()
+            .expect("Failed to flush preferences");
+        assert_eq!(create_loader(repo_root).get(&COLUMN_WIDTHS), None);
+    }
+
+    #[test]
+    fn preferences_by_key_fall_back_to_the_user() {
+        let repo_root_tmp = tempdir().expect("Failed to create tempdir");
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+        let user_file_path = repo_root.join_components(&["config", "turborepo", "tui.json"]);
+        user_file_path
+            .ensure_dir()
+            .expect("Failed to create directory");
+        user_file_path
+            .create_with_contents(r#"{ "follow_output": false, "column_widths": "wide" }"#)
+            .expect("Failed to create file");
+
+        let mut loader = PreferenceLoader::with_user_preferences(&repo_root, Some(user_file_path))
+            .expect("Failed to create PreferenceLoader");
+        assert_eq!(loader.get(&FOLLOW_OUTPUT), Some(false));
+        assert_eq!(loader.get(&COLUMN_WIDTHS), None, "value isn't a Vec<u16>");
+
+        loader
+            .set(&FOLLOW_OUTPUT, Some(true))
+            .expect("Failed to set preference");
+        assert_eq!(loader.get(&FOLLOW_OUTPUT), Some(true));
+    }
+
+    #[test]
+    fn concurrent_writes_keep_each_others_preferences_by_key() {
+        let repo_root_tmp = tempdir().expect("Failed to create tempdir");
+        let repo_root = AbsoluteSystemPathBuf::try_from(repo_root_tmp.path())
+            .expect("Failed to create AbsoluteSystemPathBuf");
+        let mut watch = create_loader(repo_root.clone());
+        let mut run = create_loader(repo_root.clone());
+
+        watch
+            .set(&FOLLOW_OUTPUT, Some(false))
+            .expect("Failed to set preference");
+        watch.flush_to_disk().expect("Failed to flush preferences");
+        run.set(&COLUMN_WIDTHS, Some(vec![20, 80]))
+            .expect("Failed to set preference");
+        run.flush_to_disk().expect("Failed to flush preferences");
+
+        let written = create_loader(repo_root);
+        assert_eq!(written.get(&FOLLOW_OUTPUT), Some(false));
+        assert_eq!(written.get(&COLUMN_WIDTHS), Some(vec![20, 80]));
+    }
 }
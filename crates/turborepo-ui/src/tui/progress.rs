@@ -0,0 +1,233 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::Widget,
+};
+
+use super::{event::TaskResult, table::format_duration, task::TasksByStatus, theme::Theme};
+
+// The bar is squeezed between its text, but never narrower than this
+const MIN_BAR_WIDTH: usize = 10;
+const MAX_BAR_WIDTH: usize = 40;
+
+/// How far along a run is, shown under the task list and logs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RunProgress {
+    pub completed: usize,
+    pub running: usize,
+    pub pending: usize,
+    /// Completed tasks that failed
+    pub failed: usize,
+    /// Completed tasks that were cache hits
+    pub cached: usize,
+    /// How long until every task finishes, estimated from how long the tasks
+    /// took before. `None` if none of the unfinished tasks have run before.
+    pub eta: Option<Duration>,
+}
+
+impl RunProgress {
+    /// Counts the tasks in `tasks`, estimating how long is left from
+    /// `durations`, the last duration of each task that wasn't a cache hit
+    pub fn new(tasks: &TasksByStatus, durations: &HashMap<String, Duration>, now: Instant) -> Self {
+        let count_results = |result| {
+            tasks
+                .finished
+                .iter()
+                .filter(|task| task.result() == result)
+                .count()
+        };
+        Self {
+            completed: tasks.finished.len(),
+            running: tasks.running.len(),
+            pending: tasks.planned.len(),
+            failed: count_results(TaskResult::Failure),
+            cached: count_results(TaskResult::CacheHit),
+            eta: estimate_remaining(tasks, durations, now),
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.completed + self.running + self.pending
+    }
+
+    fn percent(&self) -> usize {
+        match self.total() {
+            0 => 0,
+            total => self.completed * 100 / total,
+        }
+    }
+}
+
+/// Estimates how long the unfinished tasks will take. Tasks that haven't run
+/// before are assumed to take as long as the average task that has, and the
+/// work left is assumed to be shared by as many tasks as are running now.
+fn estimate_remaining(
+    tasks: &TasksByStatus,
+    durations: &HashMap<String, Duration>,
+    now: Instant,
+) -> Option<Duration> {
+    if tasks.running.is_empty() && tasks.planned.is_empty() {
+        return None;
+    }
+    let known = tasks
+        .running
+        .iter()
+        .map(|task| task.name())
+        .chain(tasks.planned.iter().map(|task| task.name()))
+        .filter_map(|task| durations.get(task))
+        .collect::<Vec<_>>();
+    if known.is_empty() {
+        return None;
+    }
+    let average = known.iter().copied().sum::<Duration>() / known.len() as u32;
+    let duration = |task: &str| durations.get(task).copied().unwrap_or(average);
+
+    let running = tasks.running.iter().map(|task| {
+        duration(task.name()).saturating_sub(now.saturating_duration_since(task.start()))
+    });
+    let planned = tasks.planned.iter().map(|task| duration(task.name()));
+    let remaining = running.chain(planned).sum::<Duration>();
+    Some(remaining / tasks.running.len().max(1) as u32)
+}
+
+/// A line with the counts from a `RunProgress` and a bar showing how many
+/// tasks have completed
+pub struct ProgressBar {
+    progress: RunProgress,
+    theme: Theme,
+}
+
+impl ProgressBar {
+    pub fn new(progress: RunProgress, theme: Theme) -> Self {
+        Self { progress, theme }
+    }
+
+    fn bar(&self, width: usize) -> String {
+        let filled = match self.progress.total() {
+            0 => 0,
+            total => self.progress.completed * width / total,
+        };
+        format!("{}{}", "█".repeat(filled), "░".repeat(width - filled))
+    }
+}
+
+impl Widget for &ProgressBar {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let RunProgress {
+            completed,
+            running,
+            pending,
+            failed,
+            cached,
+            eta,
+        } = self.progress;
+        let dim = Style::default().add_modifier(Modifier::DIM);
+        let mut counts = vec![
+            Span::raw(format!(" {completed}/{} done", self.progress.total())),
+            Span::styled(format!("   {running} running   {pending} pending"), dim),
+        ];
+        if failed > 0 {
+            counts.push(Span::styled(
+                format!("   {failed} failed"),
+                Style::default().fg(self.theme.failure),
+            ));
+        }
+        if cached > 0 {
+            counts.push(Span::styled(
+                format!("   {cached} cached"),
+                Style::default().fg(self.theme.cache_hit),
+            ));
+        }
+        let mut summary = format!(" {}%", self.progress.percent());
+        if let Some(eta) = eta {
+            summary.push_str(&format!("   ETA {}", format_duration(eta)));
+        }
+
+        let text_width = counts.iter().map(Span::width).sum::<usize>() + summary.chars().count();
+        let bar_width = usize::from(area.width)
+            .saturating_sub(text_width + 3)
+            .clamp(MIN_BAR_WIDTH, MAX_BAR_WIDTH);
+        counts.push(Span::raw("   "));
+        counts.push(Span::styled(
+            self.bar(bar_width),
+            Style::default().fg(self.theme.success),
+        ));
+        counts.push(Span::raw(summary));
+        Line::from(counts).render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tui::task::Task;
+
+    fn tasks() -> TasksByStatus {
+        let mut tasks = TasksByStatus {
+            running: Vec::new(),
+            planned: ["lint", "test", "new"]
+                .into_iter()
+                .map(|name| Task::new(name.to_owned()))
+                .collect(),
+            finished: Vec::new(),
+            status_filter: Default::default(),
+            group_by_package: false,
+            collapsed_packages: Default::default(),
+        };
+        tasks.running.push(Task::new("build".to_owned()).start());
+        tasks.running.push(Task::new("docs".to_owned()).start());
+        tasks.finished.push(
+            Task::new("cached".to_owned())
+                .start()
+                .finish(TaskResult::CacheHit),
+        );
+        tasks
+    }
+
+    #[test]
+    fn test_counts_tasks() {
+        let progress = RunProgress::new(&tasks(), &HashMap::new(), Instant::now());
+        assert_eq!(
+            progress,
+            RunProgress {
+                completed: 1,
+                running: 2,
+                pending: 3,
+                failed: 0,
+                cached: 1,
+                eta: None,
+            }
+        );
+        assert_eq!(progress.percent(), 16);
+    }
+
+    #[test]
+    fn test_estimates_remaining_time() {
+        let durations = [
+            ("build", Duration::from_secs(30)),
+            ("lint", Duration::from_secs(10)),
+            ("test", Duration::from_secs(20)),
+        ]
+        .into_iter()
+        .map(|(task, duration)| (task.to_owned(), duration))
+        .collect();
+        let tasks = tasks();
+        let now = tasks.running[0].start() + Duration::from_secs(10);
+        let progress = RunProgress::new(&tasks, &durations, now);
+        // docs and new haven't run before, so they take the average of 20s.
+        // build has 20s left, docs about 10s, then lint, test and new take
+        // 50s, and that's split between the two running tasks.
+        let eta = progress.eta.expect("tasks have run before");
+        assert!(
+            (Duration::from_secs(40)..Duration::from_secs(41)).contains(&eta),
+            "{eta:?}"
+        );
+    }
+}
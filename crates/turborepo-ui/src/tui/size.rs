@@ -1,10 +1,19 @@
 use crate::TaskTable;
 
 const PANE_SIZE_RATIO: f32 = 3.0 / 4.0;
+// Limits on how far the task list can be resized
+const MIN_TASK_LIST_WIDTH: u16 = 10;
+const MIN_PANE_COLS: u16 = 20;
+const TASK_LIST_RESIZE_STEP: u16 = 2;
+// The run's progress is shown on the last row, below the task list and logs
+const PROGRESS_ROWS: u16 = 1;
 
 #[derive(Debug, Clone, Copy)]
 pub struct SizeInfo {
     task_width_hint: u16,
+    // Set once the task list has been resized, replacing the width that fits
+    // the task names
+    task_list_width: Option<u16>,
     rows: u16,
     cols: u16,
 }
@@ -16,16 +25,49 @@ impl SizeInfo {
             rows,
             cols,
             task_width_hint,
+            task_list_width: None,
         }
     }
 
+    /// Use a task list width that was picked by resizing it
+    pub fn with_task_list_width(mut self, task_list_width: Option<u16>) -> Self {
+        self.task_list_width = task_list_width;
+        self
+    }
+
+    /// Widens or narrows the task list by a step from the width it's shown
+    /// at, so resizing past a limit doesn't need to be undone, returning its
+    /// new width
+    pub fn resize_task_list(&mut self, grow: bool) -> u16 {
+        let width = self.task_list_width();
+        self.set_task_list_width(if grow {
+            width.saturating_add(TASK_LIST_RESIZE_STEP)
+        } else {
+            width.saturating_sub(TASK_LIST_RESIZE_STEP)
+        })
+    }
+
+    /// Resizes the task list to `width`, returning it. The width is only
+    /// limited when the task list is laid out, so a width that doesn't fit in
+    /// a small terminal is still used once the terminal is large enough.
+    pub fn set_task_list_width(&mut self, width: u16) -> u16 {
+        self.task_list_width = Some(width);
+        width
+    }
+
     pub fn resize(&mut self, rows: u16, cols: u16) {
         self.rows = rows;
         self.cols = cols;
     }
 
+    /// The rows above the run's progress, which the task list and logs are
+    /// shown in
+    pub fn content_rows(&self) -> u16 {
+        self.rows.saturating_sub(PROGRESS_ROWS)
+    }
+
     pub fn pane_rows(&self) -> u16 {
-        self.rows
+        self.content_rows()
             // Account for header and footer in layout
             .saturating_sub(2)
             // Always allocate at least one row as vt100 crashes if emulating a zero area terminal
@@ -36,7 +78,21 @@ impl SizeInfo {
         self.cols - self.pane_cols()
     }
 
+    /// The widths of the left and right panes when the logs are split, which
+    /// are separated by a one column divider
+    pub fn split_pane_cols(&self) -> (u16, u16) {
+        let cols = self.pane_cols();
+        let left = cols.saturating_sub(1) / 2;
+        (left.max(1), cols.saturating_sub(left + 1).max(1))
+    }
+
     pub fn pane_cols(&self) -> u16 {
+        if let Some(task_list_width) = self.task_list_width {
+            let task_list_width = task_list_width
+                .min(self.cols.saturating_sub(MIN_PANE_COLS))
+                .max(MIN_TASK_LIST_WIDTH);
+            return self.cols.saturating_sub(task_list_width);
+        }
         // Want to maximize pane width
         let ratio_pane_width = (f32::from(self.cols) * PANE_SIZE_RATIO) as u16;
         let full_task_width = self.cols.saturating_sub(self.task_width_hint);
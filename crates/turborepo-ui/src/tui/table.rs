@@ -1,11 +1,22 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
 use ratatui::{
     layout::{Constraint, Rect},
-    style::{Color, Modifier, Style, Stylize},
-    text::Text,
+    style::{Modifier, Style, Stylize},
+    text::{Line, Span, Text},
     widgets::{Block, Borders, Cell, Row, StatefulWidget, Table, TableState},
 };
 
-use super::{event::TaskResult, spinner::SpinnerState, task::TasksByStatus};
+use super::{
+    diagnostics::DiagnosticCounts,
+    event::{CacheRestore, CacheSource, TaskResult},
+    spinner::SpinnerState,
+    task::{Finished, Task, TaskRow, TasksByStatus},
+    theme::Theme,
+};
 
 /// A widget that renders a table of their tasks and their current status
 ///
@@ -16,13 +27,24 @@ use super::{event::TaskResult, spinner::SpinnerState, task::TasksByStatus};
 ///   - failed tasks
 ///   - successful tasks
 ///   - cached tasks
+///
+/// When grouping by package, each package's tasks are in that order under a
+/// header for the package.
 pub struct TaskTable<'b> {
     tasks_by_type: &'b TasksByStatus,
     spinner: SpinnerState,
+    theme: Theme,
+    // Only tasks with warnings or errors in their output
+    diagnostics: HashMap<&'b str, DiagnosticCounts>,
 }
 
 const TASK_NAVIGATE_INSTRUCTIONS: &str = "↑ ↓ - Select";
 const MORE_BINDS_INSTRUCTIONS: &str = "m - More binds";
+// Fits the longest formatted duration, e.g. "99h59m", with the cache source
+// of a cache hit and a leading space
+const DURATION_WIDTH: u16 = 9;
+// Shown before the number of warnings in a task's output
+const WARNING_SYMBOL: &str = "▲";
 
 impl<'b> TaskTable<'b> {
     /// Construct a new table with all of the planned tasks
@@ -30,9 +52,29 @@ impl<'b> TaskTable<'b> {
         Self {
             tasks_by_type,
             spinner: SpinnerState::default(),
+            theme: Theme::default(),
+            diagnostics: HashMap::new(),
         }
     }
 
+    /// Shows how many warnings and errors are in each task's output
+    pub fn with_diagnostics(
+        mut self,
+        diagnostics: impl IntoIterator<Item = (&'b str, DiagnosticCounts)>,
+    ) -> Self {
+        self.diagnostics = diagnostics
+            .into_iter()
+            .filter(|(_, counts)| !counts.is_empty())
+            .collect();
+        self
+    }
+
+    /// Use the given theme's colors
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
     /// Provides a suggested width for the task table
     pub fn width_hint<'a>(tasks: impl Iterator<Item = &'a str>) -> u16 {
         let task_name_width = tasks
@@ -42,8 +84,8 @@ impl<'b> TaskTable<'b> {
             // Task column width should be large enough to fit "↑ ↓ to navigate instructions
             // and truncate tasks with more than 40 chars.
             .clamp(TASK_NAVIGATE_INSTRUCTIONS.len(), 40) as u16;
-        // Add space for column divider and status emoji
-        task_name_width + 1
+        // Add space for the duration, column divider and status emoji
+        task_name_width + DURATION_WIDTH + 1
     }
 
     /// Update the current time of the table
@@ -51,45 +93,180 @@ impl<'b> TaskTable<'b> {
         self.spinner.update();
     }
 
-    fn finished_rows(&self) -> impl Iterator<Item = Row> + '_ {
-        self.tasks_by_type.finished.iter().map(move |task| {
-            let name = if matches!(task.result(), TaskResult::CacheHit) {
-                Cell::new(Text::styled(task.name(), Style::default().italic()))
-            } else {
-                Cell::new(task.name())
-            };
-
-            Row::new(vec![
-                name,
-                match task.result() {
-                    // matches Next.js (and many other CLI tools) https://github.com/vercel/next.js/blob/1a04d94aaec943d3cce93487fea3b8c8f8898f31/packages/next/src/build/output/log.ts
-                    TaskResult::Success => {
-                        Cell::new(Text::styled("✓", Style::default().green().bold()))
-                    }
-                    TaskResult::CacheHit => {
-                        Cell::new(Text::styled("⊙", Style::default().magenta()))
-                    }
-                    TaskResult::Failure => {
-                        Cell::new(Text::styled("⨯", Style::default().red().bold()))
-                    }
-                },
-            ])
-        })
-    }
-
-    fn running_rows(&self) -> impl Iterator<Item = Row> + '_ {
+    fn rows(&self) -> Vec<Row> {
         let spinner = self.spinner.current();
+        let now = Instant::now();
+        let grouped = self.tasks_by_type.group_by_package;
         self.tasks_by_type
-            .running
-            .iter()
-            .map(move |task| Row::new(vec![Cell::new(task.name()), Cell::new(Text::raw(spinner))]))
+            .rows()
+            .into_iter()
+            .map(|row| match row {
+                TaskRow::Package {
+                    package,
+                    tasks,
+                    collapsed,
+                    is_running,
+                    has_failure,
+                    ..
+                } => self.package_row(package, tasks, collapsed, is_running, has_failure),
+                TaskRow::Running(task) => Row::new(vec![
+                    Cell::new(display_name(task.name(), grouped)),
+                    self.diagnostics_cell(task.name()),
+                    Cell::new(Text::raw(format_duration(now - task.start())).right_aligned()),
+                    Cell::new(Text::raw(spinner)),
+                ]),
+                TaskRow::Planned(task) => Row::new(vec![
+                    Cell::new(display_name(task.name(), grouped)),
+                    self.diagnostics_cell(task.name()),
+                    Cell::new(""),
+                    Cell::new(" "),
+                ]),
+                TaskRow::Finished(task) => self.finished_row(task, grouped),
+            })
+            .collect()
     }
 
-    fn planned_rows(&self) -> impl Iterator<Item = Row> + '_ {
-        self.tasks_by_type
-            .planned
-            .iter()
-            .map(move |task| Row::new(vec![Cell::new(task.name()), Cell::new(" ")]))
+    fn finished_row(&self, task: &Task<Finished>, grouped: bool) -> Row<'static> {
+        let name = display_name(task.name(), grouped);
+        let name = if matches!(task.result(), TaskResult::CacheHit) {
+            Cell::new(Text::styled(name, Style::default().italic()))
+        } else {
+            Cell::new(name)
+        };
+
+        let duration = if matches!(task.result(), TaskResult::CacheHit) {
+            Text::styled(
+                cache_hit_duration(task.cache_restore()),
+                Style::default().fg(self.theme.cache_hit),
+            )
+        } else {
+            Text::styled(
+                format_duration(task.end() - task.start()),
+                Style::default().add_modifier(Modifier::DIM),
+            )
+        };
+
+        Row::new(vec![
+            name,
+            self.diagnostics_cell(task.name()),
+            Cell::new(duration.right_aligned()),
+            self.status_cell(task.result()),
+        ])
+    }
+
+    fn diagnostics_text(&self, task: &str) -> Option<Line<'static>> {
+        let DiagnosticCounts { warnings, errors } = self.diagnostics.get(task)?;
+        let mut spans = Vec::new();
+        if *warnings > 0 {
+            spans.push(Span::styled(
+                format!(" {WARNING_SYMBOL}{warnings}"),
+                Style::default().fg(self.theme.warning),
+            ));
+        }
+        if *errors > 0 {
+            spans.push(Span::styled(
+                format!(" {}{errors}", self.theme.failure_symbol),
+                Style::default().fg(self.theme.failure),
+            ));
+        }
+        Some(Line::from(spans).right_aligned())
+    }
+
+    fn diagnostics_cell(&self, task: &str) -> Cell<'static> {
+        self.diagnostics_text(task).map_or(Cell::new(""), Cell::new)
+    }
+
+    // Fits the widest counts, or nothing if no task has any
+    fn diagnostics_width(&self) -> u16 {
+        self.diagnostics
+            .keys()
+            .filter_map(|task| self.diagnostics_text(task))
+            .map(|line| line.width())
+            .max()
+            .map_or(0, |width| u16::try_from(width).unwrap_or(u16::MAX))
+    }
+
+    /// A package's header. Collapsed packages show whether any of their
+    /// tasks are running or failed.
+    fn package_row(
+        &self,
+        package: &str,
+        tasks: usize,
+        collapsed: bool,
+        is_running: bool,
+        has_failure: bool,
+    ) -> Row<'static> {
+        let name = if collapsed {
+            format!("▸ {package} ({tasks})")
+        } else {
+            format!("▾ {package}")
+        };
+        let status = match (collapsed, is_running, has_failure) {
+            (false, _, _) => Cell::new(" "),
+            (true, true, _) => Cell::new(Text::raw(self.spinner.current())),
+            (true, false, true) => self.status_cell(TaskResult::Failure),
+            (true, false, false) => Cell::new(" "),
+        };
+        Row::new(vec![
+            Cell::new(Text::styled(name, Style::default().bold())),
+            Cell::new(""),
+            Cell::new(""),
+            status,
+        ])
+    }
+
+    fn status_cell(&self, result: TaskResult) -> Cell<'static> {
+        let theme = self.theme;
+        match result {
+            TaskResult::Success => {
+                Cell::new(Text::styled("✓", Style::default().fg(theme.success).bold()))
+            }
+            TaskResult::CacheHit => {
+                Cell::new(Text::styled("⊙", Style::default().fg(theme.cache_hit)))
+            }
+            TaskResult::Failure => Cell::new(Text::styled(
+                theme.failure_symbol,
+                Style::default().fg(theme.failure).bold(),
+            )),
+        }
+    }
+}
+
+/// The name a task is shown with. Under a package's header, the package is
+/// left out, e.g. `web#build` is shown as `build`.
+fn display_name(task: &str, grouped: bool) -> String {
+    if !grouped {
+        return task.to_owned();
+    }
+    let name = task.split_once('#').map_or(task, |(_, name)| name);
+    format!("  {name}")
+}
+
+/// Where a cache hit was restored from, `L` for local or `R` for remote, and
+/// how long restoring it took
+fn cache_hit_duration(cache_restore: Option<CacheRestore>) -> String {
+    let Some(CacheRestore { source, duration }) = cache_restore else {
+        return "cached".to_owned();
+    };
+    let source = match source {
+        CacheSource::Local => 'L',
+        CacheSource::Remote => 'R',
+    };
+    format!("{source} {}", format_duration(duration))
+}
+
+/// Formats how long a task has taken to fit in the duration column, e.g.
+/// `4.2s`, `3m07s` or `1h12m`. Anything from 100 hours on is shown as `>99h`.
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs < 60 {
+        format!("{secs}.{}s", duration.subsec_millis() / 100)
+    } else if secs < 60 * 60 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else if secs < 100 * 60 * 60 {
+        format!("{}h{:02}m", secs / (60 * 60), secs / 60 % 60)
+    } else {
+        ">99h".to_owned()
     }
 }
 
@@ -98,21 +275,27 @@ impl<'a> StatefulWidget for &'a TaskTable<'a> {
 
     fn render(self, area: Rect, buf: &mut ratatui::prelude::Buffer, state: &mut Self::State) {
         let table = Table::new(
-            self.running_rows()
-                .chain(self.planned_rows())
-                .chain(self.finished_rows()),
+            self.rows(),
             [
                 Constraint::Min(15),
+                Constraint::Length(self.diagnostics_width()),
+                Constraint::Length(DURATION_WIDTH),
                 // Status takes one cell to render
                 Constraint::Length(1),
             ],
         )
-        .highlight_style(Style::default().fg(Color::Yellow))
+        .highlight_style(Style::default().fg(self.theme.selection))
         .column_spacing(0)
-        .block(Block::new().borders(Borders::RIGHT))
+        .block(
+            Block::new()
+                .borders(Borders::RIGHT)
+                .border_style(Style::default().fg(self.theme.border)),
+        )
         .header(
             vec![Text::styled(
-                "Tasks",
+                self.tasks_by_type
+                    .status_filter
+                    .title(self.tasks_by_type.has_filter_matches()),
                 Style::default().add_modifier(Modifier::DIM),
             )]
             .into_iter()
@@ -133,3 +316,36 @@ impl<'a> StatefulWidget for &'a TaskTable<'a> {
         StatefulWidget::render(table, area, buf, state);
     }
 }
+
+#[cfg(test)]
+mod test {
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test_case(Duration::from_millis(0), "0.0s" ; "zero")]
+    #[test_case(Duration::from_millis(4250), "4.2s" ; "seconds")]
+    #[test_case(Duration::from_millis(59_990), "59.9s" ; "truncates")]
+    #[test_case(Duration::from_secs(187), "3m07s" ; "minutes")]
+    #[test_case(Duration::from_secs(60 * 60 - 1), "59m59s" ; "most minutes")]
+    #[test_case(Duration::from_secs(72 * 60 + 5), "1h12m" ; "hours")]
+    #[test_case(Duration::from_secs(100 * 60 * 60 - 1), "99h59m" ; "longest")]
+    #[test_case(Duration::from_secs(1000 * 60 * 60), ">99h" ; "too many hours")]
+    fn test_format_duration(duration: Duration, expected: &str) {
+        let formatted = format_duration(duration);
+        assert_eq!(formatted, expected);
+        assert!(formatted.len() < DURATION_WIDTH as usize);
+    }
+
+    #[test]
+    fn test_cache_hit_duration() {
+        assert_eq!(cache_hit_duration(None), "cached");
+        let restore = CacheRestore {
+            source: CacheSource::Remote,
+            duration: Duration::from_secs(100 * 60 * 60 - 1),
+        };
+        let formatted = cache_hit_duration(Some(restore));
+        assert_eq!(formatted, "R 99h59m");
+        assert!(formatted.len() < DURATION_WIDTH as usize);
+    }
+}
@@ -1,7 +1,16 @@
 #![allow(dead_code)]
-use std::{collections::HashSet, mem, time::Instant};
+use std::{
+    collections::{HashMap, HashSet},
+    iter, mem,
+    time::Instant,
+};
 
-use super::{event::TaskResult, Error};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    event::{CacheRestore, TaskResult},
+    Error,
+};
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
 pub struct Planned;
@@ -9,6 +18,7 @@ pub struct Planned;
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
 pub struct Running {
     start: Instant,
+    cache_restore: Option<CacheRestore>,
 }
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
@@ -16,6 +26,7 @@ pub struct Finished {
     start: Instant,
     end: Instant,
     result: TaskResult,
+    cache_restore: Option<CacheRestore>,
 }
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
@@ -49,6 +60,7 @@ impl Task<Planned> {
             name: self.name,
             state: Running {
                 start: Instant::now(),
+                cache_restore: None,
             },
         }
     }
@@ -58,7 +70,10 @@ impl Task<Running> {
     pub fn finish(self, result: TaskResult) -> Task<Finished> {
         let Task {
             name,
-            state: Running { start },
+            state: Running {
+                start,
+                cache_restore,
+            },
         } = self;
         Task {
             name,
@@ -66,6 +81,7 @@ impl Task<Running> {
                 start,
                 result,
                 end: Instant::now(),
+                cache_restore,
             },
         }
     }
@@ -74,6 +90,10 @@ impl Task<Running> {
         self.state.start
     }
 
+    pub fn set_cache_restore(&mut self, cache_restore: CacheRestore) {
+        self.state.cache_restore = Some(cache_restore);
+    }
+
     pub fn restart(self) -> Task<Planned> {
         Task {
             name: self.name,
@@ -95,6 +115,11 @@ impl Task<Finished> {
         self.state.result
     }
 
+    /// Where the task's outputs were restored from, if it was a cache hit
+    pub fn cache_restore(&self) -> Option<CacheRestore> {
+        self.state.cache_restore
+    }
+
     pub fn restart(self) -> Task<Planned> {
         Task {
             name: self.name,
@@ -110,11 +135,122 @@ pub struct TaskNamesByStatus {
     pub finished: Vec<String>,
 }
 
+/// Which tasks are shown in the task list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatusFilter {
+    #[default]
+    All,
+    Failed,
+    Running,
+    /// Tasks that are running or finished without a cache hit
+    CacheMiss,
+}
+
+impl StatusFilter {
+    fn shows_planned(self) -> bool {
+        self == StatusFilter::All
+    }
+
+    fn shows_running(self) -> bool {
+        self != StatusFilter::Failed
+    }
+
+    fn shows_finished(self, result: TaskResult) -> bool {
+        match self {
+            StatusFilter::All => true,
+            StatusFilter::Failed => result == TaskResult::Failure,
+            StatusFilter::Running => false,
+            StatusFilter::CacheMiss => result != TaskResult::CacheHit,
+        }
+    }
+
+    /// The title of the task list while filtering, depending on whether any
+    /// tasks match the filter
+    pub fn title(self, has_matches: bool) -> &'static str {
+        match (self, has_matches) {
+            (StatusFilter::All, _) => "Tasks",
+            (StatusFilter::Failed, true) => "Failed tasks",
+            (StatusFilter::Failed, false) => "No failed tasks",
+            (StatusFilter::Running, true) => "Running tasks",
+            (StatusFilter::Running, false) => "No running tasks",
+            (StatusFilter::CacheMiss, true) => "Cache misses",
+            (StatusFilter::CacheMiss, false) => "No cache misses",
+        }
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct TasksByStatus {
     pub running: Vec<Task<Running>>,
     pub planned: Vec<Task<Planned>>,
     pub finished: Vec<Task<Finished>>,
+    /// Hides the tasks that don't match from the displayed order. When no
+    /// tasks match, every task is shown so there's always one to select.
+    pub status_filter: StatusFilter,
+    /// Shows each package's tasks under a header for the package
+    pub group_by_package: bool,
+    /// Packages whose tasks are hidden under their header
+    pub collapsed_packages: HashSet<String>,
+}
+
+/// A row of the task list
+#[derive(Debug, Clone, Copy)]
+pub enum TaskRow<'a> {
+    /// The header of a package's tasks when grouping by package. `task` is
+    /// the first of them, which is shown while the header is selected.
+    Package {
+        package: &'a str,
+        task: &'a str,
+        tasks: usize,
+        collapsed: bool,
+        is_running: bool,
+        has_failure: bool,
+    },
+    Running(&'a Task<Running>),
+    Planned(&'a Task<Planned>),
+    Finished(&'a Task<Finished>),
+}
+
+impl<'a> TaskRow<'a> {
+    /// The task that's shown while the row is selected
+    pub fn task_name(&self) -> &'a str {
+        match self {
+            TaskRow::Package { task, .. } => task,
+            TaskRow::Running(task) => task.name(),
+            TaskRow::Planned(task) => task.name(),
+            TaskRow::Finished(task) => task.name(),
+        }
+    }
+
+    fn key(&self) -> RowKey {
+        match self {
+            TaskRow::Package { package, task, .. } => RowKey::Package {
+                package: package.to_string(),
+                task: task.to_string(),
+            },
+            row => RowKey::Task(row.task_name().to_owned()),
+        }
+    }
+}
+
+/// Identifies a row of the task list, so it can be found again after the
+/// list changes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RowKey {
+    Task(String),
+    /// A package's header, which is the row of its first task once the tasks
+    /// aren't grouped
+    Package {
+        package: String,
+        task: String,
+    },
+}
+
+/// The package a task belongs to, e.g. `web` for `web#build`. Tasks without a
+/// package are their own package.
+pub fn package_name(task: &str) -> &str {
+    task.split_once('#').map_or(task, |(package, _)| package)
 }
 
 impl TasksByStatus {
@@ -130,17 +266,139 @@ impl TasksByStatus {
         self.task_names_in_displayed_order().count()
     }
 
+    /// The name of the task shown for each row of the task list
     pub fn task_names_in_displayed_order(&self) -> impl DoubleEndedIterator<Item = &str> + '_ {
-        let running_names = self.running.iter().map(|task| task.name());
-        let planned_names = self.planned.iter().map(|task| task.name());
-        let finished_names = self.finished.iter().map(|task| task.name());
+        self.rows().into_iter().map(|row| row.task_name())
+    }
+
+    /// The rows of the task list. When grouping by package, packages are in
+    /// the order of their first task, so packages with running tasks are at
+    /// the top.
+    pub fn rows(&self) -> Vec<TaskRow<'_>> {
+        let tasks = self
+            .shown_running()
+            .map(TaskRow::Running)
+            .chain(self.shown_planned().map(TaskRow::Planned))
+            .chain(self.shown_finished().map(TaskRow::Finished));
+        if !self.group_by_package {
+            return tasks.collect();
+        }
+
+        let mut packages: Vec<(&str, Vec<TaskRow>)> = Vec::new();
+        let mut package_indices = HashMap::new();
+        for row in tasks {
+            let package = package_name(row.task_name());
+            let index = *package_indices.entry(package).or_insert_with(|| {
+                packages.push((package, Vec::new()));
+                packages.len() - 1
+            });
+            packages[index].1.push(row);
+        }
+
+        packages
+            .into_iter()
+            .flat_map(|(package, rows)| {
+                let collapsed = self.collapsed_packages.contains(package);
+                let header = TaskRow::Package {
+                    package,
+                    task: rows[0].task_name(),
+                    tasks: rows.len(),
+                    collapsed,
+                    is_running: rows.iter().any(|row| matches!(row, TaskRow::Running(_))),
+                    has_failure: rows.iter().any(|row| {
+                        matches!(row, TaskRow::Finished(task) if task.result() == TaskResult::Failure)
+                    }),
+                };
+                iter::once(header).chain(rows.into_iter().filter(move |_| !collapsed))
+            })
+            .collect()
+    }
+
+    /// The package of the row at `index`, if grouping by package
+    pub fn package_at(&self, index: usize) -> Option<&str> {
+        if !self.group_by_package {
+            return None;
+        }
+        let row = *self.rows().get(index)?;
+        Some(match row {
+            TaskRow::Package { package, .. } => package,
+            row => package_name(row.task_name()),
+        })
+    }
 
-        running_names.chain(planned_names).chain(finished_names)
+    pub fn row_key(&self, index: usize) -> Result<RowKey, Error> {
+        let rows = self.rows();
+        rows.get(index)
+            .map(TaskRow::key)
+            .ok_or(Error::TaskNotFoundIndex {
+                index,
+                len: rows.len(),
+            })
+    }
+
+    /// Finds the row identified by `key`. A package's header can only be found
+    /// while grouping, otherwise its first task is found instead.
+    pub fn row_index(&self, key: &RowKey) -> Option<usize> {
+        match key {
+            RowKey::Task(task) => self.active_index(task),
+            RowKey::Package { package, task } => self
+                .rows()
+                .iter()
+                .position(
+                    |row| matches!(row, TaskRow::Package { package: name, .. } if name == package),
+                )
+                .or_else(|| self.active_index(task)),
+        }
     }
 
+    /// Whether any tasks match the status filter
+    pub fn has_filter_matches(&self) -> bool {
+        let filter = self.status_filter;
+        (filter.shows_planned() && !self.planned.is_empty())
+            || (filter.shows_running() && !self.running.is_empty())
+            || self
+                .finished
+                .iter()
+                .any(|task| filter.shows_finished(task.result()))
+    }
+
+    fn displayed_filter(&self) -> StatusFilter {
+        if self.has_filter_matches() {
+            self.status_filter
+        } else {
+            StatusFilter::All
+        }
+    }
+
+    pub fn shown_running(&self) -> impl DoubleEndedIterator<Item = &Task<Running>> + '_ {
+        let shown = self.displayed_filter().shows_running();
+        self.running.iter().filter(move |_| shown)
+    }
+
+    pub fn shown_planned(&self) -> impl DoubleEndedIterator<Item = &Task<Planned>> + '_ {
+        let shown = self.displayed_filter().shows_planned();
+        self.planned.iter().filter(move |_| shown)
+    }
+
+    pub fn shown_finished(&self) -> impl DoubleEndedIterator<Item = &Task<Finished>> + '_ {
+        let filter = self.displayed_filter();
+        self.finished
+            .iter()
+            .filter(move |task| filter.shows_finished(task.result()))
+    }
+
+    /// The row of `task_name`, or the header of its package if the package
+    /// is collapsed
     pub fn active_index(&self, task_name: &str) -> Option<usize> {
-        self.task_names_in_displayed_order()
-            .position(|task| task == task_name)
+        let rows = self.rows();
+        rows.iter()
+            .position(|row| !matches!(row, TaskRow::Package { .. }) && row.task_name() == task_name)
+            .or_else(|| {
+                let package = package_name(task_name);
+                rows.iter().position(|row| {
+                    matches!(row, TaskRow::Package { package: name, collapsed: true, .. } if *name == package)
+                })
+            })
     }
 
     pub fn task_name(&self, index: usize) -> Result<&str, Error> {
@@ -290,6 +548,9 @@ mod test {
                 running: Vec::new(),
                 planned: Vec::new(),
                 finished: failed.chain(passed).chain(cached).collect(),
+                status_filter: StatusFilter::All,
+                group_by_package: false,
+                collapsed_packages: HashSet::new(),
             }
         }
     }
@@ -325,4 +586,133 @@ mod test {
         );
         assert_eq!(actual, test_case.expected_index);
     }
+
+    fn filter_tasks() -> TasksByStatus {
+        TasksByStatus {
+            running: vec![Task::new("running".into()).start()],
+            planned: vec![Task::new("planned".into())],
+            finished: vec![
+                Task::new("fail".into()).start().finish(TaskResult::Failure),
+                Task::new("pass".into()).start().finish(TaskResult::Success),
+                Task::new("cached".into())
+                    .start()
+                    .finish(TaskResult::CacheHit),
+            ],
+            status_filter: StatusFilter::All,
+            group_by_package: false,
+            collapsed_packages: HashSet::new(),
+        }
+    }
+
+    #[test_case(StatusFilter::All, &["running", "planned", "fail", "pass", "cached"] ; "all")]
+    #[test_case(StatusFilter::Failed, &["fail"] ; "failed")]
+    #[test_case(StatusFilter::Running, &["running"] ; "running")]
+    #[test_case(StatusFilter::CacheMiss, &["running", "fail", "pass"] ; "cache miss")]
+    fn test_status_filter(status_filter: StatusFilter, expected: &[&str]) {
+        let tasks = TasksByStatus {
+            status_filter,
+            ..filter_tasks()
+        };
+        assert!(tasks.has_filter_matches());
+        assert_eq!(
+            tasks.task_names_in_displayed_order().collect::<Vec<_>>(),
+            expected
+        );
+        assert_eq!(tasks.count_all(), expected.len());
+    }
+
+    #[test]
+    fn test_status_filter_without_matches_shows_all() {
+        let mut tasks = TasksByStatus {
+            status_filter: StatusFilter::Failed,
+            ..filter_tasks()
+        };
+        tasks.finished.remove(0);
+        assert!(!tasks.has_filter_matches());
+        assert_eq!(tasks.count_all(), 4);
+        assert_eq!(tasks.active_index("pass"), Some(2));
+    }
+
+    fn grouped_tasks() -> TasksByStatus {
+        TasksByStatus {
+            running: vec![Task::new("docs#build".into()).start()],
+            planned: vec![Task::new("web#lint".into()), Task::new("web#test".into())],
+            finished: vec![
+                Task::new("docs#lint".into())
+                    .start()
+                    .finish(TaskResult::Failure),
+                Task::new("web#build".into())
+                    .start()
+                    .finish(TaskResult::Success),
+            ],
+            status_filter: StatusFilter::All,
+            group_by_package: true,
+            collapsed_packages: HashSet::new(),
+        }
+    }
+
+    #[test]
+    fn test_group_by_package() {
+        let mut tasks = grouped_tasks();
+        assert_eq!(
+            tasks.task_names_in_displayed_order().collect::<Vec<_>>(),
+            &[
+                "docs#build",
+                "docs#build",
+                "docs#lint",
+                "web#lint",
+                "web#lint",
+                "web#test",
+                "web#build"
+            ],
+            "headers show their package's first task"
+        );
+        assert_eq!(tasks.active_index("docs#build"), Some(1));
+        assert_eq!(tasks.package_at(5), Some("web"));
+        assert_eq!(
+            tasks.row_key(3).unwrap(),
+            RowKey::Package {
+                package: "web".into(),
+                task: "web#lint".into()
+            }
+        );
+
+        tasks.collapsed_packages.insert("docs".into());
+        assert_eq!(
+            tasks.task_names_in_displayed_order().collect::<Vec<_>>(),
+            &[
+                "docs#build",
+                "web#lint",
+                "web#lint",
+                "web#test",
+                "web#build"
+            ]
+        );
+        assert!(matches!(
+            tasks.rows()[0],
+            TaskRow::Package {
+                tasks: 2,
+                collapsed: true,
+                is_running: true,
+                has_failure: true,
+                ..
+            }
+        ));
+        assert_eq!(
+            tasks.active_index("docs#lint"),
+            Some(0),
+            "collapsed tasks are found at their header"
+        );
+
+        tasks.group_by_package = false;
+        assert_eq!(tasks.package_at(0), None);
+        assert_eq!(
+            tasks.row_index(&RowKey::Package {
+                package: "web".into(),
+                task: "web#lint".into()
+            }),
+            Some(1),
+            "a header becomes its first task"
+        );
+    }
 }
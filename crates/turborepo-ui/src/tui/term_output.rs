@@ -1,22 +1,84 @@
-use std::{io::Write, mem};
+use std::{
+    fs::File,
+    io::{Seek, SeekFrom, Write},
+    mem,
+};
 
+use tracing::debug;
+use turbopath::AbsoluteSystemPath;
 use turborepo_vt100 as vt100;
 
 use super::{
-    event::{CacheResult, Direction, OutputLogs, TaskResult},
+    diagnostics::{DiagnosticCounts, DiagnosticPatterns},
+    event::{CacheRestore, CacheResult, CacheSource, Direction, OutputLogs, TaskResult},
+    log_search::LogSearch,
+    table::format_duration,
     Error,
 };
 
 const SCROLLBACK_LEN: usize = 1024;
+// How much of a line is kept to tell whether it's a warning or error. Output
+// that only ever returns to the start of the line, like a progress bar, would
+// otherwise be kept forever.
+const MAX_PARTIAL_LINE_LEN: usize = 4096;
+// How many bytes of dropped lines are buffered before they're written to the
+// dropped output file
+const DROPPED_OUTPUT_BATCH_LEN: usize = 64 * 1024;
 
 pub struct TerminalOutput<W> {
     output: Vec<u8>,
+    // Number of newlines in `output`
+    output_lines: usize,
+    // Maximum number of lines retained in `output`, `None` if unlimited
+    max_lines: Option<usize>,
+    // The lines dropped from `output` to stay under `max_lines`, without
+    // styling. They're kept in a temporary file so exported logs are complete.
+    dropped_output: Option<File>,
+    // Dropped lines that haven't been written to `dropped_output` yet
+    dropped_output_buffer: String,
     pub parser: vt100::Parser,
     pub stdin: Option<W>,
     pub status: Option<String>,
     pub output_logs: Option<OutputLogs>,
     pub task_result: Option<TaskResult>,
     pub cache_result: Option<CacheResult>,
+    pub cache_restore: Option<CacheRestore>,
+    log_search: Option<LogSearch>,
+    // What happened the last time the logs were exported, cleared when
+    // there's new output
+    export_notice: Option<String>,
+    // The row at the top of the view when the task was re-run, counted from
+    // the top of the scrollback. It's scrolled back to once the new run's
+    // logs reach it.
+    saved_scroll: Option<usize>,
+    // Whether the view scrolls to show new output when it's at the bottom of
+    // the logs
+    follow: bool,
+    // Whole lines selected from the keyboard
+    line_selection: Option<LineSelection>,
+    diagnostic_patterns: DiagnosticPatterns,
+    // The warnings and errors in the output since the task last started
+    diagnostic_counts: DiagnosticCounts,
+    // Output after the last newline, which is counted once the line ends
+    partial_line: Vec<u8>,
+    // The first row of the warning or error that was last jumped to, counted
+    // from the top of the scrollback
+    diagnostic_row: Option<usize>,
+}
+
+/// Output without styling, as it would appear in a terminal log file
+fn plain_text(bytes: &[u8]) -> String {
+    let output = String::from_utf8_lossy(bytes);
+    console::strip_ansi_codes(&output).replace("\r\n", "\n")
+}
+
+// Lines are counted from the top of the scrollback
+#[derive(Debug, Clone, Copy)]
+struct LineSelection {
+    // The line the selection was started on
+    anchor: usize,
+    // The line the selection was extended to
+    cursor: usize,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -30,19 +92,63 @@ impl<W> TerminalOutput<W> {
     pub fn new(rows: u16, cols: u16, stdin: Option<W>) -> Self {
         Self {
             output: Vec::new(),
+            output_lines: 0,
+            max_lines: None,
+            dropped_output: None,
+            dropped_output_buffer: String::new(),
             parser: vt100::Parser::new(rows, cols, SCROLLBACK_LEN),
             stdin,
             status: None,
             output_logs: None,
             task_result: None,
             cache_result: None,
+            cache_restore: None,
+            log_search: None,
+            export_notice: None,
+            saved_scroll: None,
+            follow: true,
+            line_selection: None,
+            diagnostic_patterns: DiagnosticPatterns::default(),
+            diagnostic_counts: DiagnosticCounts::default(),
+            partial_line: Vec::new(),
+            diagnostic_row: None,
         }
     }
 
+    /// Sets whether the logs scroll to show new output, see `set_follow`
+    pub fn with_follow(mut self, follow: bool) -> Self {
+        self.follow = follow;
+        self
+    }
+
+    /// Counts the warnings and errors in the output with `patterns`
+    pub fn with_diagnostics(mut self, patterns: DiagnosticPatterns) -> Self {
+        self.diagnostic_patterns = patterns;
+        self
+    }
+
+    /// Caps the number of log lines kept for the task, older lines are dropped
+    pub fn with_max_lines(mut self, max_lines: Option<usize>) -> Self {
+        self.max_lines = max_lines;
+        self.trim_output();
+        self
+    }
+
     pub fn title(&self, task_name: &str) -> String {
+        let restore = self.cache_restore.map(|restore| {
+            let source = match restore.source {
+                CacheSource::Local => "local",
+                CacheSource::Remote => "remote",
+            };
+            format!(
+                " ({source} cache, restored in {})",
+                format_duration(restore.duration)
+            )
+        });
+        let restore = restore.as_deref().unwrap_or_default();
         match self.status.as_deref() {
-            Some(status) => format!(" {task_name} > {status} "),
-            None => format!(" {task_name} > "),
+            Some(status) => format!(" {task_name} > {status}{restore} "),
+            None => format!(" {task_name} >{restore} "),
         }
     }
 
@@ -51,8 +157,99 @@ impl<W> TerminalOutput<W> {
     }
 
     pub fn process(&mut self, bytes: &[u8]) {
+        let scrollback_rows = self.parser.screen().scrollback_rows();
         self.parser.process(bytes);
+        self.hold_view(scrollback_rows);
+        self.restore_scroll();
+        self.export_notice = None;
         self.output.extend_from_slice(bytes);
+        self.output_lines += bytes.iter().filter(|byte| **byte == b'\n').count();
+        self.trim_output();
+        self.count_diagnostics(bytes);
+    }
+
+    fn count_diagnostics(&mut self, bytes: &[u8]) {
+        let mut rest = bytes;
+        while let Some(end) = rest.iter().position(|byte| *byte == b'\n') {
+            self.extend_partial_line(&rest[..end]);
+            let line = mem::take(&mut self.partial_line);
+            let line = String::from_utf8_lossy(&line);
+            let line = console::strip_ansi_codes(&line);
+            if let Some(severity) = self
+                .diagnostic_patterns
+                .classify(line.trim_end_matches('\r'))
+            {
+                self.diagnostic_counts.count(severity);
+            }
+            rest = &rest[end + 1..];
+        }
+        self.extend_partial_line(rest);
+    }
+
+    fn extend_partial_line(&mut self, bytes: &[u8]) {
+        let room = MAX_PARTIAL_LINE_LEN.saturating_sub(self.partial_line.len());
+        self.partial_line
+            .extend_from_slice(&bytes[..bytes.len().min(room)]);
+    }
+
+    pub fn diagnostic_counts(&self) -> DiagnosticCounts {
+        self.diagnostic_counts
+    }
+
+    /// Starts counting warnings and errors again, for a new run of the task
+    pub fn reset_diagnostic_counts(&mut self) {
+        self.diagnostic_counts = DiagnosticCounts::default();
+        self.partial_line.clear();
+    }
+
+    fn trim_output(&mut self) {
+        let Some(max_lines) = self.max_lines else {
+            return;
+        };
+        // Let the output grow a bit past the limit before dropping lines, so
+        // the buffer isn't shifted for every line of output
+        let slack = (max_lines / 10).max(1);
+        if self.output_lines <= max_lines + slack {
+            return;
+        }
+        let excess = self.output_lines - max_lines;
+        // Drop everything up to and including the newline ending the last excess line
+        if let Some((end, _)) = self
+            .output
+            .iter()
+            .enumerate()
+            .filter(|(_, byte)| **byte == b'\n')
+            .nth(excess - 1)
+        {
+            let dropped = plain_text(&self.output[..=end]);
+            self.output.drain(..=end);
+            self.output_lines = max_lines;
+            self.dropped_output_buffer.push_str(&dropped);
+            if self.dropped_output_buffer.len() >= DROPPED_OUTPUT_BATCH_LEN {
+                self.write_dropped_output();
+            }
+        }
+    }
+
+    fn write_dropped_output(&mut self) {
+        let buffer = mem::take(&mut self.dropped_output_buffer);
+        let file = match &mut self.dropped_output {
+            Some(file) => file,
+            None => match tempfile::tempfile() {
+                Ok(file) => self.dropped_output.insert(file),
+                Err(err) => {
+                    debug!("unable to create file for dropped logs: {err}");
+                    return;
+                }
+            },
+        };
+        // Exporting reads the file from the start, so go back to its end
+        let written = file
+            .seek(SeekFrom::End(0))
+            .and_then(|_| file.write_all(buffer.as_bytes()));
+        if let Err(err) = written {
+            debug!("unable to keep dropped logs: {err}");
+        }
     }
 
     pub fn resize(&mut self, rows: u16, cols: u16) {
@@ -63,17 +260,83 @@ impl<W> TerminalOutput<W> {
             new_parser.screen_mut().set_scrollback(scrollback);
             // Completely swap out the old vterm with a new correctly sized one
             mem::swap(&mut self.parser, &mut new_parser);
+            // Lines wrap differently at the new size, so the selection is lost
+            self.line_selection = None;
+            self.diagnostic_row = None;
         }
     }
 
     pub fn scroll(&mut self, direction: Direction) -> Result<(), Error> {
+        self.scroll_by(direction, 1);
+        Ok(())
+    }
+
+    pub fn scroll_by(&mut self, direction: Direction, lines: usize) {
+        self.saved_scroll = None;
         let scrollback = self.parser.screen().scrollback();
         let new_scrollback = match direction {
-            Direction::Up => scrollback + 1,
-            Direction::Down => scrollback.saturating_sub(1),
+            Direction::Up => scrollback.saturating_add(lines),
+            Direction::Down => scrollback.saturating_sub(lines),
         };
         self.parser.screen_mut().set_scrollback(new_scrollback);
-        Ok(())
+    }
+
+    /// Scrolls so `line` of the logs is at the top, counting from 1 at the
+    /// top of the scrollback
+    pub fn scroll_to_line(&mut self, line: usize) {
+        self.saved_scroll = None;
+        let screen = self.parser.screen_mut();
+        let scrollback = screen
+            .scrollback_rows()
+            .saturating_sub(line.saturating_sub(1));
+        screen.set_scrollback(scrollback);
+    }
+
+    pub fn follows(&self) -> bool {
+        self.follow
+    }
+
+    /// Sets whether the logs scroll to show new output. Logs that don't
+    /// follow stay where they are as output comes in, even at the bottom.
+    /// Following jumps to the newest output.
+    pub fn set_follow(&mut self, follow: bool) {
+        self.follow = follow;
+        if follow {
+            self.saved_scroll = None;
+            self.parser.screen_mut().set_scrollback(0);
+        }
+    }
+
+    // Logs scrolled up already stay in place as output comes in, so this
+    // only needs to keep logs at the bottom from moving
+    fn hold_view(&mut self, scrollback_rows: usize) {
+        let screen = self.parser.screen_mut();
+        if self.follow || screen.scrollback() > 0 {
+            return;
+        }
+        let new_rows = screen.scrollback_rows().saturating_sub(scrollback_rows);
+        screen.set_scrollback(new_rows);
+    }
+
+    /// Remembers where the logs are scrolled to before the task is re-run,
+    /// since the new run's logs can reset it, e.g. by clearing the screen.
+    /// Logs that are scrolled to the bottom keep following the output.
+    pub fn save_scroll(&mut self) {
+        let screen = self.parser.screen();
+        self.saved_scroll =
+            (screen.scrollback() > 0).then(|| screen.scrollback_rows() - screen.scrollback());
+    }
+
+    fn restore_scroll(&mut self) {
+        let Some(top) = self.saved_scroll else {
+            return;
+        };
+        let screen = self.parser.screen_mut();
+        // Until there's output below the saved row, the logs follow the output
+        if let Some(scrollback) = screen.scrollback_rows().checked_sub(top).filter(|n| *n > 0) {
+            screen.set_scrollback(scrollback);
+            self.saved_scroll = None;
+        }
     }
 
     fn persist_behavior(&self) -> LogBehavior {
@@ -125,6 +388,33 @@ impl<W> TerminalOutput<W> {
         Ok(())
     }
 
+    /// The captured output without styling, as it would appear in a
+    /// terminal log file
+    pub fn plain_output(&self) -> String {
+        plain_text(&self.output)
+    }
+
+    /// Writes all of the task's output to `path`, replacing whatever is there,
+    /// including the lines that were dropped to stay under the line limit
+    pub fn export_logs(&self, path: &AbsoluteSystemPath) -> std::io::Result<()> {
+        path.ensure_dir()?;
+        let mut file = path.create()?;
+        if let Some(mut dropped) = self.dropped_output.as_ref() {
+            dropped.seek(SeekFrom::Start(0))?;
+            std::io::copy(&mut dropped, &mut file)?;
+        }
+        file.write_all(self.dropped_output_buffer.as_bytes())?;
+        file.write_all(self.plain_output().as_bytes())
+    }
+
+    pub fn export_notice(&self) -> Option<&str> {
+        self.export_notice.as_deref()
+    }
+
+    pub fn set_export_notice(&mut self, notice: String) {
+        self.export_notice = Some(notice);
+    }
+
     pub fn has_selection(&self) -> bool {
         self.parser
             .screen()
@@ -136,7 +426,7 @@ impl<W> TerminalOutput<W> {
         match event.kind {
             crossterm::event::MouseEventKind::Down(crossterm::event::MouseButton::Left) => {
                 // We need to update the vterm so we don't continue to render the selection
-                self.parser.screen_mut().clear_selection();
+                self.clear_selection();
             }
             crossterm::event::MouseEventKind::Drag(crossterm::event::MouseButton::Left) => {
                 // Update selection of underlying parser
@@ -164,4 +454,483 @@ impl<W> TerminalOutput<W> {
     pub fn copy_selection(&self) -> Option<String> {
         self.parser.screen().selected_text()
     }
+
+    pub fn clear_selection(&mut self) {
+        self.line_selection = None;
+        self.parser.screen_mut().clear_selection();
+    }
+
+    pub fn is_selecting_lines(&self) -> bool {
+        self.line_selection.is_some()
+    }
+
+    /// Starts selecting whole lines from the keyboard, on the last line of
+    /// the logs in view
+    pub fn start_line_selection(&mut self) {
+        let screen = self.parser.screen();
+        let (rows, _) = screen.size();
+        let top = screen.scrollback_rows() - screen.scrollback();
+        let line = (top + usize::from(rows))
+            .saturating_sub(1)
+            .min(self.last_line());
+        self.diagnostic_row = None;
+        self.line_selection = Some(LineSelection {
+            anchor: line,
+            cursor: line,
+        });
+        self.parser.screen_mut().select_rows(line, line);
+    }
+
+    /// Extends the selected lines by a line in `direction`, scrolling so the
+    /// line it's extended to is in view
+    pub fn extend_line_selection(&mut self, direction: Direction) {
+        let last_line = self.last_line();
+        let Some(selection) = self.line_selection.as_mut() else {
+            return;
+        };
+        selection.cursor = match direction {
+            Direction::Up => selection.cursor.saturating_sub(1),
+            Direction::Down => (selection.cursor + 1).min(last_line),
+        };
+        let LineSelection { anchor, cursor } = *selection;
+
+        let screen = self.parser.screen();
+        let (rows, _) = screen.size();
+        let top = screen.scrollback_rows() - screen.scrollback();
+        if cursor < top {
+            self.scroll_by(Direction::Up, top - cursor);
+        } else if cursor >= top + usize::from(rows) {
+            self.scroll_by(Direction::Down, cursor + 1 - top - usize::from(rows));
+        }
+        self.parser.screen_mut().select_rows(anchor, cursor);
+    }
+
+    // The last line with output, which is where the cursor is
+    fn last_line(&self) -> usize {
+        let screen = self.parser.screen();
+        let (cursor_row, _) = screen.cursor_position();
+        screen.scrollback_rows() + usize::from(cursor_row)
+    }
+
+    pub fn log_search(&self) -> Option<&LogSearch> {
+        self.log_search.as_ref()
+    }
+
+    /// Searches the logs for `query` and highlights the closest match above
+    /// the bottom of the logs that are in view
+    pub fn search_logs(&mut self, query: String) {
+        self.diagnostic_row = None;
+        let mut search = LogSearch::new(query);
+        self.update_log_matches(&mut search);
+        let screen = self.parser.screen();
+        let (rows, _) = screen.size();
+        let bottom = screen.scrollback_rows() - screen.scrollback() + usize::from(rows);
+        search.select_from(bottom.saturating_sub(1));
+        self.log_search = Some(search);
+        self.show_log_match();
+    }
+
+    /// Highlights the next match of the current search in `direction`
+    pub fn scroll_to_log_match(&mut self, direction: Direction) {
+        let Some(mut search) = self.log_search.take() else {
+            return;
+        };
+        // The logs may have changed since the last match was highlighted
+        self.update_log_matches(&mut search);
+        search.select_next(direction);
+        self.log_search = Some(search);
+        self.show_log_match();
+    }
+
+    pub fn clear_log_search(&mut self) {
+        if self.log_search.take().is_some() | self.diagnostic_row.take().is_some() {
+            self.parser.screen_mut().clear_selection();
+        }
+    }
+
+    /// Highlights the next warning or error in the logs in `direction`,
+    /// wrapping around at the start and end of the logs. Lines that wrap are
+    /// classified as a whole, the same as when they're counted.
+    pub fn scroll_to_diagnostic(&mut self, direction: Direction) {
+        let diagnostic_lines = self
+            .logical_lines()
+            .into_iter()
+            .filter(|(_, _, text)| self.diagnostic_patterns.classify(text).is_some())
+            .map(|(start, end, _)| (start, end))
+            .collect::<Vec<_>>();
+        let screen = self.parser.screen();
+        let (rows, _) = screen.size();
+        let top = screen.scrollback_rows() - screen.scrollback();
+        let next = match (self.diagnostic_row, &direction) {
+            (Some(current), Direction::Down) => {
+                diagnostic_lines.iter().find(|(start, _)| *start > current)
+            }
+            (Some(current), Direction::Up) => {
+                diagnostic_lines.iter().rfind(|(start, _)| *start < current)
+            }
+            (None, Direction::Down) => diagnostic_lines.iter().find(|(_, end)| *end >= top),
+            (None, Direction::Up) => diagnostic_lines
+                .iter()
+                .rfind(|(start, _)| *start < top + usize::from(rows)),
+        };
+        let next = match direction {
+            Direction::Down => next.or(diagnostic_lines.first()),
+            Direction::Up => next.or(diagnostic_lines.last()),
+        };
+        let Some(&(start, end)) = next else {
+            return;
+        };
+        self.diagnostic_row = Some(start);
+        self.log_search = None;
+        self.line_selection = None;
+        self.scroll_row_into_view(start);
+        self.parser.screen_mut().select_rows(start, end);
+    }
+
+    // The lines of the logs with the first and last rows they take up,
+    // counted from the top of the scrollback
+    fn logical_lines(&self) -> Vec<(usize, usize, String)> {
+        let screen = self.parser.entire_screen();
+        let (_, cols) = screen.size();
+        let mut lines = Vec::new();
+        let mut line = String::new();
+        let mut start = 0;
+        let mut rows = screen
+            .rows(0, cols)
+            .zip(screen.rows_wrapped())
+            .enumerate()
+            .peekable();
+        while let Some((row, (text, wrapped))) = rows.next() {
+            line.push_str(&text);
+            if !wrapped || rows.peek().is_none() {
+                lines.push((start, row, mem::take(&mut line)));
+                start = row + 1;
+            }
+        }
+        lines
+    }
+
+    // Puts `row` in the middle of the pane if it isn't in view already
+    fn scroll_row_into_view(&mut self, row: usize) {
+        self.saved_scroll = None;
+        let screen = self.parser.screen_mut();
+        let (rows, _) = screen.size();
+        let scrollback_rows = screen.scrollback_rows();
+        let top = scrollback_rows - screen.scrollback();
+        if row < top || row >= top + usize::from(rows) {
+            screen.set_scrollback((scrollback_rows + usize::from(rows / 2)).saturating_sub(row));
+        }
+    }
+
+    fn update_log_matches(&self, search: &mut LogSearch) {
+        let screen = self.parser.entire_screen();
+        let (_, cols) = screen.size();
+        search.update_matches(screen.rows(0, cols));
+    }
+
+    /// Scrolls the highlighted match into view if it isn't already and
+    /// selects it
+    fn show_log_match(&mut self) {
+        self.saved_scroll = None;
+        self.parser.screen_mut().clear_selection();
+        let Some(log_match) = self.log_search.as_ref().and_then(LogSearch::current) else {
+            return;
+        };
+        self.scroll_row_into_view(log_match.row);
+        let screen = self.parser.screen_mut();
+        let (_, cols) = screen.size();
+        let top = screen.scrollback_rows() - screen.scrollback();
+        let Some(row) = log_match
+            .row
+            .checked_sub(top)
+            .and_then(|row| u16::try_from(row).ok())
+        else {
+            return;
+        };
+        let end_col = (log_match.col + log_match.width)
+            .saturating_sub(1)
+            .min(cols.saturating_sub(1));
+        screen.set_selection(row, log_match.col, row, end_col);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_search_logs_scrolls_to_matches() {
+        let mut term: TerminalOutput<()> = TerminalOutput::new(4, 20, None);
+        for i in 0..20 {
+            let line = if i % 5 == 0 { "error" } else { "ok" };
+            term.process(format!("{line} {i}\r\n").as_bytes());
+        }
+
+        term.search_logs("error".into());
+        let search = term.log_search().expect("searching");
+        assert_eq!(search.position(), (Some(4), 4));
+        assert_eq!(term.copy_selection().as_deref(), Some("error"));
+        let in_view = term.parser.screen().contents();
+        assert!(in_view.contains("error 15"), "{in_view}");
+
+        term.scroll_to_log_match(Direction::Down);
+        assert_eq!(term.log_search().unwrap().position(), (Some(1), 4));
+        let in_view = term.parser.screen().contents();
+        assert!(in_view.contains("error 0"), "{in_view}");
+        assert!(term.parser.screen().scrollback() > 0);
+
+        term.clear_log_search();
+        assert!(term.log_search().is_none());
+        assert!(!term.has_selection());
+    }
+
+    #[test]
+    fn test_select_lines() {
+        let mut term: TerminalOutput<()> = TerminalOutput::new(4, 20, None);
+        for i in 0..10 {
+            term.process(format!("line {i}\r\n").as_bytes());
+        }
+
+        term.start_line_selection();
+        assert!(term.is_selecting_lines());
+        // The cursor is on the empty line after the last one
+        term.extend_line_selection(Direction::Up);
+        assert_eq!(term.copy_selection().as_deref(), Some("line 9\n"));
+        for _ in 0..4 {
+            term.extend_line_selection(Direction::Up);
+        }
+        assert_eq!(
+            term.copy_selection().as_deref(),
+            Some("line 5\nline 6\nline 7\nline 8\nline 9\n")
+        );
+        let in_view = term.parser.screen().contents();
+        assert!(in_view.starts_with("line 5\n"), "{in_view}");
+
+        term.clear_selection();
+        assert!(!term.is_selecting_lines());
+        assert!(!term.has_selection());
+    }
+
+    #[test]
+    fn test_counts_diagnostics() {
+        let mut term: TerminalOutput<()> = TerminalOutput::new(4, 20, None);
+        term.process(b"\x1b[33mwarning\x1b[0m: unused\r\nerr");
+        assert_eq!(term.diagnostic_counts().warnings, 1);
+        assert_eq!(term.diagnostic_counts().errors, 0, "line isn't finished");
+        term.process(b"or: failed\r\nok\r\n");
+        assert_eq!(
+            term.diagnostic_counts(),
+            DiagnosticCounts {
+                warnings: 1,
+                errors: 1
+            }
+        );
+
+        term.reset_diagnostic_counts();
+        assert!(term.diagnostic_counts().is_empty());
+    }
+
+    #[test]
+    fn test_unfinished_line_is_bounded() {
+        let mut term: TerminalOutput<()> = TerminalOutput::new(4, 20, None);
+        for percent in 0..10_000 {
+            term.process(format!("error {percent}%\r").as_bytes());
+        }
+        assert!(term.partial_line.len() <= MAX_PARTIAL_LINE_LEN);
+        term.process(b"\n");
+        assert_eq!(term.diagnostic_counts().errors, 1);
+    }
+
+    #[test]
+    fn test_wrapped_lines_are_classified_whole() {
+        let mut term: TerminalOutput<()> = TerminalOutput::new(4, 20, None);
+        term.process(b"ok\r\n");
+        // "warning" wraps between "wa" and "rning", so neither row matches alone
+        term.process(b"module/with/long: warning: unused\r\n");
+        for _ in 0..10 {
+            term.process(b"ok\r\n");
+        }
+        assert_eq!(term.diagnostic_counts().warnings, 1);
+
+        term.scroll_to_diagnostic(Direction::Up);
+        let selected = term.copy_selection().unwrap_or_default();
+        assert!(selected.starts_with("module/with/long: wa"), "{selected}");
+        assert!(selected.ends_with("rning: unused"), "{selected}");
+    }
+
+    #[test]
+    fn test_scroll_to_diagnostic() {
+        let mut term: TerminalOutput<()> = TerminalOutput::new(4, 20, None);
+        for i in 0..20 {
+            let line = match i {
+                2 => "warning 2",
+                15 => "error 15",
+                _ => "ok",
+            };
+            term.process(format!("{line}\r\n").as_bytes());
+        }
+
+        term.scroll_to_diagnostic(Direction::Up);
+        assert_eq!(term.copy_selection().as_deref(), Some("error 15"));
+        term.scroll_to_diagnostic(Direction::Up);
+        assert_eq!(term.copy_selection().as_deref(), Some("warning 2"));
+        let in_view = term.parser.screen().contents();
+        assert!(in_view.contains("warning 2"), "{in_view}");
+        term.scroll_to_diagnostic(Direction::Up);
+        assert_eq!(
+            term.copy_selection().as_deref(),
+            Some("error 15"),
+            "wraps around"
+        );
+
+        term.clear_log_search();
+        assert!(!term.has_selection());
+    }
+
+    #[test]
+    fn test_scroll_is_restored_after_rerun() {
+        let mut term: TerminalOutput<()> = TerminalOutput::new(4, 20, None);
+        for i in 0..20 {
+            term.process(format!("first {i}\r\n").as_bytes());
+        }
+        for _ in 0..10 {
+            term.scroll(Direction::Up).unwrap();
+        }
+        let in_view = term.parser.screen().contents();
+        assert!(in_view.starts_with("first 7"), "{in_view}");
+
+        term.save_scroll();
+        // Watch mode tools often reset the terminal before rebuilding
+        term.process(b"\x1bc");
+        for i in 0..5 {
+            term.process(format!("second {i}\r\n").as_bytes());
+        }
+        assert_eq!(
+            term.parser.screen().scrollback(),
+            0,
+            "not enough output yet"
+        );
+        for i in 5..20 {
+            term.process(format!("second {i}\r\n").as_bytes());
+        }
+        let in_view = term.parser.screen().contents();
+        assert!(in_view.starts_with("second 7"), "{in_view}");
+    }
+
+    #[test]
+    fn test_logs_that_dont_follow_stay_in_place() {
+        let mut term: TerminalOutput<()> = TerminalOutput::new(4, 20, None).with_follow(false);
+        for i in 0..10 {
+            term.process(format!("line {i}\r\n").as_bytes());
+        }
+        let in_view = term.parser.screen().contents();
+        assert!(in_view.starts_with("line 0\n"), "{in_view}");
+
+        term.scroll_by(Direction::Down, usize::MAX);
+        term.process(b"more\r\nand more\r\n");
+        let in_view = term.parser.screen().contents();
+        assert!(in_view.starts_with("line 7\n"), "{in_view}");
+
+        term.set_follow(true);
+        let in_view = term.parser.screen().contents();
+        assert!(in_view.ends_with("more"), "{in_view}");
+    }
+
+    #[test]
+    fn test_follows_output_after_rerun() {
+        let mut term: TerminalOutput<()> = TerminalOutput::new(4, 20, None);
+        for i in 0..20 {
+            term.process(format!("first {i}\r\n").as_bytes());
+        }
+        term.save_scroll();
+        for i in 0..20 {
+            term.process(format!("second {i}\r\n").as_bytes());
+        }
+        assert_eq!(term.parser.screen().scrollback(), 0);
+    }
+
+    #[test]
+    fn test_unlimited_by_default() {
+        let mut term: TerminalOutput<()> = TerminalOutput::new(10, 10, None);
+        for i in 0..100 {
+            term.process(format!("line {i}\n").as_bytes());
+        }
+        assert_eq!(term.output_lines, 100);
+    }
+
+    #[test]
+    fn test_drops_oldest_lines() {
+        let mut term: TerminalOutput<()> =
+            TerminalOutput::new(10, 10, None).with_max_lines(Some(2));
+        // Lines are only dropped once the output is past the limit by a tenth,
+        // or by a line for small limits
+        term.process(b"one\ntwo\nthree\nfo");
+        assert_eq!(term.output, b"one\ntwo\nthree\nfo");
+        term.process(b"ur\nfive\n");
+        assert_eq!(term.output, b"four\nfive\n");
+        assert_eq!(term.output_lines, 2);
+    }
+
+    #[test]
+    fn test_drops_lines_in_batches() {
+        let mut term: TerminalOutput<()> =
+            TerminalOutput::new(10, 10, None).with_max_lines(Some(100));
+        for i in 0..110 {
+            term.process(format!("line {i}\n").as_bytes());
+        }
+        assert_eq!(term.output_lines, 110);
+        term.process(b"line 110\n");
+        assert_eq!(term.output_lines, 100);
+        assert!(term.output.starts_with(b"line 11\n"));
+    }
+
+    #[test]
+    fn test_export_includes_lines_written_to_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = AbsoluteSystemPath::from_std_path(dir.path())
+            .unwrap()
+            .join_component("task.log");
+        let mut term: TerminalOutput<()> =
+            TerminalOutput::new(10, 10, None).with_max_lines(Some(10));
+        let mut expected = String::new();
+        for i in 0..20_000 {
+            let line = format!("line {i}\n");
+            term.process(line.as_bytes());
+            expected.push_str(&line);
+        }
+        assert!(term.dropped_output.is_some());
+        assert!(term.dropped_output_buffer.len() < DROPPED_OUTPUT_BATCH_LEN);
+        term.export_logs(&path).unwrap();
+        assert_eq!(path.read_to_string().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_export_includes_dropped_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = AbsoluteSystemPath::from_std_path(dir.path())
+            .unwrap()
+            .join_component("task.log");
+        let mut term: TerminalOutput<()> =
+            TerminalOutput::new(10, 10, None).with_max_lines(Some(2));
+        term.process(b"\x1b[31mone\x1b[0m\r\ntwo\nthree\nfour\n");
+        term.export_logs(&path).unwrap();
+        assert_eq!(path.read_to_string().unwrap(), "one\ntwo\nthree\nfour\n");
+
+        term.process(b"five\n");
+        term.export_logs(&path).unwrap();
+        assert_eq!(
+            path.read_to_string().unwrap(),
+            "one\ntwo\nthree\nfour\nfive\n"
+        );
+    }
+
+    #[test]
+    fn test_plain_output() {
+        let mut term: TerminalOutput<()> = TerminalOutput::new(10, 10, None);
+        term.process(b"\x1b[31merror\x1b[0m: oops\r\ndone\n");
+        assert_eq!(term.plain_output(), "error: oops\ndone\n");
+        term.set_export_notice("saved".into());
+        term.process(b"more");
+        assert_eq!(term.export_notice(), None, "new output clears the notice");
+    }
 }
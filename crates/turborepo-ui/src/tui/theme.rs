@@ -0,0 +1,152 @@
+use std::str::FromStr;
+
+use ratatui::style::Color;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// The colors the TUI is drawn with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub success: Color,
+    pub failure: Color,
+    pub cache_hit: Color,
+    /// The count of warnings in a task's output
+    pub warning: Color,
+    /// The selected task in the task list
+    pub selection: Color,
+    pub border: Color,
+    /// Shown next to failed tasks, so failures can be told apart by shape in
+    /// themes that don't rely on color
+    pub failure_symbol: &'static str,
+}
+
+/// The themes that come with turbo, by the name used in the preferences file
+const BUILT_IN_THEMES: &[(&str, Theme)] = &[
+    ("dark", Theme::DARK),
+    ("light", Theme::LIGHT),
+    ("colorblind", Theme::COLORBLIND),
+];
+
+impl Theme {
+    // matches Next.js (and many other CLI tools) https://github.com/vercel/next.js/blob/1a04d94aaec943d3cce93487fea3b8c8f8898f31/packages/next/src/build/output/log.ts
+    pub const DARK: Self = Self {
+        success: Color::Green,
+        failure: Color::Red,
+        cache_hit: Color::Magenta,
+        warning: Color::Yellow,
+        selection: Color::Yellow,
+        border: Color::Reset,
+        failure_symbol: "⨯",
+    };
+
+    /// Darker colors that are readable on a light background, where yellow
+    /// and the bright terminal colors wash out
+    pub const LIGHT: Self = Self {
+        success: Color::Indexed(28),
+        failure: Color::Indexed(160),
+        cache_hit: Color::Indexed(90),
+        warning: Color::Indexed(130),
+        selection: Color::Blue,
+        border: Color::DarkGray,
+        failure_symbol: "⨯",
+    };
+
+    /// Avoids relying on red vs green by using blue and yellow, with a
+    /// heavier failure symbol so status is readable without color
+    pub const COLORBLIND: Self = Self {
+        success: Color::Blue,
+        failure: Color::Yellow,
+        cache_hit: Color::Cyan,
+        warning: Color::Magenta,
+        selection: Color::Yellow,
+        border: Color::Reset,
+        failure_symbol: "✖",
+    };
+
+    pub fn built_in(name: &str) -> Option<Self> {
+        BUILT_IN_THEMES
+            .iter()
+            .find(|(theme_name, _)| *theme_name == name)
+            .map(|(_, theme)| *theme)
+    }
+
+    /// Builds a user-defined theme. Colors that aren't set, or that can't be
+    /// parsed, come from the built-in theme it extends.
+    pub fn from_config(name: &str, config: &ThemeConfig) -> Self {
+        let extends = config.extends.as_deref().unwrap_or("dark");
+        let mut theme = Self::built_in(extends).unwrap_or_else(|| {
+            warn!("theme '{name}' extends unknown theme '{extends}', using 'dark'");
+            Self::DARK
+        });
+        let colors = [
+            ("success", &config.success, &mut theme.success),
+            ("failure", &config.failure, &mut theme.failure),
+            ("cache_hit", &config.cache_hit, &mut theme.cache_hit),
+            ("warning", &config.warning, &mut theme.warning),
+            ("selection", &config.selection, &mut theme.selection),
+            ("border", &config.border, &mut theme.border),
+        ];
+        for (field, value, color) in colors {
+            let Some(value) = value else {
+                continue;
+            };
+            match Color::from_str(value) {
+                Ok(parsed) => *color = parsed,
+                Err(_) => warn!("ignoring {field} color '{value}' of theme '{name}'"),
+            }
+        }
+        theme
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::DARK
+    }
+}
+
+/// A theme defined in the preferences file. Colors are either names like
+/// "red" or "light-blue", hex like "#ff8800", or a 256 color index.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default, PartialEq)]
+pub struct ThemeConfig {
+    /// The built-in theme to take unset colors from: "dark", "light" or
+    /// "colorblind". Defaults to "dark".
+    pub extends: Option<String>,
+    pub success: Option<String>,
+    pub failure: Option<String>,
+    pub cache_hit: Option<String>,
+    pub warning: Option<String>,
+    pub selection: Option<String>,
+    pub border: Option<String>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_user_theme_extends_built_in() {
+        let config = ThemeConfig {
+            extends: Some("light".to_owned()),
+            success: Some("#00aa00".to_owned()),
+            failure: Some("not a color".to_owned()),
+            selection: Some("42".to_owned()),
+            ..Default::default()
+        };
+        let theme = Theme::from_config("mine", &config);
+        assert_eq!(theme.success, Color::Rgb(0, 0xaa, 0));
+        assert_eq!(theme.failure, Theme::LIGHT.failure);
+        assert_eq!(theme.selection, Color::Indexed(42));
+        assert_eq!(theme.border, Theme::LIGHT.border);
+    }
+
+    #[test]
+    fn test_unknown_base_uses_dark() {
+        let config = ThemeConfig {
+            extends: Some("solarized".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(Theme::from_config("mine", &config), Theme::DARK);
+    }
+}
@@ -0,0 +1,124 @@
+/// The keys vim mode handles in the task list. They take precedence over the
+/// keybindings, so `Keybindings::with_vim_mode` moves actions off of them.
+const VIM_KEYS: &str = "hjklgG0123456789";
+
+/// What vim-style motions move
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VimTarget {
+    /// The selected task
+    #[default]
+    TaskList,
+    /// The logs of the selected task
+    Logs,
+}
+
+/// A movement made with vim-style keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Motion {
+    Down(usize),
+    Up(usize),
+    /// Jumps to a line, counting from 1, e.g. `5G` or `5gg`
+    Line(usize),
+    First,
+    Last,
+    Target(VimTarget),
+}
+
+/// The state of vim-style navigation between key presses, i.e. which part of
+/// the TUI is being navigated and the count and `g` typed so far
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VimState {
+    target: VimTarget,
+    count: Option<usize>,
+    pending_g: bool,
+}
+
+impl VimState {
+    pub fn handles(c: char) -> bool {
+        VIM_KEYS.contains(c)
+    }
+
+    pub fn target(&self) -> VimTarget {
+        self.target
+    }
+
+    /// The count and `g` typed so far, e.g. `12g`
+    pub fn pending_keys(&self) -> String {
+        let count = self
+            .count
+            .map(|count| count.to_string())
+            .unwrap_or_default();
+        let g = if self.pending_g { "g" } else { "" };
+        format!("{count}{g}")
+    }
+
+    /// Handles a key press, returning the motion it finishes. Keys that
+    /// don't make a motion, like a single `g`, wait for the next key.
+    pub fn key(&mut self, c: char) -> Option<Motion> {
+        if let Some(digit) = c.to_digit(10) {
+            // A leading 0 isn't a count
+            if digit != 0 || self.count.is_some() {
+                let count = self.count.unwrap_or(0);
+                self.count = Some(count.saturating_mul(10).saturating_add(digit as usize));
+                self.pending_g = false;
+                return None;
+            }
+        }
+        if c == 'g' && !self.pending_g {
+            self.pending_g = true;
+            return None;
+        }
+
+        let count = self.count.take();
+        let pending_g = std::mem::take(&mut self.pending_g);
+        let motion = match c {
+            'j' => Motion::Down(count.unwrap_or(1)),
+            'k' => Motion::Up(count.unwrap_or(1)),
+            'g' if pending_g => count.map_or(Motion::First, Motion::Line),
+            'G' => count.map_or(Motion::Last, Motion::Line),
+            'h' => Motion::Target(VimTarget::TaskList),
+            'l' => Motion::Target(VimTarget::Logs),
+            _ => return None,
+        };
+        if let Motion::Target(target) = motion {
+            self.target = target;
+        }
+        Some(motion)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test_case("j", Some(Motion::Down(1)) ; "down")]
+    #[test_case("5k", Some(Motion::Up(5)) ; "counted")]
+    #[test_case("12j", Some(Motion::Down(12)) ; "multi digit count")]
+    #[test_case("gg", Some(Motion::First) ; "first")]
+    #[test_case("G", Some(Motion::Last) ; "last")]
+    #[test_case("7G", Some(Motion::Line(7)) ; "counted last")]
+    #[test_case("3gg", Some(Motion::Line(3)) ; "counted first")]
+    #[test_case("0j", Some(Motion::Down(1)) ; "zero is not a count")]
+    #[test_case("5g", None ; "pending")]
+    #[test_case("l", Some(Motion::Target(VimTarget::Logs)) ; "logs")]
+    fn test_motions(keys: &str, expected: Option<Motion>) {
+        let mut vim = VimState::default();
+        let motion = keys.chars().map(|c| vim.key(c)).last().flatten();
+        assert_eq!(motion, expected);
+    }
+
+    #[test]
+    fn test_motion_resets_pending_keys() {
+        let mut vim = VimState::default();
+        vim.key('1');
+        vim.key('2');
+        vim.key('g');
+        assert_eq!(vim.pending_keys(), "12g");
+        assert_eq!(vim.key('j'), Some(Motion::Down(12)));
+        assert_eq!(vim.pending_keys(), "");
+        vim.key('l');
+        assert_eq!(vim.target(), VimTarget::Logs);
+    }
+}
@@ -7,7 +7,7 @@ pub mod sender;
 pub mod subscriber;
 
 use event::WebUIEvent;
-pub use query::RunQuery;
+pub use query::{RunQuery, RunSubscription};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -1,8 +1,9 @@
 use std::sync::Arc;
 
-use async_graphql::{Object, SimpleObject};
+use async_graphql::{Object, SimpleObject, Subscription};
+use futures::Stream;
 use serde::Serialize;
-use tokio::sync::Mutex;
+use tokio::sync::{broadcast::error::RecvError, Mutex};
 
 use crate::wui::subscriber::{TaskState, WebUIState};
 
@@ -60,3 +61,38 @@ impl RunQuery {
         })
     }
 }
+
+/// Subscriptions for actively running tasks. Like `RunQuery`, there's nothing
+/// to subscribe to when we're not running tasks.
+pub struct RunSubscription {
+    state: Option<SharedState>,
+}
+
+impl RunSubscription {
+    pub fn new(state: Option<SharedState>) -> Self {
+        Self { state }
+    }
+}
+
+#[Subscription]
+impl RunSubscription {
+    /// Emits a task whenever its state changes, e.g. when it starts, gets its
+    /// cache status or finishes. Output from a task doesn't change its state,
+    /// so it isn't emitted.
+    async fn task_state_changed(&self) -> impl Stream<Item = RunTask> {
+        let state = self.state.clone();
+        async_stream::stream! {
+            let Some(state) = state else {
+                return;
+            };
+            let mut updates = state.lock().await.subscribe();
+            loop {
+                match updates.recv().await {
+                    Ok((name, state)) => yield RunTask { name, state },
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        }
+    }
+}
@@ -2,7 +2,7 @@ use std::{collections::BTreeMap, sync::Arc};
 
 use async_graphql::{Enum, SimpleObject};
 use serde::Serialize;
-use tokio::sync::Mutex;
+use tokio::sync::{broadcast, Mutex};
 
 use crate::{
     tui::event::{CacheResult, TaskResult},
@@ -33,6 +33,16 @@ impl Subscriber {
     async fn add_message(state: &Arc<Mutex<WebUIState>>, event: WebUIEvent) {
         let mut state = state.lock().await;
 
+        // Output doesn't change the status of a task, so we don't notify
+        // subscribers about it
+        let changed_tasks = match &event {
+            WebUIEvent::StartTask { task, .. }
+            | WebUIEvent::EndTask { task, .. }
+            | WebUIEvent::CacheStatus { task, .. } => vec![task.clone()],
+            WebUIEvent::UpdateTasks { tasks } | WebUIEvent::RestartTasks { tasks } => tasks.clone(),
+            WebUIEvent::TaskOutput { .. } | WebUIEvent::Stop => Vec::new(),
+        };
+
         match event {
             WebUIEvent::StartTask {
                 task,
@@ -101,6 +111,13 @@ impl Subscriber {
                     .collect();
             }
         }
+
+        for task in changed_tasks {
+            if let Some(task_state) = state.tasks.get(&task).cloned() {
+                // An error only means that nobody is subscribed
+                let _ = state.updates.send((task, task_state));
+            }
+        }
     }
 }
 
@@ -132,25 +149,50 @@ pub struct TaskState {
     cache_message: Option<String>,
 }
 
-#[derive(Debug, Default, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct WebUIState {
     tasks: BTreeMap<String, TaskState>,
+    /// Tasks are sent here, along with their new state, whenever their
+    /// status changes
+    #[serde(skip)]
+    updates: broadcast::Sender<(String, TaskState)>,
 }
 
+impl Default for WebUIState {
+    fn default() -> Self {
+        Self {
+            tasks: BTreeMap::new(),
+            updates: broadcast::channel(TASK_UPDATES_CAPACITY).0,
+        }
+    }
+}
+
+// Subscribers that fall this far behind will miss updates
+const TASK_UPDATES_CAPACITY: usize = 1024;
+
 impl WebUIState {
     pub fn tasks(&self) -> &BTreeMap<String, TaskState> {
         &self.tasks
     }
+
+    /// Subscribes to changes in the status of tasks
+    pub fn subscribe(&self) -> broadcast::Receiver<(String, TaskState)> {
+        self.updates.subscribe()
+    }
 }
 
 #[cfg(test)]
 mod test {
     use async_graphql::{EmptyMutation, EmptySubscription, Schema};
+    use futures::StreamExt;
 
     use super::*;
     use crate::{
         tui::event::OutputLogs,
-        wui::{query::RunQuery, sender::WebUISender},
+        wui::{
+            query::{RunQuery, RunSubscription},
+            sender::WebUISender,
+        },
     };
 
     #[tokio::test]
@@ -273,4 +315,58 @@ mod test {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_task_state_subscription() -> Result<(), crate::Error> {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let state = Arc::new(Mutex::new(WebUIState::default()));
+        let subscriber = Subscriber::new(rx);
+
+        let schema = Schema::new(
+            RunQuery::new(Some(state.clone())),
+            EmptyMutation,
+            RunSubscription::new(Some(state.clone())),
+        );
+        let updates = tokio::spawn(async move {
+            schema
+                .execute_stream("subscription { taskStateChanged { name state { status } } }")
+                .take(3)
+                .map(|response| response.data.into_json().unwrap())
+                .collect::<Vec<_>>()
+                .await
+        });
+        // Wait for the subscription to start listening before sending anything
+        while state.lock().await.updates.receiver_count() == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        let sender = WebUISender::new(tx);
+        sender.start_task("task".to_string(), OutputLogs::Full);
+        sender.output("task".to_string(), b"this is my output".to_vec())?;
+        sender.end_task("task".to_string(), TaskResult::Success);
+        sender.start_task("task2".to_string(), OutputLogs::Full);
+        drop(sender);
+
+        subscriber.watch(state).await;
+
+        let statuses = updates
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|update| {
+                let task = &update["taskStateChanged"];
+                format!("{} {}", task["name"], task["state"]["status"])
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            statuses,
+            vec![
+                r#""task" "RUNNING""#,
+                r#""task" "SUCCEEDED""#,
+                r#""task2" "RUNNING""#
+            ]
+        );
+
+        Ok(())
+    }
 }
@@ -27,6 +27,37 @@ impl<'a> EntireScreen<'a> {
         s
     }
 
+    /// Returns the text contents of the terminal by row, including the
+    /// scrollback, restricted to the given subset of columns.
+    ///
+    /// This will not include any formatting information, and will be in plain
+    /// text format.
+    ///
+    /// Newlines will not be included.
+    pub fn rows(
+        &self,
+        start: u16,
+        width: u16,
+    ) -> impl Iterator<Item = String> + '_ {
+        let (rows, _) = self.size();
+        self.screen.grid().all_rows().take(rows).map(move |row| {
+            let mut contents = String::new();
+            row.write_contents(&mut contents, start, width, false);
+            contents
+        })
+    }
+
+    /// Returns whether each row of the terminal, including the scrollback,
+    /// wraps onto the next one, in the same order as `rows`.
+    pub fn rows_wrapped(&self) -> impl Iterator<Item = bool> + '_ {
+        let (rows, _) = self.size();
+        self.screen
+            .grid()
+            .all_rows()
+            .take(rows)
+            .map(crate::row::Row::wrapped)
+    }
+
     /// Returns the formatted contents of the terminal by row,
     /// restricted to the given subset of columns.
     ///
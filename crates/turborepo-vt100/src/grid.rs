@@ -253,6 +253,10 @@ impl Grid {
         self.scrollback_offset
     }
 
+    pub fn scrollback_rows(&self) -> usize {
+        self.scrollback.len()
+    }
+
     pub fn set_scrollback(&mut self, rows: usize) {
         self.scrollback_offset = rows.min(self.scrollback.len());
     }
@@ -293,6 +297,28 @@ impl Grid {
         };
     }
 
+    pub fn select_rows(&mut self, start_row: usize, end_row: usize) {
+        self.clear_selection();
+        let (start_row, end_row) =
+            (start_row.min(end_row), start_row.max(end_row));
+        let last_row =
+            (self.scrollback.len() + self.rows.len()).saturating_sub(1);
+        let start = AbsPos {
+            row: start_row.min(last_row),
+            col: 0,
+        };
+        let end = AbsPos {
+            row: end_row.min(last_row),
+            col: self.size.cols.saturating_sub(1),
+        };
+        self.selection = Some(Selection { start, end });
+        if let Some(selected_cells) = self.selection_cells() {
+            for cell in selected_cells {
+                cell.select(true);
+            }
+        };
+    }
+
     pub fn update_selection(&mut self, row: u16, col: u16) {
         let pos = self.translate_pos(row, col);
         // Copy out current selection
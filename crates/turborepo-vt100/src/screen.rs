@@ -139,6 +139,15 @@ impl Screen {
         self.grid().scrollback()
     }
 
+    /// Returns the number of rows that have scrolled off of the screen and
+    /// are kept in the scrollback.
+    ///
+    /// This is the largest position that can be passed to `set_scrollback`.
+    #[must_use]
+    pub fn scrollback_rows(&self) -> usize {
+        self.grid().scrollback_rows()
+    }
+
     /// Returns the text contents of the terminal.
     ///
     /// This will not include any formatting information, and will be in plain
@@ -318,6 +327,14 @@ impl Screen {
             .set_selection(start_row, start_col, end_row, end_col);
     }
 
+    /// Selects every row from `start_row` to `end_row`, in either order.
+    ///
+    /// Unlike `set_selection`, rows are counted from the top of the scrollback,
+    /// so the selection can include rows that aren't in view.
+    pub fn select_rows(&mut self, start_row: usize, end_row: usize) {
+        self.grid_mut().select_rows(start_row, end_row);
+    }
+
     /// Updates the current selection to end at row and col.
     ///
     /// If no selection is currently set, then a selection will be created that starts and ends at the same position.
@@ -31,3 +31,19 @@ fn test_wrapped_lines_size() {
     //         ^ last char that fits on line, rest will appear on next row
     assert_eq!(screen.cell(2, 0).unwrap().contents(), "b");
 }
+
+#[test]
+fn test_rows_include_scrollback() {
+    let mut parser = vt100::Parser::new(2, 20, 100);
+    parser.process(b"foo\r\nbar\r\nbaz\r\n");
+    assert_eq!(parser.screen().scrollback_rows(), 2);
+    let screen = parser.entire_screen();
+    assert_eq!(
+        screen.rows(0, 20).collect::<Vec<_>>(),
+        vec!["foo", "bar", "baz"]
+    );
+    assert_eq!(
+        screen.rows(1, 2).collect::<Vec<_>>(),
+        vec!["oo", "ar", "az"]
+    );
+}
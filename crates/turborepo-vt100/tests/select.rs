@@ -91,6 +91,18 @@ fn too_large() {
     );
 }
 
+#[test]
+fn select_rows_in_scrollback() {
+    let mut parser = vt100::Parser::new(2, 4, 10);
+    parser.process(b"foo\r\nbar\r\nbaz");
+
+    assert_eq!(parser.screen().contents(), "bar\nbaz");
+    parser.screen_mut().select_rows(1, 0);
+    assert_eq!(parser.screen().selected_text().as_deref(), Some("foo\nbar"));
+    parser.screen_mut().select_rows(2, 5);
+    assert_eq!(parser.screen().selected_text().as_deref(), Some("baz"));
+}
+
 #[test]
 fn selection_inversed_display() {
     let mut parser = vt100::Parser::new(2, 4, 10);
@@ -7,6 +7,50 @@ fn test_query() -> Result<(), anyhow::Error> {
         "npm@10.5.0",
         "query",
         "get package that doesn't exist" => "query { package(name: \"doesnotexist\") { path } }",
+        "get packages in apps directory" => "query { packages(filter: { directoryGlob: \"apps/*\" }) { items { name path } } }",
+        "get packages under packages directory with a build script" => "query { packages(filter: { directoryGlob: \"packages/*\", jsonPath: \"scripts.build\" }) { items { name path } } }",
+        "get packages with dependencies" => "query { packages(filter: { jsonPath: \"dependencies\" }) { items { name } } }",
+        "get packages with build task sorted by dependents" => "query { packages(filter: { hasTask: \"build\" }, sortBy: ALL_DEPENDENT_COUNT, order: DESCENDING) { items { name allDependents { length } } } }",
+        "get packages sorted by dependents with ties broken by name" => "query { packages(sortBy: ALL_DEPENDENT_COUNT, order: DESCENDING) { items { name allDependents { length } } } }",
+        "get packages with invalid name glob" => "query { packages(filter: { nameGlob: \"[\" }) { items { name } } }",
+        "get task hash details" => "query { package(name: \"my-app\") { task(name: \"build\") { fullName hash inputs { path hash } outputs envVars } } }",
+        "get task by package and name" => "query { task(package: \"my-app\", name: \"build\") { fullName hash definition { cache outputs } cacheStatus { hit source timeSaved } } }",
+        "get task definitions" => "query { package(name: \"my-app\") { tasks { items { name definition { inputs outputs cache env passThroughEnv dependsOn persistent outputLogs envMode } } } } }",
+        "get package env vars" => "query { package(name: \"util\") { envVars { task envMode globalEnv env passThroughEnv } } }",
+        "get files matching globs" => "query { files(globs: [\"**/*.txt\", \"!bar.txt\"]) { items { path } } }",
+        "get package of file in package" => "query { file(path: \"apps/my-app/package.json\") { path package { package { name } packageJsonPath allPackages reason { __typename } } } }",
+        "get package of global dependency" => "query { file(path: \"foo.txt\") { path package { package { name } packageJsonPath allPackages reason { ... on GlobalDepsChanged { filePath matchedPattern } } } } }",
+        "get orphan files" => "query { orphanFiles { items { path } } global: file(path: \"foo.txt\") { isOrphan } package: file(path: \"packages/util/package.json\") { isOrphan } }",
+        "get affected tasks of file in package" => "query { file(path: \"packages/util/package.json\") { affectedTasks { items { fullName } } } }",
+        "get affected tasks of global dependency" => "query { file(path: \"foo.txt\") { affectedTasks { length } } }",
+        "get packages selected by filters" => "query { changedPackages(filter: [\"my-app...\", \"!util\", \"./packages/*\"]) { items { name reason { __typename ... on IncludedByFilter { filters } ... on InFilteredDirectory { directoryPath } } } } }",
+        "get paginated packages" => "query { packages { length items(offset: 1, limit: 2) { name } } }",
+        "get dependency paths" => "query { package(name: \"my-app\") { dependencyPaths(to: \"util\") { items { edges { dependent { name } dependency { name } fields } } } } }",
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_shared_files() -> Result<(), anyhow::Error> {
+    check_json!(
+        "shared_files",
+        "npm@10.5.0",
+        "query",
+        "get packages of shared file" => "query { file(path: \"config/tsconfig.base.json\") { path package { package { name } } packages { length items { package { name } packageJsonPath allPackages reason { ... on SharedFileChanged { filePath pattern } } } } } }",
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_external_dependencies() -> Result<(), anyhow::Error> {
+    check_json!(
+        "framework_inference",
+        "npm@10.5.0",
+        "query",
+        "get direct external dependencies" => "query { package(name: \"docs\") { externalDependencies { items { name version direct } } } }",
+        "get transitive external dependencies" => "query { package(name: \"docs\") { externalDependencies(transitive: true) { length items(limit: 3) { name version direct } } } }",
     );
 
     Ok(())
@@ -40,6 +84,9 @@ fn test_ast() -> Result<(), anyhow::Error> {
         "npm@10.5.0",
         "query",
         "get `main.ts` with ast" => "query { file(path: \"main.ts\") { path ast } }",
+        "get `main.ts` with compact ast" => "query { file(path: \"main.ts\") { path ast(format: COMPACT) } }",
+        "get `main.ts` with only imports in ast" => "query { file(path: \"main.ts\") { path ast(format: COMPACT, onlyImports: true) } }",
+        "get `main.ts` with ast in span range" => "query { file(path: \"main.ts\") { path ast(format: COMPACT, spanRange: { start: 70, end: 100 }) } }",
     );
 
     Ok(())
@@ -54,15 +101,45 @@ fn test_trace() -> Result<(), anyhow::Error> {
             "query",
             "get `main.ts`" => "query { file(path: \"main.ts\") { path } }",
             "get `main.ts` with dependencies" => "query { file(path: \"main.ts\") { path, dependencies { files { items { path } } } } }",
+            "get `main.ts` with repeated dependencies" => "query { file(path: \"main.ts\") { path first: dependencies { files { items { path } } } second: dependencies { files { items { path exports { length } } } } } }",
             "get `button.tsx` with dependencies" => "query { file(path: \"button.tsx\") { path, dependencies { files { items { path } } } } }",
             "get `circular.ts` with dependencies" => "query { file(path: \"circular.ts\") { path dependencies { files { items { path } } } } }",
+            "get `circular.ts` with cycles" => "query { file(path: \"circular.ts\") { path dependencies { cycles } } }",
+            "get `circular2.ts` with cycles in dependents" => "query { file(path: \"circular2.ts\") { path dependents { cycles } } }",
+            "get `styles/main.scss` with dependencies" => "query { file(path: \"styles/main.scss\") { path dependencies { files { items { path } } errors { items { message } } } } }",
+            "get `styles/reset.css` with dependents" => "query { file(path: \"styles/reset.css\") { path dependents { files { items { path } } } } }",
+            "trace `main.ts` and `button.tsx` together" => "query { trace(paths: [\"main.ts\", \"button.tsx\"]) { files { items { path entries } } errors { length } } }",
+            "get `main.ts` with dependency graph" => "query { file(path: \"main.ts\") { path dependencies { dot: graph(format: DOT) mermaid: graph(format: MERMAID) json: graph(format: JSON) } } }",
             "get `invalid.ts` with dependencies" => "query { file(path: \"invalid.ts\") { path dependencies { files { items { path } } errors { items { import } } } } }",
             "get `main.ts` with depth = 0" => "query { file(path: \"main.ts\") { path dependencies(depth: 1) { files { items { path } } } } }",
+            "get `main.ts` with truncated dependencies" => "query { file(path: \"main.ts\") { path dependencies(depth: 1) { truncated truncatedFiles { items { path } } } } }",
+            "get `circular.ts` with cycle at depth limit" => "query { file(path: \"circular.ts\") { path dependencies(depth: 1) { files { items { path } } truncated truncatedFiles { items { path } } } } }",
             "get `with_prefix.ts` with dependencies" => "query { file(path: \"with_prefix.ts\") { path dependencies { files { items { path } } } } }",
             "get `import_value_and_type.ts` with all dependencies" => "query { file(path: \"import_value_and_type.ts\") { path dependencies(importType: ALL) { files { items { path } } } } }",
             "get `import_value_and_type.ts` with type dependencies" => "query { file(path: \"import_value_and_type.ts\") { path dependencies(importType: TYPES) { files { items { path } } } } }",
             "get `import_value_and_type.ts` with value dependencies" => "query { file(path: \"import_value_and_type.ts\") { path dependencies(importType: VALUES) { files { items { path } } } } }",
+            "get `import_value_and_type.ts` with edges" => "query { file(path: \"import_value_and_type.ts\") { path dependencies { edges { from to typeOnly } runtime: edges(typeOnly: false) { to } } } }",
+            "get `dynamic_import.ts` with unresolved dynamic imports" => "query { file(path: \"dynamic_import.ts\") { path dependencies { files { items { path } } unresolvedDynamicImports { items { path kind start end } } } } }",
+            "get `dynamic_import_literal.ts` with dependencies" => "query { file(path: \"dynamic_import_literal.ts\") { path dependencies { files { items { path } } edges { from to imports { kind start end } } unresolvedDynamicImports { length } } } }",
+            "get `dynamic_import_literal.ts` with imports" => "query { file(path: \"dynamic_import_literal.ts\") { path imports { items { specifier kind start end resolved { path } } } } }",
             "get `incorrect_extension.mjs` with dependencies" =>  "query { file(path: \"incorrect_extension.mjs\") { path dependencies(depth: 1) { files { items { path } } } } }",
+            "get `component.vue` with dependencies" => "query { file(path: \"component.vue\") { path dependencies { files { items { path } } errors { items { message } } } imports { items { specifier start end resolved { path } } } } }",
+            "get `component.svelte` with dependencies" => "query { file(path: \"component.svelte\") { path dependencies { files { items { path } } errors { items { message } } } } }",
+            "get `sfc_label.ts` with dependents" => "query { file(path: \"sfc_label.ts\") { path dependents { files { items { path } } } } }",
+            "get `syntax_error.ts` with diagnostics" => "query { file(path: \"syntax_error.ts\") { path diagnostics { items { message path start end } } ast } }",
+            "get exports of files when one doesn't parse" => "query { files(globs: [\"main.ts\", \"syntax_error.ts\"]) { items { path exports { length } } } }",
+            "get `main.ts` with diagnostics" => "query { file(path: \"main.ts\") { path diagnostics { length } } }",
+            "get `main.ts` with position" => "query { file(path: \"main.ts\") { path position(offset: 46) { line column } } }",
+            "get `main.ts` with source map" => "query { file(path: \"main.ts\") { path sourceMap } }",
+            "get `syntax_error.ts` with position of its error" => "query { file(path: \"syntax_error.ts\") { path position(offset: 36) { line column } } }",
+            "get `syntax_error.ts` with source map" => "query { file(path: \"syntax_error.ts\") { path sourceMap } }",
+            "get `main.ts` with size and line count" => "query { file(path: \"main.ts\") { path sizeBytes lineCount } }",
+            "get `main.ts` with content hash" => "query { file(path: \"main.ts\") { path contentHash } }",
+            "get `link.tsx` with exports" => "query { file(path: \"link.tsx\") { path exports { items { name kind start end } } } }",
+            "get `foo.js` with exports" => "query { file(path: \"foo.js\") { path exports { items { name kind start end } } } }",
+            "get `main.ts` with imports" => "query { file(path: \"main.ts\") { path imports { items { specifier kind start end resolved { path } } } } }",
+            "get `import_value_and_type.ts` with imports" => "query { file(path: \"import_value_and_type.ts\") { path imports { items { specifier kind resolved { path } } } } }",
+            "get unused exports" => "query { package(name: \"//\") { unusedExports { items { file { path } export { name kind } } } } }",
         );
 
         Ok(())
@@ -77,7 +154,21 @@ fn test_trace_on_monorepo() -> Result<(), anyhow::Error> {
             "npm@10.5.0",
             "query",
             "get `apps/my-app/index.ts` with dependencies" => "query { file(path: \"apps/my-app/index.ts\") { path dependencies { files { items { path } } errors { items { message } } } } }",
+            "get `apps/my-app/index.ts` with workspace dependencies" => "query { file(path: \"apps/my-app/index.ts\") { path dependencies(followWorkspaceDependencies: true) { files { items { path } } errors { items { message } } } } }",
             "get `packages/utils/index.ts` with dependents" => "query { file(path: \"packages/utils/index.ts\") { path dependents { files { items { path } } errors { items { message } } } } }",
+            "get `packages/utils/index.ts` with direct dependents" => "query { file(path: \"packages/utils/index.ts\") { path dependents(depth: 1) { files { items { path } } errors { items { message } } } } }",
+            "get owners of files" => "query { app: file(path: \"apps/my-app/index.ts\") { owners } utils: file(path: \"packages/utils/index.ts\") { owners } config: file(path: \"packages/utils/package.json\") { owners } unowned: file(path: \"packages/utils/types.ts\") { owners } root: file(path: \"turbo.json\") { owners } other: file(path: \"packages/another/index.jsx\") { owners } }",
+            "get `packages/conditional-exports/consumer.ts` with dependencies" => "query { file(path: \"packages/conditional-exports/consumer.ts\") { path dependencies { files { items { path } } errors { items { message } } } imports { items { specifier kind resolved { path } } } } }",
+            "get `apps/vite-app/src/main.ts` with aliases from vite config" => "query { file(path: \"apps/vite-app/src/main.ts\") { path dependencies { files { items { path } } errors { items { message } } } imports { items { specifier resolved { path } } } } }",
+            "get `packages/cargo-ship/index.ts` with tsconfig paths of each package" => "query { file(path: \"packages/cargo-ship/index.ts\") { path dependencies(followWorkspaceDependencies: true) { files { items { path } } errors { items { message } } } } }",
+            "get `packages/harbor/index.ts` with dependencies" => "query { file(path: \"packages/harbor/index.ts\") { path dependencies { files { items { path } } } } }",
+            "get `packages/harbor/index.ts` with external dependencies" => "query { file(path: \"packages/harbor/index.ts\") { path dependencies(followExternal: true) { files { items { path } } } } }",
+            "get `packages/harbor/index.ts` with external dependencies one level deep" => "query { file(path: \"packages/harbor/index.ts\") { path dependencies(followExternal: true, externalDepth: 1) { files { items { path } } } } }",
+            "get `packages/lighthouse/index.ts` with assets" => "query { file(path: \"packages/lighthouse/index.ts\") { path kind dependencies { files { items { path kind } } errors { items { message } } } } }",
+            "get `packages/module-kinds/esm.mts` with dependencies" => "query { file(path: \"packages/module-kinds/esm.mts\") { path dependencies { files { items { path } } errors { items { message } } } } }",
+            "get `packages/module-kinds/helper.mts` with dependents" => "query { file(path: \"packages/module-kinds/helper.mts\") { path dependents { files { items { path } } errors { items { message } } } } }",
+            "get `packages/reexports/index.ts` with edges" => "query { file(path: \"packages/reexports/index.ts\") { path dependencies { files { items { path } } edges { from to typeOnly imports { kind start end } } } imports { items { specifier kind } } } }",
+            "get package entry points" => "query { utils: package(name: \"utils\") { entryPoints { field subpath conditions target file { path } } } conditions: package(name: \"package-with-conditions\") { entryPoints { field subpath conditions target file { path } } } module: package(name: \"module-package\") { entryPoints { field target file { path } } } }",
             "get `packages/another/index.js` with dependents" => "query { file(path: \"packages/another/index.jsx\") { path dependents { files { items { path } } errors { items { message } } } } }",
         );
 
@@ -95,6 +186,7 @@ fn test_reverse_trace() -> Result<(), anyhow::Error> {
         "get `link.tsx` with all dependents" => "query { file(path: \"link.tsx\") { path dependents(importType: ALL) { files { items { path } } } } }",
         "get `link.tsx` with type dependents" => "query { file(path: \"link.tsx\") { path dependents(importType: TYPES) { files { items { path } } } } }",
         "get `link.tsx` with value dependents" => "query { file(path: \"link.tsx\") { path dependents(importType: VALUES) { files { items { path } } } } }",
+        "get dependents of several files" => "query { button: file(path: \"button.tsx\") { dependents { files { items { path } } } } link: file(path: \"link.tsx\") { dependents(depth: 1) { files { items { path } } } } }",
     );
 
     Ok(())
@@ -59,4 +59,26 @@ describe("affectedPackages", () => {
       assert.deepEqual(reduced, expected);
     });
   }
+
+  it("files relative to an enclosing repository", async () => {
+    const scmRoot = path.resolve(__dirname, "./fixtures");
+    const workspace = await Workspace.find(path.join(scmRoot, "monorepo"));
+
+    const reduced: PackageReduced[] = (
+      await workspace.affectedPackages(
+        [
+          path.join("monorepo", "packages", "ui", "a.txt"),
+          path.join("other", "README.md"),
+        ],
+        scmRoot
+      )
+    ).map((pkg) => {
+      return {
+        name: pkg.name,
+        relativePath: pkg.relativePath,
+      };
+    });
+
+    assert.deepEqual(reduced, [{ name: "ui", relativePath: "packages/ui" }]);
+  });
 });
@@ -54,6 +54,11 @@ export class Workspace {
    * "affected" by the changes. The `files` argument is expected to be a list
    * of strings relative to the monorepo root and use the current system's
    * path separator.
+   *
+   * If the monorepo is in a subdirectory of a larger repository, pass the
+   * absolute path of the repository root as `scm_root` and `files` will be
+   * treated as relative to it instead, e.g. the output of `git diff
+   * --name-only`. Files outside of the monorepo are ignored.
    */
-  affectedPackages(files: Array<string>): Promise<Array<Package>>;
+  affectedPackages(files: Array<string>, scmRoot?: string | undefined | null): Promise<Array<Package>>;
 }
@@ -7,7 +7,9 @@ use napi::Error;
 use napi_derive::napi;
 use turbopath::{AbsoluteSystemPath, AnchoredSystemPathBuf};
 use turborepo_repository::{
-    change_mapper::{ChangeMapper, DefaultPackageChangeMapper, PackageChanges},
+    change_mapper::{
+        anchor_to_turbo_root, ChangeMapper, DefaultPackageChangeMapper, PackageChanges,
+    },
     inference::RepoState as WorkspaceState,
     package_graph::{PackageGraph, PackageName, PackageNode, WorkspacePackage, ROOT_PKG_NAME},
 };
@@ -186,21 +188,36 @@ impl Workspace {
     /// "affected" by the changes. The `files` argument is expected to be a list
     /// of strings relative to the monorepo root and use the current system's
     /// path separator.
+    ///
+    /// If the monorepo is in a subdirectory of a larger repository, pass the
+    /// absolute path of the repository root as `scm_root` and `files` will be
+    /// treated as relative to it instead, e.g. the output of `git diff
+    /// --name-only`. Files outside of the monorepo are ignored.
     #[napi]
-    pub async fn affected_packages(&self, files: Vec<String>) -> Result<Vec<Package>, Error> {
+    pub async fn affected_packages(
+        &self,
+        files: Vec<String>,
+        scm_root: Option<String>,
+    ) -> Result<Vec<Package>, Error> {
         let workspace_root = match AbsoluteSystemPath::new(&self.absolute_path) {
             Ok(path) => path,
             Err(e) => return Err(Error::from_reason(e.to_string())),
         };
+        let scm_root = match scm_root.as_deref().map(AbsoluteSystemPath::new).transpose() {
+            Ok(path) => path.unwrap_or(workspace_root),
+            Err(e) => return Err(Error::from_reason(e.to_string())),
+        };
 
-        let hash_set_of_paths: HashSet<AnchoredSystemPathBuf> = files
-            .into_iter()
-            .filter_map(|path| {
-                let path_components = path.split(std::path::MAIN_SEPARATOR).collect::<Vec<&str>>();
-                let absolute_path = workspace_root.join_components(&path_components);
-                workspace_root.anchor(&absolute_path).ok()
-            })
-            .collect();
+        let files = files.into_iter().filter_map(|path| {
+            let path_components = path.split(std::path::MAIN_SEPARATOR).collect::<Vec<&str>>();
+            let absolute_path = scm_root.join_components(&path_components);
+            scm_root.anchor(&absolute_path).ok()
+        });
+        let hash_set_of_paths: HashSet<AnchoredSystemPathBuf> =
+            match anchor_to_turbo_root(scm_root, workspace_root, files) {
+                Ok(paths) => paths,
+                Err(e) => return Err(Error::from_reason(e.to_string())),
+            };
 
         // Create a ChangeMapper with no ignore patterns
         let default_package_detector = DefaultPackageChangeMapper::new(&self.graph);
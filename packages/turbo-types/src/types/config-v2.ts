@@ -126,6 +126,35 @@ export interface RootSchema extends BaseSchema {
    */
   dangerouslyDisablePackageManagerCheck?: boolean;
 
+  /**
+   * Add edges to the package graph for workspace packages that are only
+   * listed in `peerDependencies`.
+   *
+   * @defaultValue `false`
+   */
+  includePeerDependencies?: boolean;
+
+  /**
+   * Cycles in the package graph that are known to be safe.
+   *
+   * Each cycle is listed as the packages in the order they depend on each
+   * other, e.g. `["web", "ui"]` for `web -> ui -> web`.
+   *
+   * @defaultValue `[]`
+   */
+  allowedPackageCycles?: Array<Array<string>>;
+
+  /**
+   * Files outside of any package that only affect the listed packages.
+   *
+   * Each key is a glob relative to the root of the repository and each value
+   * is the packages that should be marked as changed when a matching file
+   * changes.
+   *
+   * @defaultValue `{}`
+   */
+  sharedFiles?: Record<string, Array<string>>;
+
   /**
    * Specify the filesystem cache directory.
    *
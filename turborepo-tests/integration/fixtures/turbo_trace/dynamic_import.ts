@@ -0,0 +1,7 @@
+import foo from "./foo";
+
+const locale = process.env.LOCALE;
+const messages = import(`./locales/${locale}.json`);
+const plugin = require(process.env.PLUGIN);
+
+foo(messages, plugin);
@@ -0,0 +1,5 @@
+const { Button } = await import("./button.tsx");
+const load = () => import("./foo");
+const { Link } = require("./link.tsx");
+
+load().then(() => new Button(Link));
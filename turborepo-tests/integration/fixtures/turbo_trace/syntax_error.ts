@@ -0,0 +1,3 @@
+const mode = 010;
+
+const total = 1 +;
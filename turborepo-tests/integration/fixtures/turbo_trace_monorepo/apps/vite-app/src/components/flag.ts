@@ -0,0 +1 @@
+export const Flag = "jolly roger";
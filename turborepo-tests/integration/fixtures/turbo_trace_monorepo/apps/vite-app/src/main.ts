@@ -0,0 +1,4 @@
+import { Cannon } from "~/components/cannon";
+import { Flag } from "@components/flag";
+
+export const ship = [Cannon, Flag];
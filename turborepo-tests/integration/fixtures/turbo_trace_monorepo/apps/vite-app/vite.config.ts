@@ -0,0 +1,10 @@
+import path from "node:path";
+
+export default {
+  resolve: {
+    alias: {
+      "~": path.resolve(__dirname, "src"),
+      "@components": "./src/components",
+    },
+  },
+};
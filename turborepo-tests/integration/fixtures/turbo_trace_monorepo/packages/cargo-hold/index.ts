@@ -0,0 +1,3 @@
+import { crate } from "~hold/crate";
+
+export const hold = [crate];
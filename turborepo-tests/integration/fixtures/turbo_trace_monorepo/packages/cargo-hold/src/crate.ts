@@ -0,0 +1 @@
+export const crate = "crate";
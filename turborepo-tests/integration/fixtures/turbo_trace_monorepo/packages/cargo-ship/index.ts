@@ -0,0 +1,4 @@
+import { hold } from "cargo-hold";
+import { deck } from "~ship/deck";
+
+export const ship = { hold, deck };
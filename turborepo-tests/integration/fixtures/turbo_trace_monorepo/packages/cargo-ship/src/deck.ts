@@ -0,0 +1 @@
+export const deck = "deck";
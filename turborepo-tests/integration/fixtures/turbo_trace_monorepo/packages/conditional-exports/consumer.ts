@@ -0,0 +1,5 @@
+import { feature } from "conditional-exports";
+import type { Feature } from "conditional-exports";
+import type { Only } from "conditional-exports/types-only";
+
+const required = require("conditional-exports");
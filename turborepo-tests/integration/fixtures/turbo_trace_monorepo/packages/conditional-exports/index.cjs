@@ -0,0 +1,3 @@
+const { internal } = require("#internal");
+
+module.exports = { feature: internal };
@@ -0,0 +1,2 @@
+export declare const feature: string;
+export type Feature = typeof feature;
@@ -0,0 +1 @@
+export type Only = string;
@@ -0,0 +1,3 @@
+import { chain } from "anchor-chain";
+
+export const harbor = chain;
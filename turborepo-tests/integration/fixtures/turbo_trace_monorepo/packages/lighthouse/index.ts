@@ -0,0 +1,5 @@
+import beacon from "./beacon.json";
+import lamp from "./lamp.svg";
+import { signal } from "./signal";
+
+export const lighthouse = { beacon, lamp, signal };
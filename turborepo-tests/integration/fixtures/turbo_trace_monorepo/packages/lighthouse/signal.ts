@@ -0,0 +1 @@
+export const signal = () => import("./lens.wasm");
@@ -0,0 +1,5 @@
+import { helper } from "./helper.mjs";
+import { legacy } from "./legacy.cjs";
+import { catchphrase } from "package-with-conditions";
+
+export const esm = [helper, legacy, catchphrase];
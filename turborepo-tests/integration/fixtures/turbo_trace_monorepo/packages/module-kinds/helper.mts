@@ -0,0 +1 @@
+export const helper = (name: string): string => name;
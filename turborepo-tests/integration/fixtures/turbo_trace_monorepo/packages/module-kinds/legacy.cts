@@ -0,0 +1,3 @@
+import catchphrase from "package-with-conditions";
+
+export const legacy: string = catchphrase;
@@ -0,0 +1 @@
+export const anchor = "heavy";
@@ -0,0 +1,3 @@
+export interface Cargo {
+  weight: number;
+}
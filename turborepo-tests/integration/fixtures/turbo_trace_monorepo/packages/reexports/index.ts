@@ -0,0 +1,7 @@
+export { anchor } from "./anchor";
+export * from "./anchor";
+export type { Cargo } from "./cargo";
+import { anchor as dropAnchor } from "./anchor";
+
+export const loadAnchor = () => import("./anchor");
+const { anchor: requiredAnchor } = require("./anchor");
@@ -61,6 +61,31 @@ Do the same thing with the `query` command
   }
 
 
+Combine the affected packages with a filter, like `--affected --filter=util`
+  $ ${TURBO} query "query { changedPackages(base: \"main\", filter: [\"util\"]) { items { name reason { __typename } } } }"
+   WARNING  query command is experimental and may change in the future
+  {
+    "data": {
+      "changedPackages": {
+        "items": [
+          {
+            "name": "my-app",
+            "reason": {
+              "__typename": "FileChanged"
+            }
+          },
+          {
+            "name": "util",
+            "reason": {
+              "__typename": "IncludedByFilter"
+            }
+          }
+        ]
+      }
+    }
+  }
+
+
 Remove the new file
   $ rm apps/my-app/new.js
 
@@ -91,6 +116,44 @@ Validate that both `my-app` and `util` are affected
     }
   }
 
+Get the changed files and the packages they affect with `diff`
+  $ ${TURBO} query "query { diff(base: \"HEAD\") { files { items { path package { package { name } } } } packages { items { name reason { __typename } } } } }"
+   WARNING  query command is experimental and may change in the future
+  {
+    "data": {
+      "diff": {
+        "files": {
+          "items": [
+            {
+              "path": "packages/util/new.js",
+              "package": {
+                "package": {
+                  "name": "util"
+                }
+              }
+            }
+          ]
+        },
+        "packages": {
+          "items": [
+            {
+              "name": "my-app",
+              "reason": {
+                "__typename": "DependencyChanged"
+              }
+            },
+            {
+              "name": "util",
+              "reason": {
+                "__typename": "FileChanged"
+              }
+            }
+          ]
+        }
+      }
+    }
+  }
+
 Remove the new file
   $ rm packages/util/new.js
 
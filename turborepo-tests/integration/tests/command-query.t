@@ -214,3 +214,75 @@ Run the query
   $ VERSION=${MONOREPO_ROOT_DIR}/version.txt
   $ diff --strip-trailing-cr <(head -n 1 ${VERSION}) <(${TURBO} --version)
 
+
+Print the schema, including subscriptions
+  $ ${TURBO} query --schema 2>/dev/null | grep -E "^type (Query|Subscription) "
+  type Query {
+  type Subscription {
+
+Serving the query API can't be combined with running a query
+  $ ${TURBO} query --serve "query { version }"
+   ERROR  the argument '--serve' cannot be used with '[QUERY]'
+  
+  Usage: turbo(.exe)? query --serve \[QUERY\] (re)
+  
+  For more information, try '--help'.
+  
+  [1]
+
+Output a list as CSV
+  $ ${TURBO} query "query { packages { items { name path } } }" --output csv
+   WARNING  query command is experimental and may change in the future
+  name,path
+  //,
+  another,packages/another
+  my-app,apps/my-app
+  util,packages/util
+
+Output a list as JSON lines
+  $ ${TURBO} query "query { packages { items { name } } }" --output jsonl
+   WARNING  query command is experimental and may change in the future
+  {"name":"//"}
+  {"name":"another"}
+  {"name":"my-app"}
+  {"name":"util"}
+
+Output a list as a GitHub Actions matrix
+  $ GITHUB_OUTPUT=github_output ${TURBO} query "query { packages(filter: { hasTask: \"build\" }) { items { name path } } }" --output github-matrix
+   WARNING  query command is experimental and may change in the future
+  {"include":[{"name":"my-app","path":"apps/my-app"},{"name":"util","path":"packages/util"}]}
+  $ cat github_output
+  matrix={"include":[{"name":"my-app","path":"apps/my-app"},{"name":"util","path":"packages/util"}]}
+
+Check whether a task is cached
+  $ ${TURBO} query "query { task(package: \"my-app\", name: \"build\") { cacheStatus { hit source } } }" | jq '.data.task.cacheStatus'
+   WARNING  query command is experimental and may change in the future
+  {
+    "hit": false,
+    "source": null
+  }
+  $ ${TURBO} run build --filter=my-app > /dev/null
+  $ ${TURBO} query "query { task(package: \"my-app\", name: \"build\") { cacheStatus { hit source } } }" | jq '.data.task.cacheStatus'
+   WARNING  query command is experimental and may change in the future
+  {
+    "hit": true,
+    "source": "LOCAL"
+  }
+
+Reject queries that are too complex or nested too deep
+  $ ${TURBO} query "query { file(path: \"apps/my-app/package.json\") { dependencies { files { length } } } }" --max-complexity 50 2> /dev/null | jq -c '.errors[].message'
+  "Query is too complex."
+  $ ${TURBO} query "query { packages { items { directDependencies { items { name } } } } }" --max-depth 3 2> /dev/null | jq -c '.errors[].message'
+  "Query is nested too deep."
+  $ ${TURBO} query "query { packages { items { name } } }" --max-depth 3 --max-complexity 50 2> /dev/null | jq -c '.data.packages.items | length'
+  4
+
+Reject a timeout of 0 seconds
+  $ ${TURBO} query "query { packages { items { name } } }" --timeout 0
+   ERROR  invalid value '0' for '--timeout <TIMEOUT>': 0 is not in 1..=18446744073709551615
+  
+  For more information, try '--help'.
+  
+  [1]
+  $ ${TURBO} query "query { packages { items { name } } }" --timeout 10 2> /dev/null | jq -c '.data.packages.items | length'
+  4
@@ -37,6 +37,7 @@ Setup
     "globalCacheInputs",
     "id",
     "monorepo",
+    "packageReasons",
     "packages",
     "scm",
     "tasks",
@@ -45,6 +46,17 @@ Setup
     "version"
   ]
 
+# Each package in scope says why it was selected
+  $ ${TURBO} run build --dry=json --filter=util | jq '.packageReasons'
+  {
+    "util": {
+      "type": "includedByFilter",
+      "filters": [
+        "util"
+      ]
+    }
+  }
+
 # Validate output of my-app#build task
   $ cat tmpjson.log | jq '.tasks | map(select(.taskId == "my-app#build")) | .[0]'
   {
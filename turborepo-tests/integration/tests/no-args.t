@@ -41,7 +41,7 @@ Make sure exit code is 2 when no args are passed
         --heap <HEAP>
             Specify a file to save a pprof heap profile
         --ui <UI>
-            Specify whether to use the streaming UI or TUI [possible values: tui, stream, web]
+            Specify whether to use the streaming UI or TUI [possible values: tui, stream, web, accessible]
         --login <LOGIN>
             Override the login endpoint
         --no-color
@@ -116,6 +116,8 @@ Make sure exit code is 2 when no args are passed
             Set type of process output logging. Use "full" to show all output. Use "hash-only" to show only turbo-computed task hashes. Use "new-only" to show only new output with only hashes for cached tasks. Use "none" to hide process output. (default full) [possible values: full, none, hash-only, new-only, errors-only]
         --log-order <LOG_ORDER>
             Set type of task output order. Use "stream" to show output as soon as it is available. Use "grouped" to show output when a command has finished execution. Use "auto" to let turbo decide based on its own heuristics. (default auto) [possible values: auto, stream, grouped]
+        --save-output-preferences
+            Save the given --output-logs and --log-order as the defaults for future runs
         --only
             Only executes the tasks specified, does not execute parent tasks
         --log-prefix <LOG_PREFIX>
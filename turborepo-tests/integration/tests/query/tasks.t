@@ -304,3 +304,63 @@ Setup
       }
     }
   }
+
+Get the task graph for `test` in `app-a`, grouped into layers
+  $ ${TURBO} query "query { taskGraph(tasks: [\"test\"], filter: { equal: { field: NAME, value: \"app-a\" } }) { edges { task dependency } layers } }"
+   WARNING  query command is experimental and may change in the future
+  {
+    "data": {
+      "taskGraph": {
+        "edges": [
+          {
+            "task": "app-a#test",
+            "dependency": "app-a#prepare"
+          },
+          {
+            "task": "app-a#test",
+            "dependency": "lib-a#build0"
+          },
+          {
+            "task": "lib-a#build0",
+            "dependency": "lib-a#prepare"
+          },
+          {
+            "task": "lib-a#build0",
+            "dependency": "lib-b#build0"
+          },
+          {
+            "task": "lib-b#build0",
+            "dependency": "lib-b#prepare"
+          },
+          {
+            "task": "lib-b#build0",
+            "dependency": "lib-d#build0"
+          },
+          {
+            "task": "lib-d#build0",
+            "dependency": "lib-d#prepare"
+          }
+        ],
+        "layers": [
+          [
+            "app-a#prepare",
+            "lib-a#prepare",
+            "lib-b#prepare",
+            "lib-d#prepare"
+          ],
+          [
+            "lib-d#build0"
+          ],
+          [
+            "lib-b#build0"
+          ],
+          [
+            "lib-a#build0"
+          ],
+          [
+            "app-a#test"
+          ]
+        ]
+      }
+    }
+  }
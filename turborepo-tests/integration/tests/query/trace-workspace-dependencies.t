@@ -0,0 +1,19 @@
+Setup
+  $ . ${TESTDIR}/../../../helpers/setup_integration_test.sh turbo_trace_monorepo
+
+Without node_modules, imports of workspace packages can't be resolved
+  $ rm -rf node_modules
+  $ ${TURBO} query "query { file(path: \"apps/my-app/index.ts\") { dependencies(emitErrors: false) { files { items { path } } } } }" 2> /dev/null | jq -c '.data.file.dependencies.files.items[].path'
+  "apps/my-app/types.ts"
+  "packages/another/index.jsx"
+
+They're resolved to the package's source when following workspace dependencies
+  $ ${TURBO} query "query { file(path: \"apps/my-app/index.ts\") { dependencies(followWorkspaceDependencies: true) { files { items { path } } } } }" 2> /dev/null | jq -c '.data.file.dependencies.files.items[].path'
+  "apps/my-app/types.ts"
+  "packages/another/index.jsx"
+  "packages/module-package/my-module.mjs"
+  "packages/package-with-conditions/index.mjs"
+  "packages/ship-types/index.ts"
+  "packages/utils/index.ts"
+  "packages/utils/my-hook.ts"
+  "packages/utils/types.ts"
@@ -29,6 +29,28 @@ Run the query
     }
   }
 
+Pass the variables directly
+  $ ${TURBO} query 'query($name: String) { package(name: $name) { name } }' --variables '{ "name": "util" }' | jq
+   WARNING  query command is experimental and may change in the future
+  {
+    "data": {
+      "package": {
+        "name": "util"
+      }
+    }
+  }
+
+Pick an operation from a query with multiple operations
+  $ ${TURBO} query 'query Version { version } query Package($name: String) { package(name: $name) { name } }' --operation-name Package --variables vars.json | jq
+   WARNING  query command is experimental and may change in the future
+  {
+    "data": {
+      "package": {
+        "name": "my-app"
+      }
+    }
+  }
+
 Make sure we can't pass variables without a query
   $ ${TURBO} query --variables vars.json
    ERROR  the following required arguments were not provided:
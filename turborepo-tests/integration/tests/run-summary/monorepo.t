@@ -37,6 +37,7 @@ Setup
     "globalCacheInputs",
     "id",
     "monorepo",
+    "packageReasons",
     "packages",
     "scm",
     "tasks",
@@ -158,6 +159,7 @@ Setup
     "globalCacheInputs",
     "id",
     "monorepo",
+    "packageReasons",
     "packages",
     "scm",
     "tasks",
@@ -41,7 +41,7 @@ Test help flag
         --heap <HEAP>
             Specify a file to save a pprof heap profile
         --ui <UI>
-            Specify whether to use the streaming UI or TUI [possible values: tui, stream, web]
+            Specify whether to use the streaming UI or TUI [possible values: tui, stream, web, accessible]
         --login <LOGIN>
             Override the login endpoint
         --no-color
@@ -116,6 +116,8 @@ Test help flag
             Set type of process output logging. Use "full" to show all output. Use "hash-only" to show only turbo-computed task hashes. Use "new-only" to show only new output with only hashes for cached tasks. Use "none" to hide process output. (default full) [possible values: full, none, hash-only, new-only, errors-only]
         --log-order <LOG_ORDER>
             Set type of task output order. Use "stream" to show output as soon as it is available. Use "grouped" to show output when a command has finished execution. Use "auto" to let turbo decide based on its own heuristics. (default auto) [possible values: auto, stream, grouped]
+        --save-output-preferences
+            Save the given --output-logs and --log-order as the defaults for future runs
         --only
             Only executes the tasks specified, does not execute parent tasks
         --log-prefix <LOG_PREFIX>
@@ -175,9 +177,10 @@ Test help flag
             Specify whether to use the streaming UI or TUI
   
             Possible values:
-            - tui:    Use the terminal user interface
-            - stream: Use the standard output stream
-            - web:    Use the web user interface (experimental)
+            - tui:        Use the terminal user interface
+            - stream:     Use the standard output stream
+            - web:        Use the web user interface (experimental)
+            - accessible: Print task progress as plain lines, for screen readers and dumb terminals
   
         --login <LOGIN>
             Override the login endpoint
@@ -309,6 +312,9 @@ Test help flag
             
             [possible values: auto, stream, grouped]
   
+        --save-output-preferences
+            Save the given --output-logs and --log-order as the defaults for future runs
+  
         --only
             Only executes the tasks specified, does not execute parent tasks
   
@@ -348,7 +354,7 @@ Test help flag for link command
         --heap <HEAP>
             Specify a file to save a pprof heap profile
         --ui <UI>
-            Specify whether to use the streaming UI or TUI [possible values: tui, stream, web]
+            Specify whether to use the streaming UI or TUI [possible values: tui, stream, web, accessible]
         --login <LOGIN>
             Override the login endpoint
         --no-color
@@ -396,7 +402,7 @@ Test help flag for unlink command
         --heap <HEAP>
             Specify a file to save a pprof heap profile
         --ui <UI>
-            Specify whether to use the streaming UI or TUI [possible values: tui, stream, web]
+            Specify whether to use the streaming UI or TUI [possible values: tui, stream, web, accessible]
         --login <LOGIN>
             Override the login endpoint
         --no-color
@@ -446,7 +452,7 @@ Test help flag for login command
         --heap <HEAP>
             Specify a file to save a pprof heap profile
         --ui <UI>
-            Specify whether to use the streaming UI or TUI [possible values: tui, stream, web]
+            Specify whether to use the streaming UI or TUI [possible values: tui, stream, web, accessible]
         --login <LOGIN>
             Override the login endpoint
         --no-color
@@ -494,7 +500,7 @@ Test help flag for logout command
         --heap <HEAP>
             Specify a file to save a pprof heap profile
         --ui <UI>
-            Specify whether to use the streaming UI or TUI [possible values: tui, stream, web]
+            Specify whether to use the streaming UI or TUI [possible values: tui, stream, web, accessible]
         --login <LOGIN>
             Override the login endpoint
         --no-color
@@ -355,4 +355,4 @@ Set depth for dependencies
         }
       }
     }
-  }
\ No newline at end of file
+  }